@@ -1,13 +1,13 @@
 use std::sync::Arc;
 
-use eframe::egui::plot::{Legend, PlotImage};
+use eframe::egui::plot::Legend;
 use eframe::egui::{self, plot::PlotBounds};
-use eframe::emath::Vec2;
 use wgpu;
 
 use egui_gpu_plot::*;
 
 const MAX_POINTS: usize = 1_000_000;
+const OVERVIEW_HEIGHT: f32 = 64.0;
 
 pub struct GpuPlot {
     q: [f32; 3],
@@ -16,7 +16,8 @@ pub struct GpuPlot {
     show_gpu: bool,
 
     dirty: bool,
-    texture_id: egui::TextureId,
+    overview_texture_id: egui::TextureId,
+    x_window: Option<[f64; 2]>,
     points: Arc<Vec<Vertex>>,
 }
 
@@ -27,11 +28,16 @@ impl GpuPlot {
         let device = &wgpu_render_state.device;
         let target_format = wgpu_render_state.target_format;
 
-        let plot = GpuAcceleratedPlot::new(device, target_format);
-        let texture_id = {
-            let mut renderer = wgpu_render_state.renderer.write();
-            renderer.register_native_texture(device, &plot.create_view(), wgpu::FilterMode::Linear)
-        };
+        let context = GpuPlotContext::shared(wgpu_render_state);
+        let mut plot =
+            GpuAcceleratedPlot::with_context(device, context, target_format, target_format).ok()?;
+        plot.set_overview(device, true);
+
+        let overview_texture_id = wgpu_render_state.renderer.write().register_native_texture(
+            device,
+            &plot.create_overview_view().unwrap(),
+            wgpu::FilterMode::Linear,
+        );
 
         wgpu_render_state
             .renderer
@@ -45,7 +51,8 @@ impl GpuPlot {
             show_cpu: false,
             show_gpu: true,
             dirty: true,
-            texture_id,
+            overview_texture_id,
+            x_window: None,
             points: Arc::new(forward_euler(lorenz, q, MAX_POINTS)),
         })
     }
@@ -129,31 +136,37 @@ impl eframe::App for GpuPlot {
                 self.dirty = true;
             }
 
+            let wgpu_render_state = frame.wgpu_render_state().unwrap();
+            let data_bounds = {
+                let mut renderer = wgpu_render_state.renderer.write();
+                let plot: &mut GpuAcceleratedPlot =
+                    renderer.paint_callback_resources.get_mut().unwrap();
+                // Follow the window between monitors with different scale
+                // factors.
+                plot.set_pixels_per_point(ctx.pixels_per_point());
+                plot.data_bounds()
+            };
+
             let mut bounds = PlotBounds::NOTHING;
             let resp = egui::plot::Plot::new("my_plot")
+                .height(ui.available_height() - OVERVIEW_HEIGHT)
                 .legend(Legend::default())
-                // Must set margins to zero or the image and plot bounds will
-                // constantly fight, expanding the plot to infinity.
-                .set_margin_fraction(Vec2::new(0.0, 0.0))
                 .include_x(-25.0)
                 .include_x(25.0)
                 .include_y(0.0)
                 .include_y(60.0)
                 .show(ui, |ui| {
-                    bounds = ui.plot_bounds();
-
-                    if self.show_gpu {
-                        // Render the plot texture filling the viewport.
-                        ui.image(
-                            PlotImage::new(
-                                self.texture_id,
-                                bounds.center(),
-                                [bounds.width() as f32, bounds.height() as f32],
-                            )
-                            .name("Lorenz attractor (GPU)"),
-                        );
+                    // Follow the overview window if it was dragged last frame.
+                    if let Some(x_window) = self.x_window.take() {
+                        let current = ui.plot_bounds();
+                        ui.set_plot_bounds(PlotBounds::from_min_max(
+                            [x_window[0], current.min()[1]],
+                            [x_window[1], current.max()[1]],
+                        ));
                     }
 
+                    bounds = ui.plot_bounds();
+
                     if self.show_cpu {
                         ui.line(
                             egui::plot::Line::new(egui::plot::PlotPoints::from_iter(
@@ -167,28 +180,47 @@ impl eframe::App for GpuPlot {
                 });
 
             if self.show_gpu {
-                // Add a callback to egui to render the plot contents to
-                // texture.
-                ui.painter().add(egui_wgpu_callback(
+                // Add a callback to egui to draw the plot contents straight
+                // into egui's render pass, in step with the axes.
+                ui.painter().add(egui_wgpu_composite_callback(
                     bounds,
                     Arc::clone(&self.points),
                     resp.response.rect,
                     self.dirty,
                 ));
 
-                // Update the texture handle in egui from the previously
-                // rendered texture (from the last frame).
-                let wgpu_render_state = frame.wgpu_render_state().unwrap();
+                // Show the whole attractor underneath, with the visible x range
+                // as a draggable window.
+                if let Some(data_bounds) = data_bounds {
+                    let data_x = [data_bounds.min()[0], data_bounds.max()[0]];
+                    let mut x_window = [bounds.min()[0], bounds.max()[0]];
+
+                    let resp = overview_strip(
+                        ui,
+                        self.overview_texture_id,
+                        OVERVIEW_HEIGHT,
+                        data_x,
+                        &mut x_window,
+                    );
+                    if resp.changed() {
+                        self.x_window = Some(x_window);
+                    }
+
+                    ui.painter().add(egui_wgpu_overview_callback(resp.rect));
+                }
+
+                // Update the overview's texture handle in egui from the
+                // previously rendered texture (from the last frame).
                 let mut renderer = wgpu_render_state.renderer.write();
 
                 let plot: &GpuAcceleratedPlot = renderer.paint_callback_resources.get().unwrap();
-                let texture_view = plot.create_view();
+                let overview_view = plot.create_overview_view().unwrap();
 
                 renderer.update_egui_texture_from_wgpu_texture(
                     &wgpu_render_state.device,
-                    &texture_view,
+                    &overview_view,
                     wgpu::FilterMode::Linear,
-                    self.texture_id,
+                    self.overview_texture_id,
                 );
 
                 self.dirty = false;
@@ -0,0 +1,242 @@
+#[cfg(feature = "gpu")]
+use std::sync::{Arc, Mutex, PoisonError};
+
+#[cfg(feature = "gpu")]
+use wgpu::util::DeviceExt;
+
+#[cfg(feature = "gpu")]
+use crate::MAX_GRADIENT_STOPS;
+
+// Marks a column as unused in the shader.
+#[cfg(feature = "gpu")]
+const NO_COLUMN: u32 = u32::MAX;
+
+/// Colors points by one of their attribute columns, see
+/// [`GpuAcceleratedPlot::set_color_by`](crate::GpuAcceleratedPlot::set_color_by).
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColorBy {
+    pub column: u32,
+    /// The values mapped to the first and last colors.
+    pub range: [f32; 2],
+    /// Evenly spaced colors, resampled to at most
+    /// [`MAX_GRADIENT_STOPS`](crate::MAX_GRADIENT_STOPS).
+    pub colormap: Vec<[f32; 4]>,
+}
+
+impl ColorBy {
+    pub fn new(column: u32, range: [f32; 2], colormap: &[[f32; 4]]) -> ColorBy {
+        ColorBy {
+            column,
+            range,
+            colormap: colormap.to_vec(),
+        }
+    }
+}
+
+/// Scales the line width of points by one of their attribute columns, see
+/// [`GpuAcceleratedPlot::set_size_by`](crate::GpuAcceleratedPlot::set_size_by).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SizeBy {
+    pub column: u32,
+    /// The values mapped to the smallest and largest widths.
+    pub range: [f32; 2],
+    /// Multiplies the line width, from the low end of the range to the high
+    /// end.
+    pub widths: [f32; 2],
+}
+
+impl SizeBy {
+    pub fn new(column: u32, range: [f32; 2], widths: [f32; 2]) -> SizeBy {
+        SizeBy {
+            column,
+            range,
+            widths,
+        }
+    }
+}
+
+// Laid out to match `AttributeParams` in the line shader.
+#[cfg(feature = "gpu")]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+struct Params {
+    columns: u32,
+    color_column: u32,
+    size_column: u32,
+    stop_count: u32,
+    color_range: [f32; 2],
+    size_range: [f32; 2],
+    widths: [f32; 2],
+    _padding: [f32; 2],
+    stops: [[f32; 4]; MAX_GRADIENT_STOPS],
+}
+
+// The bind group layout for attribute columns, created by the first plot
+// to use them, as not every device can read storage buffers in vertex
+// shaders.
+#[cfg(feature = "gpu")]
+pub(crate) struct AttributePipelines {
+    layout: Mutex<Option<Arc<wgpu::BindGroupLayout>>>,
+}
+
+#[cfg(feature = "gpu")]
+impl AttributePipelines {
+    pub fn new() -> AttributePipelines {
+        AttributePipelines {
+            layout: Mutex::new(None),
+        }
+    }
+
+    pub fn layout(&self, device: &wgpu::Device) -> Arc<wgpu::BindGroupLayout> {
+        let mut layout = self.layout.lock().unwrap_or_else(PoisonError::into_inner);
+        let layout = layout.get_or_insert_with(|| {
+            Arc::new(
+                device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                    label: Some("egui_plot_attributes_bind_group_layout"),
+                    entries: &[
+                        wgpu::BindGroupLayoutEntry {
+                            binding: 2,
+                            visibility: wgpu::ShaderStages::VERTEX,
+                            ty: wgpu::BindingType::Buffer {
+                                ty: wgpu::BufferBindingType::Uniform,
+                                has_dynamic_offset: false,
+                                min_binding_size: None,
+                            },
+                            count: None,
+                        },
+                        wgpu::BindGroupLayoutEntry {
+                            binding: 3,
+                            visibility: wgpu::ShaderStages::VERTEX,
+                            ty: wgpu::BindingType::Buffer {
+                                ty: wgpu::BufferBindingType::Storage { read_only: true },
+                                has_dynamic_offset: false,
+                                min_binding_size: None,
+                            },
+                            count: None,
+                        },
+                    ],
+                }),
+            )
+        });
+        Arc::clone(layout)
+    }
+
+    // A layout for line pipelines which read attribute columns, with the
+    // columns bound after the line shader's own bind groups.
+    pub fn pipeline_layout(
+        &self,
+        device: &wgpu::Device,
+        line_layouts: [&wgpu::BindGroupLayout; 2],
+    ) -> wgpu::PipelineLayout {
+        let layout = self.layout(device);
+        device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("egui_plot_attributes_pipeline_layout"),
+            bind_group_layouts: &[line_layouts[0], line_layouts[1], &layout],
+            push_constant_ranges: &[],
+        })
+    }
+}
+
+// A plot's attribute columns, uploaded once, and which of them drive color
+// and size.
+#[cfg(feature = "gpu")]
+pub(crate) struct AttributeColumns {
+    columns: u32,
+    params: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    written: Option<Params>,
+}
+
+#[cfg(feature = "gpu")]
+impl AttributeColumns {
+    pub fn new(
+        device: &wgpu::Device,
+        pipelines: &AttributePipelines,
+        values: &[f32],
+        columns: u32,
+    ) -> AttributeColumns {
+        let layout = pipelines.layout(device);
+        // Storage bindings can't be empty.
+        let values = if values.is_empty() {
+            &[0.0][..]
+        } else {
+            values
+        };
+        let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("egui_plot_attributes"),
+            contents: bytemuck::cast_slice(values),
+            usage: wgpu::BufferUsages::STORAGE,
+        });
+        let params = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("egui_plot_attributes_params"),
+            size: std::mem::size_of::<Params>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("egui_plot_attributes_bind_group"),
+            layout: &layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: params.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        AttributeColumns {
+            columns: columns.max(1),
+            params,
+            bind_group,
+            written: None,
+        }
+    }
+
+    pub fn bind_group(&self) -> &wgpu::BindGroup {
+        &self.bind_group
+    }
+
+    // Write the selection if it changed, returning whether it did.
+    pub fn update(
+        &mut self,
+        queue: &wgpu::Queue,
+        color: Option<&ColorBy>,
+        size: Option<&SizeBy>,
+    ) -> bool {
+        let color = color.filter(|c| c.column < self.columns && !c.colormap.is_empty());
+        let size = size.filter(|s| s.column < self.columns);
+        let colormap = color.map_or(&[][..], |c| &c.colormap[..]);
+
+        let mut params = Params {
+            columns: self.columns,
+            color_column: color.map_or(NO_COLUMN, |c| c.column),
+            size_column: size.map_or(NO_COLUMN, |s| s.column),
+            stop_count: colormap.len().min(MAX_GRADIENT_STOPS) as u32,
+            color_range: color.map_or([0.0, 1.0], |c| c.range),
+            size_range: size.map_or([0.0, 1.0], |s| s.range),
+            widths: size.map_or([1.0, 1.0], |s| s.widths),
+            _padding: [0.0; 2],
+            stops: [[0.0; 4]; MAX_GRADIENT_STOPS],
+        };
+        let stops = params.stop_count as usize;
+        for (i, stop) in params.stops[..stops].iter_mut().enumerate() {
+            let t = if stops > 1 {
+                i as f32 / (stops - 1) as f32
+            } else {
+                0.0
+            };
+            *stop = colormap[(t * (colormap.len() - 1) as f32).round() as usize];
+        }
+
+        if self.written == Some(params) {
+            return false;
+        }
+        queue.write_buffer(&self.params, 0, bytemuck::bytes_of(&params));
+        self.written = Some(params);
+        true
+    }
+}
@@ -0,0 +1,252 @@
+use std::{
+    fs,
+    io::{BufReader, BufWriter, Read, Write},
+    path::Path,
+    sync::Arc,
+};
+
+use crate::{worker, CacheError, Vertex};
+
+// "EGPK", read back as something else on a machine of the other byte order.
+const MAGIC: u32 = 0x4B50_4745;
+const VERSION: u32 = 1;
+
+/// Samples per bin of the finest peak level, as in most DAWs' peak files.
+pub const DEFAULT_PEAK_BIN: u32 = 256;
+
+/// Minimum and maximum sample values at successively coarser resolutions,
+/// each level with half as many bins as the one before.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Peaks {
+    /// Samples per bin of the first level.
+    pub bin: u32,
+    /// The number of samples the peaks were built from.
+    pub samples: u64,
+    pub levels: Vec<Vec<[f32; 2]>>,
+}
+
+impl Peaks {
+    pub fn build(samples: &[f32], bin: u32) -> Peaks {
+        let bin = bin.max(1);
+        let mut levels = vec![samples
+            .chunks(bin as usize)
+            .map(|chunk| min_max(chunk.iter().map(|&s| [s, s])))
+            .collect::<Vec<_>>()];
+
+        while let Some(last) = levels.last().filter(|level| level.len() > 1) {
+            let next = last
+                .chunks(2)
+                .map(|pair| min_max(pair.iter().copied()))
+                .collect();
+            levels.push(next);
+        }
+
+        Peaks {
+            bin,
+            samples: samples.len() as u64,
+            levels,
+        }
+    }
+
+    /// Save the peaks next to the audio, so that reopening a long recording
+    /// doesn't need a pass over all of its samples. The format is a small
+    /// header followed by the raw bins, in the machine's byte order.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), CacheError> {
+        let mut writer = BufWriter::new(fs::File::create(path)?);
+
+        let header = [MAGIC, VERSION, self.bin, self.levels.len() as u32];
+        writer.write_all(bytemuck::cast_slice(&header))?;
+        writer.write_all(&self.samples.to_ne_bytes())?;
+        for level in &self.levels {
+            writer.write_all(&(level.len() as u64).to_ne_bytes())?;
+            writer.write_all(bytemuck::cast_slice(level))?;
+        }
+
+        writer.flush()?;
+        Ok(())
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Peaks, CacheError> {
+        let file = fs::File::open(path)?;
+        let mut remaining = file.metadata()?.len();
+        let mut reader = BufReader::new(file);
+
+        let mut header = [0u32; 4];
+        reader.read_exact(bytemuck::cast_slice_mut(&mut header))?;
+        let [magic, version, bin, level_count] = header;
+        if magic != MAGIC {
+            return Err(CacheError::BadMagic);
+        }
+        if version != VERSION {
+            return Err(CacheError::UnsupportedVersion(version));
+        }
+        if bin == 0 {
+            return Err(CacheError::Corrupt);
+        }
+
+        let mut samples = [0u8; 8];
+        reader.read_exact(&mut samples)?;
+        let samples = u64::from_ne_bytes(samples);
+        remaining = remaining.saturating_sub(16 + 8);
+
+        let mut levels = Vec::new();
+        let mut expected = (samples + bin as u64 - 1) / bin as u64;
+        for _ in 0..level_count {
+            let mut len = [0u8; 8];
+            reader.read_exact(&mut len)?;
+            let len = u64::from_ne_bytes(len);
+
+            // Check the size before allocating for the bins.
+            let size = 8 + len.saturating_mul(8);
+            if len != expected || size > remaining {
+                return Err(CacheError::Corrupt);
+            }
+            remaining -= size;
+
+            let mut level = vec![[0.0f32; 2]; len as usize];
+            reader.read_exact(bytemuck::cast_slice_mut(&mut level))?;
+            levels.push(level);
+            expected = (expected + 1) / 2;
+        }
+        if remaining != 0 {
+            return Err(CacheError::Corrupt);
+        }
+
+        Ok(Peaks {
+            bin,
+            samples,
+            levels,
+        })
+    }
+}
+
+/// How an [`AudioWaveform`] was drawn for a view.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WaveformDetail {
+    /// A filled band between the minimum and maximum of each bin, with
+    /// `samples_per_bin` samples in each.
+    Envelope { samples_per_bin: u32 },
+    /// A line through the individual samples.
+    Samples,
+}
+
+/// A mono PCM waveform drawn as a DAW would: a filled min/max envelope from
+/// precomputed [`Peaks`] when zoomed out, and the samples themselves once
+/// there's more than a pixel per sample. The x axis is in seconds from the
+/// first sample.
+///
+/// Only the visible part is turned into vertices, so call
+/// [`AudioWaveform::vertices`] again (and pass the points as dirty) when the
+/// bounds or size of the plot change. The envelope's vertices have zero
+/// normals, which the line shader fills solid, so don't sanitize them.
+#[derive(Clone, Debug)]
+pub struct AudioWaveform {
+    samples: Arc<[f32]>,
+    sample_rate: f32,
+    peaks: Peaks,
+}
+
+impl AudioWaveform {
+    pub fn new(samples: impl Into<Arc<[f32]>>, sample_rate: f32) -> AudioWaveform {
+        let samples = samples.into();
+        let peaks = Peaks::build(&samples, DEFAULT_PEAK_BIN);
+        AudioWaveform {
+            samples,
+            sample_rate,
+            peaks,
+        }
+    }
+
+    /// Use peaks loaded from disk instead of building them. Returns `None`
+    /// if they were built from a different number of samples.
+    pub fn with_peaks(
+        samples: impl Into<Arc<[f32]>>,
+        sample_rate: f32,
+        peaks: Peaks,
+    ) -> Option<AudioWaveform> {
+        let samples = samples.into();
+        (peaks.samples == samples.len() as u64).then_some(AudioWaveform {
+            samples,
+            sample_rate,
+            peaks,
+        })
+    }
+
+    pub fn peaks(&self) -> &Peaks {
+        &self.peaks
+    }
+
+    pub fn sample_rate(&self) -> f32 {
+        self.sample_rate
+    }
+
+    /// The length of the audio, in seconds.
+    pub fn duration(&self) -> f64 {
+        self.samples.len() as f64 / self.sample_rate as f64
+    }
+
+    /// The vertices showing the seconds `x` at `width` pixels across.
+    pub fn vertices(
+        &self,
+        x: [f64; 2],
+        width: u32,
+        color: [f32; 4],
+    ) -> (Vec<Vertex>, WaveformDetail) {
+        let rate = self.sample_rate as f64;
+        let samples_per_pixel = (x[1] - x[0]).abs() * rate / width.max(1) as f64;
+        // One sample either side, so that the line runs off the edges.
+        let len = self.samples.len();
+        let start = ((x[0].min(x[1]) * rate).floor().max(0.0) as usize)
+            .saturating_sub(1)
+            .min(len);
+        let end = (((x[0].max(x[1]) * rate).ceil().max(0.0) as usize) + 2).min(len);
+
+        if samples_per_pixel <= 1.0 {
+            let samples: Vec<_> = (start..end)
+                .map(|i| [i as f64 / rate, self.samples[i] as f64])
+                .collect();
+            return (worker::expand(&samples, color), WaveformDetail::Samples);
+        }
+
+        // The coarsest resolution that still has a bin per pixel, from the
+        // samples themselves between one sample and the first peak level.
+        let bin = samples_per_pixel.floor() as u32;
+        let (offset, samples_per_bin, bins) = if bin < self.peaks.bin {
+            let bins = self.samples[start..end]
+                .chunks(bin as usize)
+                .map(|chunk| min_max(chunk.iter().map(|&s| [s, s])))
+                .collect();
+            (start, bin, bins)
+        } else {
+            let level = (31 - (bin / self.peaks.bin).leading_zeros()) as usize;
+            let level = level.min(self.peaks.levels.len().saturating_sub(1));
+            let samples_per_bin = self.peaks.bin << level;
+            let per_bin = samples_per_bin as usize;
+            let bins = self.peaks.levels.get(level).map_or(&[][..], Vec::as_slice);
+            let first = (start / per_bin).min(bins.len());
+            let last = ((end + per_bin - 1) / per_bin).min(bins.len());
+            (first * per_bin, samples_per_bin, bins[first..last].to_vec())
+        };
+
+        let vertices = bins
+            .into_iter()
+            .enumerate()
+            .flat_map(|(i, [lo, hi])| {
+                let sample = offset + i * samples_per_bin as usize;
+                let x = (sample as f64 / rate) as f32;
+                [lo, hi].map(|y| Vertex {
+                    position: [x, y],
+                    normal: [0.0, 0.0],
+                    color,
+                })
+            })
+            .collect();
+        (vertices, WaveformDetail::Envelope { samples_per_bin })
+    }
+}
+
+fn min_max(values: impl Iterator<Item = [f32; 2]>) -> [f32; 2] {
+    values.fold([f32::MAX, f32::MIN], |[lo, hi], [a, b]| {
+        [lo.min(a), hi.max(b)]
+    })
+}
@@ -0,0 +1,497 @@
+/// The most breaks per axis that are applied when drawing.
+pub const MAX_AXIS_BREAKS: usize = 4;
+
+/// A range of values cut out of an axis, e.g. a weekend in financial data.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AxisBreak {
+    pub start: f64,
+    pub end: f64,
+}
+
+impl AxisBreak {
+    pub fn new(start: f64, end: f64) -> AxisBreak {
+        AxisBreak {
+            start: start.min(end),
+            end: start.max(end),
+        }
+    }
+
+    pub fn width(&self) -> f64 {
+        self.end - self.start
+    }
+}
+
+/// A discontinuous axis: a piecewise linear transform which collapses each
+/// break to a single point.
+///
+/// Data keeps its own units, while the bounds passed to the plot (and the
+/// host's pan, zoom and axes) are in axis units, where everything after a
+/// break is shifted down by the break's width.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AxisBreaks {
+    breaks: Vec<AxisBreak>,
+}
+
+impl AxisBreaks {
+    /// Overlapping breaks are merged, and only the first
+    /// [`MAX_AXIS_BREAKS`] are kept.
+    pub fn new(breaks: &[AxisBreak]) -> AxisBreaks {
+        let mut sorted: Vec<AxisBreak> = breaks
+            .iter()
+            .map(|b| AxisBreak::new(b.start, b.end))
+            .filter(|b| b.width() > 0.0)
+            .collect();
+        sorted.sort_by(|a, b| a.start.total_cmp(&b.start));
+
+        let mut merged: Vec<AxisBreak> = Vec::with_capacity(sorted.len());
+        for b in sorted {
+            match merged.last_mut() {
+                Some(last) if b.start <= last.end => last.end = last.end.max(b.end),
+                _ => merged.push(b),
+            }
+        }
+        merged.truncate(MAX_AXIS_BREAKS);
+
+        AxisBreaks { breaks: merged }
+    }
+
+    pub fn breaks(&self) -> &[AxisBreak] {
+        &self.breaks
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.breaks.is_empty()
+    }
+
+    /// Map a data value to axis units. Values within a break map to where
+    /// it was collapsed.
+    pub fn to_axis(&self, value: f64) -> f64 {
+        value
+            - self
+                .breaks
+                .iter()
+                .map(|b| (value - b.start).clamp(0.0, b.width()))
+                .sum::<f64>()
+    }
+
+    /// Map an axis value back to data units. Where a break was collapsed
+    /// maps to its end.
+    pub fn to_data(&self, axis: f64) -> f64 {
+        let mut value = axis;
+        for b in &self.breaks {
+            if value < b.start {
+                break;
+            }
+            value += b.width();
+        }
+        value
+    }
+
+    /// Up to about `max_ticks` evenly spaced tick values, in data units,
+    /// for the axis range `range` in axis units. Ticks are at round data
+    /// values, and never within a break. Place them with
+    /// [`AxisBreaks::to_axis`].
+    pub fn ticks(&self, range: [f64; 2], max_ticks: usize) -> Vec<f64> {
+        let [lo, hi] = [range[0].min(range[1]), range[0].max(range[1])];
+        let step = nice_step((hi - lo) / max_ticks.max(1) as f64);
+        if !step.is_finite() || step <= 0.0 {
+            return Vec::new();
+        }
+
+        // The visible pieces of the axis, in data units.
+        let (start, end) = (self.to_data(lo), self.to_data(hi));
+        let mut pieces = Vec::with_capacity(self.breaks.len() + 1);
+        let mut from = start;
+        for b in &self.breaks {
+            if b.end <= from || b.start >= end {
+                continue;
+            }
+            pieces.push([from, b.start]);
+            from = b.end;
+        }
+        pieces.push([from, end]);
+
+        pieces
+            .into_iter()
+            .flat_map(|[a, b]| {
+                let first = (a / step).ceil() as i64;
+                let last = (b / step).floor() as i64;
+                (first..=last).map(move |i| i as f64 * step)
+            })
+            .collect()
+    }
+
+    // Packed as (start, end) pairs, two to a vec4, to match the line shader.
+    // Unused breaks are empty, which the shader treats as no-ops.
+    pub(crate) fn packed(&self) -> [[f32; 4]; 2] {
+        let mut packed = [[0.0; 4]; 2];
+        for (i, b) in self.breaks.iter().enumerate() {
+            packed[i / 2][i % 2 * 2] = b.start as f32;
+            packed[i / 2][i % 2 * 2 + 1] = b.end as f32;
+        }
+        packed
+    }
+}
+
+// Map an axis value back to data units, given breaks packed for the shader.
+pub(crate) fn packed_to_data(axis: f32, packed: &[[f32; 4]; 2]) -> f32 {
+    let mut value = axis;
+    for [start, end] in packed.iter().flat_map(|p| [[p[0], p[1]], [p[2], p[3]]]) {
+        if end > start && value >= start {
+            value += end - start;
+        }
+    }
+    value
+}
+
+// The smallest 1, 2 or 5 times a power of ten at least `raw`.
+pub(crate) fn nice_step(raw: f64) -> f64 {
+    let magnitude = 10f64.powf(raw.log10().floor());
+    [1.0, 2.0, 5.0, 10.0]
+        .into_iter()
+        .map(|m| m * magnitude)
+        .find(|&step| step >= raw)
+        .unwrap_or(10.0 * magnitude)
+}
+
+/// The calendar unit a [`TimeTick`] falls on, e.g. to pick how much of the
+/// time to show in its label.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TimeUnit {
+    Subsecond,
+    Second,
+    Minute,
+    Hour,
+    Day,
+    Month,
+    Year,
+}
+
+/// A tick on a time axis, in seconds since the Unix epoch (UTC).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TimeTick {
+    pub time: f64,
+    pub unit: TimeUnit,
+}
+
+impl TimeTick {
+    /// The tick formatted with only as much of the date and time as its
+    /// unit calls for, e.g. `14:30` for minutes or `Mar 2024` for months.
+    #[cfg(feature = "chrono")]
+    pub fn label(&self) -> String {
+        let format = match self.unit {
+            TimeUnit::Subsecond => "%H:%M:%S%.3f",
+            TimeUnit::Second => "%H:%M:%S",
+            TimeUnit::Minute | TimeUnit::Hour => "%H:%M",
+            TimeUnit::Day => "%b %d",
+            TimeUnit::Month => "%b %Y",
+            TimeUnit::Year => "%Y",
+        };
+
+        let seconds = self.time.floor();
+        let nanos = ((self.time - seconds) * 1e9).round().min(999_999_999.0) as u32;
+        chrono::NaiveDateTime::from_timestamp_opt(seconds as i64, nanos)
+            .map(|time| time.format(format).to_string())
+            .unwrap_or_default()
+    }
+}
+
+const MINUTE: f64 = 60.0;
+const HOUR: f64 = 60.0 * MINUTE;
+const DAY: f64 = 24.0 * HOUR;
+
+// Steps of a fixed length, in seconds, from finest to coarsest.
+const FIXED_STEPS: [(f64, TimeUnit); 17] = [
+    (1.0, TimeUnit::Second),
+    (2.0, TimeUnit::Second),
+    (5.0, TimeUnit::Second),
+    (10.0, TimeUnit::Second),
+    (15.0, TimeUnit::Second),
+    (30.0, TimeUnit::Second),
+    (MINUTE, TimeUnit::Minute),
+    (2.0 * MINUTE, TimeUnit::Minute),
+    (5.0 * MINUTE, TimeUnit::Minute),
+    (10.0 * MINUTE, TimeUnit::Minute),
+    (15.0 * MINUTE, TimeUnit::Minute),
+    (30.0 * MINUTE, TimeUnit::Minute),
+    (HOUR, TimeUnit::Hour),
+    (3.0 * HOUR, TimeUnit::Hour),
+    (6.0 * HOUR, TimeUnit::Hour),
+    (12.0 * HOUR, TimeUnit::Hour),
+    (DAY, TimeUnit::Day),
+];
+
+// Steps of whole months, which vary in length.
+const MONTH_STEPS: [u32; 4] = [1, 3, 6, 12];
+
+/// Up to about `max_ticks` ticks on calendar boundaries (whole seconds,
+/// minutes, hours, days, months or years, in UTC) for a time axis spanning
+/// `range`, in seconds since the Unix epoch. Ranges under a few seconds
+/// fall back to round fractions of a second.
+pub fn time_ticks(range: [f64; 2], max_ticks: usize) -> Vec<TimeTick> {
+    let [lo, hi] = [range[0].min(range[1]), range[0].max(range[1])];
+    let span = hi - lo;
+    let max_ticks = max_ticks.max(1) as f64;
+    if !span.is_finite() || span <= 0.0 {
+        return Vec::new();
+    }
+
+    let fixed = |step: f64, unit: TimeUnit| {
+        let first = (lo / step).ceil() as i64;
+        let last = (hi / step).floor() as i64;
+        (first..=last)
+            .map(|i| TimeTick {
+                time: i as f64 * step,
+                unit,
+            })
+            .collect()
+    };
+
+    if span / max_ticks < 1.0 {
+        return fixed(nice_step(span / max_ticks), TimeUnit::Subsecond);
+    }
+    if let Some(&(step, unit)) = FIXED_STEPS
+        .iter()
+        .find(|(step, _)| span / step <= max_ticks)
+    {
+        return fixed(step, unit);
+    }
+
+    // Days between days and months, aligned to the days since the epoch.
+    for days in [2.0, 7.0, 14.0] {
+        if span / (days * DAY) <= max_ticks {
+            return fixed(days * DAY, TimeUnit::Day);
+        }
+    }
+
+    // About 30.4 days to a month.
+    let months = span / (DAY * 365.25 / 12.0);
+    if let Some(&step) = MONTH_STEPS
+        .iter()
+        .find(|&&step| months / step as f64 <= max_ticks)
+    {
+        let unit = if step == 12 {
+            TimeUnit::Year
+        } else {
+            TimeUnit::Month
+        };
+        return month_ticks(lo, hi, step, unit);
+    }
+
+    let years = nice_step(months / 12.0 / max_ticks).max(1.0).round() as u32;
+    month_ticks(lo, hi, years * 12, TimeUnit::Year)
+}
+
+// Ticks on the first of every `step` months (counted from year 0).
+fn month_ticks(lo: f64, hi: f64, step: u32, unit: TimeUnit) -> Vec<TimeTick> {
+    let (year, month, _) = civil_from_days((lo / DAY).floor() as i64);
+    let step = step as i64;
+    let mut index = (year * 12 + month as i64 - 1).div_euclid(step) * step;
+
+    let mut ticks = Vec::new();
+    loop {
+        let (year, month) = (index.div_euclid(12), index.rem_euclid(12) as u32 + 1);
+        let time = days_from_civil(year, month, 1) as f64 * DAY;
+        if time > hi {
+            return ticks;
+        }
+        if time >= lo {
+            ticks.push(TimeTick { time, unit });
+        }
+        index += step;
+    }
+}
+
+// Days since the Unix epoch of a proleptic Gregorian date, after
+// http://howardhinnant.github.io/date_algorithms.html.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = month as i64;
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+// The inverse of `days_from_civil`: (year, month, day).
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = (if mp < 10 { mp + 3 } else { mp - 9 }) as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overlapping_breaks_merge() {
+        let breaks = AxisBreaks::new(&[
+            AxisBreak::new(8.0, 6.0),
+            AxisBreak::new(1.0, 3.0),
+            AxisBreak::new(2.0, 4.0),
+            AxisBreak::new(4.0, 5.0),
+            AxisBreak::new(7.0, 7.0),
+        ]);
+        assert_eq!(
+            breaks.breaks(),
+            [AxisBreak::new(1.0, 5.0), AxisBreak::new(6.0, 8.0)]
+        );
+    }
+
+    #[test]
+    fn only_the_first_breaks_are_kept() {
+        let many: Vec<AxisBreak> = (0..6)
+            .map(|i| AxisBreak::new(i as f64 * 10.0, i as f64 * 10.0 + 1.0))
+            .collect();
+        let breaks = AxisBreaks::new(&many);
+        assert_eq!(breaks.breaks(), &many[..MAX_AXIS_BREAKS]);
+    }
+
+    #[test]
+    fn axis_values_round_trip() {
+        let breaks = AxisBreaks::new(&[AxisBreak::new(10.0, 20.0), AxisBreak::new(30.0, 35.0)]);
+        assert_eq!(breaks.to_axis(5.0), 5.0);
+        assert_eq!(breaks.to_axis(15.0), 10.0);
+        assert_eq!(breaks.to_axis(25.0), 15.0);
+        assert_eq!(breaks.to_axis(40.0), 25.0);
+        // Where a break was collapsed maps back to its end.
+        assert_eq!(breaks.to_data(10.0), 20.0);
+
+        for value in [-5.0, 0.0, 9.5, 20.0, 22.5, 29.0, 35.0, 100.0] {
+            assert_eq!(breaks.to_data(breaks.to_axis(value)), value);
+        }
+        for axis in [-5.0, 0.0, 9.5, 12.0, 19.5, 20.0, 50.0] {
+            assert_eq!(breaks.to_axis(breaks.to_data(axis)), axis);
+        }
+    }
+
+    #[test]
+    fn packed_breaks_map_like_to_data() {
+        let breaks = AxisBreaks::new(&[AxisBreak::new(10.0, 20.0), AxisBreak::new(30.0, 35.0)]);
+        let packed = breaks.packed();
+        for axis in [0.0, 9.5, 12.0, 20.0, 50.0] {
+            assert_eq!(
+                packed_to_data(axis as f32, &packed) as f64,
+                breaks.to_data(axis)
+            );
+        }
+    }
+
+    #[test]
+    fn ticks_stay_out_of_breaks() {
+        let breaks = AxisBreaks::new(&[AxisBreak::new(3.5, 6.5)]);
+        // 0..10 in data units.
+        let ticks = breaks.ticks([0.0, 7.0], 7);
+        assert_eq!(ticks, [0.0, 1.0, 2.0, 3.0, 7.0, 8.0, 9.0, 10.0]);
+
+        let ticks = breaks.ticks([0.0, 7.0], 3);
+        assert!(!ticks.is_empty());
+        assert!(ticks.iter().all(|&t| t <= 3.5 || t >= 6.5));
+    }
+
+    #[test]
+    fn ticks_without_breaks_are_round() {
+        let ticks = AxisBreaks::default().ticks([0.0, 1.0], 5);
+        assert_eq!(ticks.len(), 6);
+        for (i, tick) in ticks.iter().enumerate() {
+            assert!((tick - i as f64 * 0.2).abs() < 1e-12);
+        }
+        assert!(AxisBreaks::default().ticks([1.0, 1.0], 5).is_empty());
+    }
+
+    fn date(year: i64, month: u32, day: u32) -> f64 {
+        days_from_civil(year, month, day) as f64 * DAY
+    }
+
+    #[test]
+    fn civil_dates_round_trip() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(days_from_civil(1969, 12, 31), -1);
+        assert_eq!(days_from_civil(2024, 1, 1), 19723);
+        assert_eq!(days_from_civil(2024, 2, 29), 19782);
+        assert_eq!(days_from_civil(1900, 1, 1), -25567);
+        assert_eq!(civil_from_days(11016), (2000, 2, 29));
+        // 1900 isn't a leap year.
+        assert_eq!(
+            civil_from_days(days_from_civil(1900, 2, 28) + 1),
+            (1900, 3, 1)
+        );
+
+        let mut previous = civil_from_days(-150_000);
+        for days in -150_000..150_000 {
+            let (year, month, day) = civil_from_days(days);
+            assert_eq!(days_from_civil(year, month, day), days);
+            if days > -150_000 {
+                let next_day = (previous.0, previous.1, previous.2 + 1);
+                let next_month = if previous.1 == 12 {
+                    (previous.0 + 1, 1, 1)
+                } else {
+                    (previous.0, previous.1 + 1, 1)
+                };
+                assert!((year, month, day) == next_day || (year, month, day) == next_month);
+            }
+            previous = (year, month, day);
+        }
+    }
+
+    #[test]
+    fn month_ticks_cross_a_year() {
+        let ticks = time_ticks([date(2023, 10, 15), date(2024, 3, 15)], 5);
+        let expected = [
+            date(2023, 11, 1),
+            date(2023, 12, 1),
+            date(2024, 1, 1),
+            date(2024, 2, 1),
+            date(2024, 3, 1),
+        ];
+        assert_eq!(ticks.iter().map(|t| t.time).collect::<Vec<_>>(), expected);
+        assert!(ticks.iter().all(|t| t.unit == TimeUnit::Month));
+    }
+
+    #[test]
+    fn year_ticks_fall_on_new_year() {
+        let ticks = time_ticks([date(2019, 6, 1), date(2024, 6, 1)], 6);
+        let expected: Vec<f64> = (2020..=2024).map(|year| date(year, 1, 1)).collect();
+        assert_eq!(ticks.iter().map(|t| t.time).collect::<Vec<_>>(), expected);
+        assert!(ticks.iter().all(|t| t.unit == TimeUnit::Year));
+
+        // And before the epoch.
+        let ticks = time_ticks([date(1968, 6, 1), date(1972, 6, 1)], 6);
+        let expected: Vec<f64> = (1969..=1972).map(|year| date(year, 1, 1)).collect();
+        assert_eq!(ticks.iter().map(|t| t.time).collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn fixed_ticks_fall_on_whole_seconds() {
+        let ticks = time_ticks([0.0, 30.0], 10);
+        let expected = [0.0, 5.0, 10.0, 15.0, 20.0, 25.0, 30.0];
+        assert_eq!(ticks.iter().map(|t| t.time).collect::<Vec<_>>(), expected);
+        assert!(ticks.iter().all(|t| t.unit == TimeUnit::Second));
+    }
+
+    #[test]
+    fn short_ranges_fall_back_to_subseconds() {
+        let ticks = time_ticks([10.0, 10.5], 5);
+        assert_eq!(ticks.len(), 6);
+        assert!(ticks.iter().all(|t| t.unit == TimeUnit::Subsecond));
+        for (i, tick) in ticks.iter().enumerate() {
+            assert!((tick.time - (10.0 + i as f64 * 0.1)).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn empty_ranges_have_no_ticks() {
+        assert!(time_ticks([5.0, 5.0], 10).is_empty());
+        assert!(time_ticks([0.0, f64::NAN], 10).is_empty());
+    }
+}
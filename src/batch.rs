@@ -0,0 +1,74 @@
+use std::ops::Range;
+
+use crate::Vertex;
+
+/// Packs many separate polylines into one triangle strip, so that they are
+/// drawn with a single draw call.
+///
+/// Consecutive lines are joined by repeating the last vertex of one and the
+/// first vertex of the next (as [`sanitize`](crate::sanitize) does for gaps),
+/// which only forms zero-area triangles, so nothing is drawn between them.
+/// Decimation by a render budget may drop joins, so leave the budget off for
+/// batches.
+#[derive(Clone, Debug, Default)]
+pub struct LineBatch {
+    vertices: Vec<Vertex>,
+    lines: Vec<Range<u32>>,
+}
+
+impl LineBatch {
+    pub fn new() -> LineBatch {
+        LineBatch::default()
+    }
+
+    /// Reserve room for `lines` lines of `vertices_per_line` vertices each,
+    /// including the vertices joining them.
+    pub fn with_capacity(lines: usize, vertices_per_line: usize) -> LineBatch {
+        LineBatch {
+            vertices: Vec::with_capacity(lines * (vertices_per_line + 2)),
+            lines: Vec::with_capacity(lines),
+        }
+    }
+
+    /// Append a line, given as vertex pairs like any other plot data, and
+    /// return its id: the index of its range in [`LineBatch::lines`].
+    pub fn push(&mut self, line: &[Vertex]) -> usize {
+        if let (Some(&last), Some(&first)) = (self.vertices.last(), line.first()) {
+            self.vertices.push(last);
+            self.vertices.push(first);
+        }
+
+        let start = self.vertices.len() as u32;
+        self.vertices.extend_from_slice(line);
+        self.lines.push(start..self.vertices.len() as u32);
+        self.lines.len() - 1
+    }
+
+    pub fn clear(&mut self) {
+        self.vertices.clear();
+        self.lines.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    /// All of the vertices, ready to be passed to the plot.
+    pub fn vertices(&self) -> &[Vertex] {
+        &self.vertices
+    }
+
+    /// The vertex range of each line, by id, e.g. for use as a series or
+    /// subplot range.
+    pub fn lines(&self) -> &[Range<u32>] {
+        &self.lines
+    }
+
+    pub fn into_vertices(self) -> Vec<Vertex> {
+        self.vertices
+    }
+}
@@ -0,0 +1,175 @@
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    sync::Arc,
+};
+
+use bevy::{
+    prelude::*,
+    reflect::TypeUuid,
+    render::{
+        render_asset::RenderAssets,
+        render_resource::{Extent3d, TextureDimension, TextureFormat, TextureUsages},
+        renderer::{RenderDevice, RenderQueue},
+        Extract, RenderApp, RenderStage,
+    },
+};
+
+use crate::{Bounds, GpuAcceleratedPlot, GpuPlotContext, Vertex};
+
+/// The data of a plot, stored as a Bevy asset so several plots can share it.
+///
+/// The points are only uploaded again when the `Arc` is replaced, so update
+/// the series by assigning new points rather than mutating them in place.
+#[derive(TypeUuid)]
+#[uuid = "a238ffe2-c6a2-46ed-b392-a8dca45887ea"]
+pub struct PlotSeries {
+    pub points: Arc<Vec<Vertex>>,
+}
+
+/// Renders a [`PlotSeries`] into an image every frame, which can then be
+/// shown with a UI node, a sprite, or bevy_egui. Create the image with
+/// [`plot_image`].
+#[derive(Component, Clone)]
+pub struct GpuPlot {
+    pub series: Handle<PlotSeries>,
+    pub image: Handle<Image>,
+    pub bounds: Bounds,
+}
+
+/// Create an image which plots can be rendered into.
+pub fn plot_image(width: u32, height: u32) -> Image {
+    let mut image = Image::new_fill(
+        Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[0, 0, 0, 0],
+        TextureFormat::Bgra8UnormSrgb,
+    );
+    image.texture_descriptor.usage =
+        TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST | TextureUsages::RENDER_ATTACHMENT;
+    image
+}
+
+/// Adds the [`PlotSeries`] asset and renders every [`GpuPlot`] entity, using
+/// the same pipelines as the egui integration.
+pub struct GpuPlotPlugin;
+
+impl Plugin for GpuPlotPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<PlotSeries>();
+
+        if let Ok(render_app) = app.get_sub_app_mut(RenderApp) {
+            render_app
+                .init_resource::<PlotRenderers>()
+                .add_system_to_stage(RenderStage::Extract, extract_plots)
+                // Images are prepared during `RenderStage::Prepare`, so they
+                // exist by the time the plots are drawn into them.
+                .add_system_to_stage(RenderStage::Queue, render_plots);
+        }
+    }
+}
+
+#[derive(Component)]
+struct ExtractedPlot {
+    points: Arc<Vec<Vertex>>,
+    image: Handle<Image>,
+    bounds: Bounds,
+}
+
+struct RenderedPlot {
+    plot: GpuAcceleratedPlot,
+    uploaded: Arc<Vec<Vertex>>,
+}
+
+#[derive(Default)]
+struct PlotRenderers {
+    context: Option<Arc<GpuPlotContext>>,
+    plots: HashMap<Entity, RenderedPlot>,
+}
+
+fn extract_plots(
+    mut commands: Commands,
+    plots: Extract<Query<(Entity, &GpuPlot)>>,
+    series: Extract<Res<Assets<PlotSeries>>>,
+) {
+    for (entity, plot) in plots.iter() {
+        // The series may still be loading.
+        if let Some(series) = series.get(&plot.series) {
+            commands.get_or_spawn(entity).insert(ExtractedPlot {
+                points: Arc::clone(&series.points),
+                image: plot.image.clone_weak(),
+                bounds: plot.bounds,
+            });
+        }
+    }
+}
+
+fn render_plots(
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+    images: Res<RenderAssets<Image>>,
+    mut renderers: ResMut<PlotRenderers>,
+    plots: Query<(Entity, &ExtractedPlot)>,
+) {
+    let device = render_device.wgpu_device();
+    let renderers = &mut *renderers;
+
+    let context = renderers
+        .context
+        .get_or_insert_with(|| Arc::new(GpuPlotContext::new(device)));
+
+    // Forget about plots whose entities have gone away.
+    renderers.plots.retain(|entity, _| plots.contains(*entity));
+
+    for (entity, extracted) in plots.iter() {
+        let image = match images.get(&extracted.image) {
+            Some(image) => image,
+            None => continue,
+        };
+        let format = image.texture_format;
+
+        if let Some(rendered) = renderers.plots.get(&entity) {
+            if rendered.plot.target_format() != format {
+                renderers.plots.remove(&entity);
+            }
+        }
+
+        let rendered = match renderers.plots.entry(entity) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                match GpuAcceleratedPlot::with_context(device, Arc::clone(context), format, format)
+                {
+                    Ok(plot) => entry.insert(RenderedPlot {
+                        plot,
+                        uploaded: Arc::default(),
+                    }),
+                    Err(err) => {
+                        warn!("cannot render plot into {:?}: {}", format, err);
+                        continue;
+                    }
+                }
+            }
+        };
+
+        let dirty = !Arc::ptr_eq(&rendered.uploaded, &extracted.points);
+        rendered.uploaded = Arc::clone(&extracted.points);
+
+        let plot = &mut rendered.plot;
+        plot.prepare(
+            device,
+            &render_queue,
+            [image.size.x as u32, image.size.y as u32],
+            &extracted.bounds,
+            &extracted.points,
+            dirty,
+        );
+
+        if plot.needs_render() {
+            plot.render(device, &render_queue);
+            plot.copy_to_view(device, &render_queue, &image.texture_view, format);
+        }
+    }
+}
@@ -0,0 +1,80 @@
+// Shared by the histogram shader and the density coloring in the line
+// shader, which are each prefixed with this file.
+
+struct BinParams {
+    x_range: vec2<f32>,
+    y_range: vec2<f32>,
+    bins: vec2<u32>,
+    hex: u32,
+    log_scale: u32,
+    // Vertex stride and position offset, in 4 byte words.
+    stride: u32,
+    position: u32,
+    samples: u32,
+    stop_count: u32,
+    // Which of `step` interleaved passes over the samples to bin.
+    first: u32,
+    step: u32,
+    // The fraction of the counts kept when persistent counts are decayed.
+    decay: f32,
+    stops: array<vec4<f32>, 8>,
+};
+
+// Rows of hexagons are one bin apart, so squash them to the row spacing of a
+// regular hexagonal lattice when measuring distances.
+let HEX_ROW_SCALE: f32 = 0.86602540378;
+
+// Convert a data position to bin coordinates, where bins are one unit wide.
+fn bin_coords(p: BinParams, position: vec2<f32>) -> vec2<f32> {
+    let origin = vec2<f32>(p.x_range[0], p.y_range[0]);
+    let size = vec2<f32>(p.x_range[1], p.y_range[1]) - origin;
+    return (position - origin) / size * vec2<f32>(p.bins);
+}
+
+// The index of the bin containing bin coordinates `c`, or -1 outside of the
+// grid (including NaNs).
+fn bin_index(p: BinParams, c: vec2<f32>) -> i32 {
+    let bins = vec2<f32>(p.bins);
+    if (!(c.x >= 0.0 && c.x < bins.x && c.y >= 0.0 && c.y < bins.y)) {
+        return -1;
+    }
+
+    var col = i32(floor(c.x));
+    var row = i32(floor(c.y));
+
+    if (p.hex != 0u) {
+        // Even and odd rows form two rectangular lattices, offset by half a
+        // bin in each direction. The nearest center of either is the hexagon
+        // containing the point.
+        let even = vec2<f32>(floor(c.x) + 0.5, round((c.y - 0.5) / 2.0) * 2.0 + 0.5);
+        let odd = vec2<f32>(round(c.x - 1.0) + 1.0, round((c.y - 1.5) / 2.0) * 2.0 + 1.5);
+        let scale = vec2<f32>(1.0, HEX_ROW_SCALE);
+
+        var center = even;
+        if (length((c - odd) * scale) < length((c - even) * scale)) {
+            center = odd;
+        }
+
+        row = i32(round(center.y - 0.5));
+        col = i32(round(center.x - 0.5 - 0.5 * f32(row & 1)));
+    }
+
+    // Bins have a margin of one on each side for the hexagonal lattice.
+    return (row + 1) * (i32(p.bins.x) + 2) + col + 1;
+}
+
+// Map a bin count onto the colormap, relative to the largest count.
+fn bin_color(p: BinParams, count: u32, max_count: u32) -> vec4<f32> {
+    var t = f32(count) / f32(max_count);
+    if (p.log_scale != 0u) {
+        t = log2(1.0 + f32(count)) / log2(1.0 + f32(max_count));
+    }
+
+    if (p.stop_count == 1u) {
+        return p.stops[0];
+    }
+
+    let scaled = clamp(t, 0.0, 1.0) * f32(p.stop_count - 1u);
+    let i = min(u32(scaled), p.stop_count - 2u);
+    return mix(p.stops[i], p.stops[i + 1u], scaled - f32(i));
+}
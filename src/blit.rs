@@ -0,0 +1,215 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+// How a blit reads its source.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum BlitFilter {
+    // Bilinear sampling, for any change of size.
+    Linear,
+    // Texel for texel, for conversions between formats of the same size.
+    Exact,
+    // The mean of each 2x2 block of texels, summed in a fixed order, for
+    // downsampling to the next mip level.
+    Box,
+    // Texel for texel, through the sRGB transfer function from (or to)
+    // linear, for storing a texture's pixels in its sRGB (or linear)
+    // counterpart.
+    DecodeSrgb,
+    EncodeSrgb,
+}
+
+// Copies one texture onto another with a fullscreen triangle, converting
+// between formats (and later, scaling) in the process.
+pub(crate) struct Blitter {
+    shader: wgpu::ShaderModule,
+    bind_group_layout: wgpu::BindGroupLayout,
+    pipeline_layout: wgpu::PipelineLayout,
+    sampler: wgpu::Sampler,
+    pipelines: Mutex<HashMap<(wgpu::TextureFormat, BlitFilter), Arc<wgpu::RenderPipeline>>>,
+}
+
+impl Blitter {
+    pub fn new(device: &wgpu::Device) -> Blitter {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("egui_plot_blit_shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("./blit_shader.wgsl").into()),
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("egui_plot_blit_bind_group_layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("egui_plot_blit_pipeline_layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("egui_plot_blit_sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        Blitter {
+            shader,
+            bind_group_layout,
+            pipeline_layout,
+            sampler,
+            pipelines: Mutex::new(HashMap::new()),
+        }
+    }
+
+    // Make sure a pipeline exists for the destination format ahead of time,
+    // so that `blit` doesn't need access to the device.
+    pub fn prepare(&self, device: &wgpu::Device, format: wgpu::TextureFormat) {
+        self.prepare_with(device, format, BlitFilter::Linear);
+    }
+
+    pub fn prepare_with(
+        &self,
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        filter: BlitFilter,
+    ) {
+        let entry_point = match filter {
+            BlitFilter::Linear => "fs_main",
+            BlitFilter::Exact => "fs_exact",
+            // Round to the destination's steps ourselves where they're known,
+            // rather than leave it to the output merger.
+            BlitFilter::Box
+                if matches!(
+                    format,
+                    wgpu::TextureFormat::Rgba8Unorm | wgpu::TextureFormat::Bgra8Unorm
+                ) =>
+            {
+                "fs_box_unorm8"
+            }
+            BlitFilter::Box => "fs_box",
+            BlitFilter::DecodeSrgb => "fs_decode_srgb",
+            BlitFilter::EncodeSrgb => "fs_encode_srgb",
+        };
+
+        let mut pipelines = self
+            .pipelines
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+
+        pipelines.entry((format, filter)).or_insert_with(|| {
+            Arc::new(
+                device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                    label: Some("egui_plot_blit_pipeline"),
+                    layout: Some(&self.pipeline_layout),
+                    vertex: wgpu::VertexState {
+                        module: &self.shader,
+                        entry_point: "vs_main",
+                        buffers: &[],
+                    },
+                    fragment: Some(wgpu::FragmentState {
+                        module: &self.shader,
+                        entry_point,
+                        targets: &[Some(wgpu::ColorTargetState {
+                            format,
+                            blend: None,
+                            write_mask: wgpu::ColorWrites::ALL,
+                        })],
+                    }),
+                    primitive: wgpu::PrimitiveState::default(),
+                    depth_stencil: None,
+                    multisample: wgpu::MultisampleState::default(),
+                    multiview: None,
+                }),
+            )
+        });
+    }
+
+    pub fn create_bind_group(
+        &self,
+        device: &wgpu::Device,
+        source: &wgpu::TextureView,
+    ) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("egui_plot_blit_bind_group"),
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(source),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&self.sampler),
+                },
+            ],
+        })
+    }
+
+    pub fn blit(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        source: &wgpu::BindGroup,
+        destination: &wgpu::TextureView,
+        format: wgpu::TextureFormat,
+    ) {
+        self.blit_with(encoder, source, destination, format, BlitFilter::Linear);
+    }
+
+    pub fn blit_with(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        source: &wgpu::BindGroup,
+        destination: &wgpu::TextureView,
+        format: wgpu::TextureFormat,
+        filter: BlitFilter,
+    ) {
+        // Every caller prepares the format first, so there's always one.
+        let pipeline = self
+            .pipelines
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&(format, filter))
+            .cloned();
+        let pipeline = match pipeline {
+            Some(pipeline) => pipeline,
+            None => return,
+        };
+
+        let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("egui_plot_blit_pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: destination,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: None,
+        });
+
+        rpass.set_pipeline(&pipeline);
+        rpass.set_bind_group(0, source, &[]);
+        rpass.draw(0..3, 0..1);
+    }
+}
@@ -0,0 +1,87 @@
+struct VertexOut {
+    @location(0) uv: vec2<f32>,
+    @builtin(position) position: vec4<f32>,
+};
+
+@group(0) @binding(0)
+var source_texture: texture_2d<f32>;
+@group(0) @binding(1)
+var source_sampler: sampler;
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOut {
+    var out: VertexOut;
+
+    // Generate a single triangle covering the whole viewport. The UVs run
+    // 0..1 over the visible region and are flipped vertically since texture
+    // space starts at the top-left.
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+
+    out.uv = uv;
+    out.position = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOut) -> @location(0) vec4<f32> {
+    return textureSample(source_texture, source_sampler, in.uv);
+}
+
+// Texel for texel, without the sampler's filtering.
+@fragment
+fn fs_exact(in: VertexOut) -> @location(0) vec4<f32> {
+    return textureLoad(source_texture, vec2<i32>(in.position.xy), 0);
+}
+
+// The mean of the 2x2 texels under a pixel of the next mip level down,
+// clamped at the edges of odd sized levels and summed in a fixed order.
+fn box_mean(position: vec2<f32>) -> vec4<f32> {
+    let last = textureDimensions(source_texture) - vec2<i32>(1, 1);
+    let texel = vec2<i32>(position) * 2;
+    let a = textureLoad(source_texture, min(texel, last), 0);
+    let b = textureLoad(source_texture, min(texel + vec2<i32>(1, 0), last), 0);
+    let c = textureLoad(source_texture, min(texel + vec2<i32>(0, 1), last), 0);
+    let d = textureLoad(source_texture, min(texel + vec2<i32>(1, 1), last), 0);
+    return ((a + b) + (c + d)) * 0.25;
+}
+
+@fragment
+fn fs_box(in: VertexOut) -> @location(0) vec4<f32> {
+    return box_mean(in.position.xy);
+}
+
+// Rounds halves up to the nearest 8 bit step, so that the conversion to the
+// destination has nothing left to round.
+@fragment
+fn fs_box_unorm8(in: VertexOut) -> @location(0) vec4<f32> {
+    return floor(box_mean(in.position.xy) * 255.0 + 0.5) / 255.0;
+}
+
+fn srgb_to_linear(c: vec3<f32>) -> vec3<f32> {
+    let low = c / 12.92;
+    let high = pow((c + 0.055) / 1.055, vec3<f32>(2.4));
+    return select(high, low, c <= vec3<f32>(0.04045));
+}
+
+fn linear_to_srgb(c: vec3<f32>) -> vec3<f32> {
+    let low = c * 12.92;
+    let high = 1.055 * pow(c, vec3<f32>(1.0 / 2.4)) - 0.055;
+    return select(high, low, c <= vec3<f32>(0.0031308));
+}
+
+// Texel for texel, with the transfer function undone, so that writing to an
+// sRGB destination stores the source's values unchanged.
+@fragment
+fn fs_decode_srgb(in: VertexOut) -> @location(0) vec4<f32> {
+    let texel = textureLoad(source_texture, vec2<i32>(in.position.xy), 0);
+    return vec4<f32>(srgb_to_linear(clamp(texel.rgb, vec3<f32>(0.0), vec3<f32>(1.0))), texel.a);
+}
+
+// Texel for texel, with the transfer function applied, so that a linear
+// destination stores what an sRGB one would have.
+@fragment
+fn fs_encode_srgb(in: VertexOut) -> @location(0) vec4<f32> {
+    let texel = textureLoad(source_texture, vec2<i32>(in.position.xy), 0);
+    return vec4<f32>(linear_to_srgb(clamp(texel.rgb, vec3<f32>(0.0), vec3<f32>(1.0))), texel.a);
+}
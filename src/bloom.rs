@@ -0,0 +1,315 @@
+#[cfg(feature = "gpu")]
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+#[cfg(feature = "gpu")]
+use wgpu::util::DeviceExt;
+
+#[cfg(feature = "gpu")]
+use crate::PingPong;
+
+/// How glowing series are blurred and added back on top of the plot.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Glow {
+    /// Brightness of the glow added on top of the plot.
+    pub intensity: f32,
+    /// Spread of the blur, in pixels per tap.
+    pub radius: f32,
+}
+
+impl Default for Glow {
+    fn default() -> Self {
+        Glow {
+            intensity: 1.5,
+            radius: 1.5,
+        }
+    }
+}
+
+#[cfg(feature = "gpu")]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct Params {
+    step: [f32; 2],
+    intensity: f32,
+    _padding: f32,
+}
+
+#[cfg(feature = "gpu")]
+struct Pipelines {
+    blur: wgpu::RenderPipeline,
+    composite: wgpu::RenderPipeline,
+}
+
+// The blur and composite pipelines, shared between plots like the line
+// pipelines.
+#[cfg(feature = "gpu")]
+pub(crate) struct BloomPipelines {
+    shader: wgpu::ShaderModule,
+    bind_group_layout: wgpu::BindGroupLayout,
+    pipeline_layout: wgpu::PipelineLayout,
+    sampler: wgpu::Sampler,
+    pipelines: Mutex<HashMap<wgpu::TextureFormat, Arc<Pipelines>>>,
+}
+
+#[cfg(feature = "gpu")]
+impl BloomPipelines {
+    pub fn new(device: &wgpu::Device) -> BloomPipelines {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("egui_plot_bloom_shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("./bloom_shader.wgsl").into()),
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("egui_plot_bloom_bind_group_layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("egui_plot_bloom_pipeline_layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("egui_plot_bloom_sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        BloomPipelines {
+            shader,
+            bind_group_layout,
+            pipeline_layout,
+            sampler,
+            pipelines: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn pipelines(&self, device: &wgpu::Device, format: wgpu::TextureFormat) -> Arc<Pipelines> {
+        let create = |entry_point, blend| {
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("egui_plot_bloom_pipeline"),
+                layout: Some(&self.pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &self.shader,
+                    entry_point: "vs_main",
+                    buffers: &[],
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &self.shader,
+                    entry_point,
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format,
+                        blend,
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                }),
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                multiview: None,
+            })
+        };
+
+        let additive = wgpu::BlendComponent {
+            src_factor: wgpu::BlendFactor::One,
+            dst_factor: wgpu::BlendFactor::One,
+            operation: wgpu::BlendOperation::Add,
+        };
+
+        let mut pipelines = self
+            .pipelines
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let pipelines = pipelines.entry(format).or_insert_with(|| {
+            Arc::new(Pipelines {
+                blur: create("fs_blur", None),
+                composite: create(
+                    "fs_composite",
+                    Some(wgpu::BlendState {
+                        color: additive,
+                        alpha: additive,
+                    }),
+                ),
+            })
+        });
+
+        Arc::clone(pipelines)
+    }
+}
+
+// Intermediate targets for glowing series. The series are drawn into the
+// current target, blurred horizontally into the previous one and vertically
+// back, which is then added on top of the plot. The targets are never
+// swapped, so the bind groups stay valid.
+#[cfg(feature = "gpu")]
+pub(crate) struct Bloom {
+    pipelines: Arc<Pipelines>,
+    targets: PingPong,
+    params: [wgpu::Buffer; 3],
+    bind_groups: [wgpu::BindGroup; 3],
+    style: Glow,
+}
+
+#[cfg(feature = "gpu")]
+impl Bloom {
+    pub fn new(
+        device: &wgpu::Device,
+        shared: &BloomPipelines,
+        format: wgpu::TextureFormat,
+        size: [u32; 2],
+        style: Glow,
+    ) -> Bloom {
+        let targets = PingPong::new(device, format, size);
+        let (glow, scratch) = (targets.current(), targets.previous());
+
+        let params = Self::params(size, style).map(|params| {
+            device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("egui_plot_bloom_params"),
+                contents: bytemuck::bytes_of(&params),
+                usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::UNIFORM,
+            })
+        });
+
+        let bind_group = |source: &wgpu::TextureView, params: &wgpu::Buffer| {
+            device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("egui_plot_bloom_bind_group"),
+                layout: &shared.bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(source),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::Sampler(&shared.sampler),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: params.as_entire_binding(),
+                    },
+                ],
+            })
+        };
+        let bind_groups = [
+            bind_group(glow, &params[0]),
+            bind_group(scratch, &params[1]),
+            bind_group(glow, &params[2]),
+        ];
+
+        Bloom {
+            pipelines: shared.pipelines(device, format),
+            targets,
+            params,
+            bind_groups,
+            style,
+        }
+    }
+
+    // Horizontal blur, vertical blur, and composite parameters.
+    fn params(size: [u32; 2], style: Glow) -> [Params; 3] {
+        let step = |x: f32, y: f32| Params {
+            step: [x, y],
+            intensity: style.intensity,
+            _padding: 0.0,
+        };
+
+        [
+            step(style.radius / size[0].max(1) as f32, 0.0),
+            step(0.0, style.radius / size[1].max(1) as f32),
+            step(0.0, 0.0),
+        ]
+    }
+
+    pub fn is_compatible(&self, format: wgpu::TextureFormat, size: [u32; 2]) -> bool {
+        self.targets.is_compatible(format, size)
+    }
+
+    // Returns whether the style changed.
+    pub fn set_style(&mut self, queue: &wgpu::Queue, style: Glow) -> bool {
+        if style == self.style {
+            return false;
+        }
+
+        self.style = style;
+        let size = self.targets.size();
+        for (buffer, params) in self.params.iter().zip(Self::params(size, style)) {
+            queue.write_buffer(buffer, 0, bytemuck::bytes_of(&params));
+        }
+        true
+    }
+
+    // The attachment glowing series are drawn into, before blurring.
+    pub fn color_attachment(&self) -> wgpu::RenderPassColorAttachment<'_> {
+        wgpu::RenderPassColorAttachment {
+            view: self.targets.current(),
+            resolve_target: None,
+            ops: wgpu::Operations {
+                load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                store: true,
+            },
+        }
+    }
+
+    // Blur the glow and add it on top of `destination`.
+    pub fn finish(&self, encoder: &mut wgpu::CommandEncoder, destination: &wgpu::TextureView) {
+        let passes = [
+            (self.targets.previous(), &self.pipelines.blur, true),
+            (self.targets.current(), &self.pipelines.blur, true),
+            (destination, &self.pipelines.composite, false),
+        ];
+
+        for ((view, pipeline, clear), bind_group) in passes.into_iter().zip(&self.bind_groups) {
+            let load = if clear {
+                wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT)
+            } else {
+                wgpu::LoadOp::Load
+            };
+
+            let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("egui_plot_bloom_pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view,
+                    resolve_target: None,
+                    ops: wgpu::Operations { load, store: true },
+                })],
+                depth_stencil_attachment: None,
+            });
+
+            rpass.set_pipeline(pipeline);
+            rpass.set_bind_group(0, bind_group, &[]);
+            rpass.draw(0..3, 0..1);
+        }
+    }
+}
@@ -0,0 +1,53 @@
+struct VertexOut {
+    @location(0) uv: vec2<f32>,
+    @builtin(position) position: vec4<f32>,
+};
+
+struct Params {
+    // Distance between blur taps, in UV units, along the blur direction.
+    step: vec2<f32>,
+    intensity: f32,
+    _padding: f32,
+};
+
+@group(0) @binding(0)
+var source_texture: texture_2d<f32>;
+@group(0) @binding(1)
+var source_sampler: sampler;
+@group(0) @binding(2)
+var<uniform> params: Params;
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOut {
+    var out: VertexOut;
+
+    // A single triangle covering the whole viewport, as in the blit shader.
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+
+    out.uv = uv;
+    out.position = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+
+    return out;
+}
+
+// One direction of a 9-tap Gaussian blur, using 5 bilinear samples placed
+// between texels to get two taps from each.
+@fragment
+fn fs_blur(in: VertexOut) -> @location(0) vec4<f32> {
+    let near = params.step * 1.3846153846;
+    let far = params.step * 3.2307692308;
+
+    var color = textureSample(source_texture, source_sampler, in.uv) * 0.2270270270;
+    color = color + textureSample(source_texture, source_sampler, in.uv + near) * 0.3162162162;
+    color = color + textureSample(source_texture, source_sampler, in.uv - near) * 0.3162162162;
+    color = color + textureSample(source_texture, source_sampler, in.uv + far) * 0.0702702703;
+    color = color + textureSample(source_texture, source_sampler, in.uv - far) * 0.0702702703;
+
+    return color;
+}
+
+// Added on top of the plot by the composite pipeline's blend state.
+@fragment
+fn fs_composite(in: VertexOut) -> @location(0) vec4<f32> {
+    return textureSample(source_texture, source_sampler, in.uv) * params.intensity;
+}
@@ -0,0 +1,226 @@
+use crate::{worker, LineBatch, StackedRows};
+
+/// How a [`Bode`] plot shows phase.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PhaseMode {
+    /// Within -180..180 degrees, with the line broken where it wraps.
+    #[default]
+    Wrapped,
+    /// Continuous, adding or subtracting whole turns where the phase jumps
+    /// by more than half of one.
+    Unwrapped,
+}
+
+/// A frequency response shown as a Bode plot: magnitude in decibels above
+/// phase in degrees, as two [`StackedRows`] sharing a logarithmic frequency
+/// axis. The x axis of both is `log10` of the frequency, so that the rows
+/// can be rendered as ordinary subplots; label it with
+/// [`Bode::decade_ticks`], or show it with `bode_plot` with the `egui`
+/// feature.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Bode {
+    frequencies: Vec<f64>,
+    magnitude: Vec<f64>,
+    phase: Vec<f64>,
+    pub phase_mode: PhaseMode,
+    pub magnitude_color: [f32; 4],
+    pub phase_color: [f32; 4],
+    /// Gap between the rows, in pixels.
+    pub spacing: u32,
+}
+
+/// The response at one frequency of a [`Bode`] plot, e.g. under the cursor.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BodeReadout {
+    pub frequency: f64,
+    pub magnitude_db: f64,
+    pub phase_degrees: f64,
+}
+
+impl Bode {
+    /// The response at each of `frequencies` (ascending, and positive),
+    /// as complex `[real, imaginary]` values.
+    pub fn new(frequencies: &[f64], response: &[[f64; 2]]) -> Bode {
+        let (frequencies, response): (Vec<_>, Vec<_>) = frequencies
+            .iter()
+            .zip(response)
+            .filter(|(f, _)| **f > 0.0)
+            .map(|(&f, &[re, im])| (f, [20.0 * re.hypot(im).log10(), im.atan2(re).to_degrees()]))
+            .unzip();
+
+        Bode {
+            frequencies,
+            magnitude: response.iter().map(|r| r[0]).collect(),
+            phase: response.iter().map(|r| r[1]).collect(),
+            phase_mode: PhaseMode::Wrapped,
+            magnitude_color: [0.3, 0.6, 1.0, 1.0],
+            phase_color: [1.0, 0.6, 0.2, 1.0],
+            spacing: 8,
+        }
+    }
+
+    pub fn with_phase_mode(self, phase_mode: PhaseMode) -> Bode {
+        Bode { phase_mode, ..self }
+    }
+
+    pub fn with_colors(self, magnitude: [f32; 4], phase: [f32; 4]) -> Bode {
+        Bode {
+            magnitude_color: magnitude,
+            phase_color: phase,
+            ..self
+        }
+    }
+
+    pub fn with_spacing(self, spacing: u32) -> Bode {
+        Bode { spacing, ..self }
+    }
+
+    pub fn frequencies(&self) -> &[f64] {
+        &self.frequencies
+    }
+
+    /// The phase in degrees at each frequency, as shown.
+    pub fn phase(&self) -> Vec<f64> {
+        match self.phase_mode {
+            PhaseMode::Wrapped => self.phase.clone(),
+            PhaseMode::Unwrapped => {
+                let mut turns = 0.0;
+                let mut unwrapped = Vec::with_capacity(self.phase.len());
+                for (i, &phase) in self.phase.iter().enumerate() {
+                    if i > 0 {
+                        let jump = phase - self.phase[i - 1];
+                        turns -= (jump / 360.0).round();
+                    }
+                    unwrapped.push(phase + 360.0 * turns);
+                }
+                unwrapped
+            }
+        }
+    }
+
+    /// The magnitude in decibels at each frequency.
+    pub fn magnitude(&self) -> &[f64] {
+        &self.magnitude
+    }
+
+    /// The vertices of both rows, to be uploaded as the plot's points: the
+    /// magnitude first, then the phase.
+    pub fn vertices(&self) -> LineBatch {
+        let x: Vec<_> = self.frequencies.iter().map(|f| f.log10()).collect();
+        let mut batch = LineBatch::new();
+
+        let magnitude: Vec<_> = x
+            .iter()
+            .zip(&self.magnitude)
+            .map(|(&x, &y)| [x, y])
+            .collect();
+        batch.push(&worker::expand(&magnitude, self.magnitude_color));
+
+        // Each run of wrapped phase between jumps is a line of its own.
+        let phase = self.phase();
+        let mut start = 0;
+        for end in 1..=phase.len() {
+            let wraps = end == phase.len() || (phase[end] - phase[end - 1]).abs() > 180.0;
+            if wraps {
+                let run: Vec<_> = (start..end).map(|i| [x[i], phase[i]]).collect();
+                if !run.is_empty() {
+                    batch.push(&worker::expand(&run, self.phase_color));
+                }
+                start = end;
+            }
+        }
+
+        batch
+    }
+
+    /// The rows to render `batch` (as returned by [`Bode::vertices`]) with,
+    /// each fit to its values.
+    pub fn rows(&self, batch: &LineBatch) -> StackedRows {
+        let lines = batch.lines();
+        let x = match (self.frequencies.first(), self.frequencies.last()) {
+            (Some(lo), Some(hi)) if hi > lo => [lo.log10(), hi.log10()],
+            (Some(f), _) => [f.log10() - 0.5, f.log10() + 0.5],
+            _ => [0.0, 1.0],
+        };
+
+        let mut rows = StackedRows::new(x).with_spacing(self.spacing);
+        let magnitude = lines.first().map_or(0..0, |line| line.clone());
+        let phase_start = lines.get(1).map_or(magnitude.end, |line| line.start);
+        let phase_end = lines.last().map_or(phase_start, |line| line.end);
+
+        rows.push(padded(&self.magnitude, [-1.0, 1.0]), magnitude);
+        let phase_range = match self.phase_mode {
+            PhaseMode::Wrapped => [-180.0, 180.0],
+            PhaseMode::Unwrapped => padded(&self.phase(), [-180.0, 180.0]),
+        };
+        rows.push(phase_range, phase_start..phase_end);
+        rows
+    }
+
+    /// The response at `x`, in the rows' `log10` frequency units,
+    /// interpolated between the nearest frequencies.
+    pub fn readout(&self, x: f64) -> Option<BodeReadout> {
+        let frequency = 10f64.powf(x);
+        let (first, last) = (*self.frequencies.first()?, *self.frequencies.last()?);
+        if !(first..=last).contains(&frequency) {
+            return None;
+        }
+
+        let last_index = self.frequencies.len() - 1;
+        let i = self
+            .frequencies
+            .partition_point(|&f| f < frequency)
+            .clamp(1.min(last_index), last_index);
+        let j = i.saturating_sub(1);
+        let [a, b] = [self.frequencies[j].log10(), self.frequencies[i].log10()];
+        let t = if b > a { (x - a) / (b - a) } else { 0.0 };
+        let lerp = |values: &[f64]| values[j] + (values[i] - values[j]) * t;
+
+        Some(BodeReadout {
+            frequency,
+            magnitude_db: lerp(&self.magnitude),
+            phase_degrees: lerp(&self.phase()),
+        })
+    }
+
+    /// Ticks for the frequency axis, in `log10` units: at every decade, or
+    /// also at 2 and 5 times each when the range covers few of them.
+    pub fn decade_ticks(x: [f64; 2]) -> Vec<f64> {
+        let [lo, hi] = [x[0].min(x[1]), x[0].max(x[1])];
+        if !lo.is_finite() || !hi.is_finite() {
+            return Vec::new();
+        }
+
+        let steps: &[f64] = if hi - lo < 2.0 {
+            &[1.0, 2.0, 5.0]
+        } else {
+            &[1.0]
+        };
+        let mut ticks = Vec::new();
+        for decade in lo.floor() as i32..=hi.ceil() as i32 {
+            for step in steps {
+                let tick = decade as f64 + step.log10();
+                if (lo..=hi).contains(&tick) {
+                    ticks.push(tick);
+                }
+            }
+        }
+        ticks
+    }
+}
+
+// The range of `values` with a tenth of it either side, or `fallback`.
+fn padded(values: &[f64], fallback: [f64; 2]) -> [f64; 2] {
+    let range = values
+        .iter()
+        .filter(|v| v.is_finite())
+        .fold(None::<[f64; 2]>, |range, &v| match range {
+            None => Some([v, v]),
+            Some([lo, hi]) => Some([f64::min(lo, v), f64::max(hi, v)]),
+        });
+    match range {
+        Some([lo, hi]) if hi > lo => [lo - (hi - lo) * 0.1, hi + (hi - lo) * 0.1],
+        Some([v, _]) => [v - 1.0, v + 1.0],
+        None => fallback,
+    }
+}
@@ -0,0 +1,112 @@
+use crate::ZoomAxes;
+
+/// The region of data space shown by a plot or viewport.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Bounds {
+    pub x: [f64; 2],
+    pub y: [f64; 2],
+}
+
+impl Bounds {
+    pub fn new(x: [f64; 2], y: [f64; 2]) -> Bounds {
+        Bounds { x, y }
+    }
+
+    pub fn from_min_max(min: [f64; 2], max: [f64; 2]) -> Bounds {
+        Bounds {
+            x: [min[0], max[0]],
+            y: [min[1], max[1]],
+        }
+    }
+
+    pub fn min(&self) -> [f64; 2] {
+        [self.x[0], self.y[0]]
+    }
+
+    pub fn max(&self) -> [f64; 2] {
+        [self.x[1], self.y[1]]
+    }
+
+    pub fn width(&self) -> f64 {
+        self.x[1] - self.x[0]
+    }
+
+    pub fn height(&self) -> f64 {
+        self.y[1] - self.y[0]
+    }
+
+    /// Whether the bounds are finite and span some of each axis.
+    pub fn is_valid(&self) -> bool {
+        let finite = self.x.iter().chain(&self.y).all(|v| v.is_finite());
+        finite && self.width() != 0.0 && self.height() != 0.0
+    }
+
+    /// Reverse either axis, e.g. y for depth profiles or x for wavelength
+    /// spectra, by ordering its bounds from high to low. Widths and heights
+    /// are then negative.
+    pub fn with_reversed(self, x: bool, y: bool) -> Bounds {
+        let orient = |[a, b]: [f64; 2], reversed: bool| {
+            if reversed {
+                [a.max(b), a.min(b)]
+            } else {
+                [a.min(b), a.max(b)]
+            }
+        };
+        Bounds {
+            x: orient(self.x, x),
+            y: orient(self.y, y),
+        }
+    }
+
+    /// Grow the bounds about their center so that, shown `size` pixels
+    /// large, a unit of x is `ratio` times as many pixels as a unit of y.
+    /// With a ratio of one, circles stay circles.
+    pub fn with_data_aspect(self, ratio: f64, size: [u32; 2]) -> Bounds {
+        let [sx, sy] = size.map(|s| s.max(1) as f64);
+        let (w, h) = (self.width().abs(), self.height().abs());
+        if ratio <= 0.0 || w == 0.0 || h == 0.0 {
+            return self;
+        }
+
+        let (w, h) = if (sx / w) / (sy / h) > ratio {
+            (sx * h / (sy * ratio), h)
+        } else {
+            (w, ratio * sy * w / sx)
+        };
+
+        // Keep the direction of reversed axes.
+        let [cx, cy] = self.center();
+        let half_w = w / 2.0 * self.width().signum();
+        let half_h = h / 2.0 * self.height().signum();
+        Bounds {
+            x: [cx - half_w, cx + half_w],
+            y: [cy - half_h, cy + half_h],
+        }
+    }
+
+    pub fn is_reversed(&self) -> [bool; 2] {
+        [self.x[0] > self.x[1], self.y[0] > self.y[1]]
+    }
+
+    pub fn center(&self) -> [f64; 2] {
+        [(self.x[0] + self.x[1]) / 2.0, (self.y[0] + self.y[1]) / 2.0]
+    }
+
+    /// Zoom in by `factor` (or out, below one) around `center`, keeping it
+    /// in place, along the axes picked by `axes`.
+    pub fn zoomed(self, center: [f64; 2], factor: f64, axes: ZoomAxes) -> Bounds {
+        let zoom = |[a, b]: [f64; 2], c: f64| [c + (a - c) / factor, c + (b - c) / factor];
+        Bounds {
+            x: if axes.x() {
+                zoom(self.x, center[0])
+            } else {
+                self.x
+            },
+            y: if axes.y() {
+                zoom(self.y, center[1])
+            } else {
+                self.y
+            },
+        }
+    }
+}
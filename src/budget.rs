@@ -0,0 +1,442 @@
+use std::{
+    ops::Range,
+    time::{Duration, Instant},
+};
+
+use crate::Vertex;
+
+// Frames which take much longer than the budget are assumed to be the UI
+// sitting idle (e.g. waiting for input), rather than slow rendering.
+const IDLE_FACTOR: f32 = 4.0;
+// How quickly the average frame time follows new measurements.
+const SMOOTHING: f32 = 0.1;
+// Frames to wait after changing the decimation before judging it again.
+const SETTLE_FRAMES: u32 = 10;
+// Consecutive comfortable frames required before restoring detail.
+const RECOVER_FRAMES: u32 = 60;
+
+/// A target frame time for a plot. While frames take longer than this, the
+/// plot draws fewer vertices (by doubling its decimation factor), and it
+/// restores detail once there is enough headroom again.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RenderBudget {
+    pub frame_time: Duration,
+    /// The largest decimation factor the plot will fall back to.
+    pub max_decimation: u32,
+}
+
+impl RenderBudget {
+    pub fn new(frame_time: Duration) -> RenderBudget {
+        RenderBudget {
+            frame_time,
+            max_decimation: 64,
+        }
+    }
+
+    pub fn with_max_decimation(self, max_decimation: u32) -> RenderBudget {
+        RenderBudget {
+            max_decimation: max_decimation.max(1),
+            ..self
+        }
+    }
+}
+
+pub(crate) struct BudgetController {
+    budget: RenderBudget,
+    last_frame: Option<Instant>,
+    average: Option<f32>,
+    decimation: u32,
+    settle: u32,
+    comfortable: u32,
+}
+
+impl BudgetController {
+    pub fn new(budget: RenderBudget) -> BudgetController {
+        BudgetController {
+            budget,
+            last_frame: None,
+            average: None,
+            decimation: 1,
+            settle: 0,
+            comfortable: 0,
+        }
+    }
+
+    pub fn decimation(&self) -> u32 {
+        self.decimation
+    }
+
+    // Record the start of a new frame, returning true if the decimation
+    // factor changed and the data needs to be uploaded again.
+    pub fn frame(&mut self, now: Instant) -> bool {
+        let budget = self.budget.frame_time.as_secs_f32();
+        let elapsed = self
+            .last_frame
+            .replace(now)
+            .map(|last| now.duration_since(last).as_secs_f32());
+
+        let elapsed = match elapsed {
+            Some(elapsed) if elapsed < budget * IDLE_FACTOR => elapsed,
+            _ => return false,
+        };
+
+        let average = match self.average {
+            Some(average) => average + (elapsed - average) * SMOOTHING,
+            None => elapsed,
+        };
+        self.average = Some(average);
+
+        if self.settle > 0 {
+            self.settle -= 1;
+            return false;
+        }
+
+        if average > budget && self.decimation < self.budget.max_decimation {
+            self.decimation = (self.decimation * 2).min(self.budget.max_decimation);
+        } else if average < budget * 0.5 && self.decimation > 1 {
+            self.comfortable += 1;
+            if self.comfortable < RECOVER_FRAMES {
+                return false;
+            }
+            self.decimation /= 2;
+        } else {
+            self.comfortable = 0;
+            return false;
+        }
+
+        self.average = None;
+        self.settle = SETTLE_FRAMES;
+        self.comfortable = 0;
+        true
+    }
+}
+
+// Keep every `factor`th point. Vertices come in pairs (one per side of the
+// line), so whole pairs are kept.
+pub(crate) fn decimate(points: &[Vertex], factor: usize) -> Vec<Vertex> {
+    (0..points.len() / 2)
+        .step_by(factor.max(1))
+        .flat_map(|i| [points[2 * i], points[2 * i + 1]])
+        .collect()
+}
+
+/// How points over a plot's [`VertexBudget`] are thinned to fit.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DecimationMode {
+    /// Keep every nth point.
+    #[default]
+    Stride,
+    /// Keep the lowest and highest point of every run of 2n, in the order
+    /// they occurred, so that spikes survive.
+    MinMax,
+}
+
+/// The most vertices a plot uploads. Past it, the points of each series
+/// are decimated to fit rather than any of them being dropped, and the
+/// factor used is reported in
+/// [`FrameDiagnostics::decimation`](crate::FrameDiagnostics::decimation).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VertexBudget {
+    pub max_vertices: usize,
+    pub mode: DecimationMode,
+}
+
+impl VertexBudget {
+    pub fn new(max_vertices: usize) -> VertexBudget {
+        VertexBudget {
+            max_vertices: max_vertices.max(4),
+            mode: DecimationMode::Stride,
+        }
+    }
+
+    pub fn with_mode(self, mode: DecimationMode) -> VertexBudget {
+        VertexBudget { mode, ..self }
+    }
+
+    // The factor to decimate `len` vertices by, split into `segments` which
+    // are each decimated separately, to fit in the budget.
+    pub(crate) fn decimation(&self, len: usize, segments: usize) -> u32 {
+        let pairs = len / 2;
+        // Each segment can round up by a pair, or two for min/max.
+        let available = (self.max_vertices / 2).saturating_sub(2 * segments).max(1);
+        ((pairs + available - 1) / available).max(1) as u32
+    }
+}
+
+impl Default for VertexBudget {
+    fn default() -> VertexBudget {
+        VertexBudget::new(crate::MAX_POINTS)
+    }
+}
+
+// Where the vertices of a decimated upload came from: each segment of the
+// original vertices, and the range it was decimated into.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct VertexMap {
+    segments: Vec<(Range<u32>, Range<u32>)>,
+}
+
+impl VertexMap {
+    // The uploaded vertices covering `vertices` of the original ones. Exact
+    // at segment boundaries (e.g. where series start and end), and
+    // proportional within segments.
+    pub fn map(&self, vertices: &Range<u32>) -> Range<u32> {
+        self.map_index(vertices.start)..self.map_index(vertices.end)
+    }
+
+    fn map_index(&self, index: u32) -> u32 {
+        let i = self
+            .segments
+            .partition_point(|(original, _)| original.end < index);
+        match self.segments.get(i) {
+            Some((original, uploaded)) => {
+                let t = (index.max(original.start) - original.start) as f64
+                    / (original.end - original.start).max(1) as f64;
+                let offset = (t * (uploaded.end - uploaded.start) as f64 / 2.0).round() as u32;
+                (uploaded.start + 2 * offset).min(uploaded.end)
+            }
+            None => self.segments.last().map_or(0, |(_, uploaded)| uploaded.end),
+        }
+    }
+}
+
+// Decimate `points` by `factor`, separately between each of `boundaries`
+// (vertex indices, e.g. where series start and end), so that no segment
+// loses its points to its neighbours.
+pub(crate) fn decimate_segments(
+    points: &[Vertex],
+    boundaries: &[u32],
+    factor: u32,
+    mode: DecimationMode,
+) -> (Vec<Vertex>, VertexMap) {
+    let len = (points.len() & !1) as u32;
+    let mut cuts: Vec<u32> = boundaries.iter().map(|&b| b.min(len) & !1).collect();
+    cuts.extend([0, len]);
+    cuts.sort_unstable();
+    cuts.dedup();
+
+    let mut decimated = Vec::with_capacity(points.len() / factor.max(1) as usize + cuts.len() * 4);
+    let mut map = VertexMap::default();
+    for cut in cuts.windows(2) {
+        let segment = &points[cut[0] as usize..cut[1] as usize];
+        let start = decimated.len() as u32;
+        match mode {
+            DecimationMode::Stride => decimated.extend(decimate(segment, factor as usize)),
+            DecimationMode::MinMax => {
+                decimate_min_max(&mut decimated, segment, 2 * factor as usize)
+            }
+        }
+        map.segments
+            .push((cut[0]..cut[1], start..decimated.len() as u32));
+    }
+    (decimated, map)
+}
+
+// Keep the lowest and highest pair of each run of `run` pairs, in order.
+fn decimate_min_max(decimated: &mut Vec<Vertex>, points: &[Vertex], run: usize) {
+    let pairs: Vec<_> = points.chunks_exact(2).collect();
+    for bucket in pairs.chunks(run.max(1)) {
+        let y = |pair: &&[Vertex]| pair[0].position[1];
+        let (mut min, mut max) = (0, 0);
+        for (i, pair) in bucket.iter().enumerate() {
+            if y(pair) < y(&bucket[min]) {
+                min = i;
+            }
+            if y(pair) > y(&bucket[max]) {
+                max = i;
+            }
+        }
+        let (first, last) = (min.min(max), min.max(max));
+        decimated.extend_from_slice(bucket[first]);
+        if last != first {
+            decimated.extend_from_slice(bucket[last]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A pair of vertices at x = i for each of `ys`.
+    fn pairs(ys: &[f32]) -> Vec<Vertex> {
+        ys.iter()
+            .enumerate()
+            .flat_map(|(i, &y)| {
+                [1.0, -1.0].map(|n| Vertex {
+                    position: [i as f32, y],
+                    normal: [0.0, n],
+                    color: [1.0; 4],
+                })
+            })
+            .collect()
+    }
+
+    // The x and y of each pair, checking that both of its vertices are
+    // still together.
+    fn xs(points: &[Vertex]) -> Vec<f32> {
+        points
+            .chunks(2)
+            .map(|pair| {
+                assert_eq!(pair.len(), 2);
+                assert_eq!(pair[0].position, pair[1].position);
+                assert_eq!([pair[0].normal[1], pair[1].normal[1]], [1.0, -1.0]);
+                pair[0].position[0]
+            })
+            .collect()
+    }
+
+    fn ys(points: &[Vertex]) -> Vec<f32> {
+        points.chunks(2).map(|pair| pair[0].position[1]).collect()
+    }
+
+    #[test]
+    fn decimation_leaves_room_for_each_segment() {
+        let budget = VertexBudget::new(100);
+        // 50 pairs, less two for each segment rounding up.
+        assert_eq!(budget.decimation(0, 1), 1);
+        assert_eq!(budget.decimation(96, 1), 1);
+        assert_eq!(budget.decimation(97, 1), 1);
+        assert_eq!(budget.decimation(98, 1), 2);
+        assert_eq!(budget.decimation(96, 2), 2);
+        assert_eq!(budget.decimation(1000, 1), 11);
+    }
+
+    #[test]
+    fn decimated_segments_fit_the_budget() {
+        let budget = VertexBudget::new(100);
+        for mode in [DecimationMode::Stride, DecimationMode::MinMax] {
+            for len in [1000, 1001, 5000] {
+                for segments in [1, 2, 5, 10] {
+                    let points = pairs(&vec![0.0; len / 2 + 1])[..len].to_vec();
+                    let boundaries: Vec<u32> =
+                        (1..segments).map(|i| (i * len / segments) as u32).collect();
+                    let factor = budget.decimation(len, boundaries.len() + 1);
+
+                    let (decimated, _) = decimate_segments(&points, &boundaries, factor, mode);
+                    assert!(decimated.len() <= 100, "{:?} {} {}", mode, len, segments);
+                    xs(&decimated);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn many_segments_keep_a_pair_each() {
+        // With more segments than the budget has room for, the factor
+        // bottoms out at decimating each segment to a single pair (or its
+        // lowest and highest), leaving the rest to be truncated.
+        let budget = VertexBudget::new(100);
+        let ramps: Vec<f32> = (0..240).map(|i| (i % 4) as f32).collect();
+        let points = pairs(&ramps);
+        let boundaries: Vec<u32> = (1..60).map(|i| i * 8).collect();
+        let factor = budget.decimation(points.len(), boundaries.len() + 1);
+        assert_eq!(factor, 240);
+
+        let (decimated, _) =
+            decimate_segments(&points, &boundaries, factor, DecimationMode::Stride);
+        let starts: Vec<f32> = (0..60).map(|i| (i * 4) as f32).collect();
+        assert_eq!(xs(&decimated), starts);
+
+        let (decimated, _) =
+            decimate_segments(&points, &boundaries, factor, DecimationMode::MinMax);
+        let extremes: Vec<f32> = (0..60)
+            .flat_map(|i| [(i * 4) as f32, (i * 4 + 3) as f32])
+            .collect();
+        assert_eq!(xs(&decimated), extremes);
+    }
+
+    #[test]
+    fn stride_keeps_every_nth_pair() {
+        let points = pairs(&[0.0; 10]);
+        assert_eq!(xs(&decimate(&points, 3)), [0.0, 3.0, 6.0, 9.0]);
+        assert_eq!(xs(&decimate(&points, 0)), xs(&points));
+
+        // A trailing unpaired vertex is dropped.
+        assert_eq!(xs(&decimate(&points[..5], 1)), [0.0, 1.0]);
+    }
+
+    #[test]
+    fn segments_are_decimated_separately() {
+        let points = pairs(&[0.0; 10]);
+        let (decimated, map) = decimate_segments(&points, &[6], 2, DecimationMode::Stride);
+
+        // Each segment starts again from its first pair, rather than
+        // continuing the stride from the one before.
+        assert_eq!(xs(&decimated), [0.0, 2.0, 3.0, 5.0, 7.0, 9.0]);
+        assert_eq!(map.map(&(0..6)), 0..4);
+        assert_eq!(map.map(&(6..20)), 4..12);
+    }
+
+    #[test]
+    fn odd_lengths_and_boundaries_round_down_to_pairs() {
+        let points = pairs(&[0.0; 6]);
+        // 11 vertices, split mid-pair, with boundaries past the end and
+        // repeated.
+        let (decimated, map) =
+            decimate_segments(&points[..11], &[5, 5, 99], 1, DecimationMode::Stride);
+
+        assert_eq!(xs(&decimated), [0.0, 1.0, 2.0, 3.0, 4.0]);
+        assert_eq!(map.segments, vec![(0..4, 0..4), (4..10, 4..10)]);
+    }
+
+    #[test]
+    fn min_max_keeps_extremes_in_order() {
+        let points = pairs(&[0.0, 5.0, -3.0, 1.0, 2.0, 2.0, 9.0, 1.0, 4.0, 4.0]);
+        let (decimated, _) = decimate_segments(&points, &[], 2, DecimationMode::MinMax);
+
+        // The highest comes first in the first run, the lowest in the
+        // second, and a flat run keeps a single pair.
+        assert_eq!(ys(&decimated), [5.0, -3.0, 9.0, 1.0, 4.0]);
+        assert_eq!(xs(&decimated), [1.0, 2.0, 6.0, 7.0, 8.0]);
+    }
+
+    #[test]
+    fn min_max_keeps_spikes() {
+        let mut spike = vec![0.0; 64];
+        spike[37] = 100.0;
+        let points = pairs(&spike);
+
+        let (decimated, _) = decimate_segments(&points, &[], 8, DecimationMode::Stride);
+        assert!(!ys(&decimated).contains(&100.0));
+
+        let (decimated, _) = decimate_segments(&points, &[], 8, DecimationMode::MinMax);
+        assert!(ys(&decimated).contains(&100.0));
+    }
+
+    #[test]
+    fn map_index_is_exact_at_boundaries() {
+        let points = pairs(&[0.0; 20]);
+        let (decimated, map) = decimate_segments(&points, &[8, 24], 2, DecimationMode::Stride);
+        assert_eq!(decimated.len(), 20);
+
+        assert_eq!(map.map_index(0), 0);
+        assert_eq!(map.map_index(8), 4);
+        assert_eq!(map.map_index(24), 12);
+        assert_eq!(map.map_index(40), 20);
+        assert_eq!(map.map(&(8..24)), 4..12);
+    }
+
+    #[test]
+    fn map_index_is_proportional_within_segments() {
+        let points = pairs(&[0.0; 20]);
+        let (_, map) = decimate_segments(&points, &[8, 24], 2, DecimationMode::Stride);
+
+        // Halfway through the second segment, and always on a pair.
+        assert_eq!(map.map_index(16), 8);
+        assert_eq!(map.map_index(17), 8);
+        assert!((0..=40).all(|i| map.map_index(i) % 2 == 0));
+        // Never backwards.
+        assert!((1..=40).all(|i| map.map_index(i) >= map.map_index(i - 1)));
+    }
+
+    #[test]
+    fn map_index_clamps_past_the_end() {
+        let points = pairs(&[0.0; 20]);
+        let (_, map) = decimate_segments(&points, &[8, 24], 2, DecimationMode::Stride);
+        assert_eq!(map.map_index(100), 20);
+        assert_eq!(map.map(&(30..100)), 16..20);
+
+        assert_eq!(VertexMap::default().map_index(5), 0);
+    }
+}
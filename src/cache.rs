@@ -0,0 +1,257 @@
+use std::{
+    fmt, fs,
+    io::{self, BufReader, BufWriter, Read, Write},
+    ops::Range,
+    path::Path,
+};
+
+use crate::Vertex;
+
+// "EGPC", read back as something else on a machine of the other byte order.
+const MAGIC: u32 = 0x4350_4745;
+const VERSION: u32 = 1;
+
+/// Why a cache couldn't be loaded.
+#[derive(Debug)]
+pub enum CacheError {
+    Io(io::Error),
+    /// Not a plot cache, or written on a machine of the other byte order.
+    BadMagic,
+    /// Written by a different version of this crate.
+    UnsupportedVersion(u32),
+    /// The header doesn't match the contents, e.g. a truncated file.
+    Corrupt,
+}
+
+impl fmt::Display for CacheError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CacheError::Io(err) => write!(f, "plot cache I/O error: {}", err),
+            CacheError::BadMagic => write!(f, "not a plot cache"),
+            CacheError::UnsupportedVersion(version) => {
+                write!(f, "unsupported plot cache version {}", version)
+            }
+            CacheError::Corrupt => write!(f, "plot cache is corrupt"),
+        }
+    }
+}
+
+impl std::error::Error for CacheError {}
+
+impl From<io::Error> for CacheError {
+    fn from(err: io::Error) -> Self {
+        match err.kind() {
+            io::ErrorKind::UnexpectedEof => CacheError::Corrupt,
+            _ => CacheError::Io(err),
+        }
+    }
+}
+
+/// Prepared plot data, as uploaded to the GPU: the vertices and the ranges
+/// of the plot's series. Saving it lets a large recording be reopened
+/// without preparing it again.
+///
+/// The format is a small header followed by the raw vertices, in the
+/// machine's byte order, so loading is about as fast as reading the file.
+#[derive(Clone, Default)]
+pub struct PlotCache {
+    pub points: Vec<Vertex>,
+    pub series: Vec<Range<u32>>,
+}
+
+impl PlotCache {
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), CacheError> {
+        PlotCache::write(path, &self.points, &self.series)
+    }
+
+    /// Save `points` and `series` as a cache without collecting them into a
+    /// [`PlotCache`] first, which would copy the points.
+    pub fn write(
+        path: impl AsRef<Path>,
+        points: &[Vertex],
+        series: &[Range<u32>],
+    ) -> Result<(), CacheError> {
+        let mut writer = BufWriter::new(fs::File::create(path)?);
+
+        let header = [
+            MAGIC,
+            VERSION,
+            std::mem::size_of::<Vertex>() as u32,
+            series.len() as u32,
+        ];
+        writer.write_all(bytemuck::cast_slice(&header))?;
+        writer.write_all(&(points.len() as u64).to_ne_bytes())?;
+        for range in series {
+            writer.write_all(bytemuck::cast_slice(&[range.start, range.end]))?;
+        }
+        writer.write_all(bytemuck::cast_slice(points))?;
+
+        writer.flush()?;
+        Ok(())
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<PlotCache, CacheError> {
+        let file = fs::File::open(path)?;
+        let len = file.metadata()?.len();
+        let mut reader = BufReader::new(file);
+
+        let mut header = [0u32; 4];
+        reader.read_exact(bytemuck::cast_slice_mut(&mut header))?;
+        let [magic, version, vertex_size, series_count] = header;
+        if magic != MAGIC {
+            return Err(CacheError::BadMagic);
+        }
+        if version != VERSION {
+            return Err(CacheError::UnsupportedVersion(version));
+        }
+        if vertex_size as usize != std::mem::size_of::<Vertex>() {
+            return Err(CacheError::Corrupt);
+        }
+
+        let mut count = [0u8; 8];
+        reader.read_exact(&mut count)?;
+        let count = u64::from_ne_bytes(count);
+
+        // Check the size before allocating for the vertices.
+        let expected = (16 + 8 + 8 * series_count as u64)
+            .checked_add(count.saturating_mul(vertex_size as u64));
+        if expected != Some(len) {
+            return Err(CacheError::Corrupt);
+        }
+        let count = usize::try_from(count).map_err(|_| CacheError::Corrupt)?;
+
+        let mut series = Vec::with_capacity(series_count as usize);
+        for _ in 0..series_count {
+            let mut range = [0u32; 2];
+            reader.read_exact(bytemuck::cast_slice_mut(&mut range))?;
+            if range[0] > range[1] || range[1] as usize > count {
+                return Err(CacheError::Corrupt);
+            }
+            series.push(range[0]..range[1]);
+        }
+
+        let mut points = vec![Vertex::default(); count];
+        reader.read_exact(bytemuck::cast_slice_mut(&mut points))?;
+
+        Ok(PlotCache { points, series })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A file in the temporary directory, removed when dropped.
+    struct TempFile(std::path::PathBuf);
+
+    impl TempFile {
+        fn new(name: &str) -> TempFile {
+            TempFile(std::env::temp_dir().join(format!(
+                "egui_plot_cache_{}_{}",
+                std::process::id(),
+                name
+            )))
+        }
+    }
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.0);
+        }
+    }
+
+    fn points(n: usize) -> Vec<Vertex> {
+        (0..n)
+            .map(|i| Vertex {
+                position: [i as f32, (i * i) as f32],
+                normal: [0.0, if i % 2 == 0 { 1.0 } else { -1.0 }],
+                color: [1.0, 0.5, 0.25, 1.0],
+            })
+            .collect()
+    }
+
+    fn floats(points: &[Vertex]) -> &[f32] {
+        bytemuck::cast_slice(points)
+    }
+
+    #[test]
+    fn round_trips_points_and_series() {
+        let file = TempFile::new("round_trip");
+        let points = points(10);
+        let series = [0..4, 4..10, 6..6];
+
+        PlotCache::write(&file.0, &points, &series).unwrap();
+        let cache = PlotCache::load(&file.0).unwrap();
+
+        assert_eq!(floats(&cache.points), floats(&points));
+        assert_eq!(cache.series, series);
+
+        // Saving a loaded cache writes the same file.
+        let again = TempFile::new("round_trip_again");
+        cache.save(&again.0).unwrap();
+        assert_eq!(fs::read(&again.0).unwrap(), fs::read(&file.0).unwrap());
+    }
+
+    #[test]
+    fn round_trips_empty_caches() {
+        let file = TempFile::new("empty");
+        PlotCache::write(&file.0, &[], &[]).unwrap();
+        let cache = PlotCache::load(&file.0).unwrap();
+
+        assert!(cache.points.is_empty());
+        assert!(cache.series.is_empty());
+    }
+
+    #[test]
+    fn rejects_wrong_lengths() {
+        let file = TempFile::new("length");
+        PlotCache::write(&file.0, &points(4), &[0..2, 2..4]).unwrap();
+        let bytes = fs::read(&file.0).unwrap();
+
+        // Truncated within the vertices, and within the header.
+        for len in [bytes.len() - 4, 20] {
+            fs::write(&file.0, &bytes[..len]).unwrap();
+            assert!(matches!(PlotCache::load(&file.0), Err(CacheError::Corrupt)));
+        }
+
+        // Trailing bytes.
+        let mut longer = bytes.clone();
+        longer.extend_from_slice(&[0; 4]);
+        fs::write(&file.0, &longer).unwrap();
+        assert!(matches!(PlotCache::load(&file.0), Err(CacheError::Corrupt)));
+
+        // A vertex count which doesn't match the file, however large.
+        for count in [5u64, u64::MAX] {
+            let mut wrong = bytes.clone();
+            wrong[16..24].copy_from_slice(&count.to_ne_bytes());
+            fs::write(&file.0, &wrong).unwrap();
+            assert!(matches!(PlotCache::load(&file.0), Err(CacheError::Corrupt)));
+        }
+    }
+
+    #[test]
+    fn rejects_series_past_the_points() {
+        let file = TempFile::new("series");
+        PlotCache::write(&file.0, &points(4), &[0..2, 2..5]).unwrap();
+        assert!(matches!(PlotCache::load(&file.0), Err(CacheError::Corrupt)));
+    }
+
+    #[test]
+    fn rejects_other_files() {
+        let file = TempFile::new("magic");
+        fs::write(&file.0, [0; 64]).unwrap();
+        assert!(matches!(
+            PlotCache::load(&file.0),
+            Err(CacheError::BadMagic)
+        ));
+
+        PlotCache::write(&file.0, &points(2), &[]).unwrap();
+        let mut bytes = fs::read(&file.0).unwrap();
+        bytes[4..8].copy_from_slice(&(VERSION + 1).to_ne_bytes());
+        fs::write(&file.0, &bytes).unwrap();
+        assert!(matches!(
+            PlotCache::load(&file.0),
+            Err(CacheError::UnsupportedVersion(v)) if v == VERSION + 1
+        ));
+    }
+}
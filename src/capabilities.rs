@@ -0,0 +1,92 @@
+/// What the device can do, as far as the optional parts of this crate are
+/// concerned. Subsystems consult this to disable or degrade themselves, so the
+/// same application can run anywhere from WebGL2 to a discrete desktop GPU.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Capabilities {
+    /// Adapter name and backend, if the capabilities were probed from an
+    /// adapter.
+    pub adapter: Option<(String, wgpu::Backend)>,
+    /// The largest MSAA sample count usable for the plot's render format.
+    pub max_sample_count: u32,
+    pub push_constants: bool,
+    /// Storage buffers can be read from vertex shaders, allowing vertices to
+    /// be pulled from arbitrary buffers instead of vertex buffers.
+    pub vertex_pulling: bool,
+    pub compute_shaders: bool,
+    pub timestamp_queries: bool,
+    pub max_buffer_size: u64,
+    pub max_texture_dimension_2d: u32,
+}
+
+impl Capabilities {
+    /// Conservative capabilities derived only from the device's enabled
+    /// features and limits (e.g. when the adapter isn't available, as with
+    /// eframe).
+    pub fn from_device(device: &wgpu::Device, format: wgpu::TextureFormat) -> Capabilities {
+        let features = device.features();
+        let limits = device.limits();
+
+        let format_flags = format.describe().guaranteed_format_features.flags;
+
+        Capabilities {
+            adapter: None,
+            max_sample_count: max_sample_count(format_flags),
+            push_constants: features.contains(wgpu::Features::PUSH_CONSTANTS)
+                && limits.max_push_constant_size > 0,
+            vertex_pulling: limits.max_storage_buffers_per_shader_stage > 0,
+            compute_shaders: limits.max_compute_workgroups_per_dimension > 0,
+            timestamp_queries: features.contains(wgpu::Features::TIMESTAMP_QUERY),
+            max_buffer_size: limits.max_buffer_size,
+            max_texture_dimension_2d: limits.max_texture_dimension_2d,
+        }
+    }
+
+    /// Capabilities refined with what the adapter reports, including
+    /// adapter-specific format support and downlevel restrictions.
+    pub fn probe(
+        adapter: &wgpu::Adapter,
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+    ) -> Capabilities {
+        let mut capabilities = Capabilities::from_device(device, format);
+
+        let info = adapter.get_info();
+        capabilities.adapter = Some((info.name, info.backend));
+
+        if device
+            .features()
+            .contains(wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES)
+        {
+            let flags = adapter.get_texture_format_features(format).flags;
+            capabilities.max_sample_count = max_sample_count(flags);
+        }
+
+        let downlevel = adapter.get_downlevel_capabilities().flags;
+        capabilities.vertex_pulling &= downlevel.contains(wgpu::DownlevelFlags::VERTEX_STORAGE);
+        capabilities.compute_shaders &= downlevel.contains(wgpu::DownlevelFlags::COMPUTE_SHADERS);
+
+        capabilities
+    }
+
+    /// Clamp a requested MSAA sample count to one the device supports.
+    pub fn sample_count(&self, requested: u32) -> u32 {
+        if requested > 1 && self.max_sample_count >= 4 {
+            4
+        } else {
+            1
+        }
+    }
+}
+
+// Only 1 and 4 samples are guaranteed to be valid when multisampling is
+// supported at all.
+fn max_sample_count(flags: wgpu::TextureFormatFeatureFlags) -> u32 {
+    let required = wgpu::TextureFormatFeatureFlags::MULTISAMPLE
+        | wgpu::TextureFormatFeatureFlags::MULTISAMPLE_RESOLVE;
+
+    if flags.contains(required) {
+        4
+    } else {
+        1
+    }
+}
@@ -0,0 +1,30 @@
+use std::path::PathBuf;
+
+/// The environment variable naming a directory for wgpu API traces, see
+/// [`api_trace_path`].
+pub const TRACE_ENV_VAR: &str = "WGPU_TRACE";
+
+/// Hooks called around the GPU work of a single render, e.g. to start and
+/// end a RenderDoc capture through the `renderdoc` crate, so that the
+/// capture holds only the plot's passes. See
+/// [`GpuAcceleratedPlot::render_captured`](crate::GpuAcceleratedPlot::render_captured).
+pub trait FrameCapture {
+    /// Called before anything is encoded.
+    fn start(&mut self, device: &wgpu::Device);
+
+    /// Called once the plot's work is submitted, with the device polled
+    /// until it has finished.
+    fn end(&mut self, device: &wgpu::Device);
+}
+
+/// The directory to record a wgpu API trace into, if `WGPU_TRACE` is set,
+/// to pass to `Adapter::request_device`. wgpu only records traces from
+/// devices created with a path (and built with its `trace` feature), so a
+/// trace covers the whole device rather than a single plot: use
+/// [`GpuAcceleratedPlot::set_debug_labels`](crate::GpuAcceleratedPlot::set_debug_labels)
+/// to find the plot's passes in it.
+pub fn api_trace_path() -> Option<PathBuf> {
+    std::env::var_os(TRACE_ENV_VAR)
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from)
+}
@@ -0,0 +1,108 @@
+use std::collections::HashMap;
+
+use crate::Bounds;
+
+/// Labels for an axis whose values are category indices, e.g. the groups of
+/// a bar chart or box plot. Category `i` is centered on `i` and spans half a
+/// unit either side.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Categories {
+    labels: Vec<String>,
+    indices: HashMap<String, usize>,
+}
+
+impl Categories {
+    pub fn new() -> Categories {
+        Categories::default()
+    }
+
+    pub fn from_labels<S: AsRef<str>>(labels: &[S]) -> Categories {
+        let mut categories = Categories::new();
+        for label in labels {
+            categories.index_of(label.as_ref());
+        }
+        categories
+    }
+
+    /// The index of `label`, adding it as a new category if needed.
+    pub fn index_of(&mut self, label: &str) -> usize {
+        if let Some(&index) = self.indices.get(label) {
+            return index;
+        }
+
+        self.labels.push(label.to_owned());
+        self.indices.insert(label.to_owned(), self.labels.len() - 1);
+        self.labels.len() - 1
+    }
+
+    /// The index of `label`, if it's a category.
+    pub fn get(&self, label: &str) -> Option<usize> {
+        self.indices.get(label).copied()
+    }
+
+    pub fn label(&self, index: usize) -> Option<&str> {
+        self.labels.get(index).map(String::as_str)
+    }
+
+    /// The label of the category spanning axis value `value`.
+    pub fn label_at(&self, value: f64) -> Option<&str> {
+        let index = value.round();
+        if index < 0.0 {
+            return None;
+        }
+        self.label(index as usize)
+    }
+
+    pub fn labels(&self) -> &[String] {
+        &self.labels
+    }
+
+    pub fn len(&self) -> usize {
+        self.labels.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.labels.is_empty()
+    }
+
+    /// Ticks at the centers of the categories within `range`, with their
+    /// labels. If more than `max_ticks` are visible, only every n-th
+    /// category gets one.
+    pub fn ticks(&self, range: [f64; 2], max_ticks: usize) -> Vec<(f64, &str)> {
+        let [lo, hi] = [range[0].min(range[1]), range[0].max(range[1])];
+        let first = lo.ceil().max(0.0);
+        let last = hi.floor().min(self.labels.len() as f64 - 1.0);
+        if first > last {
+            return Vec::new();
+        }
+
+        let (first, last) = (first as usize, last as usize);
+        let stride = ((last - first + 1) as f64 / max_ticks.max(1) as f64).ceil() as usize;
+        (first..=last)
+            .step_by(stride.max(1))
+            .map(|i| (i as f64, self.labels[i].as_str()))
+            .collect()
+    }
+
+    /// Clamp panning to the categories: shift `bounds` along x to stay
+    /// within them, and shrink to fit if wider.
+    pub fn clamp_bounds(&self, bounds: Bounds) -> Bounds {
+        if self.labels.is_empty() {
+            return bounds;
+        }
+
+        let [min, max] = [-0.5, self.labels.len() as f64 - 0.5];
+        let reversed = bounds.x[0] > bounds.x[1];
+        let [lo, hi] = [bounds.x[0].min(bounds.x[1]), bounds.x[0].max(bounds.x[1])];
+
+        let width = (hi - lo).min(max - min);
+        let lo = lo.clamp(min, max - width);
+        let x = if reversed {
+            [lo + width, lo]
+        } else {
+            [lo, lo + width]
+        };
+
+        Bounds { x, ..bounds }
+    }
+}
@@ -0,0 +1,141 @@
+#[cfg(feature = "gpu")]
+use wgpu::util::DeviceExt;
+
+use crate::Bounds;
+#[cfg(feature = "heatmap")]
+use crate::Histogram;
+#[cfg(feature = "gpu")]
+use crate::{LineBatch, Vertex};
+
+// The arms of the reference crosses, as a fraction of the extent.
+const CROSS_SIZE: f32 = 0.03;
+
+/// Shows streamed IQ samples as a constellation diagram: a square view
+/// centered on the origin, with the density of the samples accumulated with
+/// persistence (as a histogram heatmap) and the ideal symbol positions drawn
+/// over it. See
+/// [`GpuAcceleratedPlot::set_constellation`](crate::GpuAcceleratedPlot::set_constellation).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Constellation {
+    /// Half of the width of the view, in both I and Q.
+    pub extent: f32,
+    /// Number of bins across and up the view.
+    pub bins: u32,
+    /// The fraction of the density kept from one block of samples to the
+    /// next, see [`Histogram::with_persistence`](crate::Histogram::with_persistence).
+    pub persistence: f32,
+    /// Colors of the density, from one sample to the most, or empty for the
+    /// histogram's default.
+    pub colormap: Vec<[f32; 4]>,
+    /// The ideal symbol positions, e.g. from [`Constellation::square_qam`].
+    pub reference: Vec<[f32; 2]>,
+    pub reference_color: [f32; 4],
+}
+
+impl Constellation {
+    pub fn new(extent: f32) -> Constellation {
+        Constellation {
+            extent,
+            bins: 256,
+            persistence: 0.9,
+            colormap: Vec::new(),
+            reference: Vec::new(),
+            reference_color: [1.0, 0.3, 0.3, 1.0],
+        }
+    }
+
+    pub fn with_bins(self, bins: u32) -> Constellation {
+        Constellation { bins, ..self }
+    }
+
+    pub fn with_persistence(self, persistence: f32) -> Constellation {
+        Constellation {
+            persistence,
+            ..self
+        }
+    }
+
+    pub fn with_colormap(self, colormap: &[[f32; 4]]) -> Constellation {
+        Constellation {
+            colormap: colormap.to_vec(),
+            ..self
+        }
+    }
+
+    pub fn with_reference(self, reference: Vec<[f32; 2]>, color: [f32; 4]) -> Constellation {
+        Constellation {
+            reference,
+            reference_color: color,
+            ..self
+        }
+    }
+
+    /// The symbols of square QAM with `order` points (4 for QPSK, 16, 64,
+    /// ...), scaled to unit average power. Orders that aren't squares are
+    /// rounded down to one.
+    pub fn square_qam(order: u32) -> Vec<[f32; 2]> {
+        let side = (order as f64).sqrt().floor().max(1.0) as u32;
+        // The average power of the levels ±1, ±3, ... in each of I and Q.
+        let power = 2.0 * (side as f32 * side as f32 - 1.0) / 3.0;
+        let scale = if power > 0.0 {
+            power.sqrt().recip()
+        } else {
+            1.0
+        };
+        let level = |i: u32| (2.0 * i as f32 - (side - 1) as f32) * scale;
+
+        (0..side)
+            .flat_map(|q| (0..side).map(move |i| [level(i), level(q)]))
+            .collect()
+    }
+
+    /// The square view, before being widened to the plot's aspect ratio.
+    pub fn bounds(&self) -> Bounds {
+        let e = self.extent.abs() as f64;
+        Bounds::new([-e, e], [-e, e])
+    }
+
+    #[cfg(feature = "heatmap")]
+    pub(crate) fn histogram(&self) -> Histogram {
+        let histogram = Histogram::rect([self.bins; 2]).with_persistence(self.persistence);
+        if self.colormap.is_empty() {
+            histogram
+        } else {
+            histogram.with_colormap(&self.colormap)
+        }
+    }
+
+    // A cross at each reference symbol, as line vertices.
+    #[cfg(feature = "gpu")]
+    pub(crate) fn reference_vertices(&self, device: &wgpu::Device) -> Option<(wgpu::Buffer, u32)> {
+        let arm = self.extent.abs() * CROSS_SIZE;
+        let mut batch = LineBatch::with_capacity(2 * self.reference.len(), 4);
+        for &[i, q] in &self.reference {
+            for ([dx, dy], normal) in [([arm, 0.0], [0.0, 1.0]), ([0.0, arm], [-1.0, 0.0])] {
+                batch.push(
+                    &[
+                        ([i - dx, q - dy], 1.0),
+                        ([i - dx, q - dy], -1.0),
+                        ([i + dx, q + dy], 1.0),
+                        ([i + dx, q + dy], -1.0),
+                    ]
+                    .map(|(position, side)| Vertex {
+                        position,
+                        normal: normal.map(|n| n * side),
+                        color: self.reference_color,
+                    }),
+                );
+            }
+        }
+
+        let vertices = batch.vertices();
+        (!vertices.is_empty()).then(|| {
+            let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("egui_plot_constellation_reference"),
+                contents: bytemuck::cast_slice(vertices),
+                usage: wgpu::BufferUsages::VERTEX,
+            });
+            (buffer, vertices.len() as u32)
+        })
+    }
+}
@@ -0,0 +1,238 @@
+use std::{
+    collections::HashMap,
+    num::NonZeroU64,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+#[cfg(feature = "heatmap")]
+use crate::histogram::HistogramPipelines;
+use crate::{
+    attributes::AttributePipelines, blit::Blitter, bloom::BloomPipelines, logic::LogicPipelines,
+    series::SeriesUniform, timeline::TimelinePipelines, Instance, Uniform, VertexLayout,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(crate) struct PipelineKey {
+    pub format: wgpu::TextureFormat,
+    pub sample_count: u32,
+    pub vertex_layout: VertexLayout,
+    // Also read an `Instance` per instance from a second vertex buffer.
+    pub instanced: bool,
+    // Add colors together rather than alpha blending, to accumulate density.
+    pub additive: bool,
+    // Color points by histogram counts, bound as a third bind group.
+    pub density: bool,
+    // Color and size points by attribute columns, bound as a third bind
+    // group.
+    pub attributes: bool,
+}
+
+/// GPU state which can be shared between any number of plots on the same
+/// device: shader modules, layouts and pipelines. Plots only own their
+/// buffers and textures.
+///
+/// With egui, use `GpuPlotContext::shared` to keep a single context in the
+/// renderer's paint callback resources.
+pub struct GpuPlotContext {
+    shader: wgpu::ShaderModule,
+    bind_group_layout: wgpu::BindGroupLayout,
+    series_bind_group_layout: wgpu::BindGroupLayout,
+    pipeline_layout: wgpu::PipelineLayout,
+    pipelines: Mutex<HashMap<PipelineKey, Arc<wgpu::RenderPipeline>>>,
+    pub(crate) blitter: Blitter,
+    pub(crate) bloom: BloomPipelines,
+    #[cfg(feature = "heatmap")]
+    pub(crate) histogram: HistogramPipelines,
+    pub(crate) attributes: AttributePipelines,
+    pub(crate) logic: LogicPipelines,
+    pub(crate) timeline: TimelinePipelines,
+}
+
+impl GpuPlotContext {
+    pub fn new(device: &wgpu::Device) -> GpuPlotContext {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("egui_plot_line_shader"),
+            source: wgpu::ShaderSource::Wgsl(
+                concat!(
+                    include_str!("./bins.wgsl"),
+                    include_str!("./line_shader.wgsl")
+                )
+                .into(),
+            ),
+        });
+
+        let bind_group_layout = create_uniform_layout::<Uniform>(
+            device,
+            "egui_plot_bind_group_layout",
+            wgpu::ShaderStages::VERTEX,
+        );
+        let series_bind_group_layout = create_uniform_layout::<SeriesUniform>(
+            device,
+            "egui_plot_series_bind_group_layout",
+            wgpu::ShaderStages::VERTEX,
+        );
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("egui_plot_pipeline_layout"),
+            bind_group_layouts: &[&bind_group_layout, &series_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        GpuPlotContext {
+            shader,
+            bind_group_layout,
+            series_bind_group_layout,
+            pipeline_layout,
+            pipelines: Mutex::new(HashMap::new()),
+            blitter: Blitter::new(device),
+            bloom: BloomPipelines::new(device),
+            #[cfg(feature = "heatmap")]
+            histogram: HistogramPipelines::new(),
+            attributes: AttributePipelines::new(),
+            logic: LogicPipelines::new(device),
+            timeline: TimelinePipelines::new(device),
+        }
+    }
+
+    pub fn bind_group_layout(&self) -> &wgpu::BindGroupLayout {
+        &self.bind_group_layout
+    }
+
+    pub(crate) fn series_bind_group_layout(&self) -> &wgpu::BindGroupLayout {
+        &self.series_bind_group_layout
+    }
+
+    // Get the line pipeline for a render format, sample count, vertex layout
+    // and blend mode, creating it if this is the first plot to use them.
+    pub(crate) fn pipeline(
+        &self,
+        device: &wgpu::Device,
+        key: PipelineKey,
+    ) -> Arc<wgpu::RenderPipeline> {
+        let PipelineKey {
+            format,
+            sample_count,
+            vertex_layout,
+            instanced,
+            additive,
+            density,
+            attributes,
+        } = key;
+
+        let vertex_attributes = vertex_layout.attributes();
+        let vertices = wgpu::VertexBufferLayout {
+            array_stride: vertex_layout.stride,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &vertex_attributes,
+        };
+        let instances = wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<Instance>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &wgpu::vertex_attr_array![3 => Float32x2, 4 => Float32x2, 5 => Float32x4],
+        };
+
+        let blend = if additive {
+            let add = wgpu::BlendComponent {
+                src_factor: wgpu::BlendFactor::One,
+                dst_factor: wgpu::BlendFactor::One,
+                operation: wgpu::BlendOperation::Add,
+            };
+            wgpu::BlendState {
+                color: add,
+                alpha: add,
+            }
+        } else {
+            wgpu::BlendState::ALPHA_BLENDING
+        };
+
+        let mut pipelines = self
+            .pipelines
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let pipeline = pipelines.entry(key).or_insert_with(|| {
+            let (entry_point, buffers) = if instanced {
+                ("vs_instanced", vec![vertices, instances])
+            } else if density {
+                ("vs_density", vec![vertices])
+            } else if attributes {
+                ("vs_attributes", vec![vertices])
+            } else {
+                ("vs_main", vec![vertices])
+            };
+
+            // Density pipelines are only requested by histograms.
+            #[cfg(feature = "heatmap")]
+            let density_layout = density.then(|| {
+                self.histogram.density_pipeline_layout(
+                    device,
+                    [&self.bind_group_layout, &self.series_bind_group_layout],
+                )
+            });
+            #[cfg(not(feature = "heatmap"))]
+            let density_layout: Option<wgpu::PipelineLayout> = None;
+            let layout = density_layout.or_else(|| {
+                attributes.then(|| {
+                    self.attributes.pipeline_layout(
+                        device,
+                        [&self.bind_group_layout, &self.series_bind_group_layout],
+                    )
+                })
+            });
+
+            Arc::new(
+                device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                    label: Some("egui_plot_pipeline"),
+                    layout: Some(layout.as_ref().unwrap_or(&self.pipeline_layout)),
+                    vertex: wgpu::VertexState {
+                        module: &self.shader,
+                        entry_point,
+                        buffers: &buffers,
+                    },
+                    fragment: Some(wgpu::FragmentState {
+                        module: &self.shader,
+                        entry_point: "fs_main",
+                        targets: &[Some(wgpu::ColorTargetState {
+                            format,
+                            blend: Some(blend),
+                            write_mask: wgpu::ColorWrites::ALL,
+                        })],
+                    }),
+                    primitive: wgpu::PrimitiveState {
+                        topology: wgpu::PrimitiveTopology::TriangleStrip,
+                        ..Default::default()
+                    },
+                    depth_stencil: None,
+                    multisample: wgpu::MultisampleState {
+                        count: sample_count,
+                        ..Default::default()
+                    },
+                    multiview: None,
+                }),
+            )
+        });
+
+        Arc::clone(pipeline)
+    }
+}
+
+// A layout for one uniform buffer of `T`s, selected between with dynamic
+// offsets.
+fn create_uniform_layout<T>(
+    device: &wgpu::Device,
+    label: &str,
+    visibility: wgpu::ShaderStages,
+) -> wgpu::BindGroupLayout {
+    device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        label: Some(label),
+        entries: &[wgpu::BindGroupLayoutEntry {
+            binding: 0,
+            visibility,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: true,
+                min_binding_size: NonZeroU64::new(std::mem::size_of::<T>() as u64),
+            },
+            count: None,
+        }],
+    })
+}
@@ -0,0 +1,84 @@
+use std::borrow::Cow;
+#[cfg(feature = "egui-wgpu")]
+use std::sync::Arc;
+
+use egui::plot::PlotBounds;
+
+#[cfg(feature = "egui-wgpu")]
+use crate::egui_wgpu_callback;
+use crate::{budget, Vertex};
+
+// Must match the line shader so both paths draw lines of the same width.
+const LINE_WIDTH: f32 = 0.002;
+
+// egui tessellates on the CPU every frame, so keep the mesh to a reasonable
+// size rather than trying to draw millions of points.
+const MAX_CPU_POINTS: usize = 200_000;
+
+/// Tessellate the plot into an egui mesh, following the same triangle strip
+/// and data-to-screen transform as the GPU line shader. Used when wgpu isn't
+/// available (e.g. the glow backend), at the cost of CPU time and detail.
+pub fn cpu_plot_shape(rect: egui::Rect, bounds: &PlotBounds, points: &[Vertex]) -> egui::Shape {
+    let points = if points.len() > MAX_CPU_POINTS {
+        Cow::Owned(budget::decimate(points, points.len() / MAX_CPU_POINTS + 1))
+    } else {
+        Cow::Borrowed(points)
+    };
+
+    let [x0, y0] = bounds.min();
+    let [x1, y1] = bounds.max();
+
+    // Data space to NDC (-1..1), offset along the normal, then NDC to the
+    // screen with y pointing down.
+    let to_screen = |v: &Vertex| {
+        let x = ((v.position[0] as f64 - x0) / (x1 - x0)) as f32 * 2.0 - 1.0;
+        let y = ((v.position[1] as f64 - y0) / (y1 - y0)) as f32 * 2.0 - 1.0;
+        let x = x + LINE_WIDTH * v.normal[0];
+        let y = y + LINE_WIDTH * v.normal[1];
+
+        egui::pos2(
+            rect.left() + (x + 1.0) * 0.5 * rect.width(),
+            rect.bottom() - (y + 1.0) * 0.5 * rect.height(),
+        )
+    };
+
+    let mut mesh = egui::Mesh::default();
+    mesh.reserve_vertices(points.len());
+    mesh.reserve_triangles(points.len().saturating_sub(2));
+
+    for v in points.iter() {
+        let [r, g, b, a] = v.color;
+        mesh.colored_vertex(
+            to_screen(v),
+            egui::Rgba::from_rgba_premultiplied(r, g, b, a).into(),
+        );
+    }
+
+    // Alternating winding doesn't matter, since egui doesn't cull.
+    for i in 2..points.len() as u32 {
+        mesh.add_triangle(i - 2, i - 1, i);
+    }
+
+    egui::Shape::mesh(mesh)
+}
+
+/// Render the plot on the GPU when wgpu is available, falling back to
+/// [`cpu_plot_shape`] otherwise, so that one plotting code path works with
+/// any eframe backend.
+///
+/// With the GPU path the result still has to be displayed through the plot
+/// texture; with the CPU path the returned shape draws the data directly.
+#[cfg(feature = "egui-wgpu")]
+pub fn egui_wgpu_callback_or_cpu(
+    gpu_available: bool,
+    bounds: PlotBounds,
+    points: Arc<Vec<Vertex>>,
+    rect: egui::Rect,
+    dirty: bool,
+) -> egui::Shape {
+    if gpu_available {
+        egui::Shape::Callback(egui_wgpu_callback(bounds, points, rect, dirty))
+    } else {
+        cpu_plot_shape(rect, &bounds, &points)
+    }
+}
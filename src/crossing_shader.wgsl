@@ -0,0 +1,82 @@
+struct Params {
+    // The x value to snap, and how far either side of it to search.
+    snap_x: f32,
+    radius: f32,
+    // Vertex stride and position offset, in 4 byte words.
+    stride: u32,
+    position: u32,
+    // The range of samples to search.
+    first: u32,
+    end: u32,
+    _padding: vec2<u32>,
+};
+
+struct Nearest {
+    // The bits of the smallest distance found. Distances are never
+    // negative, so their bits order like the distances themselves.
+    distance: atomic<u32>,
+    x: f32,
+};
+
+@group(0) @binding(0)
+var<uniform> params: Params;
+@group(0) @binding(1)
+var<storage, read> points: array<f32>;
+@group(0) @binding(2)
+var<storage, read_write> nearest: Nearest;
+
+let WORKGROUP_SIZE: u32 = 64u;
+
+// Vertices come in pairs with the same position, one for each normal.
+fn sample(i: u32) -> vec2<f32> {
+    let word = 2u * i * params.stride + params.position;
+    return vec2<f32>(points[word], points[word + 1u]);
+}
+
+// Whether the line from sample `i` to the next crosses zero (in x), and
+// where. NaN gaps never cross.
+fn crossing(i: u32) -> vec2<f32> {
+    let a = sample(i);
+    let b = sample(i + 1u);
+    if ((a.y < 0.0 && b.y >= 0.0) || (a.y > 0.0 && b.y <= 0.0)) {
+        let t = a.y / (a.y - b.y);
+        return vec2<f32>(1.0, a.x + (b.x - a.x) * t);
+    }
+    return vec2<f32>(0.0, 0.0);
+}
+
+fn distance_bits(i: u32) -> u32 {
+    let c = crossing(i);
+    let distance = abs(c.y - params.snap_x);
+    if (c.x == 0.0 || !(distance <= params.radius)) {
+        return 0xffffffffu;
+    }
+    return bitcast<u32>(distance);
+}
+
+// First find the smallest distance to a crossing...
+@compute @workgroup_size(64)
+fn cs_distance(@builtin(global_invocation_id) id: vec3<u32>,
+               @builtin(num_workgroups) groups: vec3<u32>) {
+    let total = groups.x * WORKGROUP_SIZE;
+    for (var i = params.first + id.x; i + 1u < params.end; i = i + total) {
+        let bits = distance_bits(i);
+        if (bits != 0xffffffffu) {
+            atomicMin(&nearest.distance, bits);
+        }
+    }
+}
+
+// ...then record where that crossing is. Ties are equally near, so it
+// doesn't matter which is written last.
+@compute @workgroup_size(64)
+fn cs_position(@builtin(global_invocation_id) id: vec3<u32>,
+               @builtin(num_workgroups) groups: vec3<u32>) {
+    let total = groups.x * WORKGROUP_SIZE;
+    let best = atomicLoad(&nearest.distance);
+    for (var i = params.first + id.x; i + 1u < params.end; i = i + total) {
+        if (best != 0xffffffffu && distance_bits(i) == best) {
+            nearest.x = crossing(i).y;
+        }
+    }
+}
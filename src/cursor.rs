@@ -0,0 +1,72 @@
+use std::ops::Range;
+
+use crate::{SeriesId, Vertex};
+
+/// A cursor locked onto the samples of one series, which can be stepped
+/// sample by sample, e.g. with the arrow keys.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DataCursor {
+    pub series: SeriesId,
+    vertices: Range<u32>,
+    // Vertex index of the current sample's first vertex.
+    index: u32,
+}
+
+impl DataCursor {
+    /// A cursor on the first sample of a series. `vertices` is the series'
+    /// vertex range, from
+    /// [`GpuAcceleratedPlot::series_vertices`](crate::GpuAcceleratedPlot::series_vertices).
+    pub fn new(series: SeriesId, vertices: Range<u32>) -> DataCursor {
+        DataCursor {
+            series,
+            index: vertices.start,
+            vertices,
+        }
+    }
+
+    /// Vertex index of the current sample in the plot's points.
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    pub fn position(&self, points: &[Vertex]) -> Option<[f32; 2]> {
+        points.get(self.index as usize).map(|v| v.position)
+    }
+
+    /// Move onto the sample with the x value closest to `x`.
+    pub fn snap_to(&mut self, points: &[Vertex], x: f64) {
+        let end = (self.vertices.end as usize).min(points.len());
+        let start = (self.vertices.start as usize).min(end);
+
+        let nearest = points[start..end]
+            .iter()
+            .enumerate()
+            .step_by(2)
+            .min_by(|(_, a), (_, b)| {
+                let da = (a.position[0] as f64 - x).abs();
+                let db = (b.position[0] as f64 - x).abs();
+                da.total_cmp(&db)
+            });
+
+        if let Some((i, _)) = nearest {
+            self.index = (start + i) as u32;
+        }
+    }
+
+    /// Move onto the sample at a vertex index in the plot's points, e.g. one
+    /// found by [`GpuAcceleratedPlot::find_breaches`](crate::GpuAcceleratedPlot::find_breaches),
+    /// clamped to the series.
+    pub fn move_to(&mut self, index: u32) {
+        let last = self.vertices.end.saturating_sub(2).max(self.vertices.start);
+        // Samples start on even vertices.
+        self.index = (index & !1).clamp(self.vertices.start, last);
+    }
+
+    /// Step forwards (or backwards, if negative) by a number of samples,
+    /// stopping at either end of the series.
+    pub fn step(&mut self, samples: i32) {
+        let last = self.vertices.end.saturating_sub(2).max(self.vertices.start);
+        let index = self.index as i64 + samples as i64 * 2;
+        self.index = index.clamp(self.vertices.start as i64, last as i64) as u32;
+    }
+}
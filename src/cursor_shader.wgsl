@@ -0,0 +1,86 @@
+struct CursorParams {
+    x_range: vec2<f32>,
+    y_range: vec2<f32>,
+    // The viewport's size, in pixels.
+    size: vec2<f32>,
+    // Vertex stride and position offset, in 4 byte words.
+    stride: u32,
+    position: u32,
+    // The series' first sample, and how many it has.
+    first: u32,
+    samples: u32,
+    // How far along the series the cursor is, in samples.
+    sample: f32,
+    radius: f32,
+    color: vec4<f32>,
+};
+
+struct VertexOut {
+    @location(0) color: vec4<f32>,
+    // Position relative to the cursor's center, in pixels.
+    @location(1) local: vec2<f32>,
+    @location(2) radius: f32,
+    @builtin(position) position: vec4<f32>,
+};
+
+@group(0) @binding(0)
+var<uniform> params: CursorParams;
+@group(0) @binding(1)
+var<storage, read> points: array<f32>;
+
+// Vertices come in pairs with the same position, one for each normal.
+fn sample_position(i: u32) -> vec2<f32> {
+    let word = 2u * (params.first + i) * params.stride + params.position;
+    return vec2<f32>(points[word], points[word + 1u]);
+}
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOut {
+    var out: VertexOut;
+
+    // Between the two samples either side of the cursor's time.
+    let last = params.samples - 1u;
+    let i = min(u32(floor(params.sample)), last);
+    let a = sample_position(i);
+    let b = sample_position(min(i + 1u, last));
+    let p = mix(a, b, clamp(params.sample - f32(i), 0.0, 1.0));
+    if (!(abs(p.x) <= 3.4e38 && abs(p.y) <= 3.4e38)) {
+        // Outside of the clip volume.
+        out.position = vec4<f32>(2.0, 2.0, 0.0, 1.0);
+        return out;
+    }
+
+    let origin = vec2<f32>(params.x_range[0], params.y_range[0]);
+    let extent = vec2<f32>(params.x_range[1], params.y_range[1]) - origin;
+    let center = (p - origin) / extent * params.size;
+
+    let c = index % 6u;
+    let corner = vec2<f32>(
+        select(-1.0, 1.0, c == 1u || c == 2u || c == 4u),
+        select(-1.0, 1.0, c == 2u || c == 4u || c == 5u),
+    );
+
+    // Grow the quad by a pixel to leave room for antialiasing.
+    let local = corner * (params.radius + 1.0);
+    let ndc = (center + local) / params.size * 2.0 - 1.0;
+
+    out.color = params.color;
+    out.local = local;
+    out.radius = params.radius;
+    out.position = vec4<f32>(ndc, 0.0, 1.0);
+
+    return out;
+}
+
+// A filled disc with a darker rim, so that it stands out from the path.
+@fragment
+fn fs_main(in: VertexOut) -> @location(0) vec4<f32> {
+    let r = length(in.local);
+    let alpha = clamp(0.5 - (r - in.radius), 0.0, 1.0) * in.color.w;
+    if (alpha <= 0.0) {
+        discard;
+    }
+
+    let rim = smoothstep(in.radius - 2.0, in.radius - 1.0, r);
+    return vec4<f32>(mix(in.color.xyz, in.color.xyz * 0.4, rim), alpha);
+}
@@ -0,0 +1,65 @@
+use std::fmt;
+
+use crate::{Bounds, SeriesId};
+
+/// Why a plot couldn't do what was asked, and what it did instead.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PlotError {
+    /// More vertices than the plot's buffer holds. The ones beyond the
+    /// capacity were dropped.
+    BufferOverflow { len: usize, capacity: usize },
+    /// The device can't run a feature, e.g. without compute shaders. The
+    /// feature was left off.
+    UnsupportedFeature(&'static str),
+    /// Bounds which are empty or not finite. The previous bounds were kept.
+    InvalidBounds(Bounds),
+    /// A texture larger than the device allows. It was rendered at a lower
+    /// resolution, at most `max` pixels across.
+    TextureTooLarge { size: [u32; 2], max: u32 },
+}
+
+impl fmt::Display for PlotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlotError::BufferOverflow { len, capacity } => {
+                write!(f, "{} vertices exceed the capacity of {}", len, capacity)
+            }
+            PlotError::UnsupportedFeature(feature) => {
+                write!(f, "{} is not supported by the device", feature)
+            }
+            PlotError::InvalidBounds(bounds) => write!(f, "invalid bounds {:?}", bounds),
+            PlotError::TextureTooLarge { size, max } => {
+                write!(f, "texture size {:?} exceeds the maximum of {}", size, max)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PlotError {}
+
+/// What went wrong while preparing a frame, from
+/// [`GpuAcceleratedPlot::diagnostics`](crate::GpuAcceleratedPlot::diagnostics).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FrameDiagnostics {
+    /// Vertices left out of the last upload for not fitting in the buffer.
+    pub dropped_points: usize,
+    /// Series with vertices among those dropped.
+    pub truncated_series: Vec<SeriesId>,
+    /// The factor the last upload was decimated by, to meet the render
+    /// budget and vertex budget, where 1 means that every point was
+    /// uploaded.
+    pub decimation: u32,
+    /// How many buffers the last upload was split across, more than one
+    /// where the device's `max_buffer_size` can't hold all of the points.
+    /// Only the first buffer's points are hit tested, binned, merged,
+    /// diffed, followed or kept in the history.
+    pub vertex_buffers: usize,
+    /// Errors from the last prepare, each of which the plot recovered from.
+    pub errors: Vec<PlotError>,
+}
+
+impl FrameDiagnostics {
+    pub fn is_empty(&self) -> bool {
+        self.dropped_points == 0 && self.errors.is_empty()
+    }
+}
@@ -0,0 +1,261 @@
+use std::ops::Range;
+
+use crate::{Gradient, SeriesId, Vertex, VertexLayout, MAX_GRADIENT_STOPS};
+
+const WORKGROUP_SIZE: u32 = 64;
+const MAX_WORKGROUPS: u32 = 65535;
+
+// The reference line and the vertices joining it to the derived samples.
+const HEADER_VERTICES: u32 = 6;
+
+/// How a [`TraceDiff`] combines its two series.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Comparison {
+    /// `a - b`, with the reference line at zero.
+    #[default]
+    Difference,
+    /// `a / b`, with the reference line at one.
+    Ratio,
+}
+
+impl Comparison {
+    pub fn reference(&self) -> f32 {
+        match self {
+            Comparison::Difference => 0.0,
+            Comparison::Ratio => 1.0,
+        }
+    }
+}
+
+/// Compare two series, e.g. before/after or golden/measured, by drawing
+/// their difference or ratio as a derived series on top of the others.
+/// Samples are paired by index, so the series should share their x values.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TraceDiff {
+    pub a: SeriesId,
+    pub b: SeriesId,
+    pub comparison: Comparison,
+    /// The distance from the reference at which the colormap saturates.
+    pub scale: f32,
+    /// A divergent colormap, centered on the reference.
+    pub colormap: Vec<[f32; 4]>,
+    pub reference_color: [f32; 4],
+}
+
+impl TraceDiff {
+    pub fn difference(a: SeriesId, b: SeriesId) -> TraceDiff {
+        TraceDiff::new(a, b, Comparison::Difference)
+    }
+
+    pub fn ratio(a: SeriesId, b: SeriesId) -> TraceDiff {
+        TraceDiff::new(a, b, Comparison::Ratio)
+    }
+
+    pub fn new(a: SeriesId, b: SeriesId, comparison: Comparison) -> TraceDiff {
+        TraceDiff {
+            a,
+            b,
+            comparison,
+            scale: 1.0,
+            colormap: COOL_WARM.to_vec(),
+            reference_color: [0.5, 0.5, 0.5, 1.0],
+        }
+    }
+
+    pub fn with_scale(self, scale: f32) -> TraceDiff {
+        TraceDiff { scale, ..self }
+    }
+
+    pub fn with_colormap(self, colormap: &[[f32; 4]]) -> TraceDiff {
+        TraceDiff {
+            colormap: colormap.to_vec(),
+            ..self
+        }
+    }
+
+    pub fn with_reference_color(self, reference_color: [f32; 4]) -> TraceDiff {
+        TraceDiff {
+            reference_color,
+            ..self
+        }
+    }
+}
+
+// A blue-white-red divergent colormap, in linear RGB.
+const COOL_WARM: [[f32; 4]; 3] = [
+    [0.045, 0.155, 0.753, 1.0],
+    [0.863, 0.863, 0.863, 1.0],
+    [0.651, 0.005, 0.030, 1.0],
+];
+
+// Laid out to match `Params` in the diff shader.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+struct Params {
+    stride: u32,
+    position: u32,
+    a: u32,
+    b: u32,
+    samples: u32,
+    ratio: u32,
+    stop_count: u32,
+    _padding0: u32,
+    reference: f32,
+    scale: f32,
+    _padding1: [f32; 2],
+    reference_color: [f32; 4],
+    stops: [[f32; 4]; MAX_GRADIENT_STOPS],
+}
+
+// Derives a plot's diff series from the compared series on the GPU, into a
+// vertex buffer drawn with the line pipeline.
+pub(crate) struct DiffLayer {
+    diff: TraceDiff,
+    pipeline: wgpu::ComputePipeline,
+    params: wgpu::Buffer,
+    vertices: Option<wgpu::Buffer>,
+    capacity: u32,
+    written: Option<Params>,
+}
+
+impl DiffLayer {
+    pub fn new(device: &wgpu::Device, diff: TraceDiff) -> DiffLayer {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("egui_plot_diff_shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("./diff_shader.wgsl").into()),
+        });
+
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("egui_plot_diff_pipeline"),
+            layout: None,
+            module: &shader,
+            entry_point: "cs_diff",
+        });
+
+        let params = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("egui_plot_diff_params"),
+            size: std::mem::size_of::<Params>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::UNIFORM,
+            mapped_at_creation: false,
+        });
+
+        DiffLayer {
+            diff,
+            pipeline,
+            params,
+            vertices: None,
+            capacity: 0,
+            written: None,
+        }
+    }
+
+    pub fn diff(&self) -> &TraceDiff {
+        &self.diff
+    }
+
+    // Follow the compared series' vertices, laid out as `layout` from byte
+    // `offset` of the points. Returns whether anything changed.
+    pub fn update(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        layout: VertexLayout,
+        offset: wgpu::BufferAddress,
+        a: Range<u32>,
+        b: Range<u32>,
+    ) -> bool {
+        let colormap = if self.diff.colormap.is_empty() {
+            vec![[1.0; 4]]
+        } else {
+            Gradient::colormap(&self.diff.colormap).resampled()
+        };
+
+        let samples = a.len().min(b.len()) as u32 / 2;
+        let mut params = Params {
+            stride: (layout.stride / 4) as u32,
+            position: ((offset + layout.position) / 4) as u32,
+            a: a.start,
+            b: b.start,
+            samples,
+            ratio: (self.diff.comparison == Comparison::Ratio) as u32,
+            stop_count: colormap.len() as u32,
+            _padding0: 0,
+            reference: self.diff.comparison.reference(),
+            scale: self.diff.scale.abs().max(f32::EPSILON),
+            _padding1: [0.0; 2],
+            reference_color: self.diff.reference_color,
+            stops: [[0.0; 4]; MAX_GRADIENT_STOPS],
+        };
+        params.stops[..colormap.len()].copy_from_slice(&colormap);
+
+        if self.written == Some(params) {
+            return false;
+        }
+
+        let vertex_count = HEADER_VERTICES + 2 * samples;
+        if vertex_count > self.capacity {
+            self.capacity = vertex_count.next_power_of_two();
+            self.vertices = Some(device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("egui_plot_diff_vertices"),
+                size: self.capacity as wgpu::BufferAddress
+                    * std::mem::size_of::<Vertex>() as wgpu::BufferAddress,
+                usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::VERTEX,
+                mapped_at_creation: false,
+            }));
+        }
+
+        queue.write_buffer(&self.params, 0, bytemuck::bytes_of(&params));
+        self.written = Some(params);
+        true
+    }
+
+    // Derive the vertices from `points`.
+    pub fn dispatch(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        points: &wgpu::Buffer,
+    ) {
+        let (vertices, samples) = match (&self.vertices, self.written) {
+            (Some(vertices), Some(params)) if params.samples > 0 => (vertices, params.samples),
+            _ => return,
+        };
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("egui_plot_diff_bind_group"),
+            layout: &self.pipeline.get_bind_group_layout(0),
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: self.params.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: points.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: vertices.as_entire_binding(),
+                },
+            ],
+        });
+
+        let workgroups = ((samples + WORKGROUP_SIZE - 1) / WORKGROUP_SIZE).clamp(1, MAX_WORKGROUPS);
+
+        let mut cpass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("egui_plot_diff_pass"),
+        });
+        cpass.set_pipeline(&self.pipeline);
+        cpass.set_bind_group(0, &bind_group, &[]);
+        cpass.dispatch_workgroups(workgroups, 1, 1);
+    }
+
+    // The derived vertex buffer and vertex count, if there's anything to draw.
+    pub fn vertices(&self) -> Option<(&wgpu::Buffer, u32)> {
+        let samples = self.written.map_or(0, |params| params.samples);
+        self.vertices
+            .as_ref()
+            .filter(|_| samples > 0)
+            .map(|buffer| (buffer, HEADER_VERTICES + 2 * samples))
+    }
+}
@@ -0,0 +1,111 @@
+struct Params {
+    // Vertex stride and position offset, in 4 byte words.
+    stride: u32,
+    position: u32,
+    // The first vertex of each compared series.
+    a: u32,
+    b: u32,
+    samples: u32,
+    ratio: u32,
+    stop_count: u32,
+    _padding0: u32,
+    // The value the reference line is drawn at, and the distance from it at
+    // which the colormap saturates.
+    reference: f32,
+    scale: f32,
+    _padding1: vec2<f32>,
+    reference_color: vec4<f32>,
+    stops: array<vec4<f32>, 8>,
+};
+
+@group(0) @binding(0)
+var<uniform> params: Params;
+@group(0) @binding(1)
+var<storage, read> points: array<f32>;
+// Vertices laid out like `Vertex`: position, normal and color.
+@group(0) @binding(2)
+var<storage, read_write> vertices: array<f32>;
+
+let WORKGROUP_SIZE: u32 = 64u;
+
+// The reference line and the two vertices joining it to the derived
+// samples, which follow.
+let HEADER: u32 = 6u;
+
+// Vertices come in pairs with the same position, one for each normal.
+fn sample(first: u32, i: u32) -> vec2<f32> {
+    let word = (first + 2u * i) * params.stride + params.position;
+    return vec2<f32>(points[word], points[word + 1u]);
+}
+
+// Samples are paired by index. Dividing by zero gives the reference value
+// rather than poisoning the strip.
+fn derived(i: u32) -> vec2<f32> {
+    let a = sample(params.a, i);
+    let b = sample(params.b, i);
+    if (params.ratio != 0u) {
+        return vec2<f32>(a.x, select(params.reference, a.y / b.y, b.y != 0.0));
+    }
+    return vec2<f32>(a.x, a.y - b.y);
+}
+
+fn diff_color(value: f32) -> vec4<f32> {
+    if (params.stop_count == 1u) {
+        return params.stops[0];
+    }
+
+    let t = 0.5 + 0.5 * (value - params.reference) / params.scale;
+    let scaled = clamp(t, 0.0, 1.0) * f32(params.stop_count - 1u);
+    let i = min(u32(scaled), params.stop_count - 2u);
+    return mix(params.stops[i], params.stops[i + 1u], scaled - f32(i));
+}
+
+fn write_vertex(slot: u32, position: vec2<f32>, normal: vec2<f32>, color: vec4<f32>) {
+    let word = slot * 8u;
+    vertices[word] = position.x;
+    vertices[word + 1u] = position.y;
+    vertices[word + 2u] = normal.x;
+    vertices[word + 3u] = normal.y;
+    vertices[word + 4u] = color.x;
+    vertices[word + 5u] = color.y;
+    vertices[word + 6u] = color.z;
+    vertices[word + 7u] = color.w;
+}
+
+@compute @workgroup_size(64)
+fn cs_diff(@builtin(global_invocation_id) id: vec3<u32>,
+           @builtin(num_workgroups) groups: vec3<u32>) {
+    let total = groups.x * WORKGROUP_SIZE;
+
+    for (var i = id.x; i < params.samples; i = i + total) {
+        let p = derived(i);
+        let previous = derived(select(i - 1u, i, i == 0u));
+        let next = derived(min(i + 1u, params.samples - 1u));
+
+        var normal = vec2<f32>(0.0, 1.0);
+        let direction = next - previous;
+        if (length(direction) > 0.0) {
+            normal = normalize(vec2<f32>(-direction.y, direction.x));
+        }
+
+        let color = diff_color(p.y);
+        write_vertex(HEADER + 2u * i, p, normal, color);
+        write_vertex(HEADER + 2u * i + 1u, p, -normal, color);
+        if (i == 0u) {
+            write_vertex(HEADER - 1u, p, normal, color);
+        }
+    }
+
+    // The reference line spans the derived samples.
+    if (id.x == 0u && params.samples > 0u) {
+        let x0 = derived(0u).x;
+        let x1 = derived(params.samples - 1u).x;
+        let up = vec2<f32>(0.0, 1.0);
+        let c = params.reference_color;
+        write_vertex(0u, vec2<f32>(x0, params.reference), up, c);
+        write_vertex(1u, vec2<f32>(x0, params.reference), -up, c);
+        write_vertex(2u, vec2<f32>(x1, params.reference), up, c);
+        write_vertex(3u, vec2<f32>(x1, params.reference), -up, c);
+        write_vertex(4u, vec2<f32>(x1, params.reference), -up, c);
+    }
+}
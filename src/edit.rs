@@ -0,0 +1,151 @@
+use std::ops::Range;
+
+use crate::Vertex;
+
+/// A change made to the plot's points by dragging a sample, reported so the
+/// application can apply it to its own data.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PointEdit {
+    /// Vertex index of the moved sample's first vertex.
+    pub index: u32,
+    pub from: [f32; 2],
+    pub to: [f32; 2],
+    /// The vertices rewritten, including neighbors whose normals changed.
+    /// Pass these to
+    /// [`GpuAcceleratedPlot::write_points`](crate::GpuAcceleratedPlot::write_points).
+    pub vertices: Range<u32>,
+}
+
+/// The state of dragging samples of one series, e.g. the keyframes of a
+/// curve or the points of a control polygon.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PointEditor {
+    vertices: Range<u32>,
+    /// How close the pointer must be to grab a sample, in pixels.
+    pub tolerance: f32,
+    /// Only let samples move vertically, keeping their x values.
+    pub lock_x: bool,
+    // The sample being dragged, and where it started.
+    dragging: Option<(u32, [f32; 2])>,
+}
+
+impl PointEditor {
+    /// An editor for a series' samples. `vertices` is the series' vertex
+    /// range, from
+    /// [`GpuAcceleratedPlot::series_vertices`](crate::GpuAcceleratedPlot::series_vertices).
+    pub fn new(vertices: Range<u32>) -> PointEditor {
+        PointEditor {
+            vertices,
+            tolerance: 8.0,
+            lock_x: false,
+            dragging: None,
+        }
+    }
+
+    pub fn with_tolerance(self, tolerance: f32) -> PointEditor {
+        PointEditor { tolerance, ..self }
+    }
+
+    pub fn with_locked_x(self) -> PointEditor {
+        PointEditor {
+            lock_x: true,
+            ..self
+        }
+    }
+
+    pub fn vertices(&self) -> Range<u32> {
+        self.vertices.clone()
+    }
+
+    /// Vertex index of the sample being dragged, if any.
+    pub fn dragging(&self) -> Option<u32> {
+        self.dragging.map(|(index, _)| index)
+    }
+
+    /// Start dragging the sample nearest to `pointer` within the tolerance,
+    /// where `to_screen` maps data positions to pixels. Returns whether one
+    /// was grabbed.
+    pub fn grab(
+        &mut self,
+        points: &[Vertex],
+        pointer: [f32; 2],
+        to_screen: impl Fn([f32; 2]) -> [f32; 2],
+    ) -> bool {
+        let end = (self.vertices.end as usize).min(points.len());
+        let start = (self.vertices.start as usize).min(end);
+
+        let distance = |position: [f32; 2]| {
+            let [x, y] = to_screen(position);
+            (x - pointer[0]).hypot(y - pointer[1])
+        };
+        let nearest = points[start..end]
+            .iter()
+            .enumerate()
+            .step_by(2)
+            .map(|(i, v)| (start + i, distance(v.position), v.position))
+            .filter(|(_, d, _)| *d <= self.tolerance)
+            .min_by(|(_, a, _), (_, b, _)| a.total_cmp(b));
+
+        self.dragging = nearest.map(|(i, _, position)| (i as u32, position));
+        self.dragging.is_some()
+    }
+
+    /// Move the dragged sample to `position`, in data units, updating
+    /// `points`. Returns the edit, relative to where the drag started.
+    pub fn drag(&mut self, points: &mut [Vertex], position: [f32; 2]) -> Option<PointEdit> {
+        let (index, from) = self.dragging?;
+        let to = if self.lock_x {
+            [from[0], position[1]]
+        } else {
+            position
+        };
+
+        let vertices = move_sample(points, self.vertices.clone(), index, to);
+        Some(PointEdit {
+            index,
+            from,
+            to,
+            vertices,
+        })
+    }
+
+    pub fn release(&mut self) {
+        self.dragging = None;
+    }
+}
+
+/// Move the sample whose first vertex is `index` to `position`, and point
+/// its normals and its neighbors' (within the series' `vertices`) across
+/// the line again, as in the examples. Returns the vertices changed.
+pub fn move_sample(
+    points: &mut [Vertex],
+    vertices: Range<u32>,
+    index: u32,
+    position: [f32; 2],
+) -> Range<u32> {
+    let end = vertices.end.min(points.len() as u32);
+    let start = vertices.start.min(end);
+    let index = index & !1;
+    if index < start || index + 1 >= end {
+        return index..index;
+    }
+
+    points[index as usize].position = position;
+    points[index as usize + 1].position = position;
+
+    let first = index.saturating_sub(2).max(start);
+    let last = (index + 4).min(end);
+    for i in (first..last).step_by(2) {
+        let previous = points[i.saturating_sub(2).max(start) as usize].position;
+        let next = points[(i + 2).min(end - 2) as usize].position;
+        let d = [next[0] - previous[0], next[1] - previous[1]];
+        let length = d[0].hypot(d[1]);
+        if length > 0.0 {
+            let normal = [d[1] / length, -d[0] / length];
+            points[i as usize].normal = normal;
+            points[i as usize + 1].normal = [-normal[0], -normal[1]];
+        }
+    }
+
+    first..last
+}
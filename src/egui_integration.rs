@@ -0,0 +1,959 @@
+use std::{
+    ops::{Range, RangeInclusive},
+    sync::{Arc, Once},
+};
+
+use egui::plot::PlotBounds;
+
+use crate::{
+    AxisBreaks, Bode, BodeReadout, Bounds, Categories, CellRect, DataCursor, GpuAcceleratedPlot,
+    GpuPlotContext, MarkerEditor, MarkerRegion, NumberFormat, PointEdit, PointEditor, Region,
+    RegionEdit, RegionSelector, SeriesId, StackedRows, StripChart, Subplot, SubplotLayout, Vertex,
+    ZoomAxes, ZoomHistory,
+};
+
+const HANDLE_WIDTH: f32 = 4.0;
+
+// The paint callbacks can't return an error, so one that finds no plot in
+// egui's callback resources reports it, once, and draws nothing.
+fn report_missing_plot(callback: &str) {
+    static REPORTED: Once = Once::new();
+    REPORTED.call_once(|| {
+        eprintln!(
+            "{}: no GpuAcceleratedPlot in egui's paint callback resources",
+            callback
+        )
+    });
+}
+
+impl From<PlotBounds> for Bounds {
+    fn from(bounds: PlotBounds) -> Self {
+        Bounds::from_min_max(bounds.min(), bounds.max())
+    }
+}
+
+impl From<Bounds> for PlotBounds {
+    fn from(bounds: Bounds) -> Self {
+        PlotBounds::from_min_max(bounds.min(), bounds.max())
+    }
+}
+
+impl GpuAcceleratedPlot {
+    /// Register the plot texture with egui, to be shown with an image,
+    /// sampled with [`GpuAcceleratedPlot::sampler_descriptor`] so that its
+    /// mip levels are blended when mipmaps are on.
+    pub fn register_texture(
+        &self,
+        device: &wgpu::Device,
+        renderer: &mut egui_wgpu::Renderer,
+    ) -> egui::TextureId {
+        renderer.register_native_texture_with_sampler_options(
+            device,
+            &self.create_view(),
+            self.sampler_descriptor(),
+        )
+    }
+
+    /// Point a texture from [`GpuAcceleratedPlot::register_texture`] at the
+    /// current plot texture, which is recreated when the plot is resized or
+    /// its mipmaps are toggled.
+    pub fn update_texture(
+        &self,
+        device: &wgpu::Device,
+        renderer: &mut egui_wgpu::Renderer,
+        id: egui::TextureId,
+    ) {
+        renderer.update_egui_texture_from_wgpu_texture_with_sampler_options(
+            device,
+            &self.create_view(),
+            self.sampler_descriptor(),
+            id,
+        );
+    }
+}
+
+impl GpuPlotContext {
+    /// Get the context stored in egui's paint callback resources, creating it
+    /// on first use.
+    pub fn shared(render_state: &egui_wgpu::RenderState) -> Arc<GpuPlotContext> {
+        let mut renderer = render_state.renderer.write();
+        let resources = &mut renderer.paint_callback_resources;
+
+        if let Some(context) = resources.get::<Arc<GpuPlotContext>>() {
+            return Arc::clone(context);
+        }
+
+        let context = Arc::new(GpuPlotContext::new(&render_state.device));
+        resources.insert(Arc::clone(&context));
+        context
+    }
+}
+
+/// Render the plot into its texture, to be shown with an egui image.
+///
+/// The texture registered with egui is the one which existed when the UI
+/// was built, so after a resize egui shows the previous frame's texture for
+/// a frame, lagging behind the axes. [`egui_wgpu_composite_callback`] avoids
+/// this by drawing straight into egui's render pass.
+pub fn egui_wgpu_callback(
+    bounds: PlotBounds,
+    points: Arc<Vec<Vertex>>,
+    rect: egui::Rect,
+    dirty: bool,
+) -> egui::PaintCallback {
+    let cb =
+        egui_wgpu::CallbackFn::new().prepare(move |device, queue, paint_callback_resources| {
+            if let Some(plot) = paint_callback_resources.get_mut::<GpuAcceleratedPlot>() {
+                plot.prepare(
+                    device,
+                    queue,
+                    [rect.width() as u32, rect.height() as u32],
+                    &Bounds::from(bounds),
+                    &points,
+                    dirty,
+                );
+
+                plot.render(device, queue);
+            } else {
+                report_missing_plot("egui_wgpu_callback");
+            }
+        });
+
+    egui::PaintCallback {
+        rect,
+        callback: Arc::new(cb),
+    }
+}
+
+/// Like [`egui_wgpu_callback`], but draws the plot straight into egui's
+/// render pass over `rect` instead of into a texture, so it's always in step
+/// with the rest of the frame, including while panning and resizing. The
+/// plot must have been created with egui's target format and a single
+/// sample, and post-processing (glow, color vision simulation, mipmaps and
+/// thumbnails) isn't applied. See
+/// [`GpuAcceleratedPlot::render_onto_renderpass`].
+pub fn egui_wgpu_composite_callback(
+    bounds: PlotBounds,
+    points: Arc<Vec<Vertex>>,
+    rect: egui::Rect,
+    dirty: bool,
+) -> egui::PaintCallback {
+    let cb = egui_wgpu::CallbackFn::new()
+        .prepare(move |device, queue, paint_callback_resources| {
+            if let Some(plot) = paint_callback_resources.get_mut::<GpuAcceleratedPlot>() {
+                plot.prepare(
+                    device,
+                    queue,
+                    [rect.width() as u32, rect.height() as u32],
+                    &Bounds::from(bounds),
+                    &points,
+                    dirty,
+                );
+
+                plot.dispatch_compute(device, queue);
+            } else {
+                report_missing_plot("egui_wgpu_composite_callback");
+            }
+        })
+        .paint(move |info, rpass, paint_callback_resources| {
+            // egui sets the viewport to the callback's rect and clips to its
+            // clip rect; subplot cells and the inset are placed within them.
+            let viewport = info.viewport_in_pixels();
+            if let Some(plot) = paint_callback_resources.get::<GpuAcceleratedPlot>() {
+                plot.render_onto_renderpass_at(
+                    rpass,
+                    [
+                        viewport.left_px,
+                        viewport.top_px,
+                        viewport.width_px,
+                        viewport.height_px,
+                    ],
+                    clip_rect_in_pixels(info),
+                );
+            } else {
+                report_missing_plot("egui_wgpu_composite_callback");
+            }
+        });
+
+    egui::PaintCallback {
+        rect,
+        callback: Arc::new(cb),
+    }
+}
+
+// The callback's clip rect in the screen's pixels, limited to the screen as
+// egui limits its own scissor rect.
+fn clip_rect_in_pixels(info: &egui::PaintCallbackInfo) -> CellRect {
+    let clip = info.clip_rect_in_pixels();
+    let [screen_width, screen_height] = info.screen_size_px;
+    let left = clip.left_px.round().max(0.0) as u32;
+    let top = clip.top_px.round().max(0.0) as u32;
+    let right = ((clip.left_px + clip.width_px).round().max(0.0) as u32).min(screen_width);
+    let bottom = ((clip.top_px + clip.height_px).round().max(0.0) as u32).min(screen_height);
+    CellRect {
+        x: left,
+        y: top,
+        width: right.saturating_sub(left),
+        height: bottom.saturating_sub(top),
+    }
+}
+
+/// Like [`egui_wgpu_callback`], but only uploads the `edited` vertex ranges
+/// of `points`, e.g. from [`edit_points`], falling back to uploading all of
+/// them when they can't be patched in place. See
+/// [`GpuAcceleratedPlot::write_points`].
+pub fn egui_wgpu_edit_callback(
+    bounds: PlotBounds,
+    points: Arc<Vec<Vertex>>,
+    rect: egui::Rect,
+    edited: Vec<Range<u32>>,
+) -> egui::PaintCallback {
+    let cb =
+        egui_wgpu::CallbackFn::new().prepare(move |device, queue, paint_callback_resources| {
+            if let Some(plot) = paint_callback_resources.get_mut::<GpuAcceleratedPlot>() {
+                let dirty = !edited
+                    .iter()
+                    .all(|vertices| plot.write_points(queue, &points, vertices.clone()));
+
+                plot.prepare(
+                    device,
+                    queue,
+                    [rect.width() as u32, rect.height() as u32],
+                    &Bounds::from(bounds),
+                    &points,
+                    dirty,
+                );
+
+                plot.render(device, queue);
+            } else {
+                report_missing_plot("egui_wgpu_edit_callback");
+            }
+        });
+
+    egui::PaintCallback {
+        rect,
+        callback: Arc::new(cb),
+    }
+}
+
+/// Compute the statistics of a completed selection, e.g. from
+/// [`region_selector`], passing them to the plot's selection callback. Add
+/// it after the main plot callback so that it sees the same data. See
+/// [`GpuAcceleratedPlot::select_region`].
+#[cfg(feature = "compute")]
+pub fn egui_wgpu_selection_callback(rect: egui::Rect, region: Region) -> egui::PaintCallback {
+    let cb =
+        egui_wgpu::CallbackFn::new().prepare(move |device, queue, paint_callback_resources| {
+            if let Some(plot) = paint_callback_resources.get_mut::<GpuAcceleratedPlot>() {
+                plot.select_region(device, queue, region);
+            } else {
+                report_missing_plot("egui_wgpu_selection_callback");
+            }
+        });
+
+    egui::PaintCallback {
+        rect,
+        callback: Arc::new(cb),
+    }
+}
+
+/// Like [`egui_wgpu_callback`], but renders a grid of subplots. See
+/// [`GpuAcceleratedPlot::prepare_subplots`].
+pub fn egui_wgpu_subplots_callback(
+    layout: SubplotLayout,
+    subplots: Arc<Vec<Subplot>>,
+    points: Arc<Vec<Vertex>>,
+    rect: egui::Rect,
+    dirty: bool,
+) -> egui::PaintCallback {
+    let cb =
+        egui_wgpu::CallbackFn::new().prepare(move |device, queue, paint_callback_resources| {
+            if let Some(plot) = paint_callback_resources.get_mut::<GpuAcceleratedPlot>() {
+                plot.prepare_subplots(
+                    device,
+                    queue,
+                    [rect.width() as u32, rect.height() as u32],
+                    &layout,
+                    &subplots,
+                    &points,
+                    dirty,
+                );
+
+                plot.render(device, queue);
+            } else {
+                report_missing_plot("egui_wgpu_subplots_callback");
+            }
+        });
+
+    egui::PaintCallback {
+        rect,
+        callback: Arc::new(cb),
+    }
+}
+
+/// Like [`egui_wgpu_callback`], but renders the plot's overview viewport. Add
+/// it after the main plot callback so that it sees the same data.
+pub fn egui_wgpu_overview_callback(rect: egui::Rect) -> egui::PaintCallback {
+    let cb =
+        egui_wgpu::CallbackFn::new().prepare(move |device, queue, paint_callback_resources| {
+            if let Some(plot) = paint_callback_resources.get_mut::<GpuAcceleratedPlot>() {
+                plot.prepare_overview(device, [rect.width() as u32, rect.height() as u32]);
+                plot.render_overview(device, queue);
+            } else {
+                report_missing_plot("egui_wgpu_overview_callback");
+            }
+        });
+
+    egui::PaintCallback {
+        rect,
+        callback: Arc::new(cb),
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Grab {
+    Move,
+    Left,
+    Right,
+}
+
+/// Show an overview strip of the whole dataset with a draggable window
+/// selecting the `x_window` range displayed by the main plot.
+///
+/// `texture_id` should be registered from
+/// [`GpuAcceleratedPlot::create_overview_view`],
+/// and `data_x` is the full x extent of the data. The response is marked as
+/// changed whenever the window is moved or resized.
+pub fn overview_strip(
+    ui: &mut egui::Ui,
+    texture_id: egui::TextureId,
+    height: f32,
+    data_x: [f64; 2],
+    x_window: &mut [f64; 2],
+) -> egui::Response {
+    let (rect, mut response) = ui.allocate_exact_size(
+        egui::vec2(ui.available_width(), height),
+        egui::Sense::click_and_drag(),
+    );
+
+    let span = (data_x[1] - data_x[0]).max(f64::EPSILON);
+    let to_screen = |x: f64| rect.left() + ((x - data_x[0]) / span) as f32 * rect.width();
+    let to_data = |dx: f32| (dx / rect.width()) as f64 * span;
+
+    let window_rect = |window: &[f64; 2]| {
+        egui::Rect::from_x_y_ranges(to_screen(window[0])..=to_screen(window[1]), rect.y_range())
+    };
+
+    if let Some(pointer) = response.interact_pointer_pos() {
+        let current = window_rect(x_window);
+
+        if response.drag_started() || response.clicked() {
+            let grab = if (pointer.x - current.left()).abs() <= HANDLE_WIDTH {
+                Grab::Left
+            } else if (pointer.x - current.right()).abs() <= HANDLE_WIDTH {
+                Grab::Right
+            } else {
+                if !current.contains(pointer) {
+                    // Jump the window so that it is centered on the pointer.
+                    let width = x_window[1] - x_window[0];
+                    let center = data_x[0] + to_data(pointer.x - rect.left());
+                    *x_window = [center - width / 2.0, center + width / 2.0];
+                    response.mark_changed();
+                }
+                Grab::Move
+            };
+            ui.memory().data.insert_temp(response.id, grab);
+        }
+
+        let delta = to_data(response.drag_delta().x);
+        if delta != 0.0 {
+            match ui.memory().data.get_temp(response.id).unwrap_or(Grab::Move) {
+                Grab::Move => {
+                    x_window[0] += delta;
+                    x_window[1] += delta;
+                }
+                Grab::Left => x_window[0] = (x_window[0] + delta).min(x_window[1]),
+                Grab::Right => x_window[1] = (x_window[1] + delta).max(x_window[0]),
+            }
+            response.mark_changed();
+        }
+
+        // Keep the window inside of the data, preserving its width if possible.
+        let width = (x_window[1] - x_window[0]).min(span);
+        if x_window[0] < data_x[0] {
+            *x_window = [data_x[0], data_x[0] + width];
+        } else if x_window[1] > data_x[1] {
+            *x_window = [data_x[1] - width, data_x[1]];
+        }
+    }
+
+    let painter = ui.painter_at(rect);
+    let uv = egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0));
+    painter.image(texture_id, rect, uv, egui::Color32::WHITE);
+
+    // Dim everything outside of the window.
+    let window = window_rect(x_window);
+    let shade = egui::Color32::from_black_alpha(96);
+    painter.rect_filled(
+        egui::Rect::from_min_max(rect.left_top(), egui::pos2(window.left(), rect.bottom())),
+        0.0,
+        shade,
+    );
+    painter.rect_filled(
+        egui::Rect::from_min_max(egui::pos2(window.right(), rect.top()), rect.right_bottom()),
+        0.0,
+        shade,
+    );
+    painter.rect_stroke(window, 0.0, ui.visuals().selection.stroke);
+
+    response
+}
+
+/// Draw a data cursor's marker over a plot shown in `response.rect` with
+/// `bounds`, stepping it with the left and right arrow keys (by 10 samples
+/// with shift) while the plot is hovered, and snapping it to the pointer on
+/// click. Returns the cursor's position for readouts.
+pub fn data_cursor(
+    ui: &egui::Ui,
+    response: &egui::Response,
+    bounds: &Bounds,
+    cursor: &mut DataCursor,
+    points: &[Vertex],
+) -> Option<[f32; 2]> {
+    let rect = response.rect;
+
+    if response.clicked() {
+        if let Some(pointer) = response.interact_pointer_pos() {
+            let t = ((pointer.x - rect.left()) / rect.width()) as f64;
+            cursor.snap_to(points, bounds.x[0] + t * bounds.width());
+        }
+    }
+
+    if response.hovered() {
+        let input = ui.input();
+        let stride = if input.modifiers.shift { 10 } else { 1 };
+        if input.key_pressed(egui::Key::ArrowLeft) {
+            cursor.step(-stride);
+        }
+        if input.key_pressed(egui::Key::ArrowRight) {
+            cursor.step(stride);
+        }
+    }
+
+    let position = cursor.position(points)?;
+    let to_screen =
+        |value: f32, range: [f64; 2]| ((value as f64 - range[0]) / (range[1] - range[0])) as f32;
+    let painter = ui.painter_at(rect);
+    // Snap the crosshair to physical pixels so that it stays sharp at
+    // fractional scale factors.
+    let marker = egui::pos2(
+        painter.round_to_pixel(rect.left() + to_screen(position[0], bounds.x) * rect.width()),
+        painter.round_to_pixel(rect.bottom() - to_screen(position[1], bounds.y) * rect.height()),
+    );
+
+    let stroke = ui.visuals().selection.stroke;
+    painter.vline(marker.x, rect.y_range(), stroke);
+    painter.hline(rect.x_range(), marker.y, stroke);
+    painter.circle_stroke(marker, 4.0, stroke);
+
+    Some(position)
+}
+
+/// Show a [`StripChart`] following its newest sample, rendered with
+/// [`egui_wgpu_callback`], with the sample nearest the pointer marked and
+/// labeled with `format`. Returns the sample, if any.
+pub fn strip_chart(
+    ui: &mut egui::Ui,
+    chart: &mut StripChart,
+    size: egui::Vec2,
+    format: &NumberFormat,
+) -> (egui::Response, Option<[f64; 2]>) {
+    let (rect, response) = ui.allocate_exact_size(size, egui::Sense::hover());
+    let bounds = chart.bounds();
+    let points = chart.vertices(bounds.x, rect.width() as u32);
+    let dirty = chart.take_dirty();
+    ui.painter().add(egui_wgpu_callback(
+        bounds.into(),
+        Arc::new(points),
+        rect,
+        dirty,
+    ));
+
+    let readout = response
+        .hover_pos()
+        .and_then(|pos| {
+            let t = ((pos.x - rect.left()) / rect.width()) as f64;
+            chart.readout(bounds.x[0] + t * bounds.width())
+        })
+        .filter(|[t, _]| bounds.x[0] <= *t && *t <= bounds.x[1]);
+
+    if let Some([t, value]) = readout {
+        let to_screen = |v: f64, range: [f64; 2]| ((v - range[0]) / (range[1] - range[0])) as f32;
+        let marker = egui::pos2(
+            rect.left() + to_screen(t, bounds.x) * rect.width(),
+            rect.bottom() - to_screen(value, bounds.y) * rect.height(),
+        );
+        let painter = ui.painter_at(rect);
+        let visuals = ui.visuals();
+        painter.circle_stroke(marker, 4.0, visuals.selection.stroke);
+        painter.text(
+            marker + egui::vec2(6.0, -6.0),
+            egui::Align2::LEFT_BOTTOM,
+            format.format(value),
+            egui::FontId::default(),
+            visuals.text_color(),
+        );
+    }
+
+    (response, readout)
+}
+
+/// Let the user drag the samples of `editor`'s series over a plot shown in
+/// `response.rect` with `bounds` (which needs a `Sense::drag()` response),
+/// updating `points` and highlighting the sample being dragged. Returns the
+/// edit made this frame, if any: upload it with [`egui_wgpu_edit_callback`]
+/// and apply it to the application's own data.
+pub fn edit_points(
+    ui: &egui::Ui,
+    response: &egui::Response,
+    bounds: &Bounds,
+    editor: &mut PointEditor,
+    points: &mut [Vertex],
+) -> Option<PointEdit> {
+    let rect = response.rect;
+    let to_screen = |position: [f32; 2]| {
+        let t = |value: f32, range: [f64; 2]| {
+            ((value as f64 - range[0]) / (range[1] - range[0])) as f32
+        };
+        [
+            rect.left() + t(position[0], bounds.x) * rect.width(),
+            rect.bottom() - t(position[1], bounds.y) * rect.height(),
+        ]
+    };
+
+    if response.drag_started() {
+        if let Some(pointer) = response.interact_pointer_pos() {
+            editor.grab(points, [pointer.x, pointer.y], to_screen);
+        }
+    }
+
+    let mut edit = None;
+    if response.dragged() {
+        if let Some(pointer) = response.interact_pointer_pos() {
+            let x =
+                bounds.x[0] + ((pointer.x - rect.left()) / rect.width()) as f64 * bounds.width();
+            let y = bounds.y[0]
+                + ((rect.bottom() - pointer.y) / rect.height()) as f64 * bounds.height();
+            edit = editor.drag(points, [x as f32, y as f32]);
+        }
+    }
+    if response.drag_released() {
+        editor.release();
+    }
+
+    if let Some(position) = editor.dragging().and_then(|i| points.get(i as usize)) {
+        let [x, y] = to_screen(position.position);
+        ui.painter_at(rect)
+            .circle_stroke(egui::pos2(x, y), 4.0, ui.visuals().selection.stroke);
+    }
+
+    edit
+}
+
+/// Let the user drag the handles of marker regions over a plot shown in
+/// `response.rect` with `bounds` (which needs a `Sense::drag()` response),
+/// updating `regions`. Returns the edit made this frame, if any: pass the
+/// regions to [`GpuAcceleratedPlot::set_markers`] and apply the edit to the
+/// application's own model. Once a handle is released, snap it with
+/// [`MarkerEditor::pending_snap`] and [`MarkerEditor::snap`].
+pub fn edit_markers(
+    ui: &egui::Ui,
+    response: &egui::Response,
+    bounds: &Bounds,
+    editor: &mut MarkerEditor,
+    regions: &mut [MarkerRegion],
+) -> Option<RegionEdit> {
+    let rect = response.rect;
+    let to_screen =
+        |x: f32| rect.left() + ((x as f64 - bounds.x[0]) / bounds.width()) as f32 * rect.width();
+    let to_data =
+        |x: f32| (bounds.x[0] + ((x - rect.left()) / rect.width()) as f64 * bounds.width());
+
+    if response.drag_started() {
+        if let Some(pointer) = response.interact_pointer_pos() {
+            editor.grab(regions, pointer.x, to_screen);
+        }
+    }
+
+    let mut edit = None;
+    if response.dragged() {
+        if let Some(pointer) = response.interact_pointer_pos() {
+            edit = editor.drag(regions, to_data(pointer.x) as f32);
+        }
+    }
+    if response.drag_released() {
+        editor.release((bounds.width() / rect.width() as f64) as f32);
+    }
+
+    let hovered = response
+        .hover_pos()
+        .filter(|_| editor.dragging().is_none())
+        .map(|pointer| pointer.x);
+    let highlighted = editor
+        .dragging()
+        .and_then(|(region, handle)| Some(regions.get(region)?.x[handle.index()]))
+        .or_else(|| {
+            let pointer = hovered?;
+            regions
+                .iter()
+                .flat_map(|region| region.x)
+                .find(|&x| (to_screen(x) - pointer).abs() <= editor.tolerance)
+        });
+    if let Some(x) = highlighted {
+        ui.ctx().output().cursor_icon = egui::CursorIcon::ResizeHorizontal;
+        ui.painter_at(rect)
+            .vline(to_screen(x), rect.y_range(), ui.visuals().selection.stroke);
+    }
+
+    edit
+}
+
+/// Let the user drag out a selection over a plot shown in `response.rect`
+/// with `bounds` (which needs a `Sense::drag()` response), drawing it while
+/// dragging. Returns the region once the drag is released, e.g. for
+/// [`egui_wgpu_selection_callback`].
+pub fn region_selector(
+    ui: &egui::Ui,
+    response: &egui::Response,
+    bounds: &Bounds,
+    selector: &mut RegionSelector,
+) -> Option<Region> {
+    let rect = response.rect;
+    let to_data = |pos: egui::Pos2| {
+        [
+            bounds.x[0] + ((pos.x - rect.left()) / rect.width()) as f64 * bounds.width(),
+            bounds.y[0] + ((rect.bottom() - pos.y) / rect.height()) as f64 * bounds.height(),
+        ]
+    };
+    let to_screen = |x: f64, y: f64| {
+        egui::pos2(
+            rect.left() + ((x - bounds.x[0]) / bounds.width()) as f32 * rect.width(),
+            rect.bottom() - ((y - bounds.y[0]) / bounds.height()) as f32 * rect.height(),
+        )
+    };
+
+    let pointer = response.interact_pointer_pos();
+    if response.drag_started() {
+        if let Some(pos) = pointer {
+            selector.begin(to_data(pos));
+        }
+    }
+
+    let pos = pointer.or_else(|| response.hover_pos())?;
+    if response.drag_released() {
+        return selector.end(to_data(pos));
+    }
+
+    if let Some(region) = selector.region(to_data(pos)) {
+        let [y0, y1] = region.y.unwrap_or(bounds.y);
+        let selection =
+            egui::Rect::from_two_pos(to_screen(region.x[0], y0), to_screen(region.x[1], y1))
+                .intersect(rect);
+        let stroke = ui.visuals().selection.stroke;
+        ui.painter_at(rect)
+            .rect(selection, 0.0, stroke.color.linear_multiply(0.2), stroke);
+    }
+
+    None
+}
+
+/// Zoom the plot shown in `response.rect` with the scroll wheel or a pinch
+/// around the pointer while it's hovered: along x only while Ctrl is held,
+/// and y only while Shift is. Each gesture is recorded in `history`, which
+/// Alt+Left and Alt+Right step back and forward through. Returns whether
+/// `bounds` changed.
+///
+/// Turn off the zoom of an `egui::plot::Plot` underneath with
+/// `Plot::allow_zoom(false)`, so that it doesn't zoom too.
+pub fn zoom_navigation(
+    ui: &egui::Ui,
+    response: &egui::Response,
+    bounds: &mut Bounds,
+    history: &mut ZoomHistory,
+) -> bool {
+    let (modifiers, scroll, zoom, back, forward) = {
+        let input = ui.input();
+        let alt = input.modifiers.alt;
+        (
+            input.modifiers,
+            input.scroll_delta.x + input.scroll_delta.y,
+            input.zoom_delta(),
+            alt && input.key_pressed(egui::Key::ArrowLeft),
+            alt && input.key_pressed(egui::Key::ArrowRight),
+        )
+    };
+    let pointer = match response.hover_pos() {
+        Some(pointer) => pointer,
+        None => {
+            history.settle();
+            return false;
+        }
+    };
+
+    let step = if back {
+        history.zoom_back(*bounds)
+    } else if forward {
+        history.zoom_forward(*bounds)
+    } else {
+        None
+    };
+    if let Some(step) = step {
+        *bounds = step;
+        return true;
+    }
+
+    // Ctrl turns scrolling into zoom_delta, which is covered either way.
+    let factor = zoom as f64 * (scroll as f64 / 200.0).exp();
+    if factor == 1.0 || !factor.is_finite() {
+        history.settle();
+        return false;
+    }
+
+    let axes = if modifiers.command || modifiers.ctrl {
+        ZoomAxes::X
+    } else if modifiers.shift {
+        ZoomAxes::Y
+    } else {
+        ZoomAxes::Both
+    };
+    let rect = response.rect;
+    let center = [
+        bounds.x[0] + ((pointer.x - rect.left()) / rect.width()) as f64 * bounds.width(),
+        bounds.y[0] + ((rect.bottom() - pointer.y) / rect.height()) as f64 * bounds.height(),
+    ];
+    history.zoomed(*bounds);
+    *bounds = bounds.zoomed(center, factor, axes);
+    true
+}
+
+/// What the user picked from [`plot_context_menu`], for the app to carry
+/// out, as it owns the bounds, the points and the clipboard.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlotMenuAction {
+    /// Fit the bounds to the data.
+    Autoscale,
+    /// Copy the plot as an image, e.g. from
+    /// [`GpuAcceleratedPlot::request_image`] (which doesn't block, so also
+    /// works on the web) or [`GpuAcceleratedPlot::read_rgba`], through a
+    /// clipboard crate, as egui can't put images on the clipboard.
+    CopyImage,
+    /// Save the points, e.g. as [`GpuAcceleratedPlot::to_csv`].
+    ExportCsv,
+    /// Show or hide a series, with
+    /// [`GpuAcceleratedPlot::set_series_visible`].
+    SetSeriesVisible(SeriesId, bool),
+    /// Go back to the view the plot started with.
+    ResetView,
+}
+
+/// Show a context menu when the plot in `response` is right clicked, with
+/// the usual actions: autoscale, copy image, export CSV, a toggle per
+/// series and reset view. `custom` adds the app's own items below them.
+/// Returns the action picked, if any.
+pub fn plot_context_menu(
+    response: &egui::Response,
+    plot: &GpuAcceleratedPlot,
+    custom: impl FnOnce(&mut egui::Ui),
+) -> Option<PlotMenuAction> {
+    let mut action = None;
+    response.clone().context_menu(|ui| {
+        let mut item = |ui: &mut egui::Ui, label: &str, picked: PlotMenuAction| {
+            if ui.button(label).clicked() {
+                action = Some(picked);
+                ui.close_menu();
+            }
+        };
+        item(ui, "Autoscale", PlotMenuAction::Autoscale);
+        item(ui, "Reset view", PlotMenuAction::ResetView);
+        ui.separator();
+        item(ui, "Copy image", PlotMenuAction::CopyImage);
+        item(ui, "Export CSV", PlotMenuAction::ExportCsv);
+
+        if plot.series_count() > 0 {
+            ui.separator();
+            ui.menu_button("Series", |ui| {
+                for i in 0..plot.series_count() {
+                    let id = SeriesId(i);
+                    let mut visible = plot.series_visible(id);
+                    let name = match plot.series_name(id) {
+                        Some(name) => name.to_owned(),
+                        None => format!("Series {}", i + 1),
+                    };
+                    if ui.checkbox(&mut visible, name).changed() {
+                        action = Some(PlotMenuAction::SetSeriesVisible(id, visible));
+                    }
+                }
+            });
+        }
+
+        ui.separator();
+        custom(ui);
+    });
+    action
+}
+
+/// Where to show a plot texture as a `PlotImage` in an `egui::plot::Plot`
+/// with the plot's `margin_fraction` (see `Plot::set_margin_fraction`,
+/// 0.05 by default), given the plot's current bounds and rect.
+///
+/// An image covering all of the bounds counts towards the plot's automatic
+/// bounds, which then add the margins around it, so the bounds grow every
+/// frame. The bounds and rect returned leave the margins out, so the image
+/// plus its margins gives back the same bounds. Render the texture for both,
+/// e.g. by passing them to [`egui_wgpu_callback`], and place the image with
+/// `PlotImage::new(texture_id, bounds.center(), [bounds.width() as f32,
+/// bounds.height() as f32])`.
+pub fn plot_image_area(
+    bounds: PlotBounds,
+    rect: egui::Rect,
+    margin_fraction: egui::Vec2,
+) -> (PlotBounds, egui::Rect) {
+    let margin = [margin_fraction.x as f64, margin_fraction.y as f64].map(|m| m.max(0.0));
+    let inset = margin.map(|m| m / (1.0 + 2.0 * m));
+
+    let (min, max) = (bounds.min(), bounds.max());
+    let size = [max[0] - min[0], max[1] - min[1]];
+    let image_bounds = PlotBounds::from_min_max(
+        [min[0] + size[0] * inset[0], min[1] + size[1] * inset[1]],
+        [max[0] - size[0] * inset[0], max[1] - size[1] * inset[1]],
+    );
+    let image_rect = rect.shrink2(egui::vec2(
+        rect.width() * inset[0] as f32,
+        rect.height() * inset[1] as f32,
+    ));
+
+    (image_bounds, image_rect)
+}
+
+/// Label an axis of the `egui::plot::Plot` hosting a plot with `format`,
+/// e.g. `plot.y_axis_formatter(axis_formatter(NumberFormat::si("V")))`.
+pub fn axis_formatter(format: NumberFormat) -> impl Fn(f64, &RangeInclusive<f64>) -> String {
+    move |value, _| format.format(value)
+}
+
+/// Label a categorical axis with its category names at their centers, and
+/// nothing in between.
+pub fn category_formatter(categories: Categories) -> impl Fn(f64, &RangeInclusive<f64>) -> String {
+    move |value, _| {
+        if value.fract() != 0.0 {
+            return String::new();
+        }
+        categories.label_at(value).unwrap_or_default().to_owned()
+    }
+}
+
+/// Show stacked rows sharing an x axis, rendered as subplots with
+/// [`egui_wgpu_subplots_callback`], with a cursor synchronized across every
+/// row and a single x axis strip `axis_height` tall underneath, labeled with
+/// `format`. Returns the x value under the pointer, if any, e.g. for
+/// readouts.
+pub fn stacked_rows(
+    ui: &mut egui::Ui,
+    rows: &StackedRows,
+    points: Arc<Vec<Vertex>>,
+    size: egui::Vec2,
+    axis_height: f32,
+    format: &NumberFormat,
+    dirty: bool,
+) -> (egui::Response, Option<f64>) {
+    let max_ticks = (size.x / 80.0).max(1.0) as usize;
+    let ticks = AxisBreaks::default()
+        .ticks(rows.x, max_ticks)
+        .into_iter()
+        .map(|tick| (tick, format.format(tick)))
+        .collect();
+    stacked_rows_with_ticks(ui, rows, points, size, axis_height, ticks, dirty)
+}
+
+/// Show a [`Bode`] plot as stacked rows (see [`stacked_rows`]), rendering
+/// `rows` from [`Bode::rows`] over the vertices from [`Bode::vertices`],
+/// with the frequency axis labeled at decades in hertz. Returns the
+/// response under the pointer, read from both rows, if any.
+pub fn bode_plot(
+    ui: &mut egui::Ui,
+    bode: &Bode,
+    rows: &StackedRows,
+    points: Arc<Vec<Vertex>>,
+    size: egui::Vec2,
+    axis_height: f32,
+    dirty: bool,
+) -> (egui::Response, Option<BodeReadout>) {
+    let format = NumberFormat::si("Hz");
+    let ticks = Bode::decade_ticks(rows.x)
+        .into_iter()
+        .map(|tick| (tick, format.format(10f64.powf(tick))))
+        .collect();
+    let (response, x) = stacked_rows_with_ticks(ui, rows, points, size, axis_height, ticks, dirty);
+    (response, x.and_then(|x| bode.readout(x)))
+}
+
+fn stacked_rows_with_ticks(
+    ui: &mut egui::Ui,
+    rows: &StackedRows,
+    points: Arc<Vec<Vertex>>,
+    size: egui::Vec2,
+    axis_height: f32,
+    ticks: Vec<(f64, String)>,
+    dirty: bool,
+) -> (egui::Response, Option<f64>) {
+    let (rect, response) = ui.allocate_exact_size(size, egui::Sense::click_and_drag());
+    let (plot_rect, axis_rect) = rect.split_top_bottom_at_y(rect.bottom() - axis_height);
+
+    ui.painter().add(egui_wgpu_subplots_callback(
+        rows.layout(),
+        Arc::new(rows.subplots()),
+        points,
+        plot_rect,
+        dirty,
+    ));
+
+    let to_screen = |x: f64| {
+        let t = (x - rows.x[0]) / (rows.x[1] - rows.x[0]);
+        plot_rect.left() + t as f32 * plot_rect.width()
+    };
+
+    let painter = ui.painter_at(rect);
+    let visuals = ui.visuals();
+    let text_color = visuals.text_color();
+    for (tick, label) in ticks {
+        let x = to_screen(tick);
+        painter.vline(
+            x,
+            axis_rect.top()..=axis_rect.top() + 4.0,
+            visuals.widgets.noninteractive.fg_stroke,
+        );
+        painter.text(
+            egui::pos2(x, axis_rect.top() + 4.0),
+            egui::Align2::CENTER_TOP,
+            label,
+            egui::FontId::default(),
+            text_color,
+        );
+    }
+
+    // A single line through every row keeps their cursors in sync.
+    let hovered = response
+        .hover_pos()
+        .filter(|pos| plot_rect.contains(*pos))
+        .map(|pos| {
+            painter.vline(
+                painter.round_to_pixel(pos.x),
+                plot_rect.y_range(),
+                visuals.selection.stroke,
+            );
+            let t = ((pos.x - plot_rect.left()) / plot_rect.width()) as f64;
+            rows.x[0] + t * (rows.x[1] - rows.x[0])
+        });
+
+    (response, hovered)
+}
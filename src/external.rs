@@ -0,0 +1,75 @@
+use std::sync::Arc;
+
+use crate::Vertex;
+
+/// Where each vertex attribute lives within a vertex, in bytes. Attributes
+/// have the same types as in [`Vertex`]: `[f32; 2]` positions and normals
+/// and `[f32; 4]` colors.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct VertexLayout {
+    pub stride: wgpu::BufferAddress,
+    pub position: wgpu::BufferAddress,
+    pub normal: wgpu::BufferAddress,
+    pub color: wgpu::BufferAddress,
+}
+
+impl Default for VertexLayout {
+    /// The layout of [`Vertex`].
+    fn default() -> Self {
+        VertexLayout {
+            stride: std::mem::size_of::<Vertex>() as wgpu::BufferAddress,
+            position: 0,
+            normal: 8,
+            color: 16,
+        }
+    }
+}
+
+impl VertexLayout {
+    pub(crate) fn attributes(&self) -> [wgpu::VertexAttribute; 3] {
+        [
+            wgpu::VertexAttribute {
+                format: wgpu::VertexFormat::Float32x2,
+                offset: self.position,
+                shader_location: 0,
+            },
+            wgpu::VertexAttribute {
+                format: wgpu::VertexFormat::Float32x2,
+                offset: self.normal,
+                shader_location: 1,
+            },
+            wgpu::VertexAttribute {
+                format: wgpu::VertexFormat::Float32x4,
+                offset: self.color,
+                shader_location: 2,
+            },
+        ]
+    }
+}
+
+/// Vertices stored in a buffer owned by the application, e.g. the output of
+/// a compute shader, which the plot draws from directly instead of its own
+/// vertex buffer. The buffer must have been created with
+/// `BufferUsages::VERTEX`.
+#[derive(Clone, Debug)]
+pub struct ExternalPoints {
+    pub buffer: Arc<wgpu::Buffer>,
+    /// Byte offset of the first vertex.
+    pub offset: wgpu::BufferAddress,
+    /// Number of vertices to draw.
+    pub count: u32,
+    pub layout: VertexLayout,
+}
+
+impl ExternalPoints {
+    /// Vertices laid out exactly like [`Vertex`], starting at the beginning
+    /// of the buffer.
+    pub fn packed(buffer: Arc<wgpu::Buffer>, count: u32) -> ExternalPoints {
+        ExternalPoints {
+            buffer,
+            offset: 0,
+            count,
+            layout: VertexLayout::default(),
+        }
+    }
+}
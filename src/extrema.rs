@@ -0,0 +1,246 @@
+use std::ops::Range;
+
+use crate::{readback::Mapping, VertexLayout};
+
+const WORKGROUP_SIZE: u32 = 64;
+const MAX_WORKGROUPS: u32 = 65535;
+
+/// The lowest and highest y value drawn in each pixel column of the plot,
+/// e.g. to place labels and callouts that hug a curve without overlapping
+/// it. See
+/// [`GpuAcceleratedPlot::find_column_extrema`](crate::GpuAcceleratedPlot::find_column_extrema).
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColumnExtrema {
+    /// The x range the columns cover, low to high.
+    pub x: [f64; 2],
+    /// The minimum and maximum of each column, from the low end of the x
+    /// range, or `None` where nothing was drawn.
+    pub columns: Vec<Option<[f32; 2]>>,
+}
+
+impl ColumnExtrema {
+    /// The column containing `x`, if it's within the range.
+    pub fn column(&self, x: f64) -> Option<usize> {
+        let t = (x - self.x[0]) / (self.x[1] - self.x[0]);
+        (0.0..1.0).contains(&t).then(|| {
+            ((t * self.columns.len() as f64) as usize).min(self.columns.len().saturating_sub(1))
+        })
+    }
+
+    /// The extrema of the column containing `x`.
+    pub fn at(&self, x: f64) -> Option<[f32; 2]> {
+        self.columns.get(self.column(x)?).copied().flatten()
+    }
+
+    /// The x value at the center of `column`.
+    pub fn center(&self, column: usize) -> f64 {
+        let width = (self.x[1] - self.x[0]) / self.columns.len().max(1) as f64;
+        self.x[0] + (column as f64 + 0.5) * width
+    }
+
+    /// The extrema over columns `columns`, e.g. under a label's width.
+    pub fn span(&self, columns: Range<usize>) -> Option<[f32; 2]> {
+        let end = columns.end.min(self.columns.len());
+        self.columns[columns.start.min(end)..end]
+            .iter()
+            .flatten()
+            .fold(None, |span, &[lo, hi]| match span {
+                None => Some([lo, hi]),
+                Some([a, b]) => Some([f32::min(a, lo), f32::max(b, hi)]),
+            })
+    }
+}
+
+// Laid out to match `Params` in the extrema shader.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+struct Params {
+    x_range: [f32; 2],
+    columns: u32,
+    stride: u32,
+    position: u32,
+    first: u32,
+    end: u32,
+    _padding: u32,
+}
+
+// Reduces the drawn samples to the extrema of each pixel column, and reads
+// them back.
+pub(crate) struct ExtremaFinder {
+    pipeline: wgpu::ComputePipeline,
+    params: wgpu::Buffer,
+    // Sized for `columns`, grown as needed.
+    extrema: wgpu::Buffer,
+    staging: wgpu::Buffer,
+    columns: u32,
+    // The x range and column count being read back.
+    pending: Option<(Mapping, [f64; 2], u32)>,
+}
+
+impl ExtremaFinder {
+    pub fn new(device: &wgpu::Device) -> ExtremaFinder {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("egui_plot_extrema_shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("./extrema_shader.wgsl").into()),
+        });
+
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("egui_plot_extrema_pipeline"),
+            layout: None,
+            module: &shader,
+            entry_point: "cs_extrema",
+        });
+
+        let params = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("egui_plot_extrema_params"),
+            size: std::mem::size_of::<Params>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::UNIFORM,
+            mapped_at_creation: false,
+        });
+
+        let (extrema, staging) = extrema_buffers(device, 1);
+        ExtremaFinder {
+            pipeline,
+            params,
+            extrema,
+            staging,
+            columns: 1,
+            pending: None,
+        }
+    }
+
+    // Start reducing the samples of `vertices`, laid out as `layout` from
+    // byte `offset` of `points`, into `columns` columns across `x`. Returns
+    // false if the last reduction is still being read back.
+    #[allow(clippy::too_many_arguments)]
+    pub fn submit(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        points: &wgpu::Buffer,
+        layout: VertexLayout,
+        offset: wgpu::BufferAddress,
+        vertices: Range<u32>,
+        x: [f64; 2],
+        columns: u32,
+    ) -> bool {
+        if self.pending.is_some() || columns == 0 {
+            return false;
+        }
+        if columns > self.columns {
+            (self.extrema, self.staging) = extrema_buffers(device, columns);
+            self.columns = columns;
+        }
+
+        let params = Params {
+            x_range: x.map(|x| x as f32),
+            columns,
+            stride: (layout.stride / 4) as u32,
+            position: ((offset + layout.position) / 4) as u32,
+            first: vertices.start / 2,
+            end: vertices.end / 2,
+            _padding: 0,
+        };
+        queue.write_buffer(&self.params, 0, bytemuck::bytes_of(&params));
+        let empty: Vec<u32> = (0..columns).flat_map(|_| [u32::MAX, 0]).collect();
+        queue.write_buffer(&self.extrema, 0, bytemuck::cast_slice(&empty));
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("egui_plot_extrema_bind_group"),
+            layout: &self.pipeline.get_bind_group_layout(0),
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: self.params.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: points.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: self.extrema.as_entire_binding(),
+                },
+            ],
+        });
+
+        let samples = params.end.saturating_sub(params.first);
+        let workgroups = ((samples + WORKGROUP_SIZE - 1) / WORKGROUP_SIZE).clamp(1, MAX_WORKGROUPS);
+
+        let mut encoder =
+            device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        {
+            let mut cpass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("egui_plot_extrema_pass"),
+            });
+            cpass.set_pipeline(&self.pipeline);
+            cpass.set_bind_group(0, &bind_group, &[]);
+            cpass.dispatch_workgroups(workgroups, 1, 1);
+        }
+        let size = extrema_size(columns);
+        encoder.copy_buffer_to_buffer(&self.extrema, 0, &self.staging, 0, size);
+        queue.submit(std::iter::once(encoder.finish()));
+
+        let mapping = Mapping::start(&[self.staging.slice(..size)]);
+        self.pending = Some((mapping, x, columns));
+        true
+    }
+
+    // The extrema from the last reduction submitted, once read back.
+    pub fn finish(&mut self) -> Option<ColumnExtrema> {
+        let mapped = self.pending.as_ref()?.0.state()?;
+        let (mapping, x, columns) = self.pending.take()?;
+        if !mapped {
+            mapping.unmap(&[&self.staging]);
+            return None;
+        }
+
+        let columns = {
+            let data = self
+                .staging
+                .slice(..extrema_size(columns))
+                .get_mapped_range();
+            let words: &[u32] = bytemuck::cast_slice(&data);
+            words
+                .chunks_exact(2)
+                .map(|pair| (pair[0] <= pair[1]).then(|| [unordered(pair[0]), unordered(pair[1])]))
+                .collect()
+        };
+        self.staging.unmap();
+
+        Some(ColumnExtrema { x, columns })
+    }
+}
+
+fn extrema_size(columns: u32) -> wgpu::BufferAddress {
+    (2 * columns as usize * std::mem::size_of::<u32>()) as wgpu::BufferAddress
+}
+
+fn extrema_buffers(device: &wgpu::Device, columns: u32) -> (wgpu::Buffer, wgpu::Buffer) {
+    let size = extrema_size(columns);
+    let extrema = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("egui_plot_extrema"),
+        size,
+        usage: wgpu::BufferUsages::STORAGE
+            | wgpu::BufferUsages::COPY_DST
+            | wgpu::BufferUsages::COPY_SRC,
+        mapped_at_creation: false,
+    });
+    let staging = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("egui_plot_extrema_staging"),
+        size,
+        usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+    (extrema, staging)
+}
+
+// Undo the shader's flipping of the bits, which makes them order like the
+// floats do.
+fn unordered(bits: u32) -> f32 {
+    if bits & 0x8000_0000 != 0 {
+        f32::from_bits(bits & 0x7fff_ffff)
+    } else {
+        f32::from_bits(!bits)
+    }
+}
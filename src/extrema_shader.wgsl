@@ -0,0 +1,57 @@
+struct Params {
+    // The x range covered by the columns, low to high.
+    x_range: vec2<f32>,
+    columns: u32,
+    // Vertex stride and position offset, in 4 byte words.
+    stride: u32,
+    position: u32,
+    // The range of samples to reduce.
+    first: u32,
+    end: u32,
+    _padding: u32,
+};
+
+@group(0) @binding(0)
+var<uniform> params: Params;
+@group(0) @binding(1)
+var<storage, read> points: array<f32>;
+// The minimum then maximum of each column, as ordered bits.
+@group(0) @binding(2)
+var<storage, read_write> extrema: array<atomic<u32>>;
+
+let WORKGROUP_SIZE: u32 = 64u;
+
+// Vertices come in pairs with the same position, one for each normal.
+fn sample(i: u32) -> vec2<f32> {
+    let word = 2u * i * params.stride + params.position;
+    return vec2<f32>(points[word], points[word + 1u]);
+}
+
+// The bits of a float, flipped so that they order like the floats do.
+fn ordered(value: f32) -> u32 {
+    let bits = bitcast<u32>(value);
+    if ((bits & 0x80000000u) != 0u) {
+        return ~bits;
+    }
+    return bits | 0x80000000u;
+}
+
+@compute @workgroup_size(64)
+fn cs_extrema(@builtin(global_invocation_id) id: vec3<u32>,
+              @builtin(num_workgroups) groups: vec3<u32>) {
+    let total = groups.x * WORKGROUP_SIZE;
+    let width = params.x_range.y - params.x_range.x;
+    for (var i = params.first + id.x; i < params.end; i = i + total) {
+        let s = sample(i);
+        let t = (s.x - params.x_range.x) / width;
+        // Comparisons with NaN are false, so gaps are skipped too.
+        if (!(t >= 0.0 && t < 1.0) || !(abs(s.y) <= 3.4e38)) {
+            continue;
+        }
+
+        let column = min(u32(t * f32(params.columns)), params.columns - 1u);
+        let bits = ordered(s.y);
+        atomicMin(&extrema[2u * column], bits);
+        atomicMax(&extrema[2u * column + 1u], bits);
+    }
+}
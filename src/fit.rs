@@ -0,0 +1,504 @@
+#[cfg(feature = "compute")]
+use wgpu::util::DeviceExt;
+
+#[cfg(feature = "compute")]
+use crate::{readback::Mapping, VertexLayout};
+use crate::{Bounds, Vertex};
+
+/// The highest polynomial degree that can be fitted.
+pub const MAX_FIT_DEGREE: usize = 3;
+
+// Points sampled along a fitted curve.
+const CURVE_SAMPLES: usize = 64;
+
+// Half-width of a 95% confidence interval, in standard errors.
+const CONFIDENCE_Z: f64 = 1.96;
+
+// See `MOMENTS` in the fit shader.
+const MOMENTS: usize = 12;
+
+#[cfg(feature = "compute")]
+const WORKGROUP_SIZE: u32 = 64;
+#[cfg(feature = "compute")]
+const MAX_WORKGROUPS: u32 = 256;
+
+/// The kind of curve fitted by [`fit`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FitModel {
+    Linear,
+    /// Least squares polynomial, up to [`MAX_FIT_DEGREE`].
+    Polynomial {
+        degree: usize,
+    },
+    /// A moving local linear regression with tricube weights over the
+    /// nearest `span` fraction of the samples, approximating LOESS. Only fitted
+    /// on the CPU.
+    Loess {
+        span: f64,
+    },
+}
+
+/// A curve fitted to the visible samples, with a 95% confidence band.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Fit {
+    pub model: FitModel,
+    /// Polynomial coefficients in x, lowest power first. Empty for LOESS.
+    pub coefficients: Vec<f64>,
+    /// The fit sampled across the visible range, as `[x, y, band]`, where
+    /// `band` is the half-width of the confidence band around `y`.
+    pub curve: Vec<[f64; 3]>,
+    /// Standard deviation of the residuals.
+    pub residual_std: f64,
+    /// Number of samples fitted.
+    pub count: usize,
+}
+
+impl Fit {
+    /// The fitted curve as a line, e.g. to push into a
+    /// [`LineBatch`](crate::LineBatch) and style as its own series.
+    pub fn line_vertices(&self, color: [f32; 4]) -> Vec<Vertex> {
+        let mut vertices = Vec::with_capacity(self.curve.len() * 2);
+
+        for (i, &[x, y, _]) in self.curve.iter().enumerate() {
+            let [x0, y0, _] = self.curve[i.saturating_sub(1)];
+            let [x1, y1, _] = self.curve[(i + 1).min(self.curve.len() - 1)];
+            let (dx, dy) = ((x1 - x0) as f32, (y1 - y0) as f32);
+            let length = dx.hypot(dy).max(f32::EPSILON);
+            let normal = [-dy / length, dx / length];
+
+            let position = [x as f32, y as f32];
+            vertices.push(Vertex {
+                position,
+                normal,
+                color,
+            });
+            vertices.push(Vertex {
+                position,
+                normal: [-normal[0], -normal[1]],
+                color,
+            });
+        }
+
+        vertices
+    }
+
+    /// The confidence band as a filled strip. Its vertices have zero normals
+    /// (which the line shader fills solid), so don't sanitize them.
+    pub fn band_vertices(&self, color: [f32; 4]) -> Vec<Vertex> {
+        self.curve
+            .iter()
+            .flat_map(|&[x, y, band]| {
+                [y - band, y + band].map(|y| Vertex {
+                    position: [x as f32, y as f32],
+                    normal: [0.0, 0.0],
+                    color,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Fit a curve to the samples of `points` within the x range of `bounds`,
+/// on the CPU. For large data already on the GPU, see
+/// [`GpuAcceleratedPlot::fit_visible`](crate::GpuAcceleratedPlot::fit_visible).
+pub fn fit(points: &[Vertex], bounds: &Bounds, model: FitModel) -> Option<Fit> {
+    // Vertices come in pairs with the same position, one for each normal.
+    let samples = points.iter().step_by(2).filter_map(|v| {
+        let [x, y] = v.position.map(|c| c as f64);
+        let visible = x >= bounds.x[0] && x <= bounds.x[1] && y.is_finite();
+        visible.then(|| [x, y])
+    });
+
+    match model {
+        FitModel::Loess { span } => loess(&samples.collect::<Vec<_>>(), bounds, span),
+        _ => {
+            let y_center = bounds.center()[1];
+            let mut moments = [0.0; MOMENTS];
+            for [x, y] in samples {
+                add_moments(&mut moments, bounds, x, y - y_center);
+            }
+            polynomial_fit(&moments, bounds, y_center, model)
+        }
+    }
+}
+
+// The same sums as the fit shader, in f64.
+fn add_moments(moments: &mut [f64; MOMENTS], bounds: &Bounds, x: f64, y: f64) {
+    let t = normalize(bounds, x);
+    let mut power = 1.0;
+    moments[0] += 1.0;
+    for k in 1..=6 {
+        if k <= 4 {
+            moments[6 + k] += y * power;
+        }
+        power *= t;
+        moments[k] += power;
+    }
+    moments[11] += y * y;
+}
+
+// Map x onto -1..1 across the bounds, keeping the normal equations well
+// conditioned.
+fn normalize(bounds: &Bounds, x: f64) -> f64 {
+    (x - bounds.center()[0]) / (bounds.width() * 0.5)
+}
+
+fn polynomial_fit(
+    moments: &[f64; MOMENTS],
+    bounds: &Bounds,
+    y_center: f64,
+    model: FitModel,
+) -> Option<Fit> {
+    let degree = match model {
+        FitModel::Linear => 1,
+        FitModel::Polynomial { degree } => degree.clamp(1, MAX_FIT_DEGREE),
+        FitModel::Loess { .. } => return None,
+    };
+    let terms = degree + 1;
+    let count = moments[0];
+    if count < terms as f64 || bounds.width() <= 0.0 {
+        return None;
+    }
+
+    // Normal equations: sums of t^(i + j), and of y * t^i.
+    let normal: Vec<Vec<f64>> = (0..terms)
+        .map(|i| (0..terms).map(|j| moments[i + j]).collect())
+        .collect();
+    let rhs: Vec<f64> = (0..terms).map(|i| moments[7 + i]).collect();
+
+    let inverse = invert(normal)?;
+    let solution: Vec<f64> = inverse
+        .iter()
+        .map(|row| row.iter().zip(&rhs).map(|(a, b)| a * b).sum())
+        .collect();
+
+    let residual_sum = moments[11] - solution.iter().zip(&rhs).map(|(a, b)| a * b).sum::<f64>();
+    let dof = count - terms as f64;
+    let residual_std = if dof > 0.0 {
+        (residual_sum.max(0.0) / dof).sqrt()
+    } else {
+        0.0
+    };
+
+    let curve = (0..CURVE_SAMPLES)
+        .map(|i| {
+            let t = i as f64 / (CURVE_SAMPLES - 1) as f64 * 2.0 - 1.0;
+            let powers: Vec<f64> = (0..terms).map(|k| t.powi(k as i32)).collect();
+            let y = powers
+                .iter()
+                .zip(&solution)
+                .map(|(p, c)| p * c)
+                .sum::<f64>();
+
+            // Standard error of the fitted mean at t.
+            let variance: f64 = (0..terms)
+                .flat_map(|i| (0..terms).map(move |j| (i, j)))
+                .map(|(i, j)| powers[i] * inverse[i][j] * powers[j])
+                .sum();
+            let band = CONFIDENCE_Z * residual_std * variance.max(0.0).sqrt();
+
+            let x = bounds.center()[0] + t * bounds.width() * 0.5;
+            [x, y + y_center, band]
+        })
+        .collect();
+
+    Some(Fit {
+        model,
+        coefficients: in_x(&solution, bounds, y_center),
+        curve,
+        residual_std,
+        count: count as usize,
+    })
+}
+
+// Expand coefficients in t = (x - center) / half_width into coefficients in
+// x, adding back the y offset.
+fn in_x(coefficients: &[f64], bounds: &Bounds, y_center: f64) -> Vec<f64> {
+    let half_width = bounds.width() * 0.5;
+    let base = [-bounds.center()[0] / half_width, 1.0 / half_width];
+
+    let mut result = vec![0.0; coefficients.len()];
+    let mut power = vec![1.0];
+    for &c in coefficients {
+        for (r, p) in result.iter_mut().zip(&power) {
+            *r += c * p;
+        }
+
+        let mut next = vec![0.0; power.len() + 1];
+        for (i, p) in power.iter().enumerate() {
+            next[i] += p * base[0];
+            next[i + 1] += p * base[1];
+        }
+        power = next;
+    }
+
+    result[0] += y_center;
+    result
+}
+
+// Gauss-Jordan elimination with partial pivoting. None if singular.
+fn invert(mut matrix: Vec<Vec<f64>>) -> Option<Vec<Vec<f64>>> {
+    let n = matrix.len();
+    let mut inverse: Vec<Vec<f64>> = (0..n)
+        .map(|i| (0..n).map(|j| if i == j { 1.0 } else { 0.0 }).collect())
+        .collect();
+
+    for col in 0..n {
+        let pivot =
+            (col..n).max_by(|&a, &b| matrix[a][col].abs().total_cmp(&matrix[b][col].abs()))?;
+        if matrix[pivot][col].abs() < 1e-12 {
+            return None;
+        }
+        matrix.swap(col, pivot);
+        inverse.swap(col, pivot);
+
+        let scale = matrix[col][col];
+        matrix[col].iter_mut().for_each(|v| *v /= scale);
+        inverse[col].iter_mut().for_each(|v| *v /= scale);
+
+        let (pivot_row, pivot_inverse) = (matrix[col].clone(), inverse[col].clone());
+        for (row, (m, inv)) in matrix.iter_mut().zip(&mut inverse).enumerate() {
+            if row == col {
+                continue;
+            }
+            let factor = m[col];
+            for (v, p) in m.iter_mut().zip(&pivot_row) {
+                *v -= factor * p;
+            }
+            for (v, p) in inv.iter_mut().zip(&pivot_inverse) {
+                *v -= factor * p;
+            }
+        }
+    }
+
+    Some(inverse)
+}
+
+fn loess(samples: &[[f64; 2]], bounds: &Bounds, span: f64) -> Option<Fit> {
+    let neighbors = ((span.clamp(0.0, 1.0) * samples.len() as f64).ceil() as usize).max(3);
+    if samples.len() < neighbors {
+        return None;
+    }
+
+    let mut distances = vec![0.0; samples.len()];
+    let mut residuals = 0.0;
+    let curve = (0..CURVE_SAMPLES)
+        .map(|i| {
+            let x = bounds.x[0] + i as f64 / (CURVE_SAMPLES - 1) as f64 * bounds.width();
+
+            for (d, s) in distances.iter_mut().zip(samples) {
+                *d = (s[0] - x).abs();
+            }
+            let (_, &mut max_distance, _) =
+                distances.select_nth_unstable_by(neighbors - 1, |a, b| a.total_cmp(b));
+            let max_distance = max_distance.max(f64::EPSILON);
+
+            // Weighted linear regression around x.
+            let weight = |sx: f64| {
+                let d = ((sx - x).abs() / max_distance).min(1.0);
+                (1.0 - d * d * d).powi(3)
+            };
+            let [mut sw, mut sww, mut swx, mut swy, mut swxx, mut swxy] = [0.0; 6];
+            for &[sx, sy] in samples {
+                let (w, dx) = (weight(sx), sx - x);
+                sw += w;
+                sww += w * w;
+                swx += w * dx;
+                swy += w * sy;
+                swxx += w * dx * dx;
+                swxy += w * dx * sy;
+            }
+
+            let det = sw * swxx - swx * swx;
+            let (intercept, slope) = if det.abs() > 1e-12 {
+                (
+                    (swxx * swy - swx * swxy) / det,
+                    (sw * swxy - swx * swy) / det,
+                )
+            } else {
+                (swy / sw, 0.0)
+            };
+
+            // Spread of the local residuals, scaled by the effective number
+            // of samples.
+            let variance = samples
+                .iter()
+                .map(|&[sx, sy]| weight(sx) * (sy - intercept - slope * (sx - x)).powi(2))
+                .sum::<f64>()
+                / sw;
+            let effective = sw * sw / sww;
+            residuals += variance;
+
+            [x, intercept, CONFIDENCE_Z * (variance / effective).sqrt()]
+        })
+        .collect();
+
+    Some(Fit {
+        model: FitModel::Loess { span },
+        coefficients: Vec::new(),
+        curve,
+        residual_std: (residuals / CURVE_SAMPLES as f64).sqrt(),
+        count: samples.len(),
+    })
+}
+
+#[cfg(feature = "compute")]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct Params {
+    x_range: [f32; 2],
+    y_center: f32,
+    stride: u32,
+    position: u32,
+    samples: u32,
+    _padding: [u32; 2],
+}
+
+// Sums the moments of the visible samples in a vertex buffer on the GPU, for
+// polynomial fits over more data than is worth reading back.
+#[cfg(feature = "compute")]
+pub(crate) struct FitReducer {
+    pipeline: wgpu::ComputePipeline,
+    sums: wgpu::Buffer,
+    staging: wgpu::Buffer,
+    // The sums being read back, their size, and what they're fitted to.
+    pending: Option<(Mapping, wgpu::BufferAddress, Bounds, FitModel)>,
+}
+
+#[cfg(feature = "compute")]
+impl FitReducer {
+    pub fn new(device: &wgpu::Device) -> FitReducer {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("egui_plot_fit_shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("./fit_shader.wgsl").into()),
+        });
+
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("egui_plot_fit_pipeline"),
+            layout: None,
+            module: &shader,
+            entry_point: "cs_moments",
+        });
+
+        let size = (MAX_WORKGROUPS as usize * MOMENTS * std::mem::size_of::<f32>()) as u64;
+        let sums = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("egui_plot_fit_sums"),
+            size,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let staging = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("egui_plot_fit_staging"),
+            size,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        FitReducer {
+            pipeline,
+            sums,
+            staging,
+            pending: None,
+        }
+    }
+
+    // Start summing the moments of `count` vertices laid out as `layout`
+    // from byte `offset` of `points`. Returns false if the last sums are
+    // still being read back.
+    #[allow(clippy::too_many_arguments)]
+    pub fn submit(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        points: &wgpu::Buffer,
+        layout: VertexLayout,
+        offset: wgpu::BufferAddress,
+        count: u32,
+        bounds: &Bounds,
+        model: FitModel,
+    ) -> bool {
+        if self.pending.is_some() {
+            return false;
+        }
+
+        let y_center = bounds.center()[1];
+        let samples = count / 2;
+        let params = Params {
+            x_range: [bounds.x[0] as f32, bounds.x[1] as f32],
+            y_center: y_center as f32,
+            stride: (layout.stride / 4) as u32,
+            position: ((offset + layout.position) / 4) as u32,
+            samples,
+            _padding: [0; 2],
+        };
+        let params = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("egui_plot_fit_params"),
+            contents: bytemuck::bytes_of(&params),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("egui_plot_fit_bind_group"),
+            layout: &self.pipeline.get_bind_group_layout(0),
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: params.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: points.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: self.sums.as_entire_binding(),
+                },
+            ],
+        });
+
+        let workgroups = ((samples + WORKGROUP_SIZE - 1) / WORKGROUP_SIZE).clamp(1, MAX_WORKGROUPS);
+        let used = (workgroups as usize * MOMENTS * std::mem::size_of::<f32>()) as u64;
+
+        let mut encoder =
+            device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        {
+            let mut cpass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("egui_plot_fit_pass"),
+            });
+            cpass.set_pipeline(&self.pipeline);
+            cpass.set_bind_group(0, &bind_group, &[]);
+            cpass.dispatch_workgroups(workgroups, 1, 1);
+        }
+        encoder.copy_buffer_to_buffer(&self.sums, 0, &self.staging, 0, used);
+        queue.submit(std::iter::once(encoder.finish()));
+
+        let mapping = Mapping::start(&[self.staging.slice(..used)]);
+        self.pending = Some((mapping, used, *bounds, model));
+        true
+    }
+
+    // The fit of the last sums submitted, once read back.
+    pub fn finish(&mut self) -> Option<Option<Fit>> {
+        let mapped = self.pending.as_ref()?.0.state()?;
+        let (mapping, used, bounds, model) = self.pending.take()?;
+        if !mapped {
+            mapping.unmap(&[&self.staging]);
+            return Some(None);
+        }
+
+        let mut moments = [0.0; MOMENTS];
+        {
+            let data = self.staging.slice(..used).get_mapped_range();
+            let sums: &[f32] = bytemuck::cast_slice(&data);
+            for group in sums.chunks_exact(MOMENTS) {
+                for (m, &s) in moments.iter_mut().zip(group) {
+                    *m += s as f64;
+                }
+            }
+        }
+        self.staging.unmap();
+
+        let y_center = bounds.center()[1];
+        Some(polynomial_fit(&moments, &bounds, y_center, model))
+    }
+}
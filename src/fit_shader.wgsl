@@ -0,0 +1,82 @@
+struct Params {
+    x_range: vec2<f32>,
+    // Subtracted from y values to keep the sums of squares small.
+    y_center: f32,
+    // Vertex stride and position offset, in 4 byte words.
+    stride: u32,
+    position: u32,
+    samples: u32,
+};
+
+@group(0) @binding(0)
+var<uniform> params: Params;
+@group(0) @binding(1)
+var<storage, read> points: array<f32>;
+// MOMENTS sums per workgroup.
+@group(0) @binding(2)
+var<storage, read_write> sums: array<f32>;
+
+let WORKGROUP_SIZE: u32 = 64u;
+
+// The count, sums of t to t^6, sums of y * t^0 to y * t^3, and the sum of
+// y^2, where t is x mapped onto -1..1.
+let MOMENTS: u32 = 12u;
+
+var<workgroup> partial: array<array<f32, 12>, 64>;
+
+@compute @workgroup_size(64)
+fn cs_moments(@builtin(global_invocation_id) id: vec3<u32>,
+              @builtin(local_invocation_index) local: u32,
+              @builtin(workgroup_id) group: vec3<u32>,
+              @builtin(num_workgroups) groups: vec3<u32>) {
+    var m: array<f32, 12>;
+    for (var k = 0u; k < MOMENTS; k = k + 1u) {
+        m[k] = 0.0;
+    }
+
+    let total = groups.x * WORKGROUP_SIZE;
+    let center = (params.x_range[0] + params.x_range[1]) * 0.5;
+    let half_width = (params.x_range[1] - params.x_range[0]) * 0.5;
+
+    for (var i = id.x; i < params.samples; i = i + total) {
+        // Vertices come in pairs with the same position, one for each normal.
+        let word = 2u * i * params.stride + params.position;
+        let x = points[word];
+        let y = points[word + 1u] - params.y_center;
+
+        // Only fit the visible samples (which also skips NaN gaps).
+        if (!(x >= params.x_range[0] && x <= params.x_range[1] && abs(y) < 3.4e38)) {
+            continue;
+        }
+
+        let t = (x - center) / half_width;
+        var power = 1.0;
+        m[0] = m[0] + 1.0;
+        for (var k = 1u; k <= 6u; k = k + 1u) {
+            if (k <= 4u) {
+                m[6u + k] = m[6u + k] + y * power;
+            }
+            power = power * t;
+            m[k] = m[k] + power;
+        }
+        m[11] = m[11] + y * y;
+    }
+
+    partial[local] = m;
+    workgroupBarrier();
+
+    for (var stride = WORKGROUP_SIZE / 2u; stride > 0u; stride = stride >> 1u) {
+        if (local < stride) {
+            for (var k = 0u; k < MOMENTS; k = k + 1u) {
+                partial[local][k] = partial[local][k] + partial[local + stride][k];
+            }
+        }
+        workgroupBarrier();
+    }
+
+    if (local == 0u) {
+        for (var k = 0u; k < MOMENTS; k = k + 1u) {
+            sums[group.x * MOMENTS + k] = partial[0][k];
+        }
+    }
+}
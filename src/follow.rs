@@ -0,0 +1,181 @@
+use wgpu::util::DeviceExt;
+
+use crate::VertexLayout;
+
+/// Scrolls the x bounds to the newest sample on the GPU, see
+/// [`GpuAcceleratedPlot::set_follow`](crate::GpuAcceleratedPlot::set_follow).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Follow {
+    /// The width of the x bounds, in axis units (with any axis breaks
+    /// collapsed).
+    pub window: f32,
+    /// Space left after the newest sample, in the same units.
+    pub lead: f32,
+}
+
+impl Follow {
+    pub fn new(window: f32) -> Follow {
+        Follow { window, lead: 0.0 }
+    }
+
+    pub fn with_lead(self, lead: f32) -> Follow {
+        Follow { lead, ..self }
+    }
+}
+
+// Laid out to match `Params` in the follow shader.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+struct Params {
+    x_breaks: [[f32; 4]; 2],
+    stride: u32,
+    position: u32,
+    count: u32,
+    reversed: u32,
+    window: f32,
+    lead: f32,
+    _padding: [f32; 2],
+}
+
+// Advances the plot's x bounds from the newest sample before each render,
+// so that auto-scroll doesn't wait on the CPU.
+pub(crate) struct FollowLayer {
+    follow: Follow,
+    pipeline: wgpu::ComputePipeline,
+    params: wgpu::Buffer,
+    x_bounds: wgpu::Buffer,
+    bind_group: Option<wgpu::BindGroup>,
+    written: Option<Params>,
+}
+
+impl FollowLayer {
+    pub fn new(device: &wgpu::Device, follow: Follow) -> FollowLayer {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("egui_plot_follow_shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("./follow_shader.wgsl").into()),
+        });
+
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("egui_plot_follow_pipeline"),
+            layout: None,
+            module: &shader,
+            entry_point: "cs_follow",
+        });
+
+        let create = |label, size, usage| {
+            device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some(label),
+                size,
+                usage,
+                mapped_at_creation: false,
+            })
+        };
+
+        FollowLayer {
+            follow,
+            pipeline,
+            params: create(
+                "egui_plot_follow_params",
+                std::mem::size_of::<Params>() as wgpu::BufferAddress,
+                wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::UNIFORM,
+            ),
+            // Kept if the newest sample isn't finite.
+            x_bounds: device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("egui_plot_follow_bounds"),
+                contents: bytemuck::cast_slice(&[follow.lead - follow.window, follow.lead]),
+                usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+            }),
+            bind_group: None,
+            written: None,
+        }
+    }
+
+    pub fn follow(&self) -> &Follow {
+        &self.follow
+    }
+
+    // Follow the newest of `count` vertices laid out as `layout` from byte
+    // `offset` of `points`. Returns whether anything changed.
+    #[allow(clippy::too_many_arguments)]
+    pub fn update(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        x_breaks: [[f32; 4]; 2],
+        reversed: bool,
+        points: &wgpu::Buffer,
+        layout: VertexLayout,
+        offset: wgpu::BufferAddress,
+        count: u32,
+    ) -> bool {
+        let params = Params {
+            x_breaks,
+            stride: (layout.stride / 4) as u32,
+            position: ((offset + layout.position) / 4) as u32,
+            count,
+            reversed: reversed as u32,
+            window: self.follow.window,
+            lead: self.follow.lead,
+            _padding: [0.0; 2],
+        };
+
+        // The points may have moved to another buffer, so bind them afresh.
+        self.bind_group = Some(device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("egui_plot_follow_bind_group"),
+            layout: &self.pipeline.get_bind_group_layout(0),
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: self.params.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: points.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: self.x_bounds.as_entire_binding(),
+                },
+            ],
+        }));
+
+        if self.written == Some(params) {
+            return false;
+        }
+
+        queue.write_buffer(&self.params, 0, bytemuck::bytes_of(&params));
+        self.written = Some(params);
+        true
+    }
+
+    // Find the newest sample's x, and overwrite the x bounds at byte
+    // `offset` of `uniforms` with the window ending at it.
+    pub fn dispatch(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        uniforms: &wgpu::Buffer,
+        offset: wgpu::BufferAddress,
+    ) {
+        let bind_group = match (&self.bind_group, self.written) {
+            (Some(bind_group), Some(params)) if params.count > 0 => bind_group,
+            _ => return,
+        };
+
+        {
+            let mut cpass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("egui_plot_follow_pass"),
+            });
+            cpass.set_pipeline(&self.pipeline);
+            cpass.set_bind_group(0, bind_group, &[]);
+            cpass.dispatch_workgroups(1, 1, 1);
+        }
+
+        encoder.copy_buffer_to_buffer(
+            &self.x_bounds,
+            0,
+            uniforms,
+            offset,
+            std::mem::size_of::<[f32; 2]>() as wgpu::BufferAddress,
+        );
+    }
+}
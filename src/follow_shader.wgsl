@@ -0,0 +1,44 @@
+struct Params {
+    // Axis breaks, as in the line shader.
+    x_breaks: array<vec4<f32>, 2>,
+    // Vertex stride and position offset, in 4 byte words.
+    stride: u32,
+    position: u32,
+    // The number of vertices, the last of which is the newest sample.
+    count: u32,
+    reversed: u32,
+    window: f32,
+    lead: f32,
+    _padding: vec2<f32>,
+};
+
+@group(0) @binding(0)
+var<uniform> params: Params;
+@group(0) @binding(1)
+var<storage, read> points: array<f32>;
+// The x bounds, copied into the plot's uniforms.
+@group(0) @binding(2)
+var<storage, read_write> x_bounds: vec2<f32>;
+
+fn break_shift(value: f32, breaks: vec4<f32>) -> f32 {
+    return clamp(value - breaks.x, 0.0, breaks.y - breaks.x)
+         + clamp(value - breaks.z, 0.0, breaks.w - breaks.z);
+}
+
+@compute @workgroup_size(1)
+fn cs_follow() {
+    if (params.count == 0u) {
+        return;
+    }
+
+    let x = points[(params.count - 1u) * params.stride + params.position];
+    let axis = x - break_shift(x, params.x_breaks[0]) - break_shift(x, params.x_breaks[1]);
+    // Skip samples which would poison the bounds.
+    if (!(axis == axis) || abs(axis) > 3.0e38) {
+        return;
+    }
+
+    let end = axis + params.lead;
+    let bounds = vec2<f32>(end - params.window, end);
+    x_bounds = select(bounds, bounds.yx, params.reversed != 0u);
+}
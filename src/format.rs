@@ -0,0 +1,221 @@
+/// How a [`NumberFormat`] writes numbers.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Notation {
+    /// Fixed-point, e.g. `12340`.
+    #[default]
+    Plain,
+    /// A mantissa and a power of ten, e.g. `1.234e4`.
+    Scientific,
+    /// Like scientific, with exponents that are multiples of three, e.g.
+    /// `12.34e3`.
+    Engineering,
+    /// Like engineering, with SI prefixes, e.g. `12.34 k`. Falls back to
+    /// engineering beyond the prefixes.
+    SiPrefix,
+}
+
+/// Number formatting for tick labels and readouts, e.g. one per axis.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NumberFormat {
+    pub notation: Notation,
+    pub significant_digits: usize,
+    /// Appended after a space (and any SI prefix), e.g. `V` or `Hz`.
+    pub unit: String,
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {
+        NumberFormat {
+            notation: Notation::Plain,
+            significant_digits: 4,
+            unit: String::new(),
+        }
+    }
+}
+
+// The SI prefixes from 1e-24 to 1e24, in steps of a thousand.
+const SI_PREFIXES: [&str; 17] = [
+    "y", "z", "a", "f", "p", "n", "µ", "m", "", "k", "M", "G", "T", "P", "E", "Z", "Y",
+];
+
+impl NumberFormat {
+    pub fn new(notation: Notation) -> NumberFormat {
+        NumberFormat {
+            notation,
+            ..Default::default()
+        }
+    }
+
+    /// SI prefixes with a unit, e.g. `NumberFormat::si("Hz")` writes 2.4e9 as
+    /// `2.4 GHz`.
+    pub fn si(unit: &str) -> NumberFormat {
+        NumberFormat::new(Notation::SiPrefix).with_unit(unit)
+    }
+
+    pub fn with_significant_digits(self, significant_digits: usize) -> NumberFormat {
+        NumberFormat {
+            significant_digits: significant_digits.max(1),
+            ..self
+        }
+    }
+
+    pub fn with_unit(self, unit: &str) -> NumberFormat {
+        NumberFormat {
+            unit: unit.to_owned(),
+            ..self
+        }
+    }
+
+    pub fn format(&self, value: f64) -> String {
+        let digits = self.significant_digits.max(1);
+        if !value.is_finite() || value == 0.0 {
+            let number = if value == 0.0 {
+                "0".to_owned()
+            } else {
+                value.to_string()
+            };
+            return self.with_suffix(number, "");
+        }
+
+        match self.notation {
+            Notation::Plain => self.with_suffix(round_significant(value, digits), ""),
+            Notation::Scientific => {
+                let (mantissa, exponent) = split(value, digits, 1);
+                self.with_suffix(format!("{}e{}", mantissa, exponent), "")
+            }
+            Notation::Engineering => {
+                let (mantissa, exponent) = split(value, digits, 3);
+                self.with_suffix(format!("{}e{}", mantissa, exponent), "")
+            }
+            Notation::SiPrefix => {
+                let (mantissa, exponent) = split(value, digits, 3);
+                match usize::try_from(exponent / 3 + 8)
+                    .ok()
+                    .and_then(|i| SI_PREFIXES.get(i))
+                {
+                    Some(prefix) => self.with_suffix(mantissa, prefix),
+                    None => self.with_suffix(format!("{}e{}", mantissa, exponent), ""),
+                }
+            }
+        }
+    }
+
+    fn with_suffix(&self, number: String, prefix: &str) -> String {
+        if prefix.is_empty() && self.unit.is_empty() {
+            number
+        } else {
+            format!("{} {}{}", number, prefix, self.unit)
+        }
+    }
+}
+
+// Split a nonzero value into a mantissa, rounded to `digits` significant
+// digits, and an exponent that is a multiple of `multiple`.
+fn split(value: f64, digits: usize, multiple: i32) -> (String, i32) {
+    let mut exponent = (value.abs().log10().floor() as i32).div_euclid(multiple) * multiple;
+    let mut mantissa = value / 10f64.powi(exponent);
+
+    // Rounding may carry over into the next exponent, e.g. 999.96e3.
+    let rounded: f64 = round_significant(mantissa, digits)
+        .parse()
+        .unwrap_or(mantissa);
+    if rounded.abs() >= 10f64.powi(multiple) {
+        exponent += multiple;
+        mantissa = value / 10f64.powi(exponent);
+    }
+
+    (round_significant(mantissa, digits), exponent)
+}
+
+// Fixed-point with `digits` significant digits, without trailing zeros.
+fn round_significant(value: f64, digits: usize) -> String {
+    let magnitude = value.abs().log10().floor() as i32;
+    let decimals = (digits as i32 - 1 - magnitude).max(0) as usize;
+    let text = format!("{:.*}", decimals, value);
+    if text.contains('.') {
+        text.trim_end_matches('0').trim_end_matches('.').to_owned()
+    } else {
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format(notation: Notation, value: f64) -> String {
+        NumberFormat::new(notation).format(value)
+    }
+
+    #[test]
+    fn plain() {
+        assert_eq!(format(Notation::Plain, 12340.0), "12340");
+        assert_eq!(format(Notation::Plain, 1.23456), "1.235");
+        assert_eq!(format(Notation::Plain, 2.5), "2.5");
+        assert_eq!(format(Notation::Plain, -0.001234567), "-0.001235");
+    }
+
+    #[test]
+    fn scientific() {
+        assert_eq!(format(Notation::Scientific, 12340.0), "1.234e4");
+        assert_eq!(format(Notation::Scientific, -0.0012346), "-1.235e-3");
+        assert_eq!(format(Notation::Scientific, 7.0), "7e0");
+    }
+
+    #[test]
+    fn engineering() {
+        assert_eq!(format(Notation::Engineering, 12340.0), "12.34e3");
+        assert_eq!(format(Notation::Engineering, 0.00012), "120e-6");
+        assert_eq!(format(Notation::Engineering, -5.0), "-5e0");
+    }
+
+    #[test]
+    fn si_prefixes() {
+        assert_eq!(NumberFormat::si("Hz").format(2.4e9), "2.4 GHz");
+        assert_eq!(NumberFormat::si("s").format(0.0005), "500 µs");
+        assert_eq!(NumberFormat::si("F").format(-4.7e-9), "-4.7 nF");
+        assert_eq!(format(Notation::SiPrefix, 12.34), "12.34");
+        assert_eq!(format(Notation::SiPrefix, 1e24), "1 Y");
+        assert_eq!(format(Notation::SiPrefix, 1e-24), "1 y");
+    }
+
+    #[test]
+    fn si_falls_back_past_the_prefixes() {
+        assert_eq!(format(Notation::SiPrefix, 1e27), "1e27");
+        assert_eq!(NumberFormat::si("m").format(1e-27), "1e-27 m");
+    }
+
+    #[test]
+    fn rounding_carries_into_the_next_exponent() {
+        assert_eq!(format(Notation::Engineering, 999.96e3), "1e6");
+        assert_eq!(format(Notation::Scientific, 999.96e3), "1e6");
+        assert_eq!(NumberFormat::si("Hz").format(999.96e3), "1 MHz");
+        assert_eq!(NumberFormat::si("Hz").format(-999.96e3), "-1 MHz");
+    }
+
+    #[test]
+    fn zero_and_non_finite() {
+        for notation in [
+            Notation::Plain,
+            Notation::Scientific,
+            Notation::Engineering,
+            Notation::SiPrefix,
+        ] {
+            assert_eq!(format(notation, 0.0), "0");
+            assert_eq!(format(notation, -0.0), "0");
+            assert_eq!(format(notation, f64::NAN), "NaN");
+            assert_eq!(format(notation, f64::INFINITY), "inf");
+            assert_eq!(format(notation, f64::NEG_INFINITY), "-inf");
+        }
+        assert_eq!(NumberFormat::si("V").format(0.0), "0 V");
+        assert_eq!(NumberFormat::si("V").format(f64::NAN), "NaN V");
+    }
+
+    #[test]
+    fn significant_digits() {
+        let format = NumberFormat::new(Notation::Scientific).with_significant_digits(2);
+        assert_eq!(format.format(12345.0), "1.2e4");
+        let format = format.with_significant_digits(0);
+        assert_eq!(format.format(16.0), "2e1");
+    }
+}
@@ -0,0 +1,86 @@
+use crate::SeriesId;
+
+/// Identifies a group of series made with
+/// [`GpuAcceleratedPlot::add_group`](crate::GpuAcceleratedPlot::add_group) or
+/// [`GpuAcceleratedPlot::group_matching`](crate::GpuAcceleratedPlot::group_matching).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct GroupId(pub(crate) usize);
+
+// Per-series state set through groups, applied on top of the series' style.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct GroupState {
+    pub visible: bool,
+    pub alpha: f32,
+    // Added to the mapped values, for stacking.
+    pub offset: f32,
+}
+
+impl Default for GroupState {
+    fn default() -> Self {
+        GroupState {
+            visible: true,
+            alpha: 1.0,
+            offset: 0.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Groups {
+    members: Vec<Vec<SeriesId>>,
+}
+
+impl Groups {
+    pub fn add(&mut self, members: Vec<SeriesId>) -> GroupId {
+        self.members.push(members);
+        GroupId(self.members.len() - 1)
+    }
+
+    pub fn members(&self, group: GroupId) -> &[SeriesId] {
+        self.members.get(group.0).map_or(&[], Vec::as_slice)
+    }
+
+    // Drop removed series from every group.
+    pub fn forget(&mut self, removed: &[SeriesId]) {
+        for members in &mut self.members {
+            members.retain(|id| !removed.contains(id));
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.members.clear();
+    }
+}
+
+/// Whether `name` matches a glob `pattern`, where `*` matches any run of
+/// characters within one `/` separated segment and `**` matches across
+/// segments too, e.g. `sensor/*/temp` or `rack1/**`.
+pub fn matches_pattern(pattern: &str, name: &str) -> bool {
+    let (pattern, name) = (pattern.as_bytes(), name.as_bytes());
+    // Backtracking over the most recent star is enough, as for wildcard
+    // matching in general; `**` is the only star that may cross a `/`.
+    let (mut p, mut n) = (0, 0);
+    let mut star: Option<(usize, usize, bool)> = None;
+
+    while n < name.len() {
+        if pattern.get(p) == Some(&b'*') {
+            let deep = pattern.get(p + 1) == Some(&b'*');
+            p += if deep { 2 } else { 1 };
+            star = Some((p, n, deep));
+        } else if pattern.get(p) == Some(&name[n]) {
+            p += 1;
+            n += 1;
+        } else {
+            match star {
+                Some((star_p, star_n, deep)) if deep || name[star_n] != b'/' => {
+                    p = star_p;
+                    n = star_n + 1;
+                    star = Some((star_p, n, deep));
+                }
+                _ => return false,
+            }
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == b'*')
+}
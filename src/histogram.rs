@@ -0,0 +1,599 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, PoisonError,
+    },
+};
+
+use crate::{Gradient, Uniform, VertexLayout, MAX_GRADIENT_STOPS};
+
+const WORKGROUP_SIZE: u32 = 64;
+const MAX_WORKGROUPS: u32 = 65535;
+
+/// The shape of a [`Histogram`]'s bins.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Binning {
+    #[default]
+    Rect,
+    /// Hexagons, in rows offset by half a bin.
+    Hex,
+}
+
+/// How bin counts are mapped onto a [`Histogram`]'s colormap.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CountScale {
+    Linear,
+    /// `log(1 + count)`, so sparse bins stay visible next to dense ones.
+    #[default]
+    Log,
+}
+
+/// What a [`Histogram`] is used to draw.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum HistogramDisplay {
+    /// The bin counts, in place of the points.
+    #[default]
+    Heatmap,
+    /// The points, each colored by the count of the bin it falls in, for a
+    /// scatter plot showing density.
+    DensityColors,
+}
+
+/// Bins the points into a 2D grid covering the plot's bounds on the GPU and
+/// draws the counts as a heatmap, or colors the points by them. See
+/// [`GpuAcceleratedPlot::set_histogram`](crate::GpuAcceleratedPlot::set_histogram).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Histogram {
+    /// Number of bins across and up the plot.
+    pub bins: [u32; 2],
+    pub binning: Binning,
+    pub scale: CountScale,
+    pub display: HistogramDisplay,
+    /// Evenly spaced linear RGBA colors, from a count of one to the largest
+    /// count. Empty bins aren't drawn.
+    pub colormap: Vec<[f32; 4]>,
+    /// Bin about this many samples per frame rather than all of them on
+    /// every render, refining a coarse result over several frames. See
+    /// [`Histogram::with_progressive`].
+    pub samples_per_frame: Option<u32>,
+    /// Keep the counts across uploads of the points, fading them by this
+    /// factor each time. See [`Histogram::with_persistence`].
+    pub persistence: Option<f32>,
+}
+
+impl Histogram {
+    pub fn rect(bins: [u32; 2]) -> Histogram {
+        Histogram {
+            bins,
+            binning: Binning::Rect,
+            scale: CountScale::default(),
+            display: HistogramDisplay::default(),
+            colormap: VIRIDIS.to_vec(),
+            samples_per_frame: None,
+            persistence: None,
+        }
+    }
+
+    pub fn hex(bins: [u32; 2]) -> Histogram {
+        Histogram {
+            binning: Binning::Hex,
+            ..Histogram::rect(bins)
+        }
+    }
+
+    pub fn with_scale(self, scale: CountScale) -> Histogram {
+        Histogram { scale, ..self }
+    }
+
+    pub fn with_display(self, display: HistogramDisplay) -> Histogram {
+        Histogram { display, ..self }
+    }
+
+    pub fn with_colormap(self, colormap: &[[f32; 4]]) -> Histogram {
+        Histogram {
+            colormap: colormap.to_vec(),
+            ..self
+        }
+    }
+
+    /// Refine the counts progressively, binning about `samples_per_frame`
+    /// samples each frame and keeping them until the bounds, the histogram
+    /// or the data change. Each frame bins every n-th sample, interleaved
+    /// with the last, so the first frame already shows the whole picture at
+    /// lower resolution. Keep rendering until
+    /// [`GpuAcceleratedPlot::refinement_progress`](crate::GpuAcceleratedPlot::refinement_progress)
+    /// reaches one.
+    pub fn with_progressive(self, samples_per_frame: u32) -> Histogram {
+        Histogram {
+            samples_per_frame: Some(samples_per_frame.max(1)),
+            ..self
+        }
+    }
+
+    /// Count each upload of the points once and add it to the faded counts
+    /// of the ones before, like the phosphor of an analog scope, instead of
+    /// recounting the points on every render. Each upload keeps `decay` of
+    /// the counts so far, from zero (no persistence) to one (infinite
+    /// persistence). Streaming data should be uploaded a block at a time,
+    /// as dirty. Changing the bounds or the histogram starts over, and
+    /// persistent counts aren't progressive.
+    pub fn with_persistence(self, decay: f32) -> Histogram {
+        Histogram {
+            persistence: Some(decay.clamp(0.0, 1.0)),
+            ..self
+        }
+    }
+
+    // Bins, with a margin of one on each side for the hexagonal lattice,
+    // plus the largest count.
+    fn counts_len(&self) -> u64 {
+        let [x, y] = self.bins.map(|n| n.max(1) as u64 + 2);
+        x * y + 1
+    }
+}
+
+// A viridis-like colormap, in linear RGB.
+const VIRIDIS: [[f32; 4]; 5] = [
+    [0.055, 0.0, 0.087, 1.0],
+    [0.040, 0.084, 0.263, 1.0],
+    [0.011, 0.290, 0.267, 1.0],
+    [0.112, 0.592, 0.122, 1.0],
+    [0.983, 0.805, 0.014, 1.0],
+];
+
+// Laid out to match `BinParams` in the bins shader.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+struct Params {
+    x_range: [f32; 2],
+    y_range: [f32; 2],
+    bins: [u32; 2],
+    hex: u32,
+    log_scale: u32,
+    stride: u32,
+    position: u32,
+    samples: u32,
+    stop_count: u32,
+    first: u32,
+    step: u32,
+    decay: f32,
+    _padding: u32,
+    stops: [[f32; 4]; MAX_GRADIENT_STOPS],
+}
+
+struct Compute {
+    shader: wgpu::ShaderModule,
+    bind_group_layout: wgpu::BindGroupLayout,
+    pipeline_layout: wgpu::PipelineLayout,
+    // Read-only counts for coloring points in the line shader.
+    density_layout: wgpu::BindGroupLayout,
+    clear: wgpu::ComputePipeline,
+    decay: wgpu::ComputePipeline,
+    bin: wgpu::ComputePipeline,
+}
+
+// The binning and heatmap pipelines, shared between plots like the line
+// pipelines. Nothing is created until a plot uses a histogram, since devices
+// without compute shaders can't create them at all.
+pub(crate) struct HistogramPipelines {
+    compute: Mutex<Option<Arc<Compute>>>,
+    heatmaps: Mutex<HashMap<(wgpu::TextureFormat, u32), Arc<wgpu::RenderPipeline>>>,
+}
+
+impl HistogramPipelines {
+    pub fn new() -> HistogramPipelines {
+        HistogramPipelines {
+            compute: Mutex::new(None),
+            heatmaps: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn compute(&self, device: &wgpu::Device) -> Arc<Compute> {
+        let mut compute = self.compute.lock().unwrap_or_else(PoisonError::into_inner);
+        let compute = compute.get_or_insert_with(|| {
+            let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("egui_plot_histogram_shader"),
+                source: wgpu::ShaderSource::Wgsl(
+                    concat!(
+                        include_str!("./bins.wgsl"),
+                        include_str!("./histogram_shader.wgsl")
+                    )
+                    .into(),
+                ),
+            });
+
+            let storage = |read_only| wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            };
+            let bind_group_layout =
+                device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                    label: Some("egui_plot_histogram_bind_group_layout"),
+                    entries: &[
+                        wgpu::BindGroupLayoutEntry {
+                            binding: 0,
+                            visibility: wgpu::ShaderStages::COMPUTE | wgpu::ShaderStages::FRAGMENT,
+                            ty: wgpu::BindingType::Buffer {
+                                ty: wgpu::BufferBindingType::Uniform,
+                                has_dynamic_offset: false,
+                                min_binding_size: None,
+                            },
+                            count: None,
+                        },
+                        wgpu::BindGroupLayoutEntry {
+                            binding: 1,
+                            visibility: wgpu::ShaderStages::COMPUTE,
+                            ty: storage(true),
+                            count: None,
+                        },
+                        wgpu::BindGroupLayoutEntry {
+                            binding: 2,
+                            visibility: wgpu::ShaderStages::COMPUTE | wgpu::ShaderStages::FRAGMENT,
+                            ty: storage(false),
+                            count: None,
+                        },
+                    ],
+                });
+
+            let density_layout =
+                device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                    label: Some("egui_plot_density_bind_group_layout"),
+                    entries: &[
+                        wgpu::BindGroupLayoutEntry {
+                            binding: 0,
+                            visibility: wgpu::ShaderStages::VERTEX,
+                            ty: wgpu::BindingType::Buffer {
+                                ty: wgpu::BufferBindingType::Uniform,
+                                has_dynamic_offset: false,
+                                min_binding_size: None,
+                            },
+                            count: None,
+                        },
+                        wgpu::BindGroupLayoutEntry {
+                            binding: 1,
+                            visibility: wgpu::ShaderStages::VERTEX,
+                            ty: storage(true),
+                            count: None,
+                        },
+                    ],
+                });
+
+            let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("egui_plot_histogram_pipeline_layout"),
+                bind_group_layouts: &[&bind_group_layout],
+                push_constant_ranges: &[],
+            });
+
+            let create = |entry_point| {
+                device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                    label: Some("egui_plot_histogram_compute_pipeline"),
+                    layout: Some(&pipeline_layout),
+                    module: &shader,
+                    entry_point,
+                })
+            };
+            let clear = create("cs_clear");
+            let decay = create("cs_decay");
+            let bin = create("cs_bin");
+
+            Arc::new(Compute {
+                shader,
+                bind_group_layout,
+                pipeline_layout,
+                density_layout,
+                clear,
+                decay,
+                bin,
+            })
+        });
+
+        Arc::clone(compute)
+    }
+
+    // A layout for line pipelines which color points by density, with the
+    // counts bound after the line shader's own bind groups.
+    pub fn density_pipeline_layout(
+        &self,
+        device: &wgpu::Device,
+        line_layouts: [&wgpu::BindGroupLayout; 2],
+    ) -> wgpu::PipelineLayout {
+        let compute = self.compute(device);
+        device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("egui_plot_density_pipeline_layout"),
+            bind_group_layouts: &[line_layouts[0], line_layouts[1], &compute.density_layout],
+            push_constant_ranges: &[],
+        })
+    }
+
+    fn heatmap(
+        &self,
+        device: &wgpu::Device,
+        compute: &Compute,
+        format: wgpu::TextureFormat,
+        sample_count: u32,
+    ) -> Arc<wgpu::RenderPipeline> {
+        let mut heatmaps = self.heatmaps.lock().unwrap_or_else(PoisonError::into_inner);
+        let heatmap = heatmaps.entry((format, sample_count)).or_insert_with(|| {
+            Arc::new(
+                device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                    label: Some("egui_plot_heatmap_pipeline"),
+                    layout: Some(&compute.pipeline_layout),
+                    vertex: wgpu::VertexState {
+                        module: &compute.shader,
+                        entry_point: "vs_main",
+                        buffers: &[],
+                    },
+                    fragment: Some(wgpu::FragmentState {
+                        module: &compute.shader,
+                        entry_point: "fs_heatmap",
+                        targets: &[Some(wgpu::ColorTargetState {
+                            format,
+                            blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                            write_mask: wgpu::ColorWrites::ALL,
+                        })],
+                    }),
+                    primitive: wgpu::PrimitiveState::default(),
+                    depth_stencil: None,
+                    multisample: wgpu::MultisampleState {
+                        count: sample_count,
+                        ..Default::default()
+                    },
+                    multiview: None,
+                }),
+            )
+        });
+
+        Arc::clone(heatmap)
+    }
+}
+
+// A plot's bin counts, recounted from its vertex buffer on every render, a
+// pass at a time when progressive, or once per upload when persistent.
+pub(crate) struct HistogramBins {
+    histogram: Histogram,
+    compute: Arc<Compute>,
+    heatmap: Arc<wgpu::RenderPipeline>,
+    params: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    density_bind_group: Option<wgpu::BindGroup>,
+    counts_len: u32,
+    // The parameters of the whole count, before picking a pass.
+    written: Option<Params>,
+    // The next pass to bin, of `written.step`.
+    pass: u32,
+    // Whether a pass was picked by `update` and not yet dispatched.
+    pending: AtomicBool,
+    // Persistent counts: whether points were uploaded since they were last
+    // counted, and whether the pending pass adds to the faded counts rather
+    // than starting over.
+    uploaded: bool,
+    decaying: bool,
+}
+
+impl HistogramBins {
+    pub fn new(
+        device: &wgpu::Device,
+        shared: &HistogramPipelines,
+        histogram: Histogram,
+        points: &wgpu::Buffer,
+        format: wgpu::TextureFormat,
+        sample_count: u32,
+    ) -> HistogramBins {
+        let compute = shared.compute(device);
+        let heatmap = shared.heatmap(device, &compute, format, sample_count);
+
+        let params = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("egui_plot_histogram_params"),
+            size: std::mem::size_of::<Params>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::UNIFORM,
+            mapped_at_creation: false,
+        });
+
+        let counts_len = histogram.counts_len();
+        let counts = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("egui_plot_histogram_counts"),
+            size: counts_len * std::mem::size_of::<u32>() as u64,
+            usage: wgpu::BufferUsages::STORAGE,
+            mapped_at_creation: false,
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("egui_plot_histogram_bind_group"),
+            layout: &compute.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: params.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: points.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: counts.as_entire_binding(),
+                },
+            ],
+        });
+
+        let density_bind_group =
+            (histogram.display == HistogramDisplay::DensityColors).then(|| {
+                device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    label: Some("egui_plot_density_bind_group"),
+                    layout: &compute.density_layout,
+                    entries: &[
+                        wgpu::BindGroupEntry {
+                            binding: 0,
+                            resource: params.as_entire_binding(),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 1,
+                            resource: counts.as_entire_binding(),
+                        },
+                    ],
+                })
+            });
+
+        HistogramBins {
+            histogram,
+            compute,
+            heatmap,
+            params,
+            bind_group,
+            density_bind_group,
+            counts_len: counts_len as u32,
+            written: None,
+            pass: 0,
+            pending: AtomicBool::new(false),
+            uploaded: true,
+            decaying: false,
+        }
+    }
+
+    pub fn histogram(&self) -> &Histogram {
+        &self.histogram
+    }
+
+    // Bound as the line shader's third group when coloring by density.
+    pub fn density_bind_group(&self) -> Option<&wgpu::BindGroup> {
+        self.density_bind_group.as_ref()
+    }
+
+    // Bin `count` vertices laid out as `layout` from byte `offset` of the
+    // points buffer, over `bounds`. Returns whether anything changed.
+    pub fn update(
+        &mut self,
+        queue: &wgpu::Queue,
+        bounds: &Uniform,
+        layout: VertexLayout,
+        offset: wgpu::BufferAddress,
+        count: u32,
+    ) -> bool {
+        let colormap = if self.histogram.colormap.is_empty() {
+            vec![[1.0; 4]]
+        } else {
+            Gradient::colormap(&self.histogram.colormap).resampled()
+        };
+
+        let mut params = Params {
+            x_range: bounds.x_bounds,
+            y_range: bounds.y_bounds,
+            bins: self.histogram.bins.map(|n| n.max(1)),
+            hex: (self.histogram.binning == Binning::Hex) as u32,
+            log_scale: (self.histogram.scale == CountScale::Log) as u32,
+            stride: (layout.stride / 4) as u32,
+            position: ((offset + layout.position) / 4) as u32,
+            samples: count / 2,
+            stop_count: colormap.len() as u32,
+            first: 0,
+            step: 1,
+            decay: self.histogram.persistence.unwrap_or(0.0),
+            _padding: 0,
+            stops: [[0.0; 4]; MAX_GRADIENT_STOPS],
+        };
+        params.stops[..colormap.len()].copy_from_slice(&colormap);
+
+        if self.histogram.persistence.is_some() {
+            // Blocks of streamed samples may vary in length without starting
+            // over.
+            let same = self.written.map_or(false, |written| {
+                Params {
+                    samples: params.samples,
+                    ..written
+                } == params
+            });
+            if same && !self.uploaded {
+                return false;
+            }
+
+            self.decaying = same;
+            self.uploaded = false;
+            self.written = Some(params);
+            queue.write_buffer(&self.params, 0, bytemuck::bytes_of(&params));
+            self.pending.store(true, Ordering::Relaxed);
+            return true;
+        }
+
+        if let Some(n) = self.histogram.samples_per_frame {
+            params.step = ((params.samples + n - 1) / n).max(1);
+        }
+
+        if self.written == Some(params) {
+            if self.histogram.samples_per_frame.is_none() || self.pass >= params.step {
+                return false;
+            }
+        } else {
+            self.written = Some(params);
+            self.pass = 0;
+        }
+
+        params.first = self.pass;
+        queue.write_buffer(&self.params, 0, bytemuck::bytes_of(&params));
+        self.pass += 1;
+        self.pending.store(true, Ordering::Relaxed);
+        true
+    }
+
+    // Start counting again after the points were uploaded, or for
+    // persistent counts, add them to the faded counts.
+    pub fn reset(&mut self) {
+        if self.histogram.persistence.is_some() {
+            self.uploaded = true;
+        } else {
+            self.written = None;
+        }
+    }
+
+    // The fraction of the samples binned so far.
+    pub fn progress(&self) -> f32 {
+        match self.written {
+            Some(params)
+                if self.histogram.samples_per_frame.is_some()
+                    && self.histogram.persistence.is_none() =>
+            {
+                self.pass.min(params.step) as f32 / params.step as f32
+            }
+            _ => 1.0,
+        }
+    }
+
+    // Recount the bins, or add the pass picked by `update` to them.
+    pub fn dispatch(&self, encoder: &mut wgpu::CommandEncoder) {
+        let persistent = self.histogram.persistence.is_some();
+        let progressive = self.histogram.samples_per_frame.is_some() && !persistent;
+        if (progressive || persistent) && !self.pending.swap(false, Ordering::Relaxed) {
+            return;
+        }
+
+        let (samples, step) = self
+            .written
+            .map_or((0, 1), |params| (params.samples, params.step));
+        let workgroups =
+            |n: u32| ((n + WORKGROUP_SIZE - 1) / WORKGROUP_SIZE).clamp(1, MAX_WORKGROUPS);
+
+        let mut cpass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("egui_plot_histogram_pass"),
+        });
+        cpass.set_bind_group(0, &self.bind_group, &[]);
+
+        if persistent && self.decaying {
+            cpass.set_pipeline(&self.compute.decay);
+            cpass.dispatch_workgroups(workgroups(self.counts_len), 1, 1);
+        } else if !progressive || self.pass == 1 {
+            cpass.set_pipeline(&self.compute.clear);
+            cpass.dispatch_workgroups(workgroups(self.counts_len), 1, 1);
+        }
+
+        cpass.set_pipeline(&self.compute.bin);
+        cpass.dispatch_workgroups(workgroups((samples + step - 1) / step), 1, 1);
+    }
+
+    // Draw the counts over the whole viewport.
+    pub fn draw<'rp>(&'rp self, rpass: &mut wgpu::RenderPass<'rp>) {
+        rpass.set_pipeline(&self.heatmap);
+        rpass.set_bind_group(0, &self.bind_group, &[]);
+        rpass.draw(0..3, 0..1);
+    }
+}
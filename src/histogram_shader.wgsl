@@ -0,0 +1,84 @@
+struct VertexOut {
+    @location(0) uv: vec2<f32>,
+    @builtin(position) position: vec4<f32>,
+};
+
+@group(0) @binding(0)
+var<uniform> params: BinParams;
+@group(0) @binding(1)
+var<storage, read> points: array<f32>;
+// One count per bin, with a one bin margin for the edges of the hexagonal
+// lattice, followed by the largest count.
+@group(0) @binding(2)
+var<storage, read_write> counts: array<atomic<u32>>;
+
+let WORKGROUP_SIZE: u32 = 64u;
+
+@compute @workgroup_size(64)
+fn cs_clear(@builtin(global_invocation_id) id: vec3<u32>,
+            @builtin(num_workgroups) groups: vec3<u32>) {
+    let total = groups.x * WORKGROUP_SIZE;
+    for (var i = id.x; i < arrayLength(&counts); i = i + total) {
+        atomicStore(&counts[i], 0u);
+    }
+}
+
+// Fade persistent counts (and the largest count) before adding new samples.
+@compute @workgroup_size(64)
+fn cs_decay(@builtin(global_invocation_id) id: vec3<u32>,
+            @builtin(num_workgroups) groups: vec3<u32>) {
+    let total = groups.x * WORKGROUP_SIZE;
+    for (var i = id.x; i < arrayLength(&counts); i = i + total) {
+        let count = atomicLoad(&counts[i]);
+        atomicStore(&counts[i], u32(f32(count) * params.decay));
+    }
+}
+
+@compute @workgroup_size(64)
+fn cs_bin(@builtin(global_invocation_id) id: vec3<u32>,
+          @builtin(num_workgroups) groups: vec3<u32>) {
+    let total = groups.x * WORKGROUP_SIZE;
+    let max_index = arrayLength(&counts) - 1u;
+
+    for (var i = params.first + id.x * params.step; i < params.samples; i = i + total * params.step) {
+        // Vertices come in pairs with the same position, one for each normal.
+        let word = 2u * i * params.stride + params.position;
+        let position = vec2<f32>(points[word], points[word + 1u]);
+
+        let index = bin_index(params, bin_coords(params, position));
+        if (index >= 0) {
+            let count = atomicAdd(&counts[index], 1u) + 1u;
+            atomicMax(&counts[max_index], count);
+        }
+    }
+}
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOut {
+    var out: VertexOut;
+
+    // A single triangle covering the whole viewport, as in the blit shader.
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+
+    out.uv = uv;
+    out.position = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+
+    return out;
+}
+
+@fragment
+fn fs_heatmap(in: VertexOut) -> @location(0) vec4<f32> {
+    let coords = vec2<f32>(in.uv.x, 1.0 - in.uv.y) * vec2<f32>(params.bins);
+    let index = bin_index(params, coords);
+    if (index < 0) {
+        discard;
+    }
+
+    let count = atomicLoad(&counts[index]);
+    if (count == 0u) {
+        discard;
+    }
+
+    let max_count = atomicLoad(&counts[arrayLength(&counts) - 1u]);
+    return bin_color(params, count, max_count);
+}
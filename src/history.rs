@@ -0,0 +1,127 @@
+#[cfg(feature = "gpu")]
+use crate::Vertex;
+
+/// Keeps copies of the last few acquisitions on the GPU and draws them
+/// behind the live data, each more faded than the last, so transient
+/// changes stay visible. See
+/// [`GpuAcceleratedPlot::set_history`](crate::GpuAcceleratedPlot::set_history).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct History {
+    pub snapshots: usize,
+    /// The alpha of the newest snapshot, relative to the live data. Each
+    /// older snapshot is faded by this much again.
+    pub fade: f32,
+}
+
+impl History {
+    pub fn new(snapshots: usize) -> History {
+        History {
+            snapshots,
+            fade: 0.5,
+        }
+    }
+
+    pub fn with_fade(self, fade: f32) -> History {
+        History { fade, ..self }
+    }
+
+    /// The alpha a snapshot is drawn with, where the newest has age 1.
+    pub fn alpha(&self, age: usize) -> f32 {
+        self.fade.clamp(0.0, 1.0).powi(age as i32)
+    }
+}
+
+#[cfg(feature = "gpu")]
+struct Snapshot {
+    buffer: wgpu::Buffer,
+    capacity: u32,
+    count: u32,
+}
+
+// A ring of frozen copies of the plot's vertex buffer.
+#[cfg(feature = "gpu")]
+pub(crate) struct HistoryRing {
+    history: History,
+    snapshots: Vec<Snapshot>,
+    // The snapshot to overwrite next, once the ring is full.
+    next: usize,
+}
+
+#[cfg(feature = "gpu")]
+impl HistoryRing {
+    pub fn new(history: History) -> HistoryRing {
+        HistoryRing {
+            history,
+            snapshots: Vec::with_capacity(history.snapshots),
+            next: 0,
+        }
+    }
+
+    pub fn history(&self) -> &History {
+        &self.history
+    }
+
+    pub fn clear(&mut self) {
+        self.snapshots.clear();
+        self.next = 0;
+    }
+
+    // Copy the first `count` vertices of `vertices` over the oldest snapshot.
+    pub fn push(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        vertices: &wgpu::Buffer,
+        count: u32,
+    ) {
+        if count == 0 || self.history.snapshots == 0 {
+            return;
+        }
+
+        let create = |capacity: u32| {
+            device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("egui_plot_history_vertices"),
+                size: (capacity as usize * std::mem::size_of::<Vertex>()) as wgpu::BufferAddress,
+                usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::VERTEX,
+                mapped_at_creation: false,
+            })
+        };
+
+        if self.snapshots.len() < self.history.snapshots {
+            let capacity = count.next_power_of_two();
+            self.snapshots.push(Snapshot {
+                buffer: create(capacity),
+                capacity,
+                count: 0,
+            });
+            self.next = self.snapshots.len() - 1;
+        }
+
+        let snapshot = &mut self.snapshots[self.next];
+        if count > snapshot.capacity {
+            snapshot.capacity = count.next_power_of_two();
+            snapshot.buffer = create(snapshot.capacity);
+        }
+        snapshot.count = count;
+
+        let size = (count as usize * std::mem::size_of::<Vertex>()) as wgpu::BufferAddress;
+        encoder.copy_buffer_to_buffer(vertices, 0, &snapshot.buffer, 0, size);
+
+        self.next = (self.next + 1) % self.history.snapshots;
+    }
+
+    // The snapshots from oldest to newest, with their age, buffer and vertex
+    // count.
+    pub fn snapshots(&self) -> impl Iterator<Item = (usize, &wgpu::Buffer, u32)> + '_ {
+        let len = self.snapshots.len();
+        let start = if len < self.history.snapshots {
+            0
+        } else {
+            self.next
+        };
+        (0..len).map(move |j| {
+            let snapshot = &self.snapshots[(start + j) % len];
+            (len - j, &snapshot.buffer, snapshot.count)
+        })
+    }
+}
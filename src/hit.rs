@@ -0,0 +1,267 @@
+use std::ops::Range;
+
+use crate::{readback::Mapping, Coordinates, SeriesId, Uniform, ValueMapping, VertexLayout};
+
+const WORKGROUP_SIZE: u32 = 64;
+const MAX_WORKGROUPS: u32 = 65535;
+
+const NONE: u32 = u32::MAX;
+
+/// The sample found by
+/// [`GpuAcceleratedPlot::hit_test`](crate::GpuAcceleratedPlot::hit_test).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Hit {
+    /// The series the sample was drawn in, or `None` if the plot has no
+    /// series.
+    pub series_id: Option<SeriesId>,
+    /// Index of the sample's first vertex in the points passed to the plot.
+    pub index: usize,
+    /// The sample's id, if ids were attached with
+    /// [`GpuAcceleratedPlot::set_point_ids`](crate::GpuAcceleratedPlot::set_point_ids).
+    pub id: Option<u32>,
+    /// The sample's position, as passed to the plot (before value mapping).
+    pub value: [f64; 2],
+}
+
+// Laid out to match `Params` in the hit shader.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+struct Params {
+    bounds: Uniform,
+    size: [f32; 2],
+    cursor: [f32; 2],
+    stride: u32,
+    position: u32,
+    first: u32,
+    end: u32,
+    scale: f32,
+    offset: f32,
+    transfer: u32,
+    tolerance: f32,
+    slot: u32,
+    slots: u32,
+    coordinates: u32,
+    _padding: u32,
+}
+
+// A range of vertices to search, drawn with a value mapping in some
+// coordinates.
+pub(crate) struct HitSlot {
+    pub vertices: Range<u32>,
+    pub mapping: ValueMapping,
+    pub coordinates: Coordinates,
+}
+
+// Finds the sample drawn nearest to a pixel on the GPU.
+pub(crate) struct HitFinder {
+    distance: wgpu::ComputePipeline,
+    index: wgpu::ComputePipeline,
+    position: wgpu::ComputePipeline,
+    params: wgpu::Buffer,
+    results: Option<(wgpu::Buffer, wgpu::Buffer)>,
+    slots: usize,
+    // The search being read back, and the bytes read.
+    pending: Option<(Mapping, wgpu::BufferAddress)>,
+}
+
+impl HitFinder {
+    pub fn new(device: &wgpu::Device) -> HitFinder {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("egui_plot_hit_shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("./hit_shader.wgsl").into()),
+        });
+
+        let create = |entry_point| {
+            device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some("egui_plot_hit_pipeline"),
+                layout: None,
+                module: &shader,
+                entry_point,
+            })
+        };
+
+        let params = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("egui_plot_hit_params"),
+            size: std::mem::size_of::<Params>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::UNIFORM,
+            mapped_at_creation: false,
+        });
+
+        HitFinder {
+            distance: create("cs_distance"),
+            index: create("cs_index"),
+            position: create("cs_position"),
+            params,
+            results: None,
+            slots: 0,
+            pending: None,
+        }
+    }
+
+    // Start searching for the sample drawn nearest to `cursor`, within
+    // `tolerance` pixels, among the vertices laid out as `layout` from byte
+    // `offset` of `points`. Slots are searched together, and ties go to the
+    // first. Returns false if there's nothing to search, or the last search
+    // is still being read back.
+    #[allow(clippy::too_many_arguments)]
+    pub fn submit(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        points: &wgpu::Buffer,
+        layout: VertexLayout,
+        offset: wgpu::BufferAddress,
+        bounds: &Uniform,
+        size: [u32; 2],
+        cursor: [f32; 2],
+        tolerance: f32,
+        slots: &[HitSlot],
+    ) -> bool {
+        if slots.is_empty() || self.pending.is_some() {
+            return false;
+        }
+
+        let words = 1 + 3 * slots.len();
+        let bytes = (words * std::mem::size_of::<u32>()) as wgpu::BufferAddress;
+        if self.results.is_none() || slots.len() > self.slots {
+            self.slots = slots.len().next_power_of_two();
+            let size = ((1 + 3 * self.slots) * std::mem::size_of::<u32>()) as wgpu::BufferAddress;
+            let results = device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("egui_plot_hit_results"),
+                size,
+                usage: wgpu::BufferUsages::STORAGE
+                    | wgpu::BufferUsages::COPY_DST
+                    | wgpu::BufferUsages::COPY_SRC,
+                mapped_at_creation: false,
+            });
+            let staging = device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("egui_plot_hit_staging"),
+                size,
+                usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            });
+            self.results = Some((results, staging));
+        }
+        let (results, staging) = match &self.results {
+            Some(results) => results,
+            None => return false,
+        };
+        queue.write_buffer(results, 0, bytemuck::cast_slice(&vec![NONE; words]));
+
+        let bind_group = |pipeline: &wgpu::ComputePipeline| {
+            device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("egui_plot_hit_bind_group"),
+                layout: &pipeline.get_bind_group_layout(0),
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: self.params.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: points.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: results.as_entire_binding(),
+                    },
+                ],
+            })
+        };
+        let bind_groups = [&self.distance, &self.index, &self.position].map(bind_group);
+
+        // Each slot has its own parameters, so each is a submission of its
+        // own: writes to the parameters land before the next submission.
+        let run = |pipeline: &wgpu::ComputePipeline,
+                   bind_group: &wgpu::BindGroup,
+                   params: &Params,
+                   workgroups: u32| {
+            queue.write_buffer(&self.params, 0, bytemuck::bytes_of(params));
+            let mut encoder =
+                device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+            {
+                let mut cpass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                    label: Some("egui_plot_hit_pass"),
+                });
+                cpass.set_pipeline(pipeline);
+                cpass.set_bind_group(0, bind_group, &[]);
+                cpass.dispatch_workgroups(workgroups, 1, 1);
+            }
+            queue.submit(std::iter::once(encoder.finish()));
+        };
+
+        let params: Vec<Params> = slots
+            .iter()
+            .enumerate()
+            .map(|(slot, hit_slot)| Params {
+                bounds: *bounds,
+                size: size.map(|n| n as f32),
+                cursor,
+                stride: (layout.stride / 4) as u32,
+                position: ((offset + layout.position) / 4) as u32,
+                first: hit_slot.vertices.start / 2,
+                end: hit_slot.vertices.end / 2,
+                scale: hit_slot.mapping.scale,
+                offset: hit_slot.mapping.offset,
+                transfer: hit_slot.mapping.transfer.code(),
+                tolerance,
+                slot: slot as u32,
+                slots: slots.len() as u32,
+                coordinates: hit_slot.coordinates.code(),
+                _padding: 0,
+            })
+            .collect();
+        let workgroups = |params: &Params| {
+            let samples = params.end.saturating_sub(params.first);
+            ((samples + WORKGROUP_SIZE - 1) / WORKGROUP_SIZE).clamp(1, MAX_WORKGROUPS)
+        };
+
+        for params in &params {
+            run(&self.distance, &bind_groups[0], params, workgroups(params));
+        }
+        for params in &params {
+            run(&self.index, &bind_groups[1], params, workgroups(params));
+        }
+        let slot_workgroups = (slots.len() as u32 + WORKGROUP_SIZE - 1) / WORKGROUP_SIZE;
+        run(
+            &self.position,
+            &bind_groups[2],
+            &params[0],
+            slot_workgroups.clamp(1, MAX_WORKGROUPS),
+        );
+
+        let mut encoder =
+            device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        encoder.copy_buffer_to_buffer(results, 0, staging, 0, bytes);
+        queue.submit(std::iter::once(encoder.finish()));
+
+        self.pending = Some((Mapping::start(&[staging.slice(..bytes)]), bytes));
+        true
+    }
+
+    // The result of the last search submitted, once read back: the slot,
+    // the vertex index and the position of the sample found, if any.
+    pub fn finish(&mut self) -> Option<Option<(usize, u32, [f32; 2])>> {
+        let mapped = self.pending.as_ref()?.0.state()?;
+        let (mapping, bytes) = self.pending.take()?;
+        let (_, staging) = self.results.as_ref()?;
+        if !mapped {
+            mapping.unmap(&[staging]);
+            return Some(None);
+        }
+
+        let hit = {
+            let data = staging.slice(..bytes).get_mapped_range();
+            let words: &[u32] = bytemuck::cast_slice(&data);
+            words[1..].chunks(3).enumerate().find_map(|(slot, result)| {
+                (result[0] != NONE).then(|| {
+                    let position = [f32::from_bits(result[1]), f32::from_bits(result[2])];
+                    (slot, 2 * result[0], position)
+                })
+            })
+        };
+        staging.unmap();
+
+        Some(hit)
+    }
+}
@@ -0,0 +1,142 @@
+struct Params {
+    x_range: vec2<f32>,
+    y_range: vec2<f32>,
+    // Axis breaks, as in the line shader.
+    x_breaks: array<vec4<f32>, 2>,
+    y_breaks: array<vec4<f32>, 2>,
+    // The plot's size and the cursor, in pixels from the top left.
+    size: vec2<f32>,
+    cursor: vec2<f32>,
+    // Vertex stride and position offset, in 4 byte words.
+    stride: u32,
+    position: u32,
+    // The range of samples to search.
+    first: u32,
+    end: u32,
+    // The searched series' value mapping, as in the line shader.
+    scale: f32,
+    offset: f32,
+    transfer: u32,
+    tolerance: f32,
+    // Which result slot the searched series writes to, of how many.
+    slot: u32,
+    slots: u32,
+    // The searched series' coordinates, as in the line shader.
+    coordinates: u32,
+    _padding: u32,
+};
+
+@group(0) @binding(0)
+var<uniform> params: Params;
+@group(0) @binding(1)
+var<storage, read> points: array<f32>;
+// The smallest distance, then for each slot the lowest index of a sample at
+// that distance and its position, as bits.
+@group(0) @binding(2)
+var<storage, read_write> results: array<atomic<u32>>;
+
+let WORKGROUP_SIZE: u32 = 64u;
+let NONE: u32 = 0xffffffffu;
+
+// Distances are compared in 1/256ths of a pixel.
+let SUBPIXELS: f32 = 256.0;
+
+let LOG10_2: f32 = 0.30102999566;
+
+let TRANSFER_DECIBELS: u32 = 1u;
+let TRANSFER_POWER_DECIBELS: u32 = 2u;
+
+let COORDINATES_IMPEDANCE: u32 = 1u;
+
+fn break_shift(value: f32, breaks: vec4<f32>) -> f32 {
+    return clamp(value - breaks.x, 0.0, breaks.y - breaks.x)
+         + clamp(value - breaks.z, 0.0, breaks.w - breaks.z);
+}
+
+fn map_value(y: f32) -> f32 {
+    var value = y;
+    if (params.transfer == TRANSFER_DECIBELS) {
+        value = 20.0 * LOG10_2 * log2(y);
+    } else if (params.transfer == TRANSFER_POWER_DECIBELS) {
+        value = 10.0 * LOG10_2 * log2(y);
+    }
+    return params.scale * value + params.offset;
+}
+
+// The reflection coefficient (z - 1) / (z + 1) of a normalized impedance.
+fn reflection(z: vec2<f32>) -> vec2<f32> {
+    let n = vec2(z.x - 1.0, z.y);
+    let d = vec2(z.x + 1.0, z.y);
+    return vec2(n.x * d.x + n.y * d.y, n.y * d.x - n.x * d.y) / dot(d, d);
+}
+
+// Vertices come in pairs with the same position, one for each normal.
+fn sample(i: u32) -> vec2<f32> {
+    let word = 2u * i * params.stride + params.position;
+    return vec2<f32>(points[word], points[word + 1u]);
+}
+
+// The distance from the cursor to where sample `i` is drawn, in subpixels,
+// or NONE beyond the tolerance (including NaNs).
+fn distance(i: u32) -> u32 {
+    var p = sample(i);
+    p.y = map_value(p.y);
+    if (params.coordinates == COORDINATES_IMPEDANCE) {
+        p = reflection(p);
+    }
+    let x = p.x - break_shift(p.x, params.x_breaks[0]) - break_shift(p.x, params.x_breaks[1]);
+    let y = p.y - break_shift(p.y, params.y_breaks[0]) - break_shift(p.y, params.y_breaks[1]);
+
+    let origin = vec2<f32>(params.x_range[0], params.y_range[0]);
+    let size = vec2<f32>(params.x_range[1], params.y_range[1]) - origin;
+    let t = (vec2<f32>(x, y) - origin) / size;
+    let pixel = vec2<f32>(t.x, 1.0 - t.y) * params.size;
+
+    let d = length(pixel - params.cursor);
+    if (!(d <= params.tolerance)) {
+        return NONE;
+    }
+    return u32(d * SUBPIXELS);
+}
+
+@compute @workgroup_size(64)
+fn cs_distance(@builtin(global_invocation_id) id: vec3<u32>,
+               @builtin(num_workgroups) groups: vec3<u32>) {
+    let total = groups.x * WORKGROUP_SIZE;
+    for (var i = params.first + id.x; i < params.end; i = i + total) {
+        let d = distance(i);
+        if (d != NONE) {
+            atomicMin(&results[0], d);
+        }
+    }
+}
+
+// Run after every series' distances, to find the samples at the smallest.
+@compute @workgroup_size(64)
+fn cs_index(@builtin(global_invocation_id) id: vec3<u32>,
+            @builtin(num_workgroups) groups: vec3<u32>) {
+    let total = groups.x * WORKGROUP_SIZE;
+    let nearest = atomicLoad(&results[0]);
+    for (var i = params.first + id.x; i < params.end; i = i + total) {
+        let d = distance(i);
+        if (d != NONE && d == nearest) {
+            atomicMin(&results[1u + 3u * params.slot], i);
+        }
+    }
+}
+
+// Run last, to look up the position of each slot's sample.
+@compute @workgroup_size(64)
+fn cs_position(@builtin(global_invocation_id) id: vec3<u32>) {
+    let slot = id.x;
+    if (slot >= params.slots) {
+        return;
+    }
+
+    let i = atomicLoad(&results[1u + 3u * slot]);
+    if (i != NONE) {
+        let p = sample(i);
+        atomicStore(&results[2u + 3u * slot], bitcast<u32>(p.x));
+        atomicStore(&results[3u + 3u * slot], bitcast<u32>(p.y));
+    }
+}
@@ -0,0 +1,234 @@
+use crate::{Bounds, PassContext, PlotPass};
+
+/// A texture drawn over a rectangle of data space, e.g. one map tile.
+pub struct ImageTile {
+    /// A filterable float texture, e.g. `Rgba8UnormSrgb`.
+    pub view: wgpu::TextureView,
+    /// The data covered, from the image's left edge to its right and bottom
+    /// edge to its top.
+    pub bounds: Bounds,
+}
+
+impl ImageTile {
+    pub fn new(view: wgpu::TextureView, bounds: Bounds) -> ImageTile {
+        ImageTile { view, bounds }
+    }
+}
+
+// Laid out to match `Tile` in the image shader.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+struct TileUniform {
+    x: [f32; 2],
+    y: [f32; 2],
+    opacity: f32,
+    _padding: [f32; 3],
+}
+
+/// Images mapped to data coordinates, e.g. a map under GPS tracks or a
+/// camera frame under annotations. Add it to a plot as a pass, usually
+/// underneath the data:
+/// `plot.add_pass(Layer::Background, Box::new(image_layer))`.
+pub struct ImageLayer {
+    tiles: Vec<ImageTile>,
+    /// Multiplies the images' alpha.
+    pub opacity: f32,
+    /// How the images are sampled when magnified or minified.
+    pub filter: wgpu::FilterMode,
+    // Created by the first prepare, for the target's format and sample
+    // count, with a bind group and uniform buffer per tile.
+    pipeline: Option<((wgpu::TextureFormat, u32), wgpu::RenderPipeline)>,
+    bind_groups: Vec<(wgpu::Buffer, wgpu::BindGroup)>,
+    written: Vec<TileUniform>,
+}
+
+impl ImageLayer {
+    pub fn new(tiles: Vec<ImageTile>) -> ImageLayer {
+        ImageLayer {
+            tiles,
+            opacity: 1.0,
+            filter: wgpu::FilterMode::Linear,
+            pipeline: None,
+            bind_groups: Vec::new(),
+            written: Vec::new(),
+        }
+    }
+
+    pub fn with_opacity(self, opacity: f32) -> ImageLayer {
+        ImageLayer { opacity, ..self }
+    }
+
+    pub fn with_filter(self, filter: wgpu::FilterMode) -> ImageLayer {
+        ImageLayer { filter, ..self }
+    }
+
+    pub fn tiles(&self) -> &[ImageTile] {
+        &self.tiles
+    }
+
+    fn create_pipeline(
+        device: &wgpu::Device,
+        context: &PassContext,
+        bind_group_layout: &wgpu::BindGroupLayout,
+    ) -> wgpu::RenderPipeline {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("egui_plot_image_shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("./image_shader.wgsl").into()),
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("egui_plot_image_pipeline_layout"),
+            bind_group_layouts: &[context.uniform_layout, bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("egui_plot_image_pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: context.format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleStrip,
+                ..Default::default()
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState {
+                count: context.sample_count,
+                ..Default::default()
+            },
+            multiview: None,
+        })
+    }
+}
+
+impl PlotPass for ImageLayer {
+    fn prepare(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        context: &PassContext,
+    ) -> bool {
+        let key = (context.format, context.sample_count);
+        let mut changed = false;
+
+        if self.pipeline.as_ref().map(|(k, _)| *k) != Some(key) {
+            let bind_group_layout =
+                device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                    label: Some("egui_plot_image_bind_group_layout"),
+                    entries: &[
+                        wgpu::BindGroupLayoutEntry {
+                            binding: 0,
+                            visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                            ty: wgpu::BindingType::Buffer {
+                                ty: wgpu::BufferBindingType::Uniform,
+                                has_dynamic_offset: false,
+                                min_binding_size: None,
+                            },
+                            count: None,
+                        },
+                        wgpu::BindGroupLayoutEntry {
+                            binding: 1,
+                            visibility: wgpu::ShaderStages::FRAGMENT,
+                            ty: wgpu::BindingType::Texture {
+                                sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                                view_dimension: wgpu::TextureViewDimension::D2,
+                                multisampled: false,
+                            },
+                            count: None,
+                        },
+                        wgpu::BindGroupLayoutEntry {
+                            binding: 2,
+                            visibility: wgpu::ShaderStages::FRAGMENT,
+                            ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                            count: None,
+                        },
+                    ],
+                });
+            let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+                label: Some("egui_plot_image_sampler"),
+                mag_filter: self.filter,
+                min_filter: self.filter,
+                ..Default::default()
+            });
+
+            self.bind_groups = self
+                .tiles
+                .iter()
+                .map(|tile| {
+                    let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+                        label: Some("egui_plot_image_tile"),
+                        size: std::mem::size_of::<TileUniform>() as wgpu::BufferAddress,
+                        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::UNIFORM,
+                        mapped_at_creation: false,
+                    });
+                    let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                        label: Some("egui_plot_image_bind_group"),
+                        layout: &bind_group_layout,
+                        entries: &[
+                            wgpu::BindGroupEntry {
+                                binding: 0,
+                                resource: buffer.as_entire_binding(),
+                            },
+                            wgpu::BindGroupEntry {
+                                binding: 1,
+                                resource: wgpu::BindingResource::TextureView(&tile.view),
+                            },
+                            wgpu::BindGroupEntry {
+                                binding: 2,
+                                resource: wgpu::BindingResource::Sampler(&sampler),
+                            },
+                        ],
+                    });
+                    (buffer, bind_group)
+                })
+                .collect();
+            self.written.clear();
+
+            let pipeline = ImageLayer::create_pipeline(device, context, &bind_group_layout);
+            self.pipeline = Some((key, pipeline));
+            changed = true;
+        }
+
+        let uniforms: Vec<_> = self
+            .tiles
+            .iter()
+            .map(|tile| TileUniform {
+                x: tile.bounds.x.map(|x| x as f32),
+                y: tile.bounds.y.map(|y| y as f32),
+                opacity: self.opacity,
+                _padding: [0.0; 3],
+            })
+            .collect();
+        if uniforms != self.written {
+            for ((buffer, _), uniform) in self.bind_groups.iter().zip(&uniforms) {
+                queue.write_buffer(buffer, 0, bytemuck::bytes_of(uniform));
+            }
+            self.written = uniforms;
+            changed = true;
+        }
+
+        changed
+    }
+
+    fn render<'rp>(&'rp self, rpass: &mut wgpu::RenderPass<'rp>) {
+        if let Some((_, pipeline)) = &self.pipeline {
+            rpass.set_pipeline(pipeline);
+            for (_, bind_group) in &self.bind_groups {
+                rpass.set_bind_group(1, bind_group, &[]);
+                rpass.draw(0..4, 0..1);
+            }
+        }
+    }
+}
@@ -0,0 +1,66 @@
+struct VertexOut {
+    @location(0) uv: vec2<f32>,
+    @builtin(position) position: vec4<f32>,
+};
+
+// The plot's bounds, as in the line shader.
+struct Uniforms {
+    x_range: vec2<f32>,
+    y_range: vec2<f32>,
+    x_breaks: array<vec4<f32>, 2>,
+    y_breaks: array<vec4<f32>, 2>,
+};
+
+struct Tile {
+    // The data the image covers, from its left to right and bottom to top.
+    x: vec2<f32>,
+    y: vec2<f32>,
+    opacity: f32,
+    _padding: vec3<f32>,
+};
+
+@group(0) @binding(0)
+var<uniform> uniforms: Uniforms;
+
+@group(1) @binding(0)
+var<uniform> tile: Tile;
+@group(1) @binding(1)
+var tile_texture: texture_2d<f32>;
+@group(1) @binding(2)
+var tile_sampler: sampler;
+
+fn break_shift(value: f32, breaks: vec4<f32>) -> f32 {
+    return clamp(value - breaks.x, 0.0, breaks.y - breaks.x)
+         + clamp(value - breaks.z, 0.0, breaks.w - breaks.z);
+}
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOut {
+    var out: VertexOut;
+
+    // A strip of two triangles, with texture space starting at the top
+    // left.
+    let uv = vec2<f32>(f32(index & 1u), f32(index >> 1u));
+    let data = vec2<f32>(mix(tile.x[0], tile.x[1], uv.x), mix(tile.y[1], tile.y[0], uv.y));
+
+    // Only the corners are transformed, so a break within the image
+    // squeezes it rather than cutting it.
+    let x = data.x - break_shift(data.x, uniforms.x_breaks[0]) - break_shift(data.x, uniforms.x_breaks[1]);
+    let y = data.y - break_shift(data.y, uniforms.y_breaks[0]) - break_shift(data.y, uniforms.y_breaks[1]);
+
+    out.uv = uv;
+    out.position = vec4<f32>(
+        mix(-1.0, 1.0, (x - uniforms.x_range[0]) / (uniforms.x_range[1] - uniforms.x_range[0])),
+        mix(-1.0, 1.0, (y - uniforms.y_range[0]) / (uniforms.y_range[1] - uniforms.y_range[0])),
+        0.0,
+        1.0,
+    );
+
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOut) -> @location(0) vec4<f32> {
+    let color = textureSample(tile_texture, tile_sampler, in.uv);
+    return vec4<f32>(color.rgb, color.a * tile.opacity);
+}
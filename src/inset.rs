@@ -0,0 +1,122 @@
+#[cfg(feature = "gpu")]
+use wgpu::util::DeviceExt;
+
+#[cfg(feature = "gpu")]
+use crate::Vertex;
+use crate::{Bounds, CellRect};
+
+/// A picture-in-picture view of the same data at different bounds, drawn
+/// over a corner of the plot with a border.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Inset {
+    /// The region of data shown in the inset.
+    pub bounds: Bounds,
+    /// Where the inset is drawn in the plot texture, in pixels, not counting
+    /// the border.
+    pub rect: CellRect,
+    pub border_width: u32,
+    pub border_color: [f32; 4],
+    pub background: [f32; 4],
+}
+
+impl Inset {
+    /// An inset showing a fixed region of interest.
+    pub fn new(bounds: Bounds, rect: CellRect) -> Inset {
+        Inset {
+            bounds,
+            rect,
+            border_width: 1,
+            border_color: [1.0, 1.0, 1.0, 1.0],
+            background: [0.0, 0.0, 0.0, 1.0],
+        }
+    }
+
+    /// A magnifier showing `half_extent` around `center` (e.g. the data
+    /// position under the cursor).
+    pub fn magnifier(center: [f64; 2], half_extent: [f64; 2], rect: CellRect) -> Inset {
+        Inset::new(
+            Bounds::from_min_max(
+                [center[0] - half_extent[0], center[1] - half_extent[1]],
+                [center[0] + half_extent[0], center[1] + half_extent[1]],
+            ),
+            rect,
+        )
+    }
+
+    pub fn with_border(self, width: u32, color: [f32; 4]) -> Inset {
+        Inset {
+            border_width: width,
+            border_color: color,
+            ..self
+        }
+    }
+
+    pub fn with_background(self, background: [f32; 4]) -> Inset {
+        Inset { background, ..self }
+    }
+
+    // The inner rect and the rect including the border, clipped to a texture
+    // of the given size.
+    pub(crate) fn rects(&self, size: [u32; 2]) -> (CellRect, CellRect) {
+        let clip = |x0: u32, y0: u32, x1: u32, y1: u32| {
+            let (x0, y0) = (x0.min(size[0]), y0.min(size[1]));
+            CellRect {
+                x: x0,
+                y: y0,
+                width: x1.min(size[0]) - x0,
+                height: y1.min(size[1]) - y0,
+            }
+        };
+
+        let r = &self.rect;
+        let inner = clip(r.x, r.y, r.x + r.width, r.y + r.height);
+        let outer = clip(
+            r.x.saturating_sub(self.border_width),
+            r.y.saturating_sub(self.border_width),
+            r.x + r.width + self.border_width,
+            r.y + r.height + self.border_width,
+        );
+
+        (inner, outer)
+    }
+}
+
+// Two unit quads (border, then background) drawn with the line pipeline:
+// with zero normals, the line shader draws them solid.
+#[cfg(feature = "gpu")]
+pub(crate) struct InsetFrame {
+    pub vertex_buffer: wgpu::Buffer,
+    colors: [[f32; 4]; 2],
+}
+
+#[cfg(feature = "gpu")]
+impl InsetFrame {
+    pub fn new(device: &wgpu::Device, inset: &Inset) -> InsetFrame {
+        let colors = [inset.border_color, inset.background];
+        let vertices: Vec<Vertex> = colors
+            .iter()
+            .flat_map(|&color| {
+                [[0.0, 0.0], [1.0, 0.0], [0.0, 1.0], [1.0, 1.0]].map(|position| Vertex {
+                    position,
+                    normal: [0.0, 0.0],
+                    color,
+                })
+            })
+            .collect();
+
+        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("egui_plot_inset_frame"),
+            contents: bytemuck::cast_slice(&vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+
+        InsetFrame {
+            vertex_buffer,
+            colors,
+        }
+    }
+
+    pub fn matches(&self, inset: &Inset) -> bool {
+        self.colors == [inset.border_color, inset.background]
+    }
+}
@@ -1,16 +1,292 @@
-use std::{iter, sync::Arc};
+// Without `gpu`, the helpers only the plot itself uses (budgets, groups,
+// packed uniforms) are still built, but go unused.
+#![cfg_attr(not(feature = "gpu"), allow(dead_code))]
 
-use egui::plot::PlotBounds;
-use wgpu::{util::DeviceExt, TextureViewDescriptor};
+use std::ops::Range;
+#[cfg(feature = "gpu")]
+use std::{borrow::Cow, iter, sync::Arc, time::Instant};
 
+#[cfg(feature = "gpu")]
+use wgpu::util::DeviceExt;
+
+mod attributes;
+mod audio;
+mod axis;
+mod batch;
+#[cfg(feature = "bevy")]
+mod bevy_integration;
+#[cfg(feature = "gpu")]
+mod blit;
+mod bloom;
+mod bode;
+mod bounds;
+mod budget;
+mod cache;
+#[cfg(feature = "gpu")]
+mod capabilities;
+#[cfg(feature = "gpu")]
+mod capture;
+mod categories;
+mod constellation;
+#[cfg(feature = "gpu")]
+mod context;
+#[cfg(feature = "cpu-fallback")]
+mod cpu;
+mod cursor;
+mod diagnostics;
+#[cfg(feature = "compute")]
+mod diff;
+mod edit;
+#[cfg(feature = "egui-wgpu")]
+mod egui_integration;
+#[cfg(feature = "gpu")]
+mod external;
+#[cfg(feature = "compute")]
+mod extrema;
+mod fit;
+#[cfg(feature = "compute")]
+mod follow;
+mod format;
+mod groups;
+#[cfg(feature = "heatmap")]
+mod histogram;
+mod history;
+#[cfg(feature = "compute")]
+mod hit;
+#[cfg(feature = "gpu")]
+mod image;
+mod inset;
+mod logic;
+mod markers;
+#[cfg(feature = "compute")]
+mod merge;
+mod navigation;
+#[cfg(feature = "gpu")]
+mod overview;
+#[cfg(feature = "gpu")]
+mod pass;
+mod phase;
+mod picking;
+#[cfg(feature = "gpu")]
+mod ping_pong;
+mod playback;
+#[cfg(feature = "gpu")]
+mod profiling;
+#[cfg(feature = "gpu")]
+mod readback;
+#[cfg(feature = "egui")]
+mod repaint;
+mod selection;
+mod series;
+mod smith;
+mod state;
+mod strip;
+mod subplots;
+#[cfg(feature = "gpu")]
+mod target;
+mod theme;
+mod threshold;
+#[cfg(feature = "gpu")]
+mod thumbnail;
+mod timeline;
+mod transform;
+#[cfg(feature = "gpu")]
+mod uniforms;
+mod validation;
+mod versions;
+mod vision;
+mod waterfall;
+mod worker;
+
+#[cfg(feature = "gpu")]
+use attributes::AttributeColumns;
+#[cfg(feature = "gpu")]
+use bloom::Bloom;
+#[cfg(feature = "gpu")]
+use budget::{BudgetController, VertexMap};
+#[cfg(feature = "gpu")]
+use context::PipelineKey;
+#[cfg(feature = "compute")]
+use diff::DiffLayer;
+#[cfg(feature = "compute")]
+use extrema::ExtremaFinder;
+#[cfg(feature = "compute")]
+use fit::FitReducer;
+#[cfg(feature = "compute")]
+use follow::FollowLayer;
+#[cfg(feature = "gpu")]
+use groups::{GroupState, Groups};
+#[cfg(feature = "heatmap")]
+use histogram::HistogramBins;
+#[cfg(feature = "gpu")]
+use history::HistoryRing;
+#[cfg(feature = "compute")]
+use hit::{HitFinder, HitSlot};
+#[cfg(feature = "gpu")]
+use inset::InsetFrame;
+#[cfg(feature = "gpu")]
+use logic::LogicLayer;
+#[cfg(feature = "compute")]
+use markers::CrossingFinder;
+#[cfg(feature = "gpu")]
+use markers::MarkerLayer;
+#[cfg(feature = "compute")]
+use merge::MergeLayer;
+#[cfg(feature = "gpu")]
+use overview::Overview;
+#[cfg(feature = "gpu")]
+use ping_pong::FrameHistory;
+#[cfg(feature = "gpu")]
+use playback::CursorLayer;
+#[cfg(feature = "gpu")]
+use profiling::profile_scope;
+#[cfg(feature = "gpu")]
+use readback::ImageReadback;
+#[cfg(feature = "compute")]
+use selection::{StatsReducer, StatsSlot};
+#[cfg(feature = "gpu")]
+use series::{Instances, Series, SeriesUniform};
+#[cfg(feature = "gpu")]
+use target::{srgb_counterpart, RenderTarget, TargetConfig};
+#[cfg(feature = "compute")]
+use threshold::BreachFinder;
+#[cfg(feature = "gpu")]
+use threshold::ThresholdLayer;
+#[cfg(feature = "gpu")]
+use thumbnail::Thumbnail;
+#[cfg(feature = "gpu")]
+use timeline::TimelineLayer;
+#[cfg(feature = "gpu")]
+use uniforms::UniformSlots;
+#[cfg(feature = "gpu")]
+use vision::VisionFilter;
+
+pub use attributes::{ColorBy, SizeBy};
+pub use audio::{AudioWaveform, Peaks, WaveformDetail, DEFAULT_PEAK_BIN};
+pub use axis::{time_ticks, AxisBreak, AxisBreaks, TimeTick, TimeUnit, MAX_AXIS_BREAKS};
+pub use batch::LineBatch;
+#[cfg(feature = "bevy")]
+pub use bevy_integration::{plot_image, GpuPlot, GpuPlotPlugin, PlotSeries};
+pub use bloom::Glow;
+pub use bode::{Bode, BodeReadout, PhaseMode};
+pub use bounds::Bounds;
+pub use budget::{DecimationMode, RenderBudget, VertexBudget};
+pub use cache::{CacheError, PlotCache};
+#[cfg(feature = "gpu")]
+pub use capabilities::Capabilities;
+#[cfg(feature = "gpu")]
+pub use capture::{api_trace_path, FrameCapture, TRACE_ENV_VAR};
+pub use categories::Categories;
+pub use constellation::Constellation;
+#[cfg(feature = "gpu")]
+pub use context::GpuPlotContext;
+#[cfg(feature = "cpu-fallback")]
+pub use cpu::cpu_plot_shape;
+#[cfg(all(feature = "cpu-fallback", feature = "egui-wgpu"))]
+pub use cpu::egui_wgpu_callback_or_cpu;
+pub use cursor::DataCursor;
+pub use diagnostics::{FrameDiagnostics, PlotError};
+#[cfg(feature = "compute")]
+pub use diff::{Comparison, TraceDiff};
+pub use edit::{move_sample, PointEdit, PointEditor};
+#[cfg(all(feature = "egui-wgpu", feature = "compute"))]
+pub use egui_integration::egui_wgpu_selection_callback;
+#[cfg(feature = "egui-wgpu")]
+pub use egui_integration::{
+    axis_formatter, bode_plot, category_formatter, data_cursor, edit_markers, edit_points,
+    egui_wgpu_callback, egui_wgpu_composite_callback, egui_wgpu_edit_callback,
+    egui_wgpu_overview_callback, egui_wgpu_subplots_callback, overview_strip, plot_context_menu,
+    plot_image_area, region_selector, stacked_rows, strip_chart, zoom_navigation, PlotMenuAction,
+};
+#[cfg(feature = "gpu")]
+pub use external::{ExternalPoints, VertexLayout};
+#[cfg(feature = "compute")]
+pub use extrema::ColumnExtrema;
+pub use fit::{fit, Fit, FitModel, MAX_FIT_DEGREE};
+#[cfg(feature = "compute")]
+pub use follow::Follow;
+pub use format::{Notation, NumberFormat};
+pub use groups::{matches_pattern, GroupId};
+#[cfg(feature = "heatmap")]
+pub use histogram::{Binning, CountScale, Histogram, HistogramDisplay};
+pub use history::History;
+#[cfg(feature = "compute")]
+pub use hit::Hit;
+#[cfg(feature = "gpu")]
+pub use image::{ImageLayer, ImageTile};
+pub use inset::Inset;
+pub use logic::{LogicRun, LogicStyle, LogicTrack};
+pub use markers::{MarkerEditor, MarkerHandle, MarkerRegion, RegionEdit};
+#[cfg(feature = "compute")]
+pub use merge::PointMerge;
+pub use navigation::{ZoomAxes, ZoomHistory, DEFAULT_ZOOM_HISTORY};
+#[cfg(feature = "gpu")]
+pub use pass::{PassContext, PassId, PlotPass};
+pub use phase::PhaseSpace;
+pub use picking::{pick_column, ColumnPick, Sample};
+#[cfg(feature = "gpu")]
+pub use ping_pong::PingPong;
+pub use playback::TimeCursor;
+#[cfg(feature = "gpu")]
+pub use readback::ReadbackResult;
+#[cfg(feature = "egui")]
+pub use repaint::{request_animation_frame, DataNotifier, DataWatcher};
+pub use selection::{
+    Region, RegionSelector, RegionStats, SelectionEvent, SelectionMode, SeriesSelection,
+};
+pub use series::{
+    Coordinates, Fold, Gradient, GradientKey, Instance, Layer, Normalization, SeriesId,
+    SeriesStyle, StyleHandle, Trail, Transfer, ValueMapping, MAX_GRADIENT_STOPS,
+};
+pub use smith::{impedance, reflection, SmithChart};
+pub use state::GpuPlotState;
+pub use strip::{StripChart, DEFAULT_STRIP_CAPACITY};
+pub use subplots::{CellRect, StackedRow, StackedRows, Subplot, SubplotLayout};
+#[cfg(feature = "gpu")]
+pub use target::{validate_render_format, FormatError};
+pub use theme::Theme;
+pub use threshold::Threshold;
+#[cfg(feature = "compute")]
+pub use threshold::MAX_BREACHES;
+pub use timeline::{Interval, Timeline};
+pub use transform::PlotTransform;
+pub use validation::{sanitize, validate, ValidationMode, ValidationReport};
+pub use versions::{SeriesData, SeriesVersion};
+pub use vision::{ColorVision, CVD_SAFE_PALETTE};
+pub use waterfall::Waterfall;
+pub use worker::{prepare_series, PrepareJob, PreparedSeries, SeriesPreparer};
+
+#[cfg(feature = "gpu")]
 const MSAA_SAMPLE_COUNT: u32 = 1;
 const MAX_POINTS: usize = 5_000_000;
 
+// Every viewport's bounds live in one uniform buffer, selected between with
+// dynamic offsets.
+#[cfg(feature = "gpu")]
+const MAIN_SLOT: usize = 0;
+#[cfg(feature = "gpu")]
+const OVERVIEW_SLOT: usize = 1;
+#[cfg(feature = "gpu")]
+const INSET_SLOT: usize = 2;
+// Fixed unit bounds for drawing the inset's frame.
+#[cfg(feature = "gpu")]
+const INSET_FRAME_SLOT: usize = 3;
+#[cfg(feature = "gpu")]
+const FIRST_SUBPLOT_SLOT: usize = 4;
+
+// Series styles live in a second uniform buffer, with the style of the
+// implicit series (used when no series have been added) first, then one per
+// series, then one per shared style.
+#[cfg(feature = "gpu")]
+const DEFAULT_SERIES_SLOT: usize = 0;
+
+#[cfg(feature = "gpu")]
 const DEFAULT_WIDTH: u32 = 1;
+#[cfg(feature = "gpu")]
 const DEFAULT_HEIGHT: u32 = 1;
 
 #[repr(C)]
-#[derive(Clone, Copy, Default, bytemuck::Pod, bytemuck::Zeroable)]
+#[derive(Clone, Copy, Debug, Default, bytemuck::Pod, bytemuck::Zeroable)]
 pub struct Vertex {
     pub position: [f32; 2],
     pub normal: [f32; 2],
@@ -18,295 +294,3971 @@ pub struct Vertex {
 }
 
 #[repr(C)]
-#[derive(Clone, Copy, Default, bytemuck::Pod, bytemuck::Zeroable)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
 pub struct Uniform {
     pub x_bounds: [f32; 2],
     pub y_bounds: [f32; 2],
+    /// Axis breaks, packed as by [`AxisBreaks`].
+    pub x_breaks: [[f32; 4]; 2],
+    pub y_breaks: [[f32; 4]; 2],
+}
+
+impl Uniform {
+    // Byte ranges of each axis' bounds and breaks, which are written
+    // independently of each other.
+    pub(crate) const FIELDS: [Range<usize>; 4] = [0..8, 8..16, 16..48, 48..80];
+
+    // The bounds in data units, before the axis breaks are applied, from
+    // low to high even if an axis is reversed.
+    pub(crate) fn data_bounds(&self) -> ([f32; 2], [f32; 2]) {
+        (
+            ascending(self.x_bounds).map(|x| axis::packed_to_data(x, &self.x_breaks)),
+            ascending(self.y_bounds).map(|y| axis::packed_to_data(y, &self.y_breaks)),
+        )
+    }
+}
+
+#[cfg(any(feature = "compute", feature = "heatmap"))]
+fn supported_or(supported: bool, feature: &'static str) -> Result<(), PlotError> {
+    if supported {
+        Ok(())
+    } else {
+        Err(PlotError::UnsupportedFeature(feature))
+    }
+}
+
+// Quote a CSV field if it needs it.
+#[cfg(feature = "gpu")]
+fn csv_field(field: &str) -> Cow<str> {
+    if field.contains(|c| matches!(c, ',' | '"' | '\n')) {
+        Cow::Owned(format!("\"{}\"", field.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(field)
+    }
 }
 
+fn ascending([a, b]: [f32; 2]) -> [f32; 2] {
+    [a.min(b), a.max(b)]
+}
+
+impl From<&Bounds> for Uniform {
+    fn from(bounds: &Bounds) -> Self {
+        Uniform {
+            x_bounds: [bounds.x[0] as f32, bounds.x[1] as f32],
+            y_bounds: [bounds.y[0] as f32, bounds.y[1] as f32],
+            ..Default::default()
+        }
+    }
+}
+
+/// A line plot rendered into an offscreen texture.
+///
+/// The plot only depends on wgpu: call [`GpuAcceleratedPlot::prepare`] with
+/// the texture size and data bounds, then [`GpuAcceleratedPlot::render`], and
+/// display the texture from [`GpuAcceleratedPlot::create_view`] however the
+/// host application likes. The egui integration is a thin layer on top,
+/// enabled by the `egui-wgpu` feature.
+///
+/// The plot is `Send + Sync`, so it can live in egui's paint callback
+/// resources, and is updated through `&mut` by the thread rendering it.
+/// Producers on other threads push samples into a [`GpuPlotState`] instead,
+/// which [`GpuAcceleratedPlot::prepare_shared`] picks up.
+#[cfg(feature = "gpu")]
 pub struct GpuAcceleratedPlot {
-    pipeline: wgpu::RenderPipeline,
-    target_format: wgpu::TextureFormat,
-    bind_group: wgpu::BindGroup,
+    context: Arc<GpuPlotContext>,
+    capabilities: Capabilities,
+    pipeline: Arc<wgpu::RenderPipeline>,
+
+    uniforms: UniformSlots<Uniform>,
+    uniform_data: Vec<Uniform>,
+    written_uniform_data: Vec<Uniform>,
+
+    series: Vec<Series>,
+    styles: Vec<SeriesStyle>,
+    groups: Groups,
+    // Indices into `series`, stably sorted by z order.
+    draw_order: Vec<usize>,
+    series_uniforms: UniformSlots<SeriesUniform>,
+    written_series_data: Vec<SeriesUniform>,
 
-    uniform_buffer: wgpu::Buffer,
     vertex_buffer: wgpu::Buffer,
+    // Where the device can't fit `MAX_POINTS` in one buffer, the vertices
+    // beyond the first buffer's capacity go in more buffers of the same size.
+    vertex_chunks: Vec<wgpu::Buffer>,
+    chunk_capacity: u32,
     vertex_count: u32,
+    // Drawn instead of the vertex buffer when set, with its own pipeline for
+    // the external vertex layout.
+    external: Option<(ExternalPoints, Arc<wgpu::RenderPipeline>)>,
 
-    texture: (wgpu::Texture, wgpu::TextureView),
-    multisampled_texture: (wgpu::Texture, wgpu::TextureView),
-    width: u32,
-    height: u32,
+    target: RenderTarget,
+    // Both multiply the dimensions passed to prepare.
+    render_scale: f32,
+    pixels_per_point: f32,
+    // Below one while the texture would exceed the device's limits.
+    limit_scale: f32,
+    overview: Option<Overview>,
+    thumbnail: Option<Thumbnail>,
+    inset: Option<(Inset, InsetFrame)>,
+    #[cfg(feature = "heatmap")]
+    histogram: Option<HistogramBins>,
+    // Used for the points in the main viewport when coloring by density.
+    density_pipeline: Option<Arc<wgpu::RenderPipeline>>,
+    attributes: Option<AttributeColumns>,
+    // Used for the points in the main viewport when coloring or sizing by
+    // attributes, unless coloring by density.
+    attribute_pipeline: Option<Arc<wgpu::RenderPipeline>>,
+    color_by: Option<ColorBy>,
+    size_by: Option<SizeBy>,
+    // Created by the first GPU fit.
+    #[cfg(feature = "compute")]
+    fit_reducer: Option<FitReducer>,
+    thresholds: ThresholdLayer,
+    markers: MarkerLayer,
+    constellation: Option<Constellation>,
+    reference_vertices: Option<(wgpu::Buffer, u32)>,
+    smith_chart: Option<(SmithChart, wgpu::Buffer, u32)>,
+    // Only allocated while there are logic tracks.
+    logic: Option<LogicLayer>,
+    timeline: Option<TimelineLayer>,
+    #[cfg(feature = "compute")]
+    diff: Option<DiffLayer>,
+    #[cfg(feature = "compute")]
+    merge: Option<MergeLayer>,
+    #[cfg(feature = "compute")]
+    follow: Option<FollowLayer>,
+    time_cursor: Option<CursorLayer>,
+    // Sorted by z order, like the series.
+    passes: Vec<(PassId, i32, Box<dyn PlotPass>)>,
+    next_pass_id: u64,
+    // Only kept while a pass reads the previous frame.
+    frame_history: Option<FrameHistory>,
+    history: Option<HistoryRing>,
+    axis_breaks: [AxisBreaks; 2],
+    reversed: [bool; 2],
+    data_aspect: Option<f64>,
+    // Created by the first breach search.
+    #[cfg(feature = "compute")]
+    breach_finder: Option<BreachFinder>,
+    #[cfg(feature = "compute")]
+    crossing_finder: Option<CrossingFinder>,
+    #[cfg(feature = "compute")]
+    extrema_finder: Option<ExtremaFinder>,
+    #[cfg(feature = "compute")]
+    hit_finder: Option<HitFinder>,
+    image_readback: Option<ImageReadback>,
+    #[cfg(feature = "compute")]
+    stats_reducer: Option<StatsReducer>,
+    // The series searched by the pending hit test, and the series and
+    // region of the pending selection.
+    #[cfg(feature = "compute")]
+    pending_hit: Option<Vec<Option<SeriesId>>>,
+    #[cfg(feature = "compute")]
+    pending_selection: Option<(Region, Vec<Option<SeriesId>>)>,
+    // One per sample, looked up once results are read back.
+    point_ids: Option<Vec<u32>>,
+    #[cfg(feature = "compute")]
+    selection_callback: Option<Box<dyn FnMut(&SelectionEvent) + Send + Sync>>,
+    // Reduces the visible values of normalized series, separately from
+    // selections so that the two don't wait on each other. The series being
+    // reduced, and the x range they were last reduced over.
+    #[cfg(feature = "compute")]
+    normalization_reducer: Option<StatsReducer>,
+    #[cfg(feature = "compute")]
+    pending_normalization: Option<Vec<usize>>,
+    #[cfg(feature = "compute")]
+    normalized_x: Option<[f32; 2]>,
+    accumulate: bool,
+    // Only allocated while any series glows.
+    bloom: Option<Bloom>,
+    glow: Glow,
+    vision: Option<VisionFilter>,
+    cvd_safe_palette: bool,
+    theme: Theme,
+    waterfall: Option<Waterfall>,
+    // The y range of each series when the points were last uploaded, while
+    // the waterfall is normalized.
+    waterfall_ranges: Vec<Option<[f32; 2]>>,
+
+    subplots: Vec<(CellRect, Range<u32>)>,
+
+    validation_mode: ValidationMode,
+    validation_report: ValidationReport,
+    diagnostics: FrameDiagnostics,
+
+    budget: Option<BudgetController>,
+    vertex_budget: VertexBudget,
+    // Where the uploaded vertices came from, while they're decimated.
+    vertex_map: Option<VertexMap>,
+    debug_labels: bool,
+    // The vertices last taken from a shared state, and its generation then.
+    shared_points: Option<(u64, Vec<Vertex>)>,
+
+    // Set by changes made between frames, and folded into `needs_render` by
+    // the next prepare.
+    invalidated: bool,
+    needs_render: bool,
 }
 
+// Plots are stored in egui's paint callback resources, and shared state is
+// handed to producer threads, so both must stay thread safe.
+#[cfg(feature = "gpu")]
+const _: fn() = || {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<GpuAcceleratedPlot>();
+    assert_send_sync::<GpuPlotState>();
+};
+
+#[cfg(feature = "gpu")]
 impl GpuAcceleratedPlot {
     pub fn new(device: &wgpu::Device, target_format: wgpu::TextureFormat) -> GpuAcceleratedPlot {
-        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-            label: Some("egui_plot_line_shader"),
-            source: wgpu::ShaderSource::Wgsl(include_str!("./line_shader.wgsl").into()),
-        });
+        let context = Arc::new(GpuPlotContext::new(device));
+        Self::new_with_render_format(device, context, target_format, target_format)
+    }
 
-        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-            label: Some("egui_plot_bind_group_layout"),
-            entries: &[wgpu::BindGroupLayoutEntry {
-                binding: 0,
-                visibility: wgpu::ShaderStages::VERTEX,
-                ty: wgpu::BindingType::Buffer {
-                    ty: wgpu::BufferBindingType::Uniform,
-                    has_dynamic_offset: false,
-                    min_binding_size: None,
-                },
-                count: None,
-            }],
-        });
+    /// Create a plot which renders internally in `render_format` (e.g.
+    /// `Rgba16Float` for HDR colormaps or accumulation), converting to
+    /// `target_format` for display.
+    pub fn with_render_format(
+        device: &wgpu::Device,
+        target_format: wgpu::TextureFormat,
+        render_format: wgpu::TextureFormat,
+    ) -> Result<GpuAcceleratedPlot, FormatError> {
+        let context = Arc::new(GpuPlotContext::new(device));
+        Self::with_context(device, context, target_format, render_format)
+    }
 
-        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-            label: Some("egui_plot_pipeline_layout"),
-            bind_group_layouts: &[&bind_group_layout],
-            push_constant_ranges: &[],
-        });
+    /// Create a plot which shares its pipelines with every other plot using
+    /// the same context.
+    pub fn with_context(
+        device: &wgpu::Device,
+        context: Arc<GpuPlotContext>,
+        target_format: wgpu::TextureFormat,
+        render_format: wgpu::TextureFormat,
+    ) -> Result<GpuAcceleratedPlot, FormatError> {
+        validate_render_format(device, render_format)?;
+        if render_format != target_format {
+            // The output is written by the blit pass, so it needs to be
+            // renderable as well.
+            validate_render_format(device, target_format)?;
+        }
 
-        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("egui_plot_pipeline"),
-            layout: Some(&pipeline_layout),
-            vertex: wgpu::VertexState {
-                module: &shader,
-                entry_point: "vs_main",
-                buffers: &[wgpu::VertexBufferLayout {
-                    array_stride: std::mem::size_of::<Vertex>() as wgpu::BufferAddress,
-                    step_mode: wgpu::VertexStepMode::Vertex,
-                    attributes: &wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32x2, 2 => Float32x4],
-                }],
-            },
-            fragment: Some(wgpu::FragmentState {
-                module: &shader,
-                entry_point: "fs_main",
-                targets: &[Some(wgpu::ColorTargetState {
-                    format: target_format,
-                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
-                    write_mask: wgpu::ColorWrites::ALL,
-                })],
-            }),
-            primitive: wgpu::PrimitiveState {
-                topology: wgpu::PrimitiveTopology::TriangleStrip,
-                ..Default::default()
-            },
-            depth_stencil: None,
-            multisample: wgpu::MultisampleState {
-                count: MSAA_SAMPLE_COUNT,
-                ..Default::default()
-            },
-            multiview: None,
-        });
+        Ok(Self::new_with_render_format(
+            device,
+            context,
+            target_format,
+            render_format,
+        ))
+    }
 
-        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("egui_plot_uniforms"),
-            contents: bytemuck::cast_slice(&[Uniform {
-                x_bounds: [-1.0, 1.0],
-                y_bounds: [-1.0, 1.0],
-            }]),
-            usage: wgpu::BufferUsages::COPY_DST
-                | wgpu::BufferUsages::MAP_WRITE
-                | wgpu::BufferUsages::UNIFORM,
-        });
+    fn new_with_render_format(
+        device: &wgpu::Device,
+        context: Arc<GpuPlotContext>,
+        target_format: wgpu::TextureFormat,
+        render_format: wgpu::TextureFormat,
+    ) -> GpuAcceleratedPlot {
+        let capabilities = Capabilities::from_device(device, render_format);
+        let sample_count = capabilities.sample_count(MSAA_SAMPLE_COUNT);
+        let pipeline = context.pipeline(
+            device,
+            PipelineKey {
+                format: render_format,
+                sample_count,
+                vertex_layout: VertexLayout::default(),
+                instanced: false,
+                additive: false,
+                density: false,
+                attributes: false,
+            },
+        );
 
+        // An even number of vertices, so that samples aren't split between
+        // buffers.
+        let vertex_size = std::mem::size_of::<Vertex>() as u64;
+        let chunk_capacity =
+            (capabilities.max_buffer_size / vertex_size).clamp(4, MAX_POINTS as u64) as u32 & !1;
         let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("egui_plot_vertices"),
-            contents: bytemuck::cast_slice(&vec![Vertex::default(); MAX_POINTS]),
+            contents: bytemuck::cast_slice(&vec![Vertex::default(); chunk_capacity as usize]),
             usage: wgpu::BufferUsages::COPY_DST
+                | wgpu::BufferUsages::COPY_SRC
                 | wgpu::BufferUsages::MAP_WRITE
-                | wgpu::BufferUsages::VERTEX,
+                | wgpu::BufferUsages::VERTEX
+                | wgpu::BufferUsages::STORAGE,
         });
 
-        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("egui_plot_bind_group"),
-            layout: &bind_group_layout,
-            entries: &[wgpu::BindGroupEntry {
-                binding: 0,
-                resource: uniform_buffer.as_entire_binding(),
-            }],
-        });
+        let default_bounds = Uniform {
+            x_bounds: [-1.0, 1.0],
+            y_bounds: [-1.0, 1.0],
+            ..Default::default()
+        };
+        let mut uniform_data = vec![default_bounds; FIRST_SUBPLOT_SLOT];
+        uniform_data[INSET_FRAME_SLOT] = Uniform {
+            x_bounds: [0.0, 1.0],
+            y_bounds: [0.0, 1.0],
+            ..Default::default()
+        };
+        let uniforms = UniformSlots::new(device, context.bind_group_layout(), FIRST_SUBPLOT_SLOT);
+        let series_uniforms = UniformSlots::new(device, context.series_bind_group_layout(), 1);
 
         // Allocate some stand-in textures since we don't know the final width
         // and height yet.
-        let texture = Self::create_texture(device, target_format, 1, DEFAULT_WIDTH, DEFAULT_HEIGHT);
-        let multisampled_texture = Self::create_texture(
+        let target = RenderTarget::new(
             device,
-            target_format,
-            MSAA_SAMPLE_COUNT,
+            &context.blitter,
+            TargetConfig {
+                render_format,
+                output_format: target_format,
+                view_format: target_format,
+                sample_count,
+                mipmaps: false,
+                deterministic: false,
+            },
             DEFAULT_WIDTH,
             DEFAULT_HEIGHT,
         );
 
         GpuAcceleratedPlot {
+            context,
+            capabilities,
             pipeline,
-            target_format,
-            bind_group,
-            uniform_buffer,
+            uniforms,
+            uniform_data,
+            written_uniform_data: Vec::new(),
+            series: Vec::new(),
+            styles: Vec::new(),
+            groups: Groups::default(),
+            draw_order: Vec::new(),
+            series_uniforms,
+            written_series_data: Vec::new(),
             vertex_buffer,
+            vertex_chunks: Vec::new(),
+            chunk_capacity,
             vertex_count: 0,
-            texture,
-            multisampled_texture,
-            width: DEFAULT_WIDTH,
-            height: DEFAULT_HEIGHT,
+            external: None,
+            target,
+            render_scale: 1.0,
+            pixels_per_point: 1.0,
+            limit_scale: 1.0,
+            overview: None,
+            thumbnail: None,
+            inset: None,
+            #[cfg(feature = "heatmap")]
+            histogram: None,
+            density_pipeline: None,
+            attributes: None,
+            attribute_pipeline: None,
+            color_by: None,
+            size_by: None,
+            #[cfg(feature = "compute")]
+            fit_reducer: None,
+            thresholds: ThresholdLayer::new(),
+            markers: MarkerLayer::new(),
+            constellation: None,
+            reference_vertices: None,
+            smith_chart: None,
+            logic: None,
+            timeline: None,
+            #[cfg(feature = "compute")]
+            diff: None,
+            #[cfg(feature = "compute")]
+            merge: None,
+            #[cfg(feature = "compute")]
+            follow: None,
+            time_cursor: None,
+            passes: Vec::new(),
+            next_pass_id: 0,
+            frame_history: None,
+            history: None,
+            axis_breaks: Default::default(),
+            reversed: [false; 2],
+            data_aspect: None,
+            #[cfg(feature = "compute")]
+            breach_finder: None,
+            #[cfg(feature = "compute")]
+            crossing_finder: None,
+            #[cfg(feature = "compute")]
+            extrema_finder: None,
+            #[cfg(feature = "compute")]
+            hit_finder: None,
+            image_readback: None,
+            #[cfg(feature = "compute")]
+            stats_reducer: None,
+            #[cfg(feature = "compute")]
+            pending_hit: None,
+            #[cfg(feature = "compute")]
+            pending_selection: None,
+            point_ids: None,
+            #[cfg(feature = "compute")]
+            selection_callback: None,
+            #[cfg(feature = "compute")]
+            normalization_reducer: None,
+            #[cfg(feature = "compute")]
+            pending_normalization: None,
+            #[cfg(feature = "compute")]
+            normalized_x: None,
+            accumulate: false,
+            bloom: None,
+            glow: Glow::default(),
+            vision: None,
+            cvd_safe_palette: false,
+            theme: Theme::default(),
+            waterfall: None,
+            waterfall_ranges: Vec::new(),
+            subplots: Vec::new(),
+            validation_mode: ValidationMode::Off,
+            validation_report: ValidationReport::default(),
+            diagnostics: FrameDiagnostics::default(),
+            budget: None,
+            vertex_budget: VertexBudget::default(),
+            vertex_map: None,
+            debug_labels: false,
+            shared_points: None,
+            invalidated: true,
+            needs_render: true,
         }
     }
 
-    fn create_texture(
-        device: &wgpu::Device,
-        target_format: wgpu::TextureFormat,
-        sample_count: u32,
-        width: u32,
-        height: u32,
-    ) -> (wgpu::Texture, wgpu::TextureView) {
-        let texture = device.create_texture(&wgpu::TextureDescriptor {
-            label: Some("egui_plot_texture"),
-            size: wgpu::Extent3d {
-                width,
-                height,
-                depth_or_array_layers: 1,
-            },
-            mip_level_count: 1,
-            sample_count,
-            dimension: wgpu::TextureDimension::D2,
-            format: target_format,
-            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::RENDER_ATTACHMENT,
-        });
+    pub fn set_validation_mode(&mut self, mode: ValidationMode) {
+        self.validation_mode = mode;
+    }
 
-        let view = texture.create_view(&TextureViewDescriptor::default());
+    /// Problems found in the most recently uploaded vertices. Always empty
+    /// when validation is off.
+    pub fn validation_report(&self) -> &ValidationReport {
+        &self.validation_report
+    }
 
-        (texture, view)
+    /// Points dropped from the most recent upload, and what the last
+    /// [`GpuAcceleratedPlot::prepare`] had to work around, e.g. to surface
+    /// in a debug overlay.
+    pub fn diagnostics(&self) -> &FrameDiagnostics {
+        &self.diagnostics
     }
 
-    pub fn create_view(&self) -> wgpu::TextureView {
-        self.texture
-            .0
-            .create_view(&wgpu::TextureViewDescriptor::default())
+    pub fn context(&self) -> &Arc<GpuPlotContext> {
+        &self.context
     }
 
-    fn create_multisampled_view(&self) -> wgpu::TextureView {
-        self.multisampled_texture
-            .0
-            .create_view(&wgpu::TextureViewDescriptor::default())
+    /// Automatically decimate the data drawn by [`GpuAcceleratedPlot::prepare`]
+    /// while frames take longer than the budget, e.g. on integrated GPUs or
+    /// laptops on battery. `None` always draws every point.
+    pub fn set_render_budget(&mut self, budget: Option<RenderBudget>) {
+        self.budget = budget.map(BudgetController::new);
+        self.invalidated = true;
     }
 
-    pub fn prepare(
+    /// The factor the data is currently decimated by to meet the render
+    /// budget, where 1 means that every point is drawn.
+    pub fn decimation(&self) -> u32 {
+        match &self.budget {
+            Some(budget) if !self.deterministic() => budget.decimation(),
+            _ => 1,
+        }
+    }
+
+    /// Cap the vertices uploaded, decimating the points of each series to
+    /// fit (see [`VertexBudget`]) instead of dropping those past the
+    /// capacity. Applies from the next upload of dirty points; the default
+    /// holds five million vertices.
+    pub fn set_vertex_budget(&mut self, budget: VertexBudget) {
+        self.vertex_budget = budget;
+    }
+
+    pub fn vertex_budget(&self) -> VertexBudget {
+        self.vertex_budget
+    }
+
+    /// Render the same pixels for the same input on every run on the same
+    /// adapter, e.g. for regression tests which diff exported images against
+    /// golden ones. While enabled, the render budget is ignored, progressive
+    /// histograms are counted in full on the first frame, and format
+    /// conversion and mipmap generation load texels exactly (averaging
+    /// 2x2 blocks in a fixed order and rounding to the output's steps)
+    /// rather than going through the sampler's filtering.
+    pub fn set_deterministic(&mut self, device: &wgpu::Device, deterministic: bool) {
+        let config = TargetConfig {
+            deterministic,
+            ..self.target.config()
+        };
+        if !self
+            .target
+            .reconfigure(device, &self.context.blitter, config)
+        {
+            return;
+        }
+        if let Some(overview) = &mut self.overview {
+            overview.reconfigure(device, &self.context.blitter, config);
+        }
+        #[cfg(feature = "heatmap")]
+        if let Some(histogram) = self.histogram.as_ref().map(|bins| bins.histogram().clone()) {
+            self.update_histogram(device, Some(histogram));
+        }
+        self.invalidated = true;
+    }
+
+    pub fn deterministic(&self) -> bool {
+        self.target.config().deterministic
+    }
+
+    /// Wrap the plot's GPU work in debug groups: its compute, layers,
+    /// overlays and inset, and each series' draws under the series' name,
+    /// so that they can be told apart in a frame capture or API trace (see
+    /// [`GpuAcceleratedPlot::render_captured`] and [`api_trace_path`]).
+    pub fn set_debug_labels(&mut self, enabled: bool) {
+        self.debug_labels = enabled;
+    }
+
+    pub fn debug_labels(&self) -> bool {
+        self.debug_labels
+    }
+
+    pub fn capabilities(&self) -> &Capabilities {
+        &self.capabilities
+    }
+
+    /// Replace the capabilities detected from the device, e.g. with ones from
+    /// [`Capabilities::probe`] when the adapter is available. Settings which
+    /// are no longer supported are degraded accordingly.
+    pub fn set_capabilities(&mut self, device: &wgpu::Device, capabilities: Capabilities) {
+        self.capabilities = capabilities;
+        self.set_sample_count(device, self.target.config().sample_count);
+    }
+
+    /// Request a number of MSAA samples for line rendering. Returns the
+    /// sample count actually used, which falls back to 1 if the device
+    /// doesn't support multisampling the render format.
+    pub fn set_sample_count(&mut self, device: &wgpu::Device, requested: u32) -> u32 {
+        let config = TargetConfig {
+            sample_count: self.capabilities.sample_count(requested),
+            ..self.target.config()
+        };
+
+        self.invalidated |= self
+            .target
+            .reconfigure(device, &self.context.blitter, config);
+        if let Some(overview) = &mut self.overview {
+            overview.reconfigure(device, &self.context.blitter, config);
+        }
+        self.update_pipelines(device);
+
+        config.sample_count
+    }
+
+    /// Accumulate density by adding overlapping lines together instead of
+    /// alpha blending them, e.g. for eye diagrams of folded series. Use a
+    /// float render format (see [`GpuAcceleratedPlot::with_render_format`])
+    /// so that the sums don't saturate.
+    pub fn set_accumulate(&mut self, device: &wgpu::Device, accumulate: bool) {
+        if accumulate != self.accumulate {
+            self.accumulate = accumulate;
+            self.update_pipelines(device);
+            self.invalidated = true;
+        }
+    }
+
+    fn pipeline_key(&self, vertex_layout: VertexLayout, instanced: bool) -> PipelineKey {
+        let config = self.target.config();
+        PipelineKey {
+            format: config.render_format,
+            sample_count: config.sample_count,
+            vertex_layout,
+            instanced,
+            additive: self.accumulate,
+            density: false,
+            attributes: false,
+        }
+    }
+
+    // Follow changes to the target, blend mode or vertex layout.
+    fn update_pipelines(&mut self, device: &wgpu::Device) {
+        self.pipeline = self
+            .context
+            .pipeline(device, self.pipeline_key(VertexLayout::default(), false));
+        if self.external.is_some() {
+            let pipeline = self
+                .context
+                .pipeline(device, self.pipeline_key(self.vertex_layout(), false));
+            if let Some((_, external)) = &mut self.external {
+                *external = pipeline;
+            }
+        }
+
+        if self.series.iter().any(|s| s.instances.is_some()) {
+            let pipeline = self.instanced_pipeline(device);
+            for instances in self.series.iter_mut().filter_map(|s| s.instances.as_mut()) {
+                instances.pipeline = Arc::clone(&pipeline);
+            }
+        }
+
+        #[cfg(feature = "heatmap")]
+        {
+            let histogram = self.histogram.take().map(|bins| bins.histogram().clone());
+            self.update_histogram(device, histogram);
+        }
+        self.update_attribute_pipeline(device);
+
+        if let Some(logic) = &mut self.logic {
+            let config = self.target.config();
+            logic.pipeline =
+                self.context
+                    .logic
+                    .pipeline(device, config.render_format, config.sample_count);
+        }
+        if let Some(timeline) = &mut self.timeline {
+            let config = self.target.config();
+            timeline.pipeline =
+                self.context
+                    .timeline
+                    .pipeline(device, config.render_format, config.sample_count);
+        }
+        #[cfg(feature = "compute")]
+        if let Some(merge) = &mut self.merge {
+            let config = self.target.config();
+            merge.set_target(device, config.render_format, config.sample_count);
+        }
+        if let Some(cursor) = &mut self.time_cursor {
+            let config = self.target.config();
+            cursor.set_target(device, config.render_format, config.sample_count);
+        }
+    }
+
+    pub fn render_format(&self) -> wgpu::TextureFormat {
+        self.target.config().render_format
+    }
+
+    pub fn target_format(&self) -> wgpu::TextureFormat {
+        self.target.config().output_format
+    }
+
+    /// Sample the plot texture through the sRGB counterpart of the target
+    /// format (or the linear one, for an sRGB target format), e.g. so that
+    /// egui, which expects native textures to sample as linear, shows
+    /// lines as bright as identical CPU-drawn egui_plot lines when the
+    /// texture holds sRGB-encoded colors in a linear format.
+    ///
+    /// wgpu 0.13 can't view a texture as another format (`view_formats`),
+    /// so the texture is created in the view format, and the pass converting
+    /// it from the render format stores the pixels the target format would
+    /// have held, costing that pass when the formats would otherwise match.
+    /// Views created afterwards, e.g. by
+    /// [`GpuAcceleratedPlot::create_view`], are in the view format. Fails
+    /// with [`FormatError::NoSrgbView`] for formats other than 8 bit RGBA
+    /// and BGRA.
+    pub fn set_srgb_view(
         &mut self,
         device: &wgpu::Device,
-        queue: &wgpu::Queue,
-        dimensions: [u32; 2],
-        bounds: &PlotBounds,
-        points: &[Vertex],
-        dirty: bool,
-    ) {
-        // Re-allocate the render targets if the requested dimensions have changed.
-        if dimensions[0] != self.width || dimensions[1] != self.height {
-            self.width = dimensions[0];
-            self.height = dimensions[1];
-
-            self.texture =
-                Self::create_texture(device, self.target_format, 1, self.width, self.height);
-            self.multisampled_texture = Self::create_texture(
-                device,
-                self.target_format,
-                MSAA_SAMPLE_COUNT,
-                self.width,
-                self.height,
-            );
+        enabled: bool,
+    ) -> Result<(), FormatError> {
+        let output_format = self.target.config().output_format;
+        let view_format = if enabled {
+            srgb_counterpart(output_format).ok_or(FormatError::NoSrgbView(output_format))?
+        } else {
+            output_format
+        };
+
+        let config = TargetConfig {
+            view_format,
+            ..self.target.config()
+        };
+        if !self
+            .target
+            .reconfigure(device, &self.context.blitter, config)
+        {
+            return Ok(());
         }
+        if let Some(overview) = &mut self.overview {
+            overview.reconfigure(device, &self.context.blitter, config);
+        }
+        if let Some(size) = self.thumbnail.as_ref().map(Thumbnail::size) {
+            self.set_thumbnail(device, Some(size));
+        }
+        self.invalidated = true;
+        Ok(())
+    }
 
-        queue.write_buffer(
-            &self.uniform_buffer,
-            0,
-            bytemuck::cast_slice(&[Uniform {
-                x_bounds: [bounds.min()[0] as f32, bounds.max()[0] as f32],
-                y_bounds: [bounds.min()[1] as f32, bounds.max()[1] as f32],
-            }]),
+    /// The format the plot texture is sampled as, see
+    /// [`GpuAcceleratedPlot::set_srgb_view`].
+    pub fn view_format(&self) -> wgpu::TextureFormat {
+        self.target.config().view_format
+    }
+
+    /// Render the plot texture at `scale` times the size it's displayed at,
+    /// e.g. 2 to supersample static plots for extra-crisp lines, or 0.5 to
+    /// spare a weak GPU. The texture is sampled back to the widget's rect
+    /// when displayed. Clamped to 0.25..=4.
+    ///
+    /// Pixel sizes, e.g. of point merge cells and insets, are in the scaled
+    /// texture's pixels.
+    pub fn set_render_scale(&mut self, scale: f32) {
+        self.render_scale = scale.clamp(0.25, 4.0);
+    }
+
+    pub fn render_scale(&self) -> f32 {
+        self.render_scale
+    }
+
+    /// The display's scale factor, e.g. egui's `Context::pixels_per_point`,
+    /// so that the dimensions passed to [`GpuAcceleratedPlot::prepare`] (and
+    /// positions passed to [`GpuAcceleratedPlot::hit_test`]) can be in
+    /// logical points while the texture is rendered in physical pixels. Set
+    /// it every frame where windows can move between monitors with different
+    /// scale factors.
+    pub fn set_pixels_per_point(&mut self, pixels_per_point: f32) {
+        if pixels_per_point.is_finite() && pixels_per_point > 0.0 {
+            self.pixels_per_point = pixels_per_point;
+        }
+    }
+
+    pub fn pixels_per_point(&self) -> f32 {
+        self.pixels_per_point
+    }
+
+    /// Texture pixels per point of the widget the plot is shown in, with
+    /// both the display's scale factor and the render scale applied, and
+    /// lowered if the texture would otherwise be too large for the device.
+    pub fn texture_scale(&self) -> f32 {
+        self.pixels_per_point * self.render_scale * self.limit_scale
+    }
+
+    /// The transform from data to the plot's pixels used by the last
+    /// prepare, including axis breaks, reversal and the data aspect, in the
+    /// units of the dimensions passed to prepare (see
+    /// [`GpuAcceleratedPlot::set_pixels_per_point`]). Apply a series' value
+    /// mapping with [`PlotTransform::with_value_mapping`].
+    pub fn transform(&self) -> PlotTransform {
+        let uniform = &self.uniform_data[MAIN_SLOT];
+        let bounds = Bounds::new(
+            uniform.x_bounds.map(f64::from),
+            uniform.y_bounds.map(f64::from),
         );
+        let size = self.target.size().map(|n| n as f32 / self.texture_scale());
+        PlotTransform::new(bounds, size)
+            .with_breaks(self.axis_breaks[0].clone(), self.axis_breaks[1].clone())
+    }
 
-        // Only re-upload the vertex buffer if it has changed.
-        // TODO: for time-series charts where the buffer acts as a ring, we
-        // could be smart about updating only the subset of added/removed
-        // vertices.
-        if dirty {
-            self.vertex_count = points.len() as u32;
-            queue.write_buffer(&self.vertex_buffer, 0, bytemuck::cast_slice(points));
+    /// The size of the plot texture, in pixels, e.g. the width to pass to
+    /// [`pick_column`] so that columns match the rendered pixels.
+    pub fn texture_size(&self) -> [u32; 2] {
+        self.target.size()
+    }
+
+    // The size of the plot texture for a widget of `dimensions` points.
+    fn scaled(&self, dimensions: [u32; 2]) -> [u32; 2] {
+        dimensions.map(|n| ((n as f32 * self.texture_scale()).round() as u32).max(1))
+    }
+
+    /// Generate a full mip chain for the plot texture after each render, for
+    /// when the plot is displayed smaller than it is rendered (thumbnails,
+    /// overview panes).
+    ///
+    /// The levels are only blended if the texture is sampled with a linear
+    /// mipmap filter, which egui-wgpu's `register_native_texture` doesn't
+    /// use: register it with [`GpuAcceleratedPlot::register_texture`], or
+    /// sample it with [`GpuAcceleratedPlot::sampler_descriptor`].
+    pub fn set_mipmaps(&mut self, device: &wgpu::Device, enabled: bool) {
+        let config = TargetConfig {
+            mipmaps: enabled,
+            ..self.target.config()
+        };
+        self.invalidated |= self
+            .target
+            .reconfigure(device, &self.context.blitter, config);
+    }
+
+    pub fn mip_level_count(&self) -> u32 {
+        self.target.mip_level_count()
+    }
+
+    /// A sampler for displaying the plot texture, filtering trilinearly
+    /// across its mip chain when [mipmaps](GpuAcceleratedPlot::set_mipmaps)
+    /// are on.
+    pub fn sampler_descriptor(&self) -> wgpu::SamplerDescriptor<'static> {
+        wgpu::SamplerDescriptor {
+            label: Some("egui_plot_texture_sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
         }
     }
 
-    pub fn render(&self, device: &wgpu::Device, queue: &wgpu::Queue) {
-        let mut encoder =
-            device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
-        {
-            let view = self.create_view();
-            let msaa_view = self.create_multisampled_view();
-
-            // Render directly to the texture if no MSAA, or use the
-            // multisampled buffer and resolve to the texture if using MSAA.
-            let rpass_color_attachment = if MSAA_SAMPLE_COUNT == 1 {
-                wgpu::RenderPassColorAttachment {
-                    view: &view,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
-                        store: true,
-                    },
-                }
-            } else {
-                wgpu::RenderPassColorAttachment {
-                    view: &msaa_view,
-                    resolve_target: Some(&view),
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
-                        store: false,
-                    },
-                }
-            };
+    /// Enable a second, coarse viewport showing the full extent of the data,
+    /// e.g. for use with `overview_strip`. It shares the plot's pipeline and is
+    /// populated on the next upload of dirty data.
+    pub fn set_overview(&mut self, device: &wgpu::Device, enabled: bool) {
+        if !enabled {
+            self.overview = None;
+        } else if self.overview.is_none() {
+            self.overview = Some(Overview::new(
+                device,
+                &self.context.blitter,
+                self.target.config(),
+            ));
+        }
+    }
 
-            let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: None,
-                color_attachments: &[Some(rpass_color_attachment)],
-                depth_stencil_attachment: None,
-            });
+    /// Keep a downscaled copy of the plot of `size` pixels, e.g. 256×128,
+    /// refreshed whenever the data changes, for live previews in a browser of
+    /// many plots. Pass `None` to stop.
+    ///
+    /// The thumbnail is downsampled from the plot's mip chain, so this turns
+    /// mipmaps on. It follows the plot's current bounds, and is available
+    /// from [`GpuAcceleratedPlot::create_thumbnail_view`] after the next
+    /// render.
+    pub fn set_thumbnail(&mut self, device: &wgpu::Device, size: Option<[u32; 2]>) {
+        let format = self.target.config().view_format;
+        self.thumbnail = size.map(|size| match self.thumbnail.take() {
+            Some(thumbnail) if thumbnail.size() == size && thumbnail.format() == format => {
+                thumbnail
+            }
+            _ => Thumbnail::new(device, &self.context.blitter, format, size),
+        });
+        if self.thumbnail.is_some() {
+            self.set_mipmaps(device, true);
+            self.invalidated = true;
+        }
+    }
 
-            self.render_onto_renderpass(&mut rpass);
+    /// The thumbnail's texture, in the plot's view format, e.g. to
+    /// register with egui as a native texture. The view stays valid until the
+    /// thumbnail's size or the plot's view format changes.
+    pub fn create_thumbnail_view(&self) -> Option<wgpu::TextureView> {
+        self.thumbnail.as_ref().map(Thumbnail::create_view)
+    }
+
+    pub fn create_overview_view(&self) -> Option<wgpu::TextureView> {
+        self.overview.as_ref().map(Overview::create_view)
+    }
+
+    /// The bounds of the main viewport in data units, from the last
+    /// [`GpuAcceleratedPlot::prepare`] and any [`GpuAcceleratedPlot::pan`]
+    /// since.
+    pub fn view_bounds(&self) -> Bounds {
+        let (x, y) = self.uniform_data[MAIN_SLOT].data_bounds();
+        Bounds::new(x.map(f64::from), y.map(f64::from))
+    }
+
+    /// Shift the main viewport by a fraction of its size along each axis,
+    /// in screen direction (towards the right and top, whether or not the
+    /// axis is reversed), e.g. by a drag's delta over the plot's size,
+    /// writing only the bounds of the axes that moved. Nothing is prepared
+    /// again: the points, series styles and layers are kept as they are
+    /// (layers binned or laid out for the old bounds, like histograms, catch
+    /// up on the next prepare), so pass [`GpuAcceleratedPlot::view_bounds`]
+    /// to it once panning ends.
+    pub fn pan(&mut self, queue: &wgpu::Queue, delta: [f32; 2]) {
+        let uniform = &mut self.uniform_data[MAIN_SLOT];
+        for (bounds, delta) in [&mut uniform.x_bounds, &mut uniform.y_bounds]
+            .into_iter()
+            .zip(delta)
+        {
+            // The span is negative along reversed axes, so that the view
+            // moves the same way on screen either way.
+            let shift = delta * (bounds[1] - bounds[0]);
+            *bounds = bounds.map(|b| b + shift);
         }
 
-        queue.submit(iter::once(encoder.finish()));
+        // Otherwise they're written in full by the next prepare.
+        if self.uniforms.write_changed(
+            queue,
+            &self.uniform_data,
+            &self.written_uniform_data,
+            &Uniform::FIELDS,
+        ) {
+            self.written_uniform_data.clone_from(&self.uniform_data);
+        }
+        self.invalidated = true;
+        self.needs_render = true;
     }
 
-    pub fn render_onto_renderpass<'rp>(&'rp self, rpass: &mut wgpu::RenderPass<'rp>) {
-        rpass.set_pipeline(&self.pipeline);
-        rpass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
-        rpass.set_bind_group(0, &self.bind_group, &[]);
-        rpass.draw(0..self.vertex_count, 0..1);
+    /// The extent of the most recently uploaded data. Only tracked while the
+    /// overview is enabled.
+    pub fn data_bounds(&self) -> Option<Bounds> {
+        self.overview.as_ref().and_then(Overview::data_bounds)
     }
-}
 
-pub fn egui_wgpu_callback(
-    bounds: PlotBounds,
-    points: Arc<Vec<Vertex>>,
-    rect: egui::Rect,
-    dirty: bool,
-) -> egui::PaintCallback {
-    let cb =
-        egui_wgpu::CallbackFn::new().prepare(move |device, queue, paint_callback_resources| {
-            let plot: &mut GpuAcceleratedPlot = paint_callback_resources.get_mut().unwrap();
-
-            plot.prepare(
-                device,
-                queue,
-                [rect.width() as u32, rect.height() as u32],
-                &bounds,
-                &points,
-                dirty,
+    pub fn prepare_overview(&mut self, device: &wgpu::Device, dimensions: [u32; 2]) {
+        // The overview bounds only change with the data, so they have
+        // already been written along with the main plot's uniforms.
+        let dimensions = self.scaled(dimensions);
+        if let Some(overview) = &mut self.overview {
+            overview.prepare(device, &self.context.blitter, dimensions);
+        }
+    }
+
+    pub fn render_overview(&self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        if let Some(overview) = self.overview.as_ref().filter(|o| o.needs_render()) {
+            profile_scope!("egui_plot::render_overview");
+            let mut encoder =
+                device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+            overview.render(
+                &mut encoder,
+                &self.pipeline,
+                (
+                    self.uniforms.bind_group(),
+                    self.uniforms.offset(OVERVIEW_SLOT),
+                ),
+                (
+                    self.series_uniforms.bind_group(),
+                    self.series_uniforms.offset(DEFAULT_SERIES_SLOT),
+                ),
+                &self.context.blitter,
             );
+            queue.submit(iter::once(encoder.finish()));
+        }
+    }
+
+    pub fn create_view(&self) -> wgpu::TextureView {
+        self.target.create_view()
+    }
+
+    /// Add a series drawing a range of the plot's vertices with its own
+    /// style. Once any series exist, vertices outside of all of them are no
+    /// longer drawn.
+    pub fn add_series(&mut self, vertices: Range<u32>) -> SeriesId {
+        self.series.push(Series {
+            vertices,
+            style: SeriesStyle::default(),
+            shared: None,
+            name: None,
+            group: GroupState::default(),
+            normalization: None,
+            reference: None,
+            z_order: Layer::Lines.into(),
+            instances: None,
+        });
+        self.sort_series();
+        SeriesId(self.series.len() - 1)
+    }
+
+    /// Draw a series above every series with a lower z order. Series with
+    /// equal z orders keep the order they were added in.
+    pub fn set_z_order(&mut self, id: SeriesId, z_order: impl Into<i32>) {
+        if let Some(series) = self.series.get_mut(id.0) {
+            series.z_order = z_order.into();
+            self.sort_series();
+        }
+    }
+
+    fn sort_series(&mut self) {
+        let series = &self.series;
+        self.draw_order = (0..series.len()).collect();
+        self.draw_order.sort_by_key(|&i| series[i].z_order);
+        self.invalidated = true;
+    }
+
+    pub fn series_vertices(&self, id: SeriesId) -> Option<Range<u32>> {
+        self.series.get(id.0).map(|series| series.vertices.clone())
+    }
+
+    pub fn set_series_vertices(&mut self, id: SeriesId, vertices: Range<u32>) {
+        if let Some(series) = self.series.get_mut(id.0) {
+            series.vertices = vertices;
+            self.invalidated = true;
+        }
+    }
+
+    /// Name a series, e.g. `sensor/3/temp`, so that it can be grouped with
+    /// [`GpuAcceleratedPlot::group_matching`].
+    pub fn set_series_name(&mut self, id: SeriesId, name: impl Into<String>) {
+        if let Some(series) = self.series.get_mut(id.0) {
+            series.name = Some(name.into());
+        }
+    }
+
+    pub fn series_name(&self, id: SeriesId) -> Option<&str> {
+        self.series.get(id.0)?.name.as_deref()
+    }
+
+    pub fn series_count(&self) -> usize {
+        self.series.len()
+    }
+
+    /// Show or hide a single series, as [`GpuAcceleratedPlot::set_group_visible`]
+    /// does for groups.
+    pub fn set_series_visible(&mut self, id: SeriesId, visible: bool) {
+        if let Some(series) = self.series.get_mut(id.0) {
+            series.group.visible = visible;
+            self.invalidated = true;
+        }
+    }
+
+    pub fn series_visible(&self, id: SeriesId) -> bool {
+        self.series.get(id.0).map_or(false, |s| s.group.visible)
+    }
+
+    /// Group series to show, hide, fade, stack or remove together.
+    pub fn add_group(&mut self, members: &[SeriesId]) -> GroupId {
+        let members = members
+            .iter()
+            .copied()
+            .filter(|id| id.0 < self.series.len())
+            .collect();
+        self.groups.add(members)
+    }
+
+    /// Group every series whose name matches any of `patterns` (see
+    /// [`matches_pattern`]), in the order they were added. Series named
+    /// later aren't added to the group.
+    pub fn group_matching(&mut self, patterns: &[&str]) -> GroupId {
+        let members = self
+            .series
+            .iter()
+            .enumerate()
+            .filter(|(_, series)| {
+                let name = series.name.as_deref();
+                name.map_or(false, |name| {
+                    patterns
+                        .iter()
+                        .any(|pattern| matches_pattern(pattern, name))
+                })
+            })
+            .map(|(i, _)| SeriesId(i))
+            .collect();
+        self.groups.add(members)
+    }
 
-            plot.render(device, queue);
+    pub fn group_members(&self, group: GroupId) -> &[SeriesId] {
+        self.groups.members(group)
+    }
+
+    /// Show or hide every series in a group. Hidden series aren't drawn,
+    /// hit tested or selected. For series in several groups, the last
+    /// change wins.
+    pub fn set_group_visible(&mut self, group: GroupId, visible: bool) {
+        self.update_group(group, |state, _| state.visible = visible);
+    }
+
+    /// Multiply the opacity of every series in a group, on top of their own
+    /// or shared styles.
+    pub fn set_group_alpha(&mut self, group: GroupId, alpha: f32) {
+        let alpha = alpha.clamp(0.0, 1.0);
+        self.update_group(group, |state, _| state.alpha = alpha);
+    }
+
+    /// Offset each series in a group by `step` more than the one before it
+    /// (in plotted y units, after its value mapping), e.g. for a waterfall
+    /// of channels. A step of zero removes the offsets.
+    pub fn stack_group(&mut self, group: GroupId, step: f32) {
+        self.update_group(group, |state, position| {
+            state.offset = step * position as f32;
         });
+    }
+
+    /// Remove every series in a group (and the group's members). The ids of
+    /// other series stay valid; the removed ones draw nothing from then on
+    /// and must not be used again.
+    pub fn remove_group(&mut self, group: GroupId) {
+        let removed = self.groups.members(group).to_vec();
+        for id in &removed {
+            if let Some(series) = self.series.get_mut(id.0) {
+                series.vertices = 0..0;
+                series.instances = None;
+                series.group.visible = false;
+            }
+        }
+        self.groups.forget(&removed);
+        self.invalidated = true;
+    }
+
+    fn update_group(&mut self, group: GroupId, mut update: impl FnMut(&mut GroupState, usize)) {
+        for (position, id) in self.groups.members(group).iter().enumerate() {
+            if let Some(series) = self.series.get_mut(id.0) {
+                update(&mut series.group, position);
+            }
+        }
+        self.invalidated = true;
+    }
+
+    /// Remove every series, going back to drawing all vertices with the
+    /// default style. Previously returned ids must not be used again, and
+    /// neither must group ids.
+    pub fn clear_series(&mut self) {
+        self.series.clear();
+        self.groups.clear();
+        self.draw_order.clear();
+        self.invalidated = true;
+    }
+
+    /// Save `points`, as passed to [`GpuAcceleratedPlot::prepare`], and the
+    /// ranges of the plot's series to a [`PlotCache`] at `path`.
+    pub fn save_cache(
+        &self,
+        path: impl AsRef<std::path::Path>,
+        points: &[Vertex],
+    ) -> Result<(), CacheError> {
+        let series: Vec<_> = self.series.iter().map(|s| s.vertices.clone()).collect();
+        PlotCache::write(path, points, &series)
+    }
+
+    /// Load a [`PlotCache`] saved with [`GpuAcceleratedPlot::save_cache`],
+    /// replacing the plot's series with default styled ones over the saved
+    /// ranges, in order, and returning the points to pass to
+    /// [`GpuAcceleratedPlot::prepare`] (as dirty).
+    pub fn load_cache(
+        &mut self,
+        path: impl AsRef<std::path::Path>,
+    ) -> Result<Vec<Vertex>, CacheError> {
+        let cache = PlotCache::load(path)?;
+        self.clear_series();
+        for vertices in cache.series {
+            self.add_series(vertices);
+        }
+        Ok(cache.points)
+    }
 
-    egui::PaintCallback {
-        rect,
-        callback: Arc::new(cb),
+    /// Add a style that any number of series can share. Restyling them all
+    /// with [`GpuAcceleratedPlot::update_style`] only changes one uniform,
+    /// e.g. when switching the theme of a dashboard with hundreds of traces.
+    pub fn add_style(&mut self, style: SeriesStyle) -> StyleHandle {
+        self.styles.push(style);
+        self.invalidated = true;
+        StyleHandle(self.styles.len() - 1)
     }
+
+    pub fn update_style(&mut self, handle: StyleHandle, style: SeriesStyle) {
+        if let Some(shared) = self.styles.get_mut(handle.0) {
+            self.invalidated |= shared.glow != style.glow;
+            *shared = style;
+        }
+    }
+
+    pub fn style(&self, handle: StyleHandle) -> Option<&SeriesStyle> {
+        self.styles.get(handle.0)
+    }
+
+    /// Draw a series with a shared style, or with its own again with `None`.
+    /// The series' own style is kept (and can still be changed) while it's
+    /// unused. A shared [`GradientKey::Index`] gradient spans every series
+    /// sharing the style, from the lowest vertex to the highest.
+    pub fn set_series_style(&mut self, id: SeriesId, handle: Option<StyleHandle>) {
+        if let Some(series) = self.series.get_mut(id.0) {
+            series.shared = handle.filter(|handle| handle.0 < self.styles.len());
+            self.invalidated = true;
+        }
+    }
+
+    // The uniform slot of a series' style, relative to the first of its
+    // history snapshot.
+    fn style_slot(&self, index: usize) -> usize {
+        let series = &self.series[index];
+        match series.shared {
+            Some(handle) if series.uses_shared_slot() && self.waterfall.is_none() => {
+                1 + self.series.len() + handle.0
+            }
+            _ => 1 + index,
+        }
+    }
+
+    /// Change how a series' values are mapped to the y axis, e.g. to switch
+    /// units without uploading the data again.
+    pub fn set_value_mapping(&mut self, id: SeriesId, mapping: ValueMapping) {
+        if let Some(series) = self.series.get_mut(id.0) {
+            series.style.mapping = mapping;
+        }
+    }
+
+    /// Multiply the opacity of a series, e.g. to fade in new traces or dim
+    /// deselected ones, without touching its vertices.
+    pub fn set_alpha(&mut self, id: SeriesId, alpha: f32) {
+        if let Some(series) = self.series.get_mut(id.0) {
+            series.style.alpha = alpha.clamp(0.0, 1.0);
+        }
+    }
+
+    /// Color a series with a gradient computed in the shader instead of its
+    /// vertex colors, or go back to the vertex colors with `None`.
+    pub fn set_gradient(&mut self, id: SeriesId, gradient: Option<Gradient>) {
+        if let Some(series) = self.series.get_mut(id.0) {
+            series.style.gradient = gradient;
+        }
+    }
+
+    /// Normalize a series' values in the shader, or show them as mapped again
+    /// with `None`. Visible range normalizations are recomputed on the GPU
+    /// whenever the x bounds or the data change, and take effect once read
+    /// back, a frame or two later (see
+    /// [`GpuAcceleratedPlot::normalization_pending`]). They need the
+    /// `compute` feature and compute shaders; fixed ranges work anywhere.
+    pub fn set_normalization(
+        &mut self,
+        id: SeriesId,
+        normalization: Option<Normalization>,
+    ) -> Result<(), PlotError> {
+        let visible_range = normalization.map_or(false, Normalization::is_visible_range);
+        if visible_range && !(cfg!(feature = "compute") && self.capabilities.compute_shaders) {
+            return Err(PlotError::UnsupportedFeature("visible range normalization"));
+        }
+
+        if let Some(series) = self.series.get_mut(id.0) {
+            if series.normalization != normalization {
+                series.normalization = normalization;
+                series.reference = None;
+                self.invalidated = true;
+                #[cfg(feature = "compute")]
+                {
+                    self.normalized_x = None;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn normalization(&self, id: SeriesId) -> Option<Normalization> {
+        self.series.get(id.0)?.normalization
+    }
+
+    /// Whether visible ranges are being reduced for normalized series, in
+    /// which case keep repainting to pick up the result.
+    pub fn normalization_pending(&self) -> bool {
+        #[cfg(feature = "compute")]
+        {
+            self.pending_normalization.is_some()
+        }
+        #[cfg(not(feature = "compute"))]
+        {
+            false
+        }
+    }
+
+    // Pick up the last reduction of normalized series, and start another if
+    // the x bounds or the data have changed since.
+    #[cfg(feature = "compute")]
+    fn update_normalization(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        bounds: &Bounds,
+        dirty: bool,
+    ) {
+        if self.pending_normalization.is_some() {
+            device.poll(wgpu::Maintain::Poll);
+            let results = self
+                .normalization_reducer
+                .as_mut()
+                .and_then(StatsReducer::finish);
+            if let Some(results) = results {
+                let indices = self.pending_normalization.take().unwrap_or_default();
+                for (i, result) in indices.into_iter().zip(results) {
+                    // The series may have been cleared meanwhile.
+                    let series = match self.series.get_mut(i) {
+                        Some(series) => series,
+                        None => continue,
+                    };
+                    series.reference = match (series.normalization, result) {
+                        (Some(normalization), Some((stats, _))) if stats.count > 0 => {
+                            Some(normalization.reference(&stats))
+                        }
+                        _ => None,
+                    };
+                }
+                self.invalidated = true;
+            }
+        }
+
+        let x_breaks = &self.axis_breaks[0];
+        let x_range = bounds.x.map(|x| x_breaks.to_data(x) as f32);
+        let stale = dirty || self.normalized_x != Some(x_range);
+        if !stale || self.pending_normalization.is_some() || !self.capabilities.compute_shaders {
+            return;
+        }
+
+        let count = self.vertex_source().3;
+        let (indices, slots): (Vec<_>, Vec<_>) = (0..self.series.len())
+            .filter(|&i| {
+                let series = &self.series[i];
+                series.group.visible
+                    && series
+                        .normalization
+                        .map_or(false, Normalization::is_visible_range)
+            })
+            .map(|i| {
+                let series = &self.series[i];
+                let vertices = self.drawn_vertices(&series.vertices);
+                let vertices = vertices.start.min(count)..vertices.end.min(count);
+                let mapping = series.effective_style(&self.styles).mapping;
+                (i, StatsSlot { vertices, mapping })
+            })
+            .unzip();
+        if slots.is_empty() {
+            return;
+        }
+
+        let mut reducer = self
+            .normalization_reducer
+            .take()
+            .unwrap_or_else(|| StatsReducer::new(device));
+        let (points, layout, offset, _) = self.vertex_source();
+        let submitted = reducer.submit(
+            device,
+            queue,
+            points,
+            layout,
+            offset,
+            x_range,
+            [-f32::MAX, f32::MAX],
+            &slots,
+        );
+        self.normalization_reducer = Some(reducer);
+
+        if submitted {
+            self.pending_normalization = Some(indices);
+            self.normalized_x = Some(x_range);
+        }
+    }
+
+    /// Draw a series once per instance, each with its own transform and tint,
+    /// e.g. for overlaid acquisitions of the same waveform. All instances are
+    /// drawn with a single draw call. Pass no instances to draw the series
+    /// normally again.
+    pub fn set_instances(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        id: SeriesId,
+        instances: &[Instance],
+    ) {
+        let pipeline = self.instanced_pipeline(device);
+        let series = match self.series.get_mut(id.0) {
+            Some(series) => series,
+            None => return,
+        };
+        self.invalidated = true;
+
+        if instances.is_empty() {
+            series.instances = None;
+            return;
+        }
+
+        let count = instances.len() as u32;
+        match &mut series.instances {
+            // Reuse the buffer if the instances still fit.
+            Some(existing) if existing.capacity >= instances.len() => {
+                queue.write_buffer(&existing.buffer, 0, bytemuck::cast_slice(instances));
+                existing.count = count;
+            }
+            _ => {
+                let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("egui_plot_instances"),
+                    contents: bytemuck::cast_slice(instances),
+                    usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::VERTEX,
+                });
+                series.instances = Some(Instances {
+                    buffer,
+                    capacity: instances.len(),
+                    count,
+                    pipeline,
+                });
+            }
+        }
+    }
+
+    // The instanced variant of the pipeline for the current target and
+    // vertex layout.
+    fn instanced_pipeline(&self, device: &wgpu::Device) -> Arc<wgpu::RenderPipeline> {
+        self.context
+            .pipeline(device, self.pipeline_key(self.vertex_layout(), true))
+    }
+
+    fn vertex_layout(&self) -> VertexLayout {
+        self.external
+            .as_ref()
+            .map_or_else(VertexLayout::default, |(points, _)| points.layout)
+    }
+
+    /// Fold a series' x values modulo a period (after subtracting a phase) in
+    /// the shader, overlaying every period of the raw samples on `0..period`,
+    /// e.g. for eye diagrams or phase-folded light curves. Combine with
+    /// [`GpuAcceleratedPlot::set_accumulate`] to show density.
+    pub fn set_fold(&mut self, id: SeriesId, fold: Option<Fold>) {
+        if let Some(series) = self.series.get_mut(id.0) {
+            series.style.fold = fold;
+        }
+    }
+
+    /// Place a series' points in other coordinates in the shader, e.g. as
+    /// impedances on a [`SmithChart`]. The line is widened along the
+    /// vertices' normals as given, so for thick lines compute them from the
+    /// placed points.
+    pub fn set_coordinates(&mut self, id: SeriesId, coordinates: Coordinates) {
+        if let Some(series) = self.series.get_mut(id.0) {
+            series.style.coordinates = coordinates;
+        }
+    }
+
+    /// Fade a series out by vertex index in the shader, leaving only a trail
+    /// of its last points, e.g. for a [`PhaseSpace`]. Pass `None` to draw
+    /// all of it again.
+    pub fn set_trail(&mut self, id: SeriesId, trail: Option<Trail>) {
+        if let Some(series) = self.series.get_mut(id.0) {
+            series.style.trail = trail;
+        }
+    }
+
+    /// Make a series glow: it is also drawn into an intermediate target,
+    /// blurred, and added on top of the plot. Glow isn't applied to subplots.
+    pub fn set_glow(&mut self, id: SeriesId, glow: bool) {
+        if let Some(series) = self.series.get_mut(id.0) {
+            series.style.glow = glow;
+            self.invalidated = true;
+        }
+    }
+
+    /// Change how glowing series are blurred and blended.
+    pub fn set_glow_style(&mut self, glow: Glow) {
+        self.glow = glow;
+    }
+
+    /// Show the plot as it would look with a color vision deficiency, to
+    /// check that its colors can still be told apart. Pass `None` to go
+    /// back to normal rendering.
+    pub fn set_color_vision(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        vision: Option<ColorVision>,
+    ) {
+        match (&mut self.vision, vision) {
+            (Some(filter), Some(vision)) => self.invalidated |= filter.set_vision(queue, vision),
+            (None, None) => {}
+            (filter, vision) => {
+                *filter = vision.map(|vision| VisionFilter::new(device, vision));
+                self.invalidated = true;
+            }
+        }
+    }
+
+    pub fn color_vision(&self) -> Option<ColorVision> {
+        self.vision.as_ref().map(VisionFilter::vision)
+    }
+
+    /// Color each series (or all of the points, if there are no series)
+    /// from [`CVD_SAFE_PALETTE`] in the order they were added, instead of
+    /// by their vertex colors. Series with a gradient keep it.
+    pub fn set_cvd_safe_palette(&mut self, enabled: bool) {
+        self.cvd_safe_palette = enabled;
+    }
+
+    pub fn cvd_safe_palette(&self) -> bool {
+        self.cvd_safe_palette
+    }
+
+    /// Stack the visible series in rows of one axis, or go back to drawing
+    /// them over each other with `None`. The data isn't modified; only the
+    /// series' value mappings are adjusted in the shader. A normalized
+    /// waterfall measures each series when the points are next uploaded, so
+    /// pass them as dirty after enabling it.
+    pub fn set_waterfall(&mut self, waterfall: Option<Waterfall>) {
+        if waterfall != self.waterfall {
+            if !waterfall.map_or(false, |waterfall| waterfall.normalize) {
+                self.waterfall_ranges.clear();
+            }
+            self.waterfall = waterfall;
+            self.invalidated = true;
+        }
+    }
+
+    pub fn waterfall(&self) -> Option<Waterfall> {
+        self.waterfall
+    }
+
+    // Each series' style as drawn: its own or shared style, with its group's
+    // state and the waterfall applied.
+    fn drawn_styles(&self) -> Vec<SeriesStyle> {
+        let rows = self.series.iter().filter(|s| s.group.visible).count();
+        let mut row = 0;
+        self.series
+            .iter()
+            .enumerate()
+            .map(|(i, series)| {
+                let mut style = series.grouped_style(&self.styles);
+                if let Some(waterfall) = self.waterfall.filter(|_| series.group.visible) {
+                    let range = self.waterfall_ranges.get(i).copied().flatten();
+                    style.mapping = waterfall.apply(style.mapping, row, rows, range);
+                    row += 1;
+                }
+                style
+            })
+            .collect()
+    }
+
+    /// Restyle the whole plot, e.g. [`Theme::print`] for publication-ready
+    /// output. Series with a gradient or colored by the CVD-safe palette keep
+    /// their colors.
+    pub fn set_theme(&mut self, theme: Theme) {
+        if theme != self.theme {
+            self.theme = theme;
+            self.invalidated = true;
+        }
+    }
+
+    pub fn theme(&self) -> &Theme {
+        &self.theme
+    }
+
+    /// Show a picture-in-picture inset of the same data at different bounds
+    /// on top of the plot, e.g. a magnifier following the cursor. Insets
+    /// aren't drawn over subplots.
+    pub fn set_inset(&mut self, device: &wgpu::Device, inset: Option<Inset>) {
+        let previous = self.inset.take();
+        if previous.as_ref().map(|(inset, _)| inset) != inset.as_ref() {
+            self.invalidated = true;
+        }
+
+        self.inset = inset.map(|inset| {
+            self.uniform_data[INSET_SLOT] = Uniform::from(&inset.bounds);
+            let frame = match previous {
+                Some((_, frame)) if frame.matches(&inset) => frame,
+                _ => InsetFrame::new(device, &inset),
+            };
+            (inset, frame)
+        });
+    }
+
+    /// Bin the points into a 2D histogram on the GPU, showing density
+    /// structure that overplotting hides: either as a heatmap drawn in place
+    /// of the points, or by coloring each point by its bin's count. The bins
+    /// cover the bounds passed to [`GpuAcceleratedPlot::prepare`] and are
+    /// recounted by every [`GpuAcceleratedPlot::render`]. Subplots ignore
+    /// the histogram, and insets always draw the points.
+    ///
+    /// The points are binned as drawn, so leave the render budget off for
+    /// exact counts. External points need `BufferUsages::STORAGE`. Fails
+    /// with [`PlotError::UnsupportedFeature`], leaving the points drawn as
+    /// usual, if the device can't run compute shaders (or, for density
+    /// colors, read storage buffers in vertex shaders).
+    #[cfg(feature = "heatmap")]
+    pub fn set_histogram(
+        &mut self,
+        device: &wgpu::Device,
+        histogram: Option<Histogram>,
+    ) -> Result<(), PlotError> {
+        let supported = histogram.as_ref().map_or(true, |histogram| {
+            self.capabilities.compute_shaders
+                && (histogram.display != HistogramDisplay::DensityColors
+                    || self.capabilities.vertex_pulling)
+        });
+
+        self.update_histogram(device, histogram.filter(|_| supported));
+        self.invalidated = true;
+        supported_or(supported, "histograms")
+    }
+
+    /// How much of a progressive histogram (see
+    /// [`Histogram::with_progressive`]) has been counted, from zero to one,
+    /// or `None` without a histogram. Request another frame while it's
+    /// below one.
+    #[cfg(feature = "heatmap")]
+    pub fn refinement_progress(&self) -> Option<f32> {
+        self.histogram.as_ref().map(|bins| bins.progress())
+    }
+
+    #[cfg(feature = "heatmap")]
+    fn update_histogram(&mut self, device: &wgpu::Device, histogram: Option<Histogram>) {
+        let config = self.target.config();
+        let (points, ..) = self.vertex_source();
+        let bins = histogram.map(|histogram| {
+            let histogram = if config.deterministic {
+                Histogram {
+                    samples_per_frame: None,
+                    ..histogram
+                }
+            } else {
+                histogram
+            };
+            HistogramBins::new(
+                device,
+                &self.context.histogram,
+                histogram,
+                points,
+                config.render_format,
+                config.sample_count,
+            )
+        });
+
+        self.density_pipeline = bins
+            .as_ref()
+            .filter(|bins| bins.density_bind_group().is_some())
+            .map(|_| {
+                let key = PipelineKey {
+                    density: true,
+                    ..self.pipeline_key(self.vertex_layout(), false)
+                };
+                self.context.pipeline(device, key)
+            });
+        self.histogram = bins;
+    }
+
+    /// Upload extra per-point attributes (e.g. temperature, error) as rows of
+    /// `columns` values, one row per pair of vertices, which
+    /// [`GpuAcceleratedPlot::set_color_by`] and
+    /// [`GpuAcceleratedPlot::set_size_by`] can then switch between without
+    /// uploading anything again. Pass `None` to drop them.
+    ///
+    /// Only applies to the points in the main viewport, and not while
+    /// coloring by density. Fails with [`PlotError::UnsupportedFeature`] if
+    /// the device can't read storage buffers in vertex shaders.
+    pub fn set_attributes(
+        &mut self,
+        device: &wgpu::Device,
+        values: Option<&[f32]>,
+        columns: u32,
+    ) -> Result<(), PlotError> {
+        let supported = values.is_none() || self.capabilities.vertex_pulling;
+        self.attributes = values
+            .filter(|_| supported)
+            .map(|values| AttributeColumns::new(device, &self.context.attributes, values, columns));
+        self.update_attribute_pipeline(device);
+        self.invalidated = true;
+
+        if supported {
+            Ok(())
+        } else {
+            Err(PlotError::UnsupportedFeature("attribute columns"))
+        }
+    }
+
+    /// Color the points by one of their attribute columns, or by their own
+    /// colors again with `None`.
+    pub fn set_color_by(&mut self, color_by: Option<ColorBy>) {
+        self.color_by = color_by;
+    }
+
+    /// Scale the line width of the points by one of their attribute
+    /// columns, or not with `None`.
+    pub fn set_size_by(&mut self, size_by: Option<SizeBy>) {
+        self.size_by = size_by;
+    }
+
+    pub fn color_by(&self) -> Option<&ColorBy> {
+        self.color_by.as_ref()
+    }
+
+    pub fn size_by(&self) -> Option<&SizeBy> {
+        self.size_by.as_ref()
+    }
+
+    fn update_attribute_pipeline(&mut self, device: &wgpu::Device) {
+        self.attribute_pipeline = self.attributes.as_ref().map(|_| {
+            let key = PipelineKey {
+                attributes: true,
+                ..self.pipeline_key(self.vertex_layout(), false)
+            };
+            self.context.pipeline(device, key)
+        });
+    }
+
+    /// Cut ranges out of the x and y axes. Bounds are then given in axis
+    /// units, see [`AxisBreaks`], while points keep their data units.
+    ///
+    /// Breaks are applied to everything drawn as lines. Logic tracks,
+    /// timelines and histograms are drawn in axis units without them.
+    pub fn set_axis_breaks(&mut self, x: AxisBreaks, y: AxisBreaks) {
+        self.axis_breaks = [x, y];
+        self.invalidated = true;
+    }
+
+    pub fn axis_breaks(&self) -> &[AxisBreaks; 2] {
+        &self.axis_breaks
+    }
+
+    /// Reverse the direction of either axis in every viewport. Bounds may be
+    /// passed in either order, as with [`Bounds::with_reversed`], which
+    /// interactions such as `data_cursor` should be given to match.
+    pub fn set_reversed(&mut self, x: bool, y: bool) {
+        self.reversed = [x, y];
+        self.invalidated = true;
+    }
+
+    pub fn reversed(&self) -> [bool; 2] {
+        self.reversed
+    }
+
+    /// Lock the main viewport's aspect ratio, so that a unit of x is `ratio`
+    /// times as many pixels as a unit of y, e.g. 1.0 for trajectories and
+    /// maps. Bounds passed to [`GpuAcceleratedPlot::prepare`] are grown to
+    /// fit with [`Bounds::with_data_aspect`], which the host should also use
+    /// for pan, zoom and fitting (or egui's `Plot::data_aspect`) so that
+    /// interactions line up.
+    pub fn set_data_aspect(&mut self, ratio: Option<f64>) {
+        self.data_aspect = ratio;
+        self.invalidated = true;
+    }
+
+    pub fn data_aspect(&self) -> Option<f64> {
+        self.data_aspect
+    }
+
+    /// Keep the last few acquisitions, i.e. the points passed to
+    /// [`GpuAcceleratedPlot::prepare`] each time they were dirty, and draw
+    /// them fading out behind the live data. Pass `None` to drop them.
+    /// Only applies to uploaded points in the main viewport.
+    pub fn set_history(&mut self, history: Option<History>) {
+        self.history = history.map(HistoryRing::new);
+        self.invalidated = true;
+    }
+
+    /// Draw the difference or ratio of two series as a derived series,
+    /// computed on the GPU each time the plot is rendered, or stop with
+    /// `None`. Fails with [`PlotError::UnsupportedFeature`] if the device
+    /// can't run compute shaders, in which case nothing is drawn.
+    #[cfg(feature = "compute")]
+    pub fn set_trace_diff(
+        &mut self,
+        device: &wgpu::Device,
+        diff: Option<TraceDiff>,
+    ) -> Result<(), PlotError> {
+        let supported = diff.is_none() || self.capabilities.compute_shaders;
+        self.diff = diff
+            .filter(|_| supported)
+            .map(|diff| DiffLayer::new(device, diff));
+        self.invalidated = true;
+        supported_or(supported, "trace diffs")
+    }
+
+    /// Draw the points as scatter markers instead of lines, merging the
+    /// points that fall in the same pixel (or cell, see [`PointMerge`]) on
+    /// the GPU, so drawing costs as much as the plot has pixels however many
+    /// points it has. Like a histogram heatmap, the markers replace the
+    /// series, aren't drawn in subplots or insets, and ignore axis breaks.
+    ///
+    /// External points need `BufferUsages::STORAGE`. Fails with
+    /// [`PlotError::UnsupportedFeature`], leaving the points drawn as usual,
+    /// if the device can't run compute shaders or read storage buffers in
+    /// vertex shaders.
+    #[cfg(feature = "compute")]
+    pub fn set_point_merge(
+        &mut self,
+        device: &wgpu::Device,
+        merge: Option<PointMerge>,
+    ) -> Result<(), PlotError> {
+        let supported = merge.is_none()
+            || (self.capabilities.compute_shaders && self.capabilities.vertex_pulling);
+        let config = self.target.config();
+        self.merge = merge
+            .filter(|_| supported)
+            .map(|merge| MergeLayer::new(device, merge, config.render_format, config.sample_count));
+        self.invalidated = true;
+        supported_or(supported, "point merging")
+    }
+
+    #[cfg(feature = "compute")]
+    pub fn point_merge(&self) -> Option<&PointMerge> {
+        self.merge.as_ref().map(|layer| layer.merge())
+    }
+
+    /// Replay a series along the times of its samples with a marker drawn
+    /// over it, see [`TimeCursor`], or stop with `None`. Advance it with
+    /// [`TimeCursor::advance`] through
+    /// [`GpuAcceleratedPlot::time_cursor_mut`] on every frame; the marker
+    /// follows on the next prepare. The cursor is only drawn in the main
+    /// plot, ignores axis breaks, and with the series decimated, moves along
+    /// the drawn samples.
+    ///
+    /// External points need `BufferUsages::STORAGE`. Fails with
+    /// [`PlotError::UnsupportedFeature`] if the device can't read storage
+    /// buffers in vertex shaders.
+    pub fn set_time_cursor(
+        &mut self,
+        device: &wgpu::Device,
+        cursor: Option<TimeCursor>,
+    ) -> Result<(), PlotError> {
+        let supported = cursor.is_none() || self.capabilities.vertex_pulling;
+        let config = self.target.config();
+        self.time_cursor = cursor.filter(|_| supported).map(|cursor| {
+            CursorLayer::new(device, cursor, config.render_format, config.sample_count)
+        });
+        self.invalidated = true;
+
+        if supported {
+            Ok(())
+        } else {
+            Err(PlotError::UnsupportedFeature("time cursors"))
+        }
+    }
+
+    pub fn time_cursor(&self) -> Option<&TimeCursor> {
+        self.time_cursor.as_ref().map(|layer| layer.cursor())
+    }
+
+    /// E.g. to play, pause or seek.
+    pub fn time_cursor_mut(&mut self) -> Option<&mut TimeCursor> {
+        self.time_cursor.as_mut().map(|layer| layer.cursor_mut())
+    }
+
+    /// Scroll the x bounds to end at the newest (last) sample, on the GPU
+    /// just before each render, instead of following it on the CPU a frame
+    /// late. The x bounds passed to [`GpuAcceleratedPlot::prepare`] are
+    /// replaced when drawing the lines, but are still used by everything
+    /// else: thresholds, logic tracks, timelines, merged points, hit tests
+    /// and the application's own axes. Not applied to subplots.
+    ///
+    /// External points need `BufferUsages::STORAGE`. Fails with
+    /// [`PlotError::UnsupportedFeature`], leaving the bounds as they are, if
+    /// the device can't run compute shaders.
+    #[cfg(feature = "compute")]
+    pub fn set_follow(
+        &mut self,
+        device: &wgpu::Device,
+        follow: Option<Follow>,
+    ) -> Result<(), PlotError> {
+        let supported = follow.is_none() || self.capabilities.compute_shaders;
+        self.follow = follow
+            .filter(|_| supported)
+            .map(|follow| FollowLayer::new(device, follow));
+        self.invalidated = true;
+        supported_or(supported, "following")
+    }
+
+    #[cfg(feature = "compute")]
+    pub fn follow(&self) -> Option<&Follow> {
+        self.follow.as_ref().map(|layer| layer.follow())
+    }
+
+    /// Render a custom pass into the plot's target, with access to the
+    /// plot's bounds. Passes with a negative z order (e.g.
+    /// [`Layer::Background`]) are drawn underneath everything else, and the
+    /// rest on top of the series, both in z order. Passes aren't drawn in
+    /// subplots.
+    pub fn add_pass(&mut self, z_order: impl Into<i32>, pass: Box<dyn PlotPass>) -> PassId {
+        let id = PassId(self.next_pass_id);
+        self.next_pass_id += 1;
+
+        let z_order = z_order.into();
+        let index = self.passes.partition_point(|(_, z, _)| *z <= z_order);
+        self.passes.insert(index, (id, z_order, pass));
+        self.invalidated = true;
+        id
+    }
+
+    pub fn remove_pass(&mut self, id: PassId) -> Option<Box<dyn PlotPass>> {
+        let index = self.passes.iter().position(|(i, ..)| *i == id)?;
+        self.invalidated = true;
+        Some(self.passes.remove(index).2)
+    }
+
+    /// Fit a polynomial to the samples within the x range of the bounds
+    /// last passed to [`GpuAcceleratedPlot::prepare`], summing them on the
+    /// GPU so that large data is never read back. Blocks until the GPU has
+    /// finished. For small data, or LOESS, use [`fit()`] on the CPU.
+    ///
+    /// Returns None for LOESS, when there are too few samples, or if the
+    /// device can't run compute shaders. External points need
+    /// `BufferUsages::STORAGE`.
+    #[cfg(feature = "compute")]
+    pub fn fit_visible(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        model: FitModel,
+    ) -> Option<Fit> {
+        if !self.request_fit(device, queue, model) {
+            return None;
+        }
+        device.poll(wgpu::Maintain::Wait);
+        self.fit_reducer.as_mut()?.finish().flatten()
+    }
+
+    /// Start [`GpuAcceleratedPlot::fit_visible`] without blocking, e.g. on
+    /// the web. The fit is returned by
+    /// [`GpuAcceleratedPlot::poll_result`] once it's been read back.
+    ///
+    /// Returns false if the fit can't be started, or the last one hasn't
+    /// been read back yet.
+    #[cfg(feature = "compute")]
+    pub fn request_fit(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        model: FitModel,
+    ) -> bool {
+        if !self.capabilities.compute_shaders || matches!(model, FitModel::Loess { .. }) {
+            return false;
+        }
+
+        let main = self.uniform_data[MAIN_SLOT];
+        let bounds = Bounds::new(
+            ascending(main.x_bounds).map(|x| x as f64),
+            ascending(main.y_bounds).map(|y| y as f64),
+        );
+        let mut reducer = self
+            .fit_reducer
+            .take()
+            .unwrap_or_else(|| FitReducer::new(device));
+        let (points, layout, offset, count) = self.vertex_source();
+        let submitted =
+            reducer.submit(device, queue, points, layout, offset, count, &bounds, model);
+        self.fit_reducer = Some(reducer);
+        submitted
+    }
+
+    /// Draw horizontal alarm thresholds behind the series in the main
+    /// viewport, shading the regions that breach them.
+    pub fn set_thresholds(&mut self, thresholds: Vec<Threshold>) {
+        self.thresholds.set(thresholds);
+        self.invalidated = true;
+    }
+
+    /// Draw regions of the x axis with a handle at each end over the series,
+    /// e.g. loop or trim markers edited with [`MarkerEditor`]. Pass the
+    /// regions again after each edit.
+    pub fn set_markers(&mut self, regions: Vec<MarkerRegion>) {
+        if regions != self.markers.regions() {
+            self.markers.set(regions);
+            self.invalidated = true;
+        }
+    }
+
+    pub fn markers(&self) -> &[MarkerRegion] {
+        self.markers.regions()
+    }
+
+    /// Show the points as an IQ constellation: the bounds passed to
+    /// [`GpuAcceleratedPlot::prepare`] are replaced by the constellation's
+    /// square view (widened to the plot's aspect ratio), the points are
+    /// binned into a persistent histogram heatmap that decays as new blocks
+    /// of samples are passed as dirty, and the reference symbols are drawn
+    /// as crosses over it. Pass each block of samples as `[i, q]` points;
+    /// only the latest block needs uploading. Pass `None` to go back to
+    /// drawing the points as lines.
+    ///
+    /// Fails with [`PlotError::UnsupportedFeature`], leaving the points
+    /// drawn as usual in the square view, without the `heatmap` feature or
+    /// if the device can't run compute shaders.
+    pub fn set_constellation(
+        &mut self,
+        device: &wgpu::Device,
+        constellation: Option<Constellation>,
+    ) -> Result<(), PlotError> {
+        self.reference_vertices = constellation
+            .as_ref()
+            .and_then(|constellation| constellation.reference_vertices(device));
+        #[cfg(feature = "heatmap")]
+        let had_constellation = self.constellation.is_some();
+        self.constellation = constellation;
+        self.invalidated = true;
+
+        #[cfg(feature = "heatmap")]
+        {
+            let histogram = self.constellation.as_ref().map(Constellation::histogram);
+            if histogram.is_some() || had_constellation {
+                return self.set_histogram(device, histogram);
+            }
+            Ok(())
+        }
+        #[cfg(not(feature = "heatmap"))]
+        match self.constellation {
+            Some(_) => Err(PlotError::UnsupportedFeature("constellation density")),
+            None => Ok(()),
+        }
+    }
+
+    pub fn constellation(&self) -> Option<&Constellation> {
+        self.constellation.as_ref()
+    }
+
+    /// Draw a Smith chart's grid behind the series, replacing the bounds
+    /// passed to [`GpuAcceleratedPlot::prepare`] with its square view
+    /// (widened to the plot's aspect ratio). Pass `None` to remove it.
+    pub fn set_smith_chart(&mut self, device: &wgpu::Device, chart: Option<SmithChart>) {
+        self.smith_chart = chart.map(|chart| {
+            let (buffer, count) = chart.grid_vertices(device);
+            (chart, buffer, count)
+        });
+        self.invalidated = true;
+    }
+
+    pub fn smith_chart(&self) -> Option<&SmithChart> {
+        self.smith_chart.as_ref().map(|(chart, ..)| chart)
+    }
+
+    // Bounds replacing those passed to `prepare`, for the modes with a fixed
+    // square view.
+    fn fixed_bounds(&self) -> Option<Bounds> {
+        let square = match (&self.constellation, &self.smith_chart) {
+            (Some(constellation), _) => constellation.bounds(),
+            (None, Some((chart, ..))) => chart.bounds(),
+            (None, None) => return None,
+        };
+        Some(square.with_data_aspect(1.0, self.target.size()))
+    }
+
+    /// Find where the samples (of `series`, or of all points) cross zero
+    /// nearest to `x`, within `radius`, on the GPU, e.g. to snap a marker
+    /// handle so that an audio loop doesn't click. The crossing is
+    /// interpolated between samples. Blocks until the GPU has finished.
+    ///
+    /// Returns None if there's no crossing within the radius, or the device
+    /// can't run compute shaders. External points need
+    /// `BufferUsages::STORAGE`.
+    #[cfg(feature = "compute")]
+    pub fn find_zero_crossing(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        series: Option<SeriesId>,
+        x: f32,
+        radius: f32,
+    ) -> Option<f32> {
+        if !self.request_zero_crossing(device, queue, series, x, radius) {
+            return None;
+        }
+        device.poll(wgpu::Maintain::Wait);
+        self.crossing_finder.as_mut()?.finish()?
+    }
+
+    /// Start [`GpuAcceleratedPlot::find_zero_crossing`] without blocking.
+    /// The crossing is returned by [`GpuAcceleratedPlot::poll_result`] once
+    /// it's been read back.
+    ///
+    /// Returns false if the search can't be started, or the last one hasn't
+    /// been read back yet.
+    #[cfg(feature = "compute")]
+    pub fn request_zero_crossing(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        series: Option<SeriesId>,
+        x: f32,
+        radius: f32,
+    ) -> bool {
+        if !self.capabilities.compute_shaders {
+            return false;
+        }
+
+        let (points, layout, offset, count) = self.vertex_source();
+        let vertices = match series {
+            Some(id) => match self.series_vertices(id).map(|v| self.drawn_vertices(&v)) {
+                Some(vertices) => vertices.start.min(count)..vertices.end.min(count),
+                None => return false,
+            },
+            None => 0..count,
+        };
+
+        let mut finder = self
+            .crossing_finder
+            .take()
+            .unwrap_or_else(|| CrossingFinder::new(device));
+        let submitted = finder.submit(
+            device,
+            queue,
+            points,
+            layout,
+            offset,
+            vertices,
+            x,
+            radius.abs(),
+        );
+        self.crossing_finder = Some(finder);
+        submitted
+    }
+
+    /// Find the lowest and highest y value drawn (of `series`, or of all
+    /// points) in each pixel column of the plot's texture, over the bounds
+    /// last passed to [`GpuAcceleratedPlot::prepare`], e.g. to place labels
+    /// that hug a curve without overlapping it. Values are the series' raw
+    /// values, before their value mappings, and columns are spread evenly
+    /// over the x range in data units, ignoring axis breaks. Blocks until
+    /// the GPU has finished.
+    ///
+    /// Returns None if the device can't run compute shaders. External
+    /// points need `BufferUsages::STORAGE`.
+    #[cfg(feature = "compute")]
+    pub fn find_column_extrema(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        series: Option<SeriesId>,
+    ) -> Option<ColumnExtrema> {
+        if !self.request_column_extrema(device, queue, series) {
+            return None;
+        }
+        device.poll(wgpu::Maintain::Wait);
+        self.extrema_finder.as_mut()?.finish()
+    }
+
+    /// Start [`GpuAcceleratedPlot::find_column_extrema`] without blocking.
+    /// The extrema are returned by [`GpuAcceleratedPlot::poll_result`] once
+    /// they've been read back.
+    ///
+    /// Returns false if the reduction can't be started, or the last one
+    /// hasn't been read back yet.
+    #[cfg(feature = "compute")]
+    pub fn request_column_extrema(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        series: Option<SeriesId>,
+    ) -> bool {
+        if !self.capabilities.compute_shaders {
+            return false;
+        }
+
+        let (points, layout, offset, count) = self.vertex_source();
+        let vertices = match series {
+            Some(id) => match self.series_vertices(id).map(|v| self.drawn_vertices(&v)) {
+                Some(vertices) => vertices.start.min(count)..vertices.end.min(count),
+                None => return false,
+            },
+            None => 0..count,
+        };
+        let (x, _) = self.uniform_data[MAIN_SLOT].data_bounds();
+
+        let mut finder = self
+            .extrema_finder
+            .take()
+            .unwrap_or_else(|| ExtremaFinder::new(device));
+        let submitted = finder.submit(
+            device,
+            queue,
+            points,
+            layout,
+            offset,
+            vertices,
+            x.map(f64::from),
+            self.target.size()[0],
+        );
+        self.extrema_finder = Some(finder);
+        submitted
+    }
+
+    /// Draw digital channels, e.g. under the analog traces for a
+    /// mixed-signal view. Each track occupies its own lane in the main
+    /// viewport, in the plot's y units. Pass no tracks to remove them.
+    pub fn set_logic_tracks(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        tracks: &[LogicTrack],
+    ) {
+        self.invalidated = true;
+        if tracks.is_empty() {
+            self.logic = None;
+            return;
+        }
+
+        let config = self.target.config();
+        let context = &self.context;
+        self.logic
+            .get_or_insert_with(|| {
+                LogicLayer::new(
+                    device,
+                    &context.logic,
+                    config.render_format,
+                    config.sample_count,
+                )
+            })
+            .set_tracks(device, queue, tracks);
+    }
+
+    /// Draw a Gantt-style timeline in the main viewport, or remove it with
+    /// `None`. Use [`Timeline::pick`] to map clicks back to interval ids.
+    pub fn set_timeline(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        timeline: Option<&Timeline>,
+    ) {
+        self.invalidated = true;
+        let timeline = match timeline {
+            Some(timeline) => timeline,
+            None => {
+                self.timeline = None;
+                return;
+            }
+        };
+
+        let config = self.target.config();
+        let context = &self.context;
+        self.timeline
+            .get_or_insert_with(|| {
+                TimelineLayer::new(
+                    device,
+                    &context.timeline,
+                    config.render_format,
+                    config.sample_count,
+                )
+            })
+            .set_timeline(device, queue, timeline);
+    }
+
+    /// Find where the samples within the x range of the bounds last passed
+    /// to [`GpuAcceleratedPlot::prepare`] start breaching `threshold`, on the
+    /// GPU. Returns the vertex index of each breach's first sample, in
+    /// order, e.g. to step through with [`DataCursor::move_to`]. At most
+    /// [`MAX_BREACHES`] are returned. Blocks until the GPU has finished.
+    ///
+    /// Returns None if the device can't run compute shaders. External points
+    /// need `BufferUsages::STORAGE`.
+    #[cfg(feature = "compute")]
+    pub fn find_breaches(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        threshold: &Threshold,
+    ) -> Option<Vec<u32>> {
+        if !self.request_breaches(device, queue, threshold) {
+            return None;
+        }
+        device.poll(wgpu::Maintain::Wait);
+        self.breach_finder.as_mut()?.finish()
+    }
+
+    /// Start [`GpuAcceleratedPlot::find_breaches`] without blocking, e.g. on
+    /// the web. The breaches are returned by
+    /// [`GpuAcceleratedPlot::poll_result`] once they've been read back.
+    ///
+    /// Returns false if the search can't be started, or the last one hasn't
+    /// been read back yet.
+    #[cfg(feature = "compute")]
+    pub fn request_breaches(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        threshold: &Threshold,
+    ) -> bool {
+        if !self.capabilities.compute_shaders {
+            return false;
+        }
+
+        let x_range = ascending(self.uniform_data[MAIN_SLOT].x_bounds);
+        let mut finder = self
+            .breach_finder
+            .take()
+            .unwrap_or_else(|| BreachFinder::new(device));
+        let (points, layout, offset, count) = self.vertex_source();
+        let submitted = finder.submit(
+            device, queue, points, layout, offset, count, x_range, threshold,
+        );
+        self.breach_finder = Some(finder);
+        submitted
+    }
+
+    /// Attach an id to each sample, e.g. a database row or entity id, to be
+    /// returned with hits ([`Hit::id`]) and selections
+    /// ([`SeriesSelection::ids`]) so that they map straight back to the
+    /// application's data. Sample `i` is drawn by vertices `2 * i` and
+    /// `2 * i + 1`, as hits and selections index them; samples past the end
+    /// of `ids` have none. Pass `None` to stop.
+    ///
+    /// The ids are kept on the CPU, apart from the points, and only looked
+    /// up once a result has been read back, so they cost no GPU memory or
+    /// bandwidth.
+    pub fn set_point_ids(&mut self, ids: Option<Vec<u32>>) {
+        self.point_ids = ids;
+    }
+
+    pub fn point_ids(&self) -> Option<&[u32]> {
+        self.point_ids.as_deref()
+    }
+
+    /// The id of the sample drawn by vertex `index`, e.g. [`Hit::index`].
+    pub fn point_id(&self, index: usize) -> Option<u32> {
+        self.point_ids.as_ref()?.get(index / 2).copied()
+    }
+
+    /// Find the sample drawn nearest to `pos`, from the top left of the plot,
+    /// within `tolerance`, e.g. for context menus or editing data on click.
+    /// Both are in the units of the dimensions passed to prepare, i.e. in
+    /// points if [`GpuAcceleratedPlot::set_pixels_per_point`] is used, and
+    /// are converted to texture pixels exactly. Uses the bounds, size, axis
+    /// breaks and value mappings of the last [`GpuAcceleratedPlot::prepare`],
+    /// and searches on the GPU. Where series overlap, the one drawn on top
+    /// wins. Impedance series are searched where they're drawn, at their
+    /// reflection coefficients. Folding is not taken into account. Blocks
+    /// until the GPU has finished.
+    ///
+    /// Returns None if nothing is close enough, or if the device can't run
+    /// compute shaders. External points need `BufferUsages::STORAGE`.
+    #[cfg(feature = "compute")]
+    pub fn hit_test(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        pos: [f32; 2],
+        tolerance: f32,
+    ) -> Option<Hit> {
+        if !self.request_hit_test(device, queue, pos, tolerance) {
+            return None;
+        }
+        device.poll(wgpu::Maintain::Wait);
+        self.finish_hit_test().flatten()
+    }
+
+    /// Start [`GpuAcceleratedPlot::hit_test`] without blocking, e.g. on the
+    /// web. The hit is returned by [`GpuAcceleratedPlot::poll_result`] once
+    /// it's been read back.
+    ///
+    /// Returns false if the search can't be started, or the last one hasn't
+    /// been read back yet.
+    #[cfg(feature = "compute")]
+    pub fn request_hit_test(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        pos: [f32; 2],
+        tolerance: f32,
+    ) -> bool {
+        if !self.capabilities.compute_shaders {
+            return false;
+        }
+
+        // Topmost first, as ties go to the first slot.
+        let (ids, slots): (Vec<_>, Vec<_>) = self
+            .drawn_series()
+            .into_iter()
+            .map(|(id, vertices, mapping, coordinates)| {
+                (
+                    id,
+                    HitSlot {
+                        vertices,
+                        mapping,
+                        coordinates,
+                    },
+                )
+            })
+            .unzip();
+
+        let mut finder = self
+            .hit_finder
+            .take()
+            .unwrap_or_else(|| HitFinder::new(device));
+        let (points, layout, offset, _) = self.vertex_source();
+        let submitted = finder.submit(
+            device,
+            queue,
+            points,
+            layout,
+            offset,
+            &self.uniform_data[MAIN_SLOT],
+            self.target.size(),
+            pos.map(|c| c * self.texture_scale()),
+            tolerance * self.texture_scale(),
+            &slots,
+        );
+        self.hit_finder = Some(finder);
+
+        if submitted {
+            self.pending_hit = Some(ids);
+        }
+        submitted
+    }
+
+    // The pending hit test's result, once read back.
+    #[cfg(feature = "compute")]
+    fn finish_hit_test(&mut self) -> Option<Option<Hit>> {
+        let found = self.hit_finder.as_mut()?.finish()?;
+        let ids = self.pending_hit.take()?;
+
+        Some(found.map(|(slot, index, value)| Hit {
+            series_id: ids[slot],
+            index: index as usize,
+            id: self.point_id(index as usize),
+            value: value.map(|v| v as f64),
+        }))
+    }
+
+    /// Compute statistics of the samples within `region` (in axis units) on
+    /// the GPU, per series, e.g. once the user finishes a selection with
+    /// [`RegionSelector`]. The event is passed to the selection callback,
+    /// if any, and returned. Values are in the series' plotted units. Blocks
+    /// until the GPU has finished.
+    ///
+    /// Returns None if the device can't run compute shaders. External points
+    /// need `BufferUsages::STORAGE`.
+    #[cfg(feature = "compute")]
+    pub fn select_region(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        region: Region,
+    ) -> Option<SelectionEvent> {
+        if !self.request_selection(device, queue, region) {
+            return None;
+        }
+        device.poll(wgpu::Maintain::Wait);
+        self.finish_selection()
+    }
+
+    /// Start [`GpuAcceleratedPlot::select_region`] without blocking, e.g. on
+    /// the web. The event is passed to the selection callback and returned
+    /// by [`GpuAcceleratedPlot::poll_result`] once it's been read back.
+    ///
+    /// Returns false if the selection can't be started, or the last one
+    /// hasn't been read back yet.
+    #[cfg(feature = "compute")]
+    pub fn request_selection(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        region: Region,
+    ) -> bool {
+        if !self.capabilities.compute_shaders {
+            return false;
+        }
+
+        let [x_breaks, y_breaks] = &self.axis_breaks;
+        let x_range = region.x.map(|x| x_breaks.to_data(x) as f32);
+        let y_range = region.y.map_or([-f32::MAX, f32::MAX], |y| {
+            y.map(|y| y_breaks.to_data(y) as f32)
+        });
+
+        let (ids, slots): (Vec<_>, Vec<_>) = self
+            .drawn_series()
+            .into_iter()
+            .map(|(id, vertices, mapping, _)| (id, StatsSlot { vertices, mapping }))
+            .unzip();
+
+        let mut reducer = self
+            .stats_reducer
+            .take()
+            .unwrap_or_else(|| StatsReducer::new(device));
+        let (points, layout, offset, _) = self.vertex_source();
+        let submitted = reducer.submit(
+            device, queue, points, layout, offset, x_range, y_range, &slots,
+        );
+        self.stats_reducer = Some(reducer);
+
+        if submitted {
+            self.pending_selection = Some((region, ids));
+        }
+        submitted
+    }
+
+    // The pending selection's event, once read back.
+    #[cfg(feature = "compute")]
+    fn finish_selection(&mut self) -> Option<SelectionEvent> {
+        let results = self.stats_reducer.as_mut()?.finish()?;
+        let (region, ids) = self.pending_selection.take()?;
+
+        let event = SelectionEvent {
+            region,
+            series: ids
+                .into_iter()
+                .zip(results)
+                .filter_map(|(series_id, result)| {
+                    let (stats, vertices) = result?;
+                    let ids = self.point_ids.as_ref().map(|ids| {
+                        let samples = (vertices.start / 2) as usize..(vertices.end / 2) as usize;
+                        ids[samples.start.min(ids.len())..samples.end.min(ids.len())].to_vec()
+                    });
+                    Some(SeriesSelection {
+                        series_id,
+                        vertices,
+                        ids,
+                        stats,
+                    })
+                })
+                .collect(),
+        };
+
+        if let Some(callback) = &mut self.selection_callback {
+            callback(&event);
+        }
+        Some(event)
+    }
+
+    /// A result of the readbacks started with `request_hit_test`,
+    /// `request_selection`, `request_breaches`, `request_fit` or
+    /// `request_image`, once it's ready. Call it each frame (until it returns
+    /// None) while any are pending; it never blocks, so this also works in
+    /// browsers, where the results arrive once control returns to the event
+    /// loop. Each kind of readback can only be pending once at a time.
+    pub fn poll_result(&mut self, device: &wgpu::Device) -> Option<ReadbackResult> {
+        device.poll(wgpu::Maintain::Poll);
+
+        if let Some(image) = self.image_readback.as_ref().and_then(ImageReadback::finish) {
+            self.image_readback = None;
+            return Some(ReadbackResult::Image(image));
+        }
+        #[cfg(feature = "compute")]
+        if let Some(result) = self.poll_compute_result() {
+            return Some(result);
+        }
+        None
+    }
+
+    // A result of the compute readbacks, once one is ready.
+    #[cfg(feature = "compute")]
+    fn poll_compute_result(&mut self) -> Option<ReadbackResult> {
+        if let Some(hit) = self.finish_hit_test() {
+            return Some(ReadbackResult::Hit(hit));
+        }
+        if let Some(event) = self.finish_selection() {
+            return Some(ReadbackResult::Selection(event));
+        }
+        if let Some(breaches) = self.breach_finder.as_mut().and_then(BreachFinder::finish) {
+            return Some(ReadbackResult::Breaches(breaches));
+        }
+        if let Some(crossing) = self
+            .crossing_finder
+            .as_mut()
+            .and_then(CrossingFinder::finish)
+        {
+            return Some(ReadbackResult::ZeroCrossing(crossing));
+        }
+        if let Some(extrema) = self.extrema_finder.as_mut().and_then(ExtremaFinder::finish) {
+            return Some(ReadbackResult::ColumnExtrema(extrema));
+        }
+        self.fit_reducer
+            .as_mut()
+            .and_then(FitReducer::finish)
+            .map(ReadbackResult::Fit)
+    }
+
+    /// Call `callback` with every [`SelectionEvent`] from
+    /// [`GpuAcceleratedPlot::select_region`] or
+    /// [`GpuAcceleratedPlot::request_selection`], e.g. to forward them to a
+    /// channel.
+    #[cfg(feature = "compute")]
+    pub fn set_selection_callback(
+        &mut self,
+        callback: Option<Box<dyn FnMut(&SelectionEvent) + Send + Sync>>,
+    ) {
+        self.selection_callback = callback;
+    }
+
+    // The series in reverse draw order (topmost first), or the implicit
+    // series if there are none, with their vertices clamped to the drawn
+    // vertices, and their value mappings and coordinates.
+    #[cfg(feature = "compute")]
+    fn drawn_series(&self) -> Vec<(Option<SeriesId>, Range<u32>, ValueMapping, Coordinates)> {
+        let (_, _, _, count) = self.vertex_source();
+        if self.series.is_empty() {
+            return vec![(
+                None,
+                0..count,
+                ValueMapping::default(),
+                Coordinates::Cartesian,
+            )];
+        }
+
+        let styles = self.drawn_styles();
+        self.draw_order
+            .iter()
+            .rev()
+            .filter(|&&i| self.series[i].group.visible)
+            .map(|&i| {
+                let series = &self.series[i];
+                let vertices = self.drawn_vertices(&series.vertices);
+                let vertices = vertices.start.min(count)..vertices.end.min(count);
+                let style = &styles[i];
+                (
+                    Some(SeriesId(i)),
+                    vertices,
+                    style.mapping,
+                    style.coordinates,
+                )
+            })
+            .collect()
+    }
+
+    // The buffer of whichever vertices are being drawn, with their layout,
+    // byte offset and count. Where the plot's own vertices are split across
+    // buffers, only the first.
+    fn vertex_source(&self) -> (&wgpu::Buffer, VertexLayout, wgpu::BufferAddress, u32) {
+        match &self.external {
+            Some((points, _)) => (&points.buffer, points.layout, points.offset, points.count),
+            None => (
+                &self.vertex_buffer,
+                VertexLayout::default(),
+                0,
+                self.vertex_count.min(self.chunk_capacity),
+            ),
+        }
+    }
+
+    /// Draw vertices straight from an application-owned buffer instead of
+    /// the points passed to [`GpuAcceleratedPlot::prepare`], so data produced
+    /// on the GPU never round-trips through the CPU. Pass `None` to go back to
+    /// uploading points.
+    ///
+    /// The plot can't tell when the buffer's contents change, so call
+    /// [`GpuAcceleratedPlot::invalidate`] after writing to it. Validation,
+    /// decimation and the overview only apply to uploaded points.
+    pub fn set_external_points(&mut self, device: &wgpu::Device, points: Option<ExternalPoints>) {
+        self.external = points.map(|points| (points, Arc::clone(&self.pipeline)));
+        self.update_pipelines(device);
+        self.invalidated = true;
+    }
+
+    /// Upload only `vertices` of `points`, e.g. after a [`PointEdit`],
+    /// instead of the whole buffer. `points` must otherwise match what was
+    /// last uploaded. Validation isn't applied, and the overview isn't
+    /// updated.
+    ///
+    /// Returns false, writing nothing, if the points can't be patched in
+    /// place: with external points, while the render budget or vertex budget
+    /// is decimating, or if the range is beyond the uploaded points. Upload them in full
+    /// instead.
+    pub fn write_points(
+        &mut self,
+        queue: &wgpu::Queue,
+        points: &[Vertex],
+        vertices: Range<u32>,
+    ) -> bool {
+        if self.external.is_some()
+            || self.decimation() > 1
+            || self.vertex_map.is_some()
+            || vertices.end > self.vertex_count
+            || vertices.end > self.chunk_capacity
+            || vertices.end as usize > points.len()
+        {
+            return false;
+        }
+        if vertices.is_empty() {
+            return true;
+        }
+
+        let offset = vertices.start as usize * std::mem::size_of::<Vertex>();
+        queue.write_buffer(
+            &self.vertex_buffer,
+            offset as wgpu::BufferAddress,
+            bytemuck::cast_slice(&points[vertices.start as usize..vertices.end as usize]),
+        );
+        #[cfg(feature = "heatmap")]
+        if let Some(bins) = &mut self.histogram {
+            bins.reset();
+        }
+        if let Some(thumbnail) = &self.thumbnail {
+            thumbnail.invalidate();
+        }
+        self.invalidated = true;
+        true
+    }
+
+    /// Copy the rendered plot into a texture owned by someone else, e.g. an
+    /// engine's render target. `destination` must be the same size as the
+    /// plot and usable as a render attachment.
+    pub fn copy_to_view(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        destination: &wgpu::TextureView,
+        format: wgpu::TextureFormat,
+    ) {
+        let blitter = &self.context.blitter;
+        blitter.prepare(device, format);
+        let source = blitter.create_bind_group(device, &self.target.create_view());
+
+        let mut encoder =
+            device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        blitter.blit(&mut encoder, &source, destination, format);
+        queue.submit(iter::once(encoder.finish()));
+    }
+
+    /// Read the plot texture back as rows of 8 bit RGBA pixels, along with
+    /// its size, e.g. to copy the plot to the clipboard or save it as an
+    /// image. Blocks until the GPU has finished, see
+    /// [`GpuAcceleratedPlot::request_image`] otherwise. `None` if the output
+    /// format isn't 8 bit RGBA or BGRA, or the read failed.
+    pub fn read_rgba(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) -> Option<([u32; 2], Vec<u8>)> {
+        let readback = ImageReadback::start(device, queue, &self.target)?;
+        device.poll(wgpu::Maintain::Wait);
+        readback.finish().flatten()
+    }
+
+    /// Start [`GpuAcceleratedPlot::read_rgba`] without blocking, e.g. on the
+    /// web. The pixels are returned by [`GpuAcceleratedPlot::poll_result`]
+    /// as [`ReadbackResult::Image`] once they've been read back, as the
+    /// texture was when requested.
+    ///
+    /// Returns false if the output format isn't 8 bit RGBA or BGRA, or the
+    /// last image hasn't been read back yet.
+    pub fn request_image(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) -> bool {
+        if self.image_readback.is_some() {
+            return false;
+        }
+        self.image_readback = ImageReadback::start(device, queue, &self.target);
+        self.image_readback.is_some()
+    }
+
+    /// The samples of `points`, as passed to [`GpuAcceleratedPlot::prepare`],
+    /// as CSV with a row per sample: the name of its series (empty for
+    /// unnamed series, or without any), x and y.
+    pub fn to_csv(&self, points: &[Vertex]) -> String {
+        let mut csv = String::from("series,x,y\n");
+        let mut write_rows = |name: &str, vertices: Range<u32>| {
+            let end = (vertices.end as usize).min(points.len());
+            let start = (vertices.start as usize).min(end);
+            // Vertices come in pairs, one for each side of the line.
+            for vertex in points[start..end].iter().step_by(2) {
+                let [x, y] = vertex.position;
+                csv.push_str(&format!("{},{},{}\n", csv_field(name), x, y));
+            }
+        };
+
+        if self.series.is_empty() {
+            write_rows("", 0..points.len() as u32);
+        }
+        for series in &self.series {
+            write_rows(
+                series.name.as_deref().unwrap_or(""),
+                series.vertices.clone(),
+            );
+        }
+        csv
+    }
+
+    /// Like [`GpuAcceleratedPlot::prepare`], but with the points of a
+    /// [`GpuPlotState`] which other threads push samples into. They're
+    /// copied out and uploaded only when the state changed since the last
+    /// call, and its series become the plot's first series (added as
+    /// needed), in order, keeping any styles given to them.
+    pub fn prepare_shared(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        dimensions: [u32; 2],
+        bounds: &Bounds,
+        state: &GpuPlotState,
+    ) {
+        let seen = self
+            .shared_points
+            .as_ref()
+            .map(|(generation, _)| *generation);
+        let dirty = seen != Some(state.generation());
+        if dirty {
+            let (generation, points, ranges) = state.vertices();
+            for (i, range) in ranges.into_iter().enumerate() {
+                if i < self.series.len() {
+                    self.set_series_vertices(SeriesId(i), range);
+                } else {
+                    self.add_series(range);
+                }
+            }
+            self.shared_points = Some((generation, points));
+        }
+
+        let points = self.shared_points.take().unwrap_or_default();
+        self.prepare(device, queue, dimensions, bounds, &points.1, dirty);
+        self.shared_points = Some(points);
+    }
+
+    pub fn prepare(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        dimensions: [u32; 2],
+        bounds: &Bounds,
+        points: &[Vertex],
+        dirty: bool,
+    ) {
+        profile_scope!("egui_plot::prepare", points = points.len(), dirty = dirty);
+        self.begin_prepare(device, dimensions);
+
+        if !self.subplots.is_empty() {
+            self.subplots.clear();
+            self.needs_render = true;
+        }
+        self.uniform_data.truncate(FIRST_SUBPLOT_SLOT);
+        let fixed = self.fixed_bounds();
+        let bounds = fixed.as_ref().unwrap_or(bounds);
+        if bounds.is_valid() {
+            self.uniform_data[MAIN_SLOT] = match self.data_aspect {
+                Some(ratio) => Uniform::from(&bounds.with_data_aspect(ratio, self.target.size())),
+                None => Uniform::from(bounds),
+            };
+        } else {
+            self.diagnostics
+                .errors
+                .push(PlotError::InvalidBounds(*bounds));
+        }
+
+        // A change in decimation means the data must be uploaded again, even
+        // if it hasn't changed.
+        let decimation_changed = match &mut self.budget {
+            Some(budget) if !self.target.config().deterministic => budget.frame(Instant::now()),
+            _ => false,
+        };
+
+        // Only re-upload the vertex buffer if it has changed.
+        if (dirty || decimation_changed) && self.external.is_none() {
+            self.snapshot_history(device, queue);
+            self.upload_points(device, queue, points, self.decimation());
+            #[cfg(feature = "heatmap")]
+            if let Some(bins) = &mut self.histogram {
+                bins.reset();
+            }
+        }
+
+        #[cfg(feature = "compute")]
+        self.update_normalization(device, queue, bounds, dirty || decimation_changed);
+
+        self.write_uniforms(device, queue);
+        self.update_bloom(device, queue);
+
+        self.needs_render |= self
+            .thresholds
+            .update(device, queue, &self.uniform_data[MAIN_SLOT]);
+        self.needs_render |= self
+            .markers
+            .update(device, queue, &self.uniform_data[MAIN_SLOT]);
+        if let Some(logic) = &mut self.logic {
+            self.needs_render |=
+                logic.update(queue, &self.uniform_data[MAIN_SLOT], self.target.size());
+        }
+        if let Some(timeline) = &mut self.timeline {
+            self.needs_render |=
+                timeline.update(queue, &self.uniform_data[MAIN_SLOT], self.target.size());
+        }
+        if let Some(attributes) = &mut self.attributes {
+            self.needs_render |=
+                attributes.update(queue, self.color_by.as_ref(), self.size_by.as_ref());
+        }
+
+        #[cfg(feature = "heatmap")]
+        if let Some(bins) = &mut self.histogram {
+            let (_, layout, offset, count) = self.vertex_source();
+            self.needs_render |=
+                bins.update(queue, &self.uniform_data[MAIN_SLOT], layout, offset, count);
+        }
+
+        #[cfg(feature = "compute")]
+        if let Some(mut layer) = self.diff.take() {
+            let (_, layout, offset, count) = self.vertex_source();
+            let clamp = |range: Range<u32>| range.start.min(count)..range.end.min(count);
+            let a = self.series_vertices(layer.diff().a).map(clamp);
+            let b = self.series_vertices(layer.diff().b).map(clamp);
+            let (a, b) = a.zip(b).unwrap_or((0..0, 0..0));
+            self.needs_render |= layer.update(device, queue, layout, offset, a, b);
+            self.diff = Some(layer);
+        }
+
+        if self
+            .passes
+            .iter()
+            .any(|(_, _, pass)| pass.reads_previous_frame())
+        {
+            self.frame_history
+                .get_or_insert_with(FrameHistory::new)
+                .prepare(device, &self.context.blitter, &self.target);
+            self.needs_render = true;
+        } else {
+            self.frame_history = None;
+        }
+
+        if !self.passes.is_empty() {
+            let config = self.target.config();
+            let context = PassContext {
+                format: config.render_format,
+                sample_count: config.sample_count,
+                uniform_layout: self.context.bind_group_layout(),
+                transform: self.transform(),
+                previous_frame: self.frame_history.as_ref().and_then(|h| h.previous()),
+            };
+            for (_, _, pass) in &mut self.passes {
+                self.needs_render |= pass.prepare(device, queue, &context);
+            }
+        }
+
+        #[cfg(feature = "compute")]
+        if let Some(mut follow) = self.follow.take() {
+            let (points, layout, offset, count) = self.vertex_source();
+            self.needs_render |= follow.update(
+                device,
+                queue,
+                self.axis_breaks[0].packed(),
+                self.reversed[0],
+                points,
+                layout,
+                offset,
+                count,
+            );
+            self.follow = Some(follow);
+        }
+
+        #[cfg(feature = "compute")]
+        if let Some(mut merge) = self.merge.take() {
+            let (points, layout, offset, count) = self.vertex_source();
+            self.needs_render |= merge.update(
+                device,
+                queue,
+                &self.uniform_data[MAIN_SLOT],
+                self.target.size(),
+                points,
+                layout,
+                offset,
+                count,
+            );
+            self.merge = Some(merge);
+        }
+
+        if let Some(mut cursor) = self.time_cursor.take() {
+            let (points, layout, offset, count) = self.vertex_source();
+            let vertices = match cursor.cursor().series {
+                Some(id) => self
+                    .series_vertices(id)
+                    .map(|v| self.drawn_vertices(&v))
+                    .unwrap_or(0..0),
+                None => 0..count,
+            };
+            self.needs_render |= cursor.update(
+                device,
+                queue,
+                &self.uniform_data[MAIN_SLOT],
+                self.target.size(),
+                points,
+                layout,
+                offset,
+                vertices.start.min(count)..vertices.end.min(count),
+            );
+            self.time_cursor = Some(cursor);
+        }
+    }
+
+    /// Like [`GpuAcceleratedPlot::prepare`], but splits the plot texture into
+    /// a grid of subplots. Each subplot draws a range of `points` with its
+    /// own bounds, and all of them are rendered in a single render pass.
+    ///
+    /// The render budget is not applied to subplots, since decimation would
+    /// invalidate their vertex ranges.
+    #[allow(clippy::too_many_arguments)]
+    pub fn prepare_subplots(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        dimensions: [u32; 2],
+        layout: &SubplotLayout,
+        subplots: &[Subplot],
+        points: &[Vertex],
+        dirty: bool,
+    ) {
+        profile_scope!(
+            "egui_plot::prepare_subplots",
+            points = points.len(),
+            subplots = subplots.len(),
+            dirty = dirty,
+        );
+        self.begin_prepare(device, dimensions);
+
+        let subplots = &subplots[..subplots.len().min(layout.len())];
+        self.uniform_data.truncate(FIRST_SUBPLOT_SLOT);
+        self.uniform_data
+            .extend(subplots.iter().map(|s| Uniform::from(&s.bounds)));
+
+        // Cells are laid out over the scaled texture.
+        let size = self.target.size();
+        let cells: Vec<_> = subplots
+            .iter()
+            .enumerate()
+            .filter_map(|(i, s)| Some((layout.cell(i, size)?, s.vertices.clone())))
+            .collect();
+        if cells != self.subplots {
+            self.subplots = cells;
+            self.needs_render = true;
+        }
+
+        if dirty && self.external.is_none() {
+            self.upload_points(device, queue, points, 1);
+        }
+
+        self.write_uniforms(device, queue);
+        self.update_bloom(device, queue);
+    }
+
+    fn begin_prepare(&mut self, device: &wgpu::Device, dimensions: [u32; 2]) {
+        self.diagnostics.errors.clear();
+
+        // Render at a lower resolution rather than beyond the device's
+        // limits, keeping the aspect ratio.
+        let max = self.capabilities.max_texture_dimension_2d.max(1);
+        self.limit_scale = 1.0;
+        let requested = self.scaled(dimensions);
+        if requested.iter().any(|&n| n > max) {
+            self.diagnostics.errors.push(PlotError::TextureTooLarge {
+                size: requested,
+                max,
+            });
+            self.limit_scale = max as f32 / requested[0].max(requested[1]) as f32;
+        }
+        let dimensions = self.scaled(dimensions).map(|n| n.min(max));
+        let resized = self
+            .target
+            .resize(device, &self.context.blitter, dimensions);
+        self.needs_render = resized || std::mem::take(&mut self.invalidated);
+
+        if let Some(filter) = &mut self.vision {
+            filter.prepare(device, &self.context.blitter, &self.target);
+        }
+        if let Some(thumbnail) = &mut self.thumbnail {
+            thumbnail.prepare(device, &self.context.blitter, &self.target);
+        }
+    }
+
+    // Upload the bounds of every viewport and the style of every series with
+    // a single buffer write each, but only if any of them have changed.
+    fn write_uniforms(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        // The inset frame is drawn in its own units, without breaks or
+        // reversal.
+        let (x_breaks, y_breaks) = (self.axis_breaks[0].packed(), self.axis_breaks[1].packed());
+        let [x_reversed, y_reversed] = self.reversed;
+        for (slot, uniform) in self.uniform_data.iter_mut().enumerate() {
+            if slot != INSET_FRAME_SLOT {
+                uniform.x_breaks = x_breaks;
+                uniform.y_breaks = y_breaks;
+
+                // Swapping the bounds flips the transform in the shaders.
+                let [x, y] = [uniform.x_bounds, uniform.y_bounds].map(ascending);
+                uniform.x_bounds = if x_reversed { [x[1], x[0]] } else { x };
+                uniform.y_bounds = if y_reversed { [y[1], y[0]] } else { y };
+            }
+        }
+
+        if self.uniform_data != self.written_uniform_data {
+            // Only the axes which moved, where few enough did.
+            if !self.uniforms.write_changed(
+                queue,
+                &self.uniform_data,
+                &self.written_uniform_data,
+                &Uniform::FIELDS,
+            ) {
+                self.uniforms.write(
+                    device,
+                    self.context.bind_group_layout(),
+                    queue,
+                    &self.uniform_data,
+                );
+            }
+            self.written_uniform_data.clone_from(&self.uniform_data);
+            self.needs_render = true;
+        }
+
+        // The live slots, then a faded copy of them per history snapshot.
+        let history = self.history.as_ref().map(|ring| *ring.history());
+        let snapshots = history.map_or(0, |history| history.snapshots);
+        let cvd_safe_palette = self.cvd_safe_palette;
+        let theme = self.theme;
+        let (styles, series_count) = (&self.styles, self.series.len());
+        let drawn_styles = self.drawn_styles();
+        let drawn_ranges: Vec<_> = self
+            .series
+            .iter()
+            .map(|s| self.drawn_vertices(&s.vertices))
+            .collect();
+        let shared_ranges: Vec<Range<u32>> = (0..styles.len())
+            .map(|h| {
+                let sharing = self
+                    .series
+                    .iter()
+                    .filter(|s| s.shared == Some(StyleHandle(h)));
+                sharing
+                    .map(|s| self.drawn_vertices(&s.vertices))
+                    .reduce(|a, b| a.start.min(b.start)..a.end.max(b.end))
+                    .unwrap_or(0..0)
+            })
+            .collect();
+        let series_data: Vec<_> = (0..=snapshots)
+            .flat_map(|age| {
+                let alpha = history.map_or(1.0, |history| history.alpha(age));
+                // A single gradient stop colors the whole series.
+                let faded = move |index: usize, style: &SeriesStyle| SeriesStyle {
+                    alpha: style.alpha * alpha,
+                    gradient: match &style.gradient {
+                        None if cvd_safe_palette => Some(Gradient::colormap(&[
+                            CVD_SAFE_PALETTE[index % CVD_SAFE_PALETTE.len()]
+                        ])),
+                        None => theme.line_color.map(|color| Gradient::colormap(&[color])),
+                        gradient => gradient.clone(),
+                    },
+                    width: style.width * theme.line_width,
+                    ..style.clone()
+                };
+                iter::once(SeriesUniform::new(
+                    &faded(0, &SeriesStyle::default()),
+                    &(0..0),
+                ))
+                .chain(drawn_ranges.iter().zip(&drawn_styles).enumerate().map(
+                    move |(i, (vertices, style))| SeriesUniform::new(&faded(i, style), vertices),
+                ))
+                .chain(styles.iter().zip(&shared_ranges).enumerate().map(
+                    move |(h, (style, vertices))| {
+                        SeriesUniform::new(&faded(series_count + h, style), vertices)
+                    },
+                ))
+            })
+            .collect();
+        if series_data != self.written_series_data {
+            self.series_uniforms.write(
+                device,
+                self.context.series_bind_group_layout(),
+                queue,
+                &series_data,
+            );
+            self.written_series_data = series_data;
+            self.needs_render = true;
+        }
+    }
+
+    // Allocate the glow targets while they're in use, following the size
+    // and format of the plot.
+    fn update_bloom(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        let glowing = self.theme.glow
+            && self.subplots.is_empty()
+            && self
+                .series
+                .iter()
+                .any(|s| s.group.visible && s.effective_style(&self.styles).glow);
+        if !glowing {
+            self.needs_render |= self.bloom.take().is_some();
+            return;
+        }
+
+        let format = self.render_format();
+        let size = self.target.size();
+        match &mut self.bloom {
+            Some(bloom) if bloom.is_compatible(format, size) => {
+                self.needs_render |= bloom.set_style(queue, self.glow);
+            }
+            _ => {
+                self.bloom = Some(Bloom::new(
+                    device,
+                    &self.context.bloom,
+                    format,
+                    size,
+                    self.glow,
+                ));
+                self.needs_render = true;
+            }
+        }
+    }
+
+    /// Whether the last prepare changed anything (data, bounds, size, or
+    /// settings) that requires the plot texture to be rendered again.
+    /// [`GpuAcceleratedPlot::render`] does nothing otherwise.
+    pub fn needs_render(&self) -> bool {
+        self.needs_render
+    }
+
+    /// Force the plot to be rendered again on the next frame.
+    pub fn invalidate(&mut self) {
+        self.invalidated = true;
+        // External points may have changed behind the plot's back.
+        if let Some(thumbnail) = &self.thumbnail {
+            thumbnail.invalidate();
+        }
+    }
+
+    // Freeze the points about to be replaced into the history, if any.
+    fn snapshot_history(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        if let Some(history) = &mut self.history {
+            let mut encoder =
+                device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+            history.push(
+                device,
+                &mut encoder,
+                &self.vertex_buffer,
+                self.vertex_count.min(self.chunk_capacity),
+            );
+            // Submitted before the new points are written.
+            queue.submit(iter::once(encoder.finish()));
+        }
+    }
+
+    fn upload_points(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        points: &[Vertex],
+        decimation: u32,
+    ) {
+        profile_scope!(
+            "egui_plot::upload",
+            points = points.len(),
+            decimation = decimation
+        );
+        // TODO: for time-series charts where the buffer acts as a ring, we
+        // could be smart about updating only the subset of added/removed
+        // vertices.
+        //
+        // A single NaN poisons the whole triangle strip, so optionally
+        // check (and repair) the data before it reaches the GPU.
+        let (points, report) = validation::apply(self.validation_mode, points);
+        self.validation_report = report;
+
+        if self
+            .waterfall
+            .map_or(false, |waterfall| waterfall.normalize)
+        {
+            let series: Vec<_> = self.series.iter().map(|s| s.vertices.clone()).collect();
+            self.waterfall_ranges = waterfall::series_ranges(&points, &series);
+        }
+
+        // Decimate each series separately, so that their ranges still
+        // line up with the uploaded vertices.
+        let boundaries: Vec<u32> = self
+            .series
+            .iter()
+            .flat_map(|s| [s.vertices.start, s.vertices.end])
+            .collect();
+
+        // The overview gets the full data and does its own decimation.
+        if let Some(overview) = &mut self.overview {
+            overview.update_vertices(queue, &points, &boundaries);
+            if let Some(bounds) = overview.data_bounds() {
+                self.uniform_data[OVERVIEW_SLOT] = Uniform::from(&bounds);
+            }
+        }
+        let decimation = decimation.max(
+            self.vertex_budget
+                .decimation(points.len(), boundaries.len() + 1),
+        );
+        let mut points = if decimation > 1 {
+            let (decimated, map) = budget::decimate_segments(
+                &points,
+                &boundaries,
+                decimation,
+                self.vertex_budget.mode,
+            );
+            self.vertex_map = Some(map);
+            Cow::Owned(decimated)
+        } else {
+            self.vertex_map = None;
+            points
+        };
+        self.diagnostics.decimation = decimation;
+
+        // Drop whatever still doesn't fit rather than overflowing the buffer.
+        let capacity = self.vertex_budget.max_vertices;
+        self.diagnostics.dropped_points = points.len().saturating_sub(capacity);
+        self.diagnostics.truncated_series.clear();
+        if points.len() > capacity {
+            self.diagnostics.errors.push(PlotError::BufferOverflow {
+                len: points.len(),
+                capacity,
+            });
+            self.diagnostics.truncated_series = (0..self.series.len())
+                .filter(|&i| self.drawn_vertices(&self.series[i].vertices).end as usize > capacity)
+                .map(SeriesId)
+                .collect();
+            points.to_mut().truncate(capacity);
+        }
+
+        self.vertex_count = points.len() as u32;
+        self.write_chunks(device, queue, &points);
+        if let Some(thumbnail) = &self.thumbnail {
+            thumbnail.invalidate();
+        }
+        self.needs_render = true;
+    }
+
+    pub fn render(&self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        // Nothing has changed since the last render, so the texture already
+        // holds the right image.
+        if !self.needs_render {
+            return;
+        }
+
+        self.submit_render(device, queue);
+    }
+
+    /// Render the plot, whether or not anything changed, between the hooks
+    /// of `capture`, e.g. to capture just this plot's passes in RenderDoc
+    /// while debugging a frame with many series. Pair with
+    /// [`GpuAcceleratedPlot::set_debug_labels`] to label the draws.
+    pub fn render_captured(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        capture: &mut dyn FrameCapture,
+    ) {
+        capture.start(device);
+        self.submit_render(device, queue);
+        device.poll(wgpu::Maintain::Wait);
+        capture.end(device);
+    }
+
+    fn submit_render(&self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        profile_scope!(
+            "egui_plot::render",
+            vertices = self.vertex_count,
+            series = self.series.len()
+        );
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("egui_plot_render_encoder"),
+        });
+        self.encode_compute(device, &mut encoder);
+        {
+            let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("egui_plot_pass"),
+                color_attachments: &[Some(
+                    self.target
+                        .color_attachment(wgpu::LoadOp::Clear(self.theme.clear_color())),
+                )],
+                depth_stencil_attachment: None,
+            });
+
+            self.render_onto_renderpass(&mut rpass);
+        }
+
+        if let Some(bloom) = &self.bloom {
+            // The glow target is never multisampled.
+            let pipeline = self.context.pipeline(
+                device,
+                PipelineKey {
+                    sample_count: 1,
+                    ..self.pipeline_key(self.vertex_layout(), false)
+                },
+            );
+            {
+                let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                    label: Some("egui_plot_glow_pass"),
+                    color_attachments: &[Some(bloom.color_attachment())],
+                    depth_stencil_attachment: None,
+                });
+
+                rpass.set_pipeline(&pipeline);
+                self.render_glow(&mut rpass);
+            }
+
+            bloom.finish(&mut encoder, self.target.color_view());
+        }
+
+        if self.debug_labels {
+            encoder.push_debug_group("egui_plot_post");
+        }
+        if let Some(filter) = &self.vision {
+            filter.apply(&mut encoder, &self.context.blitter, &self.target);
+        }
+        if let Some(history) = &self.frame_history {
+            history.record(&mut encoder, &self.context.blitter);
+        }
+
+        self.target.finish(&mut encoder, &self.context.blitter);
+        if let Some(thumbnail) = &self.thumbnail {
+            thumbnail.update(&mut encoder, &self.context.blitter);
+        }
+        if self.debug_labels {
+            encoder.pop_debug_group();
+        }
+
+        queue.submit(iter::once(encoder.finish()));
+    }
+
+    /// Run the compute passes a render depends on (binning, merging,
+    /// diffing and following), without rendering the plot texture. Call
+    /// this after [`GpuAcceleratedPlot::prepare`] when drawing the plot
+    /// straight into another render pass with
+    /// [`GpuAcceleratedPlot::render_onto_renderpass`].
+    pub fn dispatch_compute(&self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        let mut encoder =
+            device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        self.encode_compute(device, &mut encoder);
+        queue.submit(iter::once(encoder.finish()));
+    }
+
+    fn encode_compute(&self, device: &wgpu::Device, encoder: &mut wgpu::CommandEncoder) {
+        if !self.subplots.is_empty() {
+            return;
+        }
+
+        profile_scope!("egui_plot::compute", vertices = self.vertex_count);
+        if self.debug_labels {
+            encoder.push_debug_group("egui_plot_compute");
+        }
+        self.encode_compute_passes(device, encoder);
+        if self.debug_labels {
+            encoder.pop_debug_group();
+        }
+    }
+
+    #[cfg_attr(not(feature = "compute"), allow(unused_variables))]
+    fn encode_compute_passes(&self, device: &wgpu::Device, encoder: &mut wgpu::CommandEncoder) {
+        #[cfg(feature = "heatmap")]
+        if let Some(bins) = &self.histogram {
+            bins.dispatch(encoder);
+        }
+        #[cfg(feature = "compute")]
+        if let Some(merge) = &self.merge {
+            merge.dispatch(encoder);
+        }
+        #[cfg(feature = "compute")]
+        if let Some(diff) = &self.diff {
+            let (points, ..) = self.vertex_source();
+            diff.dispatch(device, encoder, points);
+        }
+        #[cfg(feature = "compute")]
+        if let Some(follow) = &self.follow {
+            // The x bounds are the first field of the main slot.
+            follow.dispatch(
+                encoder,
+                self.uniforms.buffer(),
+                self.uniforms.offset(MAIN_SLOT) as wgpu::BufferAddress,
+            );
+        }
+    }
+
+    /// Draw the plot into a render pass owned by someone else, e.g. egui's
+    /// (see `egui_wgpu_composite_callback`), over its current viewport. The
+    /// pass must target the plot's render format with its sample count.
+    /// Glow, color vision simulation, the theme's background, mipmaps and
+    /// thumbnails only apply when rendering into the plot's own texture.
+    /// Subplot cells and the inset are placed from the pass's origin at the
+    /// texture's size; use `render_onto_renderpass_at` when the viewport is
+    /// elsewhere.
+    pub fn render_onto_renderpass<'rp>(&'rp self, rpass: &mut wgpu::RenderPass<'rp>) {
+        self.render_within(rpass, None);
+    }
+
+    /// Like `render_onto_renderpass`, for a pass whose viewport was set to
+    /// `viewport` (`[x, y, width, height]` in the pass's pixels) and which is
+    /// clipped to `clip`, as egui does for a paint callback. Subplot cells
+    /// and the inset are scaled from the texture's size into the viewport
+    /// and clipped to `clip`.
+    pub fn render_onto_renderpass_at<'rp>(
+        &'rp self,
+        rpass: &mut wgpu::RenderPass<'rp>,
+        viewport: [f32; 4],
+        clip: CellRect,
+    ) {
+        self.render_within(
+            rpass,
+            Some(PassFrame {
+                viewport,
+                clip,
+                size: self.target.size(),
+            }),
+        );
+    }
+
+    fn render_within<'rp>(&'rp self, rpass: &mut wgpu::RenderPass<'rp>, pass: Option<PassFrame>) {
+        profile_scope!(
+            "egui_plot::draw",
+            vertices = self.vertex_count,
+            series = self.series.len(),
+            subplots = self.subplots.len()
+        );
+        if self.subplots.is_empty() {
+            self.render_passes(rpass, false);
+        }
+
+        self.set_pipeline(rpass);
+        let vertex_count = self.set_vertex_buffer(rpass);
+
+        if self.subplots.is_empty() {
+            self.push_debug_group(rpass, "egui_plot_layers");
+            if let Some(attributes) = &self.attributes {
+                rpass.set_bind_group(2, attributes.bind_group(), &[]);
+            }
+            #[cfg(feature = "heatmap")]
+            if let Some(bins) = &self.histogram {
+                if let Some(bind_group) = bins.density_bind_group() {
+                    rpass.set_bind_group(2, bind_group, &[]);
+                }
+                if bins.histogram().display == HistogramDisplay::Heatmap {
+                    bins.draw(rpass);
+                }
+            }
+
+            // These bind their own group 0, so go before the main bounds.
+            if let Some(logic) = &self.logic {
+                logic.draw(rpass);
+            }
+            if let Some(timeline) = &self.timeline {
+                timeline.draw(rpass);
+            }
+            #[cfg(feature = "compute")]
+            if let Some(merge) = &self.merge {
+                merge.draw(rpass);
+            }
+
+            rpass.set_bind_group(
+                0,
+                self.uniforms.bind_group(),
+                &[self.uniforms.offset(MAIN_SLOT)],
+            );
+            self.render_smith_grid(rpass);
+            self.render_thresholds(rpass);
+            self.pop_debug_group(rpass);
+
+            if !self.replaces_series() {
+                self.set_pipeline(rpass);
+                self.push_debug_group(rpass, "egui_plot_history");
+                self.render_history(rpass);
+                self.pop_debug_group(rpass);
+                self.draw_points(rpass, 0..vertex_count, DEFAULT_SERIES_SLOT);
+            }
+            self.push_debug_group(rpass, "egui_plot_overlays");
+            #[cfg(feature = "compute")]
+            self.render_diff(rpass);
+            self.render_markers(rpass);
+            self.render_reference(rpass);
+            self.render_passes(rpass, true);
+            // Binds its own group 0, so goes last.
+            if let Some(cursor) = &self.time_cursor {
+                cursor.draw(rpass);
+            }
+            self.pop_debug_group(rpass);
+
+            if let Some((inset, frame)) = &self.inset {
+                self.push_debug_group(rpass, "egui_plot_inset");
+                // The passes may have replaced the density counts.
+                if let Some(attributes) = &self.attributes {
+                    rpass.set_bind_group(2, attributes.bind_group(), &[]);
+                }
+                #[cfg(feature = "heatmap")]
+                if let Some(bind_group) =
+                    self.histogram.as_ref().and_then(|b| b.density_bind_group())
+                {
+                    rpass.set_bind_group(2, bind_group, &[]);
+                }
+                self.render_inset(rpass, inset, frame, vertex_count, pass);
+                self.pop_debug_group(rpass);
+            }
+            return;
+        }
+
+        // Each subplot is confined to its cell.
+        for (i, (cell, vertices)) in self.subplots.iter().enumerate() {
+            if cell.is_empty() || !set_viewport(rpass, cell, pass) {
+                continue;
+            }
+
+            rpass.set_bind_group(
+                0,
+                self.uniforms.bind_group(),
+                &[self.uniforms.offset(FIRST_SUBPLOT_SLOT + i)],
+            );
+
+            if self.debug_labels {
+                rpass.push_debug_group(&format!("egui_plot_subplot {}", i));
+            }
+            self.draw_points(rpass, self.drawn_vertices(vertices), DEFAULT_SERIES_SLOT);
+            self.pop_debug_group(rpass);
+        }
+    }
+
+    fn push_debug_group(&self, rpass: &mut wgpu::RenderPass, label: &str) {
+        if self.debug_labels {
+            rpass.push_debug_group(label);
+        }
+    }
+
+    fn pop_debug_group(&self, rpass: &mut wgpu::RenderPass) {
+        if self.debug_labels {
+            rpass.pop_debug_group();
+        }
+    }
+
+    // Whether a heatmap or merged points are drawn in place of the series.
+    fn replaces_series(&self) -> bool {
+        #[cfg(feature = "heatmap")]
+        if let Some(bins) = &self.histogram {
+            if bins.histogram().display == HistogramDisplay::Heatmap {
+                return true;
+            }
+        }
+        #[cfg(feature = "compute")]
+        if self.merge.is_some() {
+            return true;
+        }
+        false
+    }
+
+    // Draw the custom passes under the series, or on top of them, with the
+    // main bounds bound.
+    fn render_passes<'rp>(&'rp self, rpass: &mut wgpu::RenderPass<'rp>, foreground: bool) {
+        for (_, z_order, pass) in &self.passes {
+            if (*z_order >= 0) == foreground {
+                rpass.set_bind_group(
+                    0,
+                    self.uniforms.bind_group(),
+                    &[self.uniforms.offset(MAIN_SLOT)],
+                );
+                pass.render(rpass);
+            }
+        }
+    }
+
+    // Draw the inset's border and background, then the series again with the
+    // inset's bounds, confined to its rect.
+    fn render_inset<'rp>(
+        &'rp self,
+        rpass: &mut wgpu::RenderPass<'rp>,
+        inset: &Inset,
+        frame: &'rp InsetFrame,
+        vertex_count: u32,
+        pass: Option<PassFrame>,
+    ) {
+        let (inner, outer) = inset.rects(self.target.size());
+        if inner.is_empty() {
+            return;
+        }
+
+        rpass.set_pipeline(&self.pipeline);
+        rpass.set_vertex_buffer(0, frame.vertex_buffer.slice(..));
+        rpass.set_bind_group(
+            0,
+            self.uniforms.bind_group(),
+            &[self.uniforms.offset(INSET_FRAME_SLOT)],
+        );
+        rpass.set_bind_group(
+            1,
+            self.series_uniforms.bind_group(),
+            &[self.series_uniforms.offset(DEFAULT_SERIES_SLOT)],
+        );
+
+        if set_viewport(rpass, &outer, pass) {
+            rpass.draw(0..4, 0..1);
+        }
+        if !set_viewport(rpass, &inner, pass) {
+            return;
+        }
+        rpass.draw(4..8, 0..1);
+
+        self.set_pipeline(rpass);
+        rpass.set_bind_group(
+            0,
+            self.uniforms.bind_group(),
+            &[self.uniforms.offset(INSET_SLOT)],
+        );
+        self.draw_points(rpass, 0..vertex_count, DEFAULT_SERIES_SLOT);
+    }
+
+    // Draw the thresholds with the plain line pipeline, in whichever
+    // viewport is bound.
+    fn render_thresholds<'rp>(&'rp self, rpass: &mut wgpu::RenderPass<'rp>) {
+        if let Some((buffer, count)) = self.thresholds.vertices() {
+            rpass.set_pipeline(&self.pipeline);
+            rpass.set_vertex_buffer(0, buffer.slice(..));
+            rpass.set_bind_group(
+                1,
+                self.series_uniforms.bind_group(),
+                &[self.series_uniforms.offset(DEFAULT_SERIES_SLOT)],
+            );
+            rpass.draw(0..count, 0..1);
+        }
+    }
+
+    // Draw the marker regions over the series, like the thresholds.
+    fn render_markers<'rp>(&'rp self, rpass: &mut wgpu::RenderPass<'rp>) {
+        if let Some((buffer, count)) = self.markers.vertices() {
+            rpass.set_pipeline(&self.pipeline);
+            rpass.set_vertex_buffer(0, buffer.slice(..));
+            rpass.set_bind_group(
+                1,
+                self.series_uniforms.bind_group(),
+                &[self.series_uniforms.offset(DEFAULT_SERIES_SLOT)],
+            );
+            rpass.draw(0..count, 0..1);
+        }
+    }
+
+    // Draw the Smith chart's grid behind everything else in the main bounds.
+    fn render_smith_grid<'rp>(&'rp self, rpass: &mut wgpu::RenderPass<'rp>) {
+        if let Some((_, buffer, count)) = &self.smith_chart {
+            rpass.set_pipeline(&self.pipeline);
+            rpass.set_vertex_buffer(0, buffer.slice(..));
+            rpass.set_bind_group(
+                1,
+                self.series_uniforms.bind_group(),
+                &[self.series_uniforms.offset(DEFAULT_SERIES_SLOT)],
+            );
+            rpass.draw(0..*count, 0..1);
+        }
+    }
+
+    // Draw the constellation's reference symbols over the density.
+    fn render_reference<'rp>(&'rp self, rpass: &mut wgpu::RenderPass<'rp>) {
+        if let Some((buffer, count)) = &self.reference_vertices {
+            rpass.set_pipeline(&self.pipeline);
+            rpass.set_vertex_buffer(0, buffer.slice(..));
+            rpass.set_bind_group(
+                1,
+                self.series_uniforms.bind_group(),
+                &[self.series_uniforms.offset(DEFAULT_SERIES_SLOT)],
+            );
+            rpass.draw(0..*count, 0..1);
+        }
+    }
+
+    // Draw the derived diff series on top of the others, like the thresholds.
+    #[cfg(feature = "compute")]
+    fn render_diff<'rp>(&'rp self, rpass: &mut wgpu::RenderPass<'rp>) {
+        if let Some((buffer, count)) = self.diff.as_ref().and_then(|diff| diff.vertices()) {
+            rpass.set_pipeline(&self.pipeline);
+            rpass.set_vertex_buffer(0, buffer.slice(..));
+            rpass.set_bind_group(
+                1,
+                self.series_uniforms.bind_group(),
+                &[self.series_uniforms.offset(DEFAULT_SERIES_SLOT)],
+            );
+            rpass.draw(0..count, 0..1);
+        }
+    }
+
+    // Use the pipeline matching whichever vertices are being drawn.
+    fn set_pipeline<'rp>(&'rp self, rpass: &mut wgpu::RenderPass<'rp>) {
+        if let Some(pipeline) = self
+            .density_pipeline
+            .as_ref()
+            .or(self.attribute_pipeline.as_ref())
+        {
+            if self.subplots.is_empty() {
+                rpass.set_pipeline(pipeline);
+                return;
+            }
+        }
+
+        match &self.external {
+            Some((_, pipeline)) => rpass.set_pipeline(pipeline),
+            None => rpass.set_pipeline(&self.pipeline),
+        }
+    }
+
+    // Bind whichever vertices are being drawn, returning how many there are.
+    fn set_vertex_buffer<'rp>(&'rp self, rpass: &mut wgpu::RenderPass<'rp>) -> u32 {
+        match &self.external {
+            Some((points, _)) => {
+                rpass.set_vertex_buffer(0, points.buffer.slice(points.offset..));
+                points.count
+            }
+            None => {
+                rpass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+                self.vertex_count
+            }
+        }
+    }
+
+    // Draw only the glowing series, in the main viewport.
+    fn render_glow<'rp>(&'rp self, rpass: &mut wgpu::RenderPass<'rp>) {
+        rpass.set_bind_group(
+            0,
+            self.uniforms.bind_group(),
+            &[self.uniforms.offset(MAIN_SLOT)],
+        );
+
+        for (slice, first, count) in self.vertex_slices() {
+            rpass.set_vertex_buffer(0, slice);
+            for &i in &self.draw_order {
+                let series = &self.series[i];
+                let vertices = self.drawn_vertices(&series.vertices);
+                let start = vertices.start.max(first);
+                let end = vertices.end.min(count);
+                let glow = series.effective_style(&self.styles).glow;
+                if !glow || !series.group.visible || start >= end {
+                    continue;
+                }
+
+                rpass.set_bind_group(
+                    1,
+                    self.series_uniforms.bind_group(),
+                    &[self
+                        .series_uniforms
+                        .offset(DEFAULT_SERIES_SLOT + self.style_slot(i))],
+                );
+                rpass.draw(start - first..end - first, 0..1);
+            }
+        }
+    }
+
+    // Write the points to the vertex buffer, and any more buffers needed
+    // past its capacity. Each buffer after the first starts with the last
+    // sample of the one before, so that their strips join up.
+    fn write_chunks(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, points: &[Vertex]) {
+        let capacity = self.chunk_capacity as usize;
+        let stride = capacity - 2;
+        let chunks = if points.len() > capacity {
+            1 + (points.len() - capacity + stride - 1) / stride
+        } else {
+            1
+        };
+
+        while self.vertex_chunks.len() + 1 < chunks {
+            self.vertex_chunks
+                .push(device.create_buffer(&wgpu::BufferDescriptor {
+                    label: Some("egui_plot_vertex_chunk"),
+                    size: (capacity * std::mem::size_of::<Vertex>()) as wgpu::BufferAddress,
+                    usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::VERTEX,
+                    mapped_at_creation: false,
+                }));
+        }
+        self.vertex_chunks.truncate(chunks - 1);
+        self.diagnostics.vertex_buffers = chunks;
+
+        let buffers = iter::once(&self.vertex_buffer).chain(&self.vertex_chunks);
+        for (k, buffer) in buffers.enumerate() {
+            let start = k * stride;
+            let end = (start + capacity).min(points.len());
+            queue.write_buffer(buffer, 0, bytemuck::cast_slice(&points[start..end]));
+        }
+    }
+
+    // The buffers holding the drawn vertices, each with the index of its
+    // first vertex and the end of its vertices.
+    fn vertex_slices(&self) -> Vec<(wgpu::BufferSlice, u32, u32)> {
+        if let Some((points, _)) = &self.external {
+            return vec![(points.buffer.slice(points.offset..), 0, points.count)];
+        }
+
+        let stride = self.chunk_capacity - 2;
+        iter::once(&self.vertex_buffer)
+            .chain(&self.vertex_chunks)
+            .enumerate()
+            .map(|(k, buffer)| {
+                let first = k as u32 * stride;
+                let end = (first + self.chunk_capacity).min(self.vertex_count);
+                (buffer.slice(..), first, end)
+            })
+            .collect()
+    }
+
+    // The uploaded vertices drawing `vertices` of the points passed in,
+    // which differ while the upload is decimated.
+    fn drawn_vertices(&self, vertices: &Range<u32>) -> Range<u32> {
+        match &self.vertex_map {
+            Some(map) if self.external.is_none() => map.map(vertices),
+            _ => vertices.clone(),
+        }
+    }
+
+    // Draw a range of the drawn vertices, from whichever buffers hold them.
+    fn draw_points<'rp>(
+        &'rp self,
+        rpass: &mut wgpu::RenderPass<'rp>,
+        vertices: Range<u32>,
+        first_slot: usize,
+    ) {
+        for (slice, first, count) in self.vertex_slices() {
+            if vertices.start.max(first) < vertices.end.min(count) {
+                rpass.set_vertex_buffer(0, slice);
+                self.draw_series(rpass, vertices.clone(), count, first, first_slot);
+            }
+        }
+    }
+
+    // Draw the history snapshots from oldest to newest, with the series
+    // uniform slots faded for their age.
+    fn render_history<'rp>(&'rp self, rpass: &mut wgpu::RenderPass<'rp>) {
+        let history = match &self.history {
+            Some(history) if self.external.is_none() => history,
+            _ => return,
+        };
+
+        for (age, buffer, count) in history.snapshots() {
+            rpass.set_vertex_buffer(0, buffer.slice(..));
+            let first_slot =
+                DEFAULT_SERIES_SLOT + age * (1 + self.series.len() + self.styles.len());
+            self.draw_series(rpass, 0..count, count, 0, first_slot);
+        }
+    }
+
+    // Draw a range of vertices, split up between the series they belong to,
+    // from the lowest z order to the highest, with the series uniforms from
+    // `first_slot` on. The bound buffer holds vertices from `first` to
+    // `vertex_count`.
+    fn draw_series<'rp>(
+        &'rp self,
+        rpass: &mut wgpu::RenderPass<'rp>,
+        vertices: Range<u32>,
+        vertex_count: u32,
+        first: u32,
+        first_slot: usize,
+    ) {
+        let bind_group = self.series_uniforms.bind_group();
+
+        if self.series.is_empty() {
+            let end = vertices.end.min(vertex_count);
+            let start = vertices.start.max(first).min(end);
+            rpass.set_bind_group(1, bind_group, &[self.series_uniforms.offset(first_slot)]);
+            rpass.draw(start - first..end - first, 0..1);
+            return;
+        }
+
+        for &i in &self.draw_order {
+            let series = &self.series[i];
+            let drawn = self.drawn_vertices(&series.vertices);
+            let start = vertices.start.max(drawn.start).max(first);
+            let end = vertices.end.min(drawn.end).min(vertex_count);
+            if !series.group.visible || start >= end {
+                continue;
+            }
+
+            rpass.set_bind_group(
+                1,
+                bind_group,
+                &[self.series_uniforms.offset(first_slot + self.style_slot(i))],
+            );
+
+            if self.debug_labels {
+                match &series.name {
+                    Some(name) => rpass.push_debug_group(name),
+                    None => rpass.push_debug_group(&format!("series {}", i)),
+                }
+            }
+            match &series.instances {
+                Some(instances) => {
+                    rpass.set_pipeline(&instances.pipeline);
+                    rpass.set_vertex_buffer(1, instances.buffer.slice(..));
+                    rpass.draw(start - first..end - first, 0..instances.count);
+                    self.set_pipeline(rpass);
+                }
+                None => rpass.draw(start - first..end - first, 0..1),
+            }
+            self.pop_debug_group(rpass);
+        }
+    }
+}
+
+// Where the plot's texture lands in a render pass set up by someone else: the
+// viewport it was given, in the pass's pixels, and the rect it's clipped to.
+#[cfg(feature = "gpu")]
+#[derive(Clone, Copy)]
+struct PassFrame {
+    viewport: [f32; 4],
+    clip: CellRect,
+    size: [u32; 2],
+}
+
+// Confine drawing to a rect of the target. The viewport maps the NDC range
+// onto the rect, and the scissor clips line widths that spill over its edge.
+// Within a pass frame the rect is scaled into its viewport and clipped to its
+// clip rect. Returns false if nothing of the rect is visible.
+#[cfg(feature = "gpu")]
+fn set_viewport(
+    rpass: &mut wgpu::RenderPass<'_>,
+    rect: &CellRect,
+    pass: Option<PassFrame>,
+) -> bool {
+    let pass = match pass {
+        Some(pass) => pass,
+        None => {
+            rpass.set_viewport(
+                rect.x as f32,
+                rect.y as f32,
+                rect.width as f32,
+                rect.height as f32,
+                0.0,
+                1.0,
+            );
+            rpass.set_scissor_rect(rect.x, rect.y, rect.width, rect.height);
+            return true;
+        }
+    };
+
+    let [left, top, width, height] = pass.viewport;
+    let scale_x = width / pass.size[0].max(1) as f32;
+    let scale_y = height / pass.size[1].max(1) as f32;
+    let x = left + rect.x as f32 * scale_x;
+    let y = top + rect.y as f32 * scale_y;
+    let w = rect.width as f32 * scale_x;
+    let h = rect.height as f32 * scale_y;
+
+    let clip = pass.clip;
+    let x0 = x.round().max(clip.x as f32).max(0.0) as u32;
+    let y0 = y.round().max(clip.y as f32).max(0.0) as u32;
+    let x1 = (x + w).round().min((clip.x + clip.width) as f32).max(0.0) as u32;
+    let y1 = (y + h).round().min((clip.y + clip.height) as f32).max(0.0) as u32;
+    if x1 <= x0 || y1 <= y0 {
+        return false;
+    }
+
+    rpass.set_viewport(x, y, w, h, 0.0, 1.0);
+    rpass.set_scissor_rect(x0, y0, x1 - x0, y1 - y0);
+    true
 }
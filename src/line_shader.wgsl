@@ -1,40 +1,246 @@
 struct VertexOut {
     @location(0) color: vec4<f32>,
     @location(1) norm: vec2<f32>,
+    // Which period a folded vertex came from.
+    @location(2) fold: f32,
     @builtin(position) position: vec4<f32>,
 };
 
 struct Uniforms {
     x_range: vec2<f32>,
     y_range: vec2<f32>,
+    // Axis breaks as (start, end) pairs, two to a vec4. Empty breaks are
+    // no-ops.
+    x_breaks: array<vec4<f32>, 2>,
+    y_breaks: array<vec4<f32>, 2>,
+};
+
+struct Series {
+    scale: f32,
+    offset: f32,
+    transfer: u32,
+    alpha: f32,
+    gradient_key: u32,
+    stop_count: u32,
+    key_range: vec2<f32>,
+    stops: array<vec4<f32>, 8>,
+    // Period and phase, with a zero period meaning no folding.
+    fold: vec2<f32>,
+    // Multiplies LINE_WIDTH.
+    width: f32,
+    coordinates: u32,
+    // Last vertex, length in vertices and falloff, with a zero length
+    // meaning no trail.
+    trail: vec4<f32>,
 };
 
 @group(0) @binding(0)
 var<uniform> uniforms: Uniforms;
 
+@group(1) @binding(0)
+var<uniform> series: Series;
+
+// Histogram counts, only bound for density coloring.
+@group(2) @binding(0)
+var<uniform> bins: BinParams;
+@group(2) @binding(1)
+var<storage, read> bin_counts: array<u32>;
+
+struct AttributeParams {
+    columns: u32,
+    color_column: u32,
+    size_column: u32,
+    stop_count: u32,
+    color_range: vec2<f32>,
+    size_range: vec2<f32>,
+    widths: vec2<f32>,
+    stops: array<vec4<f32>, 8>,
+};
+
+// Per-point attribute columns, only bound when coloring or sizing by them.
+@group(2) @binding(2)
+var<uniform> attributes: AttributeParams;
+@group(2) @binding(3)
+var<storage, read> attribute_values: array<f32>;
+
 let LINE_WIDTH: f32 = 0.002;
 let FEATHER: f32 = 0.50;
 
+// WGSL only has a base 2 logarithm.
+let LOG10_2: f32 = 0.30102999566;
+
+let TRANSFER_DECIBELS: u32 = 1u;
+let TRANSFER_POWER_DECIBELS: u32 = 2u;
+
+let GRADIENT_X: u32 = 1u;
+
+let COORDINATES_IMPEDANCE: u32 = 1u;
+
+let NO_COLUMN: u32 = 0xffffffffu;
+
+// How much of the range below `value` a pair of breaks cut out.
+fn break_shift(value: f32, breaks: vec4<f32>) -> f32 {
+    return clamp(value - breaks.x, 0.0, breaks.y - breaks.x)
+         + clamp(value - breaks.z, 0.0, breaks.w - breaks.z);
+}
+
+// Map data values to axis units, collapsing the axis breaks.
+fn axis_x(value: f32) -> f32 {
+    return value - break_shift(value, uniforms.x_breaks[0]) - break_shift(value, uniforms.x_breaks[1]);
+}
+
+fn axis_y(value: f32) -> f32 {
+    return value - break_shift(value, uniforms.y_breaks[0]) - break_shift(value, uniforms.y_breaks[1]);
+}
+
+// Map a raw value to the series' display units.
+fn map_value(y: f32) -> f32 {
+    var value = y;
+    if (series.transfer == TRANSFER_DECIBELS) {
+        value = 20.0 * LOG10_2 * log2(y);
+    } else if (series.transfer == TRANSFER_POWER_DECIBELS) {
+        value = 10.0 * LOG10_2 * log2(y);
+    }
+    return series.scale * value + series.offset;
+}
+
+// The reflection coefficient (z - 1) / (z + 1) of a normalized impedance,
+// for Smith charts.
+fn reflection(z: vec2<f32>) -> vec2<f32> {
+    let n = vec2(z.x - 1.0, z.y);
+    let d = vec2(z.x + 1.0, z.y);
+    return vec2(n.x * d.x + n.y * d.y, n.y * d.x - n.x * d.y) / dot(d, d);
+}
+
+// Sample the series' gradient at t (0..1), interpolating between stops.
+fn gradient_color(t: f32) -> vec4<f32> {
+    if (series.stop_count == 1u) {
+        return series.stops[0];
+    }
+
+    let scaled = clamp(t, 0.0, 1.0) * f32(series.stop_count - 1u);
+    let i = min(u32(scaled), series.stop_count - 2u);
+    return mix(series.stops[i], series.stops[i + 1u], scaled - f32(i));
+}
+
 @vertex
-fn vs_main(@location(0) position: vec2<f32>,
+fn vs_main(@builtin(vertex_index) index: u32,
+           @location(0) position: vec2<f32>,
            @location(1) norm: vec2<f32>,
            @location(2) color: vec4<f32>) -> VertexOut {
+    return line_vertex(index, position, norm, color, 1.0);
+}
+
+// Draws the same vertices once per instance, each moved, scaled and tinted
+// by its instance.
+@vertex
+fn vs_instanced(@builtin(vertex_index) index: u32,
+                @location(0) position: vec2<f32>,
+                @location(1) norm: vec2<f32>,
+                @location(2) color: vec4<f32>,
+                @location(3) instance_offset: vec2<f32>,
+                @location(4) instance_scale: vec2<f32>,
+                @location(5) instance_color: vec4<f32>) -> VertexOut {
+    return line_vertex(index, position * instance_scale + instance_offset, norm, color * instance_color, 1.0);
+}
+
+// Colors each point by the count of the histogram bin it falls in.
+@vertex
+fn vs_density(@builtin(vertex_index) index: u32,
+              @location(0) position: vec2<f32>,
+              @location(1) norm: vec2<f32>,
+              @location(2) color: vec4<f32>) -> VertexOut {
+    var density = color;
+    let bin = bin_index(bins, bin_coords(bins, position));
+    if (bin >= 0) {
+        density = bin_color(bins, bin_counts[bin], bin_counts[arrayLength(&bin_counts) - 1u]);
+    }
+    return line_vertex(index, position, norm, density, 1.0);
+}
+
+// The value in `column` of the point a vertex belongs to, one row of
+// columns per pair of vertices.
+fn attribute(index: u32, column: u32) -> f32 {
+    let i = index / 2u * attributes.columns + column;
+    if (i >= arrayLength(&attribute_values)) {
+        return 0.0;
+    }
+    return attribute_values[i];
+}
+
+// Colors and sizes each point by its attribute columns.
+@vertex
+fn vs_attributes(@builtin(vertex_index) index: u32,
+                 @location(0) position: vec2<f32>,
+                 @location(1) norm: vec2<f32>,
+                 @location(2) color: vec4<f32>) -> VertexOut {
+    var base = color;
+    if (attributes.color_column != NO_COLUMN && attributes.stop_count > 0u) {
+        let range = attributes.color_range;
+        let t = clamp((attribute(index, attributes.color_column) - range.x) / (range.y - range.x), 0.0, 1.0);
+        let scaled = t * f32(attributes.stop_count - 1u);
+        let i = min(u32(scaled), max(attributes.stop_count, 2u) - 2u);
+        let j = min(i + 1u, attributes.stop_count - 1u);
+        base = mix(attributes.stops[i], attributes.stops[j], scaled - f32(i));
+    }
+
+    var width = 1.0;
+    if (attributes.size_column != NO_COLUMN) {
+        let range = attributes.size_range;
+        let t = clamp((attribute(index, attributes.size_column) - range.x) / (range.y - range.x), 0.0, 1.0);
+        width = mix(attributes.widths.x, attributes.widths.y, t);
+    }
+
+    return line_vertex(index, position, norm, base, width);
+}
+
+fn line_vertex(index: u32, position: vec2<f32>, norm: vec2<f32>, color: vec4<f32>, scale: f32) -> VertexOut {
     var out: VertexOut;
 
     let width = (uniforms.x_range[1] - uniforms.x_range[0]);
     let height = (uniforms.y_range[1] - uniforms.y_range[0]);
 
+    // Fold x onto 0..period, remembering which period it came from.
+    var data_x = position.x;
+    var fold = 0.0;
+    if (series.fold[0] > 0.0) {
+        let shifted = (data_x - series.fold[1]) / series.fold[0];
+        fold = floor(shifted);
+        data_x = (shifted - fold) * series.fold[0];
+    }
+
+    var data = vec2(data_x, map_value(position.y));
+    if (series.coordinates == COORDINATES_IMPEDANCE) {
+        data = reflection(data);
+    }
+
     // Convert from data space (x0..x1, y0..y1) to view space (-1..1, -1..1).
-    let x = mix(-1.0, 1.0, (position.x - uniforms.x_range[0]) / width);
-    let y = mix(-1.0, 1.0, (position.y - uniforms.y_range[0]) / height);
+    let x = mix(-1.0, 1.0, (axis_x(data.x) - uniforms.x_range[0]) / width);
+    let y = mix(-1.0, 1.0, (axis_y(data.y) - uniforms.y_range[0]) / height);
 
     // Move the point along the normal by LINE_WIDTH. If the normals are
     // provided such that they are sequentially flipped, this forms a triangle
     // strip the width of the line.
-    let delta = vec4(LINE_WIDTH * norm, 0.0, 0.0);
+    let delta = vec4(LINE_WIDTH * series.width * scale * norm, 0.0, 0.0);
+
+    var base = color;
+    if (series.stop_count > 0u) {
+        var key = f32(index);
+        if (series.gradient_key == GRADIENT_X) {
+            key = position.x;
+        }
+        base = gradient_color((key - series.key_range[0]) / (series.key_range[1] - series.key_range[0]));
+    }
 
-    out.color = color;
+    var alpha = base.w * series.alpha;
+    if (series.trail.y > 0.0) {
+        let age = (series.trail.x - f32(index)) / series.trail.y;
+        alpha = alpha * pow(clamp(1.0 - age, 0.0, 1.0), series.trail.z);
+    }
+
+    out.color = vec4(base.xyz, alpha);
     out.norm = norm;
+    out.fold = fold;
     out.position = vec4<f32>(x, y, 0.0, 1.0) + delta;
 
     return out;
@@ -42,6 +248,12 @@ fn vs_main(@location(0) position: vec2<f32>,
 
 @fragment
 fn fs_main(in: VertexOut) -> @location(0) vec4<f32> {
+    // Triangles joining the end of one period to the start of the next span
+    // the whole plot, so skip them.
+    if (abs(in.fold - round(in.fold)) > 0.01) {
+        discard;
+    }
+
     // At the edge of the line (final FEATHER % width) feather out the alpha
     // channel to zero.
     let alpha = smoothstep(0.0, 1.0, (1.0 - length(in.norm)) / FEATHER);
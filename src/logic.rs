@@ -0,0 +1,338 @@
+#[cfg(feature = "gpu")]
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+#[cfg(feature = "gpu")]
+use crate::Uniform;
+
+// Three quads of two triangles, see the logic shader.
+#[cfg(feature = "gpu")]
+const VERTICES_PER_RUN: u32 = 18;
+
+/// How a [`LogicTrack`]'s values are drawn.
+#[derive(Clone, Debug, PartialEq)]
+pub enum LogicStyle {
+    /// A waveform between low (zero) and high (anything else) levels.
+    Boolean { color: [f32; 4] },
+    /// A block per state, colored from the palette by value (wrapping
+    /// around).
+    Enumerated { palette: Vec<[f32; 4]> },
+}
+
+/// A run of equal values, lasting until the next run starts.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LogicRun {
+    pub start: f32,
+    pub value: u32,
+}
+
+/// One channel of a logic analyzer view, drawn as a thin lane spanning
+/// `lane` in the plot's y units, so that it can be stacked under analog
+/// traces in the same plot. Samples are run-length encoded as they're
+/// pushed, and each run is expanded into quads on the GPU.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LogicTrack {
+    pub lane: [f32; 2],
+    pub style: LogicStyle,
+    runs: Vec<LogicRun>,
+    end: f32,
+}
+
+impl LogicTrack {
+    pub fn boolean(lane: [f32; 2], color: [f32; 4]) -> LogicTrack {
+        LogicTrack::new(lane, LogicStyle::Boolean { color })
+    }
+
+    pub fn enumerated(lane: [f32; 2], palette: &[[f32; 4]]) -> LogicTrack {
+        LogicTrack::new(
+            lane,
+            LogicStyle::Enumerated {
+                palette: palette.to_vec(),
+            },
+        )
+    }
+
+    pub fn new(lane: [f32; 2], style: LogicStyle) -> LogicTrack {
+        LogicTrack {
+            lane,
+            style,
+            runs: Vec::new(),
+            end: 0.0,
+        }
+    }
+
+    /// Append a sample at `x`, which must not be before the previous one.
+    /// The last run lasts until the last sample.
+    pub fn push(&mut self, x: f32, value: u32) {
+        if self.runs.last().map_or(true, |run| run.value != value) {
+            self.runs.push(LogicRun { start: x, value });
+        }
+        self.end = x;
+    }
+
+    pub fn clear(&mut self) {
+        self.runs.clear();
+    }
+
+    pub fn runs(&self) -> &[LogicRun] {
+        &self.runs
+    }
+
+    #[cfg(feature = "gpu")]
+    fn instances(&self) -> impl Iterator<Item = LogicInstance> + '_ {
+        let ends = self.runs.iter().skip(1).map(|run| run.start);
+        self.runs
+            .iter()
+            .zip(ends.chain(Some(self.end)))
+            .map(|(run, end)| {
+                let (color, level, block) = match &self.style {
+                    LogicStyle::Boolean { color } => {
+                        (*color, if run.value != 0 { 1.0 } else { 0.0 }, 0)
+                    }
+                    LogicStyle::Enumerated { palette } => {
+                        let color = palette
+                            .get(run.value as usize % palette.len().max(1))
+                            .copied()
+                            .unwrap_or([1.0; 4]);
+                        (color, 1.0, 1)
+                    }
+                };
+
+                LogicInstance {
+                    x: [run.start, end],
+                    lane: self.lane,
+                    color,
+                    level,
+                    block,
+                }
+            })
+    }
+}
+
+#[cfg(feature = "gpu")]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct LogicInstance {
+    x: [f32; 2],
+    lane: [f32; 2],
+    color: [f32; 4],
+    level: f32,
+    block: u32,
+}
+
+// Laid out to match `Params` in the logic shader.
+#[cfg(feature = "gpu")]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+struct Params {
+    x_range: [f32; 2],
+    y_range: [f32; 2],
+    pixel: [f32; 2],
+    _padding: [f32; 2],
+}
+
+// The logic track pipelines, shared between plots like the line pipelines.
+#[cfg(feature = "gpu")]
+pub(crate) struct LogicPipelines {
+    shader: wgpu::ShaderModule,
+    bind_group_layout: wgpu::BindGroupLayout,
+    pipeline_layout: wgpu::PipelineLayout,
+    pipelines: Mutex<HashMap<(wgpu::TextureFormat, u32), Arc<wgpu::RenderPipeline>>>,
+}
+
+#[cfg(feature = "gpu")]
+impl LogicPipelines {
+    pub fn new(device: &wgpu::Device) -> LogicPipelines {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("egui_plot_logic_shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("./logic_shader.wgsl").into()),
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("egui_plot_logic_bind_group_layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("egui_plot_logic_pipeline_layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        LogicPipelines {
+            shader,
+            bind_group_layout,
+            pipeline_layout,
+            pipelines: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn pipeline(
+        &self,
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        sample_count: u32,
+    ) -> Arc<wgpu::RenderPipeline> {
+        let mut pipelines = self
+            .pipelines
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let pipeline = pipelines.entry((format, sample_count)).or_insert_with(|| {
+            Arc::new(
+                device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                    label: Some("egui_plot_logic_pipeline"),
+                    layout: Some(&self.pipeline_layout),
+                    vertex: wgpu::VertexState {
+                        module: &self.shader,
+                        entry_point: "vs_main",
+                        buffers: &[wgpu::VertexBufferLayout {
+                            array_stride: std::mem::size_of::<LogicInstance>()
+                                as wgpu::BufferAddress,
+                            step_mode: wgpu::VertexStepMode::Instance,
+                            attributes: &wgpu::vertex_attr_array![
+                                0 => Float32x2,
+                                1 => Float32x2,
+                                2 => Float32x4,
+                                3 => Float32,
+                                4 => Uint32,
+                            ],
+                        }],
+                    },
+                    fragment: Some(wgpu::FragmentState {
+                        module: &self.shader,
+                        entry_point: "fs_main",
+                        targets: &[Some(wgpu::ColorTargetState {
+                            format,
+                            blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                            write_mask: wgpu::ColorWrites::ALL,
+                        })],
+                    }),
+                    primitive: wgpu::PrimitiveState::default(),
+                    depth_stencil: None,
+                    multisample: wgpu::MultisampleState {
+                        count: sample_count,
+                        ..Default::default()
+                    },
+                    multiview: None,
+                }),
+            )
+        });
+
+        Arc::clone(pipeline)
+    }
+}
+
+// A plot's logic tracks, as one instance per run.
+#[cfg(feature = "gpu")]
+pub(crate) struct LogicLayer {
+    pub pipeline: Arc<wgpu::RenderPipeline>,
+    instances: Option<wgpu::Buffer>,
+    capacity: usize,
+    count: u32,
+    params: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    written: Option<Params>,
+}
+
+#[cfg(feature = "gpu")]
+impl LogicLayer {
+    pub fn new(
+        device: &wgpu::Device,
+        shared: &LogicPipelines,
+        format: wgpu::TextureFormat,
+        sample_count: u32,
+    ) -> LogicLayer {
+        let params = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("egui_plot_logic_params"),
+            size: std::mem::size_of::<Params>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::UNIFORM,
+            mapped_at_creation: false,
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("egui_plot_logic_bind_group"),
+            layout: &shared.bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: params.as_entire_binding(),
+            }],
+        });
+
+        LogicLayer {
+            pipeline: shared.pipeline(device, format, sample_count),
+            instances: None,
+            capacity: 0,
+            count: 0,
+            params,
+            bind_group,
+            written: None,
+        }
+    }
+
+    pub fn set_tracks(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        tracks: &[LogicTrack],
+    ) {
+        let instances: Vec<LogicInstance> = tracks.iter().flat_map(|t| t.instances()).collect();
+
+        if instances.len() > self.capacity {
+            self.capacity = instances.len().next_power_of_two();
+            self.instances = Some(device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("egui_plot_logic_instances"),
+                size: (self.capacity * std::mem::size_of::<LogicInstance>()) as wgpu::BufferAddress,
+                usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::VERTEX,
+                mapped_at_creation: false,
+            }));
+        }
+
+        if let Some(buffer) = &self.instances {
+            queue.write_buffer(buffer, 0, bytemuck::cast_slice(&instances));
+        }
+        self.count = instances.len() as u32;
+    }
+
+    // Follow the plot's bounds and size. Returns whether they changed.
+    pub fn update(&mut self, queue: &wgpu::Queue, bounds: &Uniform, size: [u32; 2]) -> bool {
+        let [x0, x1] = bounds.x_bounds;
+        let [y0, y1] = bounds.y_bounds;
+        let params = Params {
+            x_range: bounds.x_bounds,
+            y_range: bounds.y_bounds,
+            pixel: [
+                (x1 - x0) / size[0].max(1) as f32,
+                (y1 - y0) / size[1].max(1) as f32,
+            ],
+            _padding: [0.0; 2],
+        };
+
+        if self.written == Some(params) {
+            return false;
+        }
+
+        queue.write_buffer(&self.params, 0, bytemuck::bytes_of(&params));
+        self.written = Some(params);
+        true
+    }
+
+    pub fn draw<'rp>(&'rp self, rpass: &mut wgpu::RenderPass<'rp>) {
+        if let Some(instances) = self.instances.as_ref().filter(|_| self.count > 0) {
+            rpass.set_pipeline(&self.pipeline);
+            rpass.set_bind_group(0, &self.bind_group, &[]);
+            rpass.set_vertex_buffer(0, instances.slice(..));
+            rpass.draw(0..VERTICES_PER_RUN, 0..self.count);
+        }
+    }
+}
@@ -0,0 +1,83 @@
+struct VertexOut {
+    @location(0) color: vec4<f32>,
+    @builtin(position) position: vec4<f32>,
+};
+
+struct Params {
+    x_range: vec2<f32>,
+    y_range: vec2<f32>,
+    // The size of one pixel, in data units.
+    pixel: vec2<f32>,
+    _padding: vec2<f32>,
+};
+
+@group(0) @binding(0)
+var<uniform> params: Params;
+
+// Width of level lines and edges, in pixels.
+let LINE_PIXELS: f32 = 1.5;
+
+// Opacity of the fill under high boolean levels, relative to the line.
+let HIGH_FILL_ALPHA: f32 = 0.25;
+
+// Each run is expanded into three quads of two triangles each: the fill,
+// the level line and the edge where the run starts. Blocks of enumerated
+// states only use the fill.
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32,
+           @location(0) x: vec2<f32>,
+           @location(1) lane: vec2<f32>,
+           @location(2) color: vec4<f32>,
+           @location(3) level: f32,
+           @location(4) block: u32) -> VertexOut {
+    var out: VertexOut;
+
+    let quad = index / 6u;
+    let c = index % 6u;
+    let corner = vec2<f32>(
+        select(0.0, 1.0, c == 1u || c == 2u || c == 4u),
+        select(0.0, 1.0, c == 2u || c == 4u || c == 5u),
+    );
+
+    let line = LINE_PIXELS * 0.5 * params.pixel;
+    let level_y = mix(lane[0], lane[1], level);
+
+    var lo = vec2<f32>(x[0], lane[0]);
+    var hi = vec2<f32>(x[1], lane[1]);
+    var alpha = color.w;
+
+    if (quad == 0u) {
+        if (block != 0u) {
+            // Leave a pixel between neighboring blocks.
+            lo.x = lo.x + abs(params.pixel.x);
+        } else {
+            hi.y = level_y;
+            alpha = alpha * HIGH_FILL_ALPHA;
+        }
+    } else if (quad == 1u) {
+        lo.y = level_y - line.y;
+        hi.y = level_y + line.y;
+    } else {
+        lo.x = x[0] - line.x;
+        hi.x = x[0] + line.x;
+    }
+
+    if (block != 0u && quad > 0u) {
+        hi = lo;
+    }
+
+    let p = mix(lo, hi, corner);
+    let origin = vec2<f32>(params.x_range[0], params.y_range[0]);
+    let size = vec2<f32>(params.x_range[1], params.y_range[1]) - origin;
+    let ndc = (p - origin) / size * 2.0 - 1.0;
+
+    out.color = vec4<f32>(color.xyz, alpha);
+    out.position = vec4<f32>(ndc, 0.0, 1.0);
+
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOut) -> @location(0) vec4<f32> {
+    return in.color;
+}
@@ -0,0 +1,74 @@
+struct VertexOut {
+    @location(0) color: vec4<f32>,
+    // Position relative to the marker's center, in pixels.
+    @location(1) local: vec2<f32>,
+    @location(2) radius: f32,
+    @builtin(position) position: vec4<f32>,
+};
+
+@group(0) @binding(0)
+var<uniform> params: MergeParams;
+@group(0) @binding(1)
+var<storage, read> points: array<f32>;
+@group(0) @binding(2)
+var<storage, read> cells: array<u32>;
+
+// One quad per cell, which is empty unless points fell in the cell.
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32,
+           @builtin(instance_index) cell: u32) -> VertexOut {
+    var out: VertexOut;
+
+    let count = cells[2u * cell];
+    if (count == 0u) {
+        // Outside of the clip volume.
+        out.position = vec4<f32>(2.0, 2.0, 0.0, 1.0);
+        return out;
+    }
+
+    // A lone point is drawn where it is, and merged points at the center of
+    // their cell.
+    var center: vec2<f32>;
+    if (count == 1u) {
+        let i = cells[2u * cell + 1u] - 1u;
+        let word = 2u * i * params.stride + params.position;
+        center = viewport_coords(params, vec2<f32>(points[word], points[word + 1u])) * params.size;
+    } else {
+        let c = vec2<f32>(f32(cell % params.cells.x), f32(cell / params.cells.x));
+        center = (c + 0.5) * params.cell_size;
+    }
+
+    // Size and brightness grow with the count, relative to the largest.
+    let max_count = cells[arrayLength(&cells) - 1u];
+    let t = log2(1.0 + f32(count)) / log2(1.0 + f32(max_count));
+    let s = select(0.0, log2(f32(count)) / log2(f32(max_count)), max_count > 1u);
+    let radius = mix(params.marker_size, params.max_marker_size, s) * 0.5;
+
+    let c = index % 6u;
+    let corner = vec2<f32>(
+        select(-1.0, 1.0, c == 1u || c == 2u || c == 4u),
+        select(-1.0, 1.0, c == 2u || c == 4u || c == 5u),
+    );
+
+    // Grow the quad by a pixel to leave room for antialiasing.
+    let local = corner * (radius + 1.0);
+    let ndc = (center + local) / params.size * 2.0 - 1.0;
+
+    out.color = vec4<f32>(params.color.xyz * (0.5 + 0.5 * t), params.color.w);
+    out.local = local;
+    out.radius = radius;
+    out.position = vec4<f32>(ndc, 0.0, 1.0);
+
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOut) -> @location(0) vec4<f32> {
+    let d = length(in.local) - in.radius;
+    let alpha = clamp(0.5 - d, 0.0, 1.0) * in.color.w;
+    if (alpha <= 0.0) {
+        discard;
+    }
+
+    return vec4<f32>(in.color.xyz, alpha);
+}
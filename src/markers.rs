@@ -0,0 +1,484 @@
+#[cfg(feature = "compute")]
+use std::ops::Range;
+
+#[cfg(feature = "compute")]
+use crate::{readback::Mapping, VertexLayout};
+use crate::{LineBatch, Uniform, Vertex};
+
+#[cfg(feature = "compute")]
+const WORKGROUP_SIZE: u32 = 64;
+#[cfg(feature = "compute")]
+const MAX_WORKGROUPS: u32 = 65535;
+
+// The grips at the top of the handles, as fractions of the view.
+const GRIP_WIDTH: f32 = 0.006;
+const GRIP_HEIGHT: f32 = 0.04;
+
+/// A span of the x axis with a handle at each end, e.g. a loop or a trim
+/// region on an audio timeline. Drawn over the series.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MarkerRegion {
+    /// The x values of the start and end handles.
+    pub x: [f32; 2],
+    pub fill: [f32; 4],
+    pub handle_color: [f32; 4],
+}
+
+impl MarkerRegion {
+    pub fn new(x: [f32; 2]) -> MarkerRegion {
+        MarkerRegion {
+            x,
+            fill: [0.3, 0.5, 1.0, 0.15],
+            handle_color: [0.3, 0.5, 1.0, 1.0],
+        }
+    }
+
+    pub fn with_colors(self, fill: [f32; 4], handle_color: [f32; 4]) -> MarkerRegion {
+        MarkerRegion {
+            fill,
+            handle_color,
+            ..self
+        }
+    }
+
+    fn push_vertices(&self, batch: &mut LineBatch, bounds: &Uniform) {
+        let ([x0, x1], [y0, y1]) = bounds.data_bounds();
+        let [start, end] = [self.x[0].min(self.x[1]), self.x[0].max(self.x[1])];
+
+        // The region, as a solid quad (zero normals).
+        let [lo, hi] = [start.max(x0), end.min(x1)];
+        if lo < hi {
+            batch.push(
+                &[[lo, y0], [hi, y0], [lo, y1], [hi, y1]].map(|position| Vertex {
+                    position,
+                    normal: [0.0, 0.0],
+                    color: self.fill,
+                }),
+            );
+        }
+
+        let grip = [(x1 - x0) * GRIP_WIDTH, (y1 - y0) * GRIP_HEIGHT];
+        for x in [start, end] {
+            if x < x0 || x > x1 {
+                continue;
+            }
+            batch.push(
+                &[[y0, 1.0], [y0, -1.0], [y1, 1.0], [y1, -1.0]].map(|[y, n]| Vertex {
+                    position: [x, y],
+                    normal: [n, 0.0],
+                    color: self.handle_color,
+                }),
+            );
+            let [left, right] = [x - grip[0], x + grip[0]];
+            batch.push(
+                &[
+                    [left, y1 - grip[1]],
+                    [right, y1 - grip[1]],
+                    [left, y1],
+                    [right, y1],
+                ]
+                .map(|position| Vertex {
+                    position,
+                    normal: [0.0, 0.0],
+                    color: self.handle_color,
+                }),
+            );
+        }
+    }
+}
+
+/// One of the two handles of a [`MarkerRegion`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MarkerHandle {
+    Start,
+    End,
+}
+
+impl MarkerHandle {
+    pub(crate) fn index(self) -> usize {
+        match self {
+            MarkerHandle::Start => 0,
+            MarkerHandle::End => 1,
+        }
+    }
+}
+
+/// A change made to a [`MarkerRegion`] by dragging or snapping one of its
+/// handles, reported so the application can apply it to its own model.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RegionEdit {
+    /// Index of the region in the slice being edited.
+    pub region: usize,
+    pub handle: MarkerHandle,
+    pub from: f32,
+    pub to: f32,
+}
+
+/// The state of dragging the handles of marker regions.
+///
+/// With snapping on, releasing a handle leaves it waiting for a snap: pass
+/// [`MarkerEditor::pending_snap`] to
+/// [`GpuAcceleratedPlot::request_zero_crossing`](crate::GpuAcceleratedPlot::request_zero_crossing),
+/// and the crossing it finds to [`MarkerEditor::snap`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct MarkerEditor {
+    /// How close the pointer must be to grab a handle, in pixels.
+    pub tolerance: f32,
+    /// How far a released handle may move to snap to a zero crossing, in
+    /// pixels, or zero to not snap.
+    pub snap_distance: f32,
+    // The handle being dragged, and where it started.
+    dragging: Option<(usize, MarkerHandle, f32)>,
+    // The released handle waiting to snap, where the drag started, and how
+    // far it may move, in data units.
+    snapping: Option<(usize, MarkerHandle, f32, f32)>,
+}
+
+impl Default for MarkerEditor {
+    fn default() -> Self {
+        MarkerEditor {
+            tolerance: 6.0,
+            snap_distance: 0.0,
+            dragging: None,
+            snapping: None,
+        }
+    }
+}
+
+impl MarkerEditor {
+    pub fn new() -> MarkerEditor {
+        MarkerEditor::default()
+    }
+
+    pub fn with_tolerance(self, tolerance: f32) -> MarkerEditor {
+        MarkerEditor { tolerance, ..self }
+    }
+
+    pub fn with_snapping(self, snap_distance: f32) -> MarkerEditor {
+        MarkerEditor {
+            snap_distance,
+            ..self
+        }
+    }
+
+    /// The region and handle being dragged, if any.
+    pub fn dragging(&self) -> Option<(usize, MarkerHandle)> {
+        self.dragging.map(|(region, handle, _)| (region, handle))
+    }
+
+    /// Start dragging the handle nearest to `pointer_x` within the
+    /// tolerance, where `to_screen` maps x values to pixels. Returns whether
+    /// one was grabbed.
+    pub fn grab(
+        &mut self,
+        regions: &[MarkerRegion],
+        pointer_x: f32,
+        to_screen: impl Fn(f32) -> f32,
+    ) -> bool {
+        let nearest = regions
+            .iter()
+            .enumerate()
+            .flat_map(|(i, region)| {
+                [MarkerHandle::Start, MarkerHandle::End]
+                    .map(|handle| (i, handle, region.x[handle.index()]))
+            })
+            .map(|(i, handle, x)| (i, handle, x, (to_screen(x) - pointer_x).abs()))
+            .filter(|&(_, _, _, distance)| distance <= self.tolerance)
+            .min_by(|a, b| a.3.total_cmp(&b.3));
+
+        self.dragging = nearest.map(|(i, handle, x, _)| (i, handle, x));
+        self.snapping = None;
+        self.dragging.is_some()
+    }
+
+    /// Move the dragged handle to `x`, updating `regions`. Returns the edit,
+    /// relative to where the drag started.
+    pub fn drag(&mut self, regions: &mut [MarkerRegion], x: f32) -> Option<RegionEdit> {
+        let (region, handle, from) = self.dragging?;
+        regions.get_mut(region)?.x[handle.index()] = x;
+        Some(RegionEdit {
+            region,
+            handle,
+            from,
+            to: x,
+        })
+    }
+
+    /// Stop dragging. With snapping on, `pixel` is the width of a pixel in
+    /// x units, to convert the snap distance.
+    pub fn release(&mut self, pixel: f32) {
+        if let Some((region, handle, from)) = self.dragging.take() {
+            if self.snap_distance > 0.0 {
+                self.snapping = Some((region, handle, from, self.snap_distance * pixel.abs()));
+            }
+        }
+    }
+
+    /// The x value of the released handle waiting to snap and how far it may
+    /// move, given the regions it was released in.
+    pub fn pending_snap(&self, regions: &[MarkerRegion]) -> Option<(f32, f32)> {
+        let (region, handle, _, radius) = self.snapping?;
+        Some((regions.get(region)?.x[handle.index()], radius))
+    }
+
+    /// Move the released handle to the zero crossing found for
+    /// [`MarkerEditor::pending_snap`], or leave it if there wasn't one.
+    /// Returns the whole edit, from where the drag started.
+    pub fn snap(
+        &mut self,
+        regions: &mut [MarkerRegion],
+        crossing: Option<f32>,
+    ) -> Option<RegionEdit> {
+        let (region, handle, from, _) = self.snapping.take()?;
+        let x = &mut regions.get_mut(region)?.x[handle.index()];
+        if let Some(crossing) = crossing {
+            *x = crossing;
+        }
+        Some(RegionEdit {
+            region,
+            handle,
+            from,
+            to: *x,
+        })
+    }
+}
+
+// The vertices of a plot's marker regions, rebuilt whenever the regions or
+// the bounds they span change.
+#[cfg(feature = "gpu")]
+pub(crate) struct MarkerLayer {
+    regions: Vec<MarkerRegion>,
+    vertex_buffer: Option<wgpu::Buffer>,
+    capacity: usize,
+    vertex_count: u32,
+    // The bounds the vertices were built for, or None if they are stale.
+    bounds: Option<Uniform>,
+}
+
+#[cfg(feature = "gpu")]
+impl MarkerLayer {
+    pub fn new() -> MarkerLayer {
+        MarkerLayer {
+            regions: Vec::new(),
+            vertex_buffer: None,
+            capacity: 0,
+            vertex_count: 0,
+            bounds: None,
+        }
+    }
+
+    pub fn set(&mut self, regions: Vec<MarkerRegion>) {
+        self.regions = regions;
+        self.bounds = None;
+    }
+
+    pub fn regions(&self) -> &[MarkerRegion] {
+        &self.regions
+    }
+
+    // Returns whether the vertices changed.
+    pub fn update(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, bounds: &Uniform) -> bool {
+        if self.bounds.as_ref() == Some(bounds) {
+            return false;
+        }
+        self.bounds = Some(*bounds);
+
+        let mut batch = LineBatch::new();
+        for region in &self.regions {
+            region.push_vertices(&mut batch, bounds);
+        }
+        let vertices = batch.vertices();
+
+        if vertices.len() > self.capacity {
+            self.capacity = vertices.len().next_power_of_two();
+            self.vertex_buffer = Some(device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("egui_plot_marker_vertices"),
+                size: (self.capacity * std::mem::size_of::<Vertex>()) as wgpu::BufferAddress,
+                usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::VERTEX,
+                mapped_at_creation: false,
+            }));
+        }
+
+        if let Some(buffer) = &self.vertex_buffer {
+            queue.write_buffer(buffer, 0, bytemuck::cast_slice(vertices));
+        }
+        self.vertex_count = vertices.len() as u32;
+        true
+    }
+
+    // The vertex buffer and vertex count, if there's anything to draw.
+    pub fn vertices(&self) -> Option<(&wgpu::Buffer, u32)> {
+        self.vertex_buffer
+            .as_ref()
+            .filter(|_| self.vertex_count > 0)
+            .map(|buffer| (buffer, self.vertex_count))
+    }
+}
+
+#[cfg(feature = "compute")]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct Params {
+    snap_x: f32,
+    radius: f32,
+    stride: u32,
+    position: u32,
+    first: u32,
+    end: u32,
+    _padding: [u32; 2],
+}
+
+// Finds the zero crossing nearest to an x value, on the GPU.
+#[cfg(feature = "compute")]
+pub(crate) struct CrossingFinder {
+    // Finding the smallest distance, then its position.
+    pipelines: [wgpu::ComputePipeline; 2],
+    params: wgpu::Buffer,
+    nearest: wgpu::Buffer,
+    staging: wgpu::Buffer,
+    // The search being read back.
+    pending: Option<Mapping>,
+}
+
+#[cfg(feature = "compute")]
+impl CrossingFinder {
+    pub fn new(device: &wgpu::Device) -> CrossingFinder {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("egui_plot_crossing_shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("./crossing_shader.wgsl").into()),
+        });
+
+        let pipelines = ["cs_distance", "cs_position"].map(|entry_point| {
+            device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some("egui_plot_crossing_pipeline"),
+                layout: None,
+                module: &shader,
+                entry_point,
+            })
+        });
+
+        let params = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("egui_plot_crossing_params"),
+            size: std::mem::size_of::<Params>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::UNIFORM,
+            mapped_at_creation: false,
+        });
+
+        // The distance's bits, then the crossing.
+        let size = (2 * std::mem::size_of::<u32>()) as wgpu::BufferAddress;
+        let nearest = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("egui_plot_crossing_nearest"),
+            size,
+            usage: wgpu::BufferUsages::STORAGE
+                | wgpu::BufferUsages::COPY_DST
+                | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let staging = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("egui_plot_crossing_staging"),
+            size,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        CrossingFinder {
+            pipelines,
+            params,
+            nearest,
+            staging,
+            pending: None,
+        }
+    }
+
+    // Start searching the samples of `vertices`, laid out as `layout` from
+    // byte `offset` of `points`, for the crossing nearest to `x` within
+    // `radius`. Returns false if the last search is still being read back.
+    #[allow(clippy::too_many_arguments)]
+    pub fn submit(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        points: &wgpu::Buffer,
+        layout: VertexLayout,
+        offset: wgpu::BufferAddress,
+        vertices: Range<u32>,
+        x: f32,
+        radius: f32,
+    ) -> bool {
+        if self.pending.is_some() {
+            return false;
+        }
+
+        let params = Params {
+            snap_x: x,
+            radius,
+            stride: (layout.stride / 4) as u32,
+            position: ((offset + layout.position) / 4) as u32,
+            first: vertices.start / 2,
+            end: vertices.end / 2,
+            _padding: [0; 2],
+        };
+        queue.write_buffer(&self.params, 0, bytemuck::bytes_of(&params));
+        queue.write_buffer(&self.nearest, 0, bytemuck::cast_slice(&[u32::MAX, 0]));
+
+        let samples = params.end.saturating_sub(params.first);
+        let workgroups = ((samples + WORKGROUP_SIZE - 1) / WORKGROUP_SIZE).clamp(1, MAX_WORKGROUPS);
+
+        let mut encoder =
+            device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        for pipeline in &self.pipelines {
+            // Each pipeline has its own derived layout.
+            let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("egui_plot_crossing_bind_group"),
+                layout: &pipeline.get_bind_group_layout(0),
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: self.params.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: points.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: self.nearest.as_entire_binding(),
+                    },
+                ],
+            });
+
+            let mut cpass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("egui_plot_crossing_pass"),
+            });
+            cpass.set_pipeline(pipeline);
+            cpass.set_bind_group(0, &bind_group, &[]);
+            cpass.dispatch_workgroups(workgroups, 1, 1);
+        }
+        let size = (2 * std::mem::size_of::<u32>()) as wgpu::BufferAddress;
+        encoder.copy_buffer_to_buffer(&self.nearest, 0, &self.staging, 0, size);
+        queue.submit(std::iter::once(encoder.finish()));
+
+        self.pending = Some(Mapping::start(&[self.staging.slice(..)]));
+        true
+    }
+
+    // The crossing found by the last search submitted, once read back:
+    // `Some(None)` if there was none within the radius.
+    pub fn finish(&mut self) -> Option<Option<f32>> {
+        let mapped = self.pending.as_ref()?.state()?;
+        let mapping = self.pending.take()?;
+        if !mapped {
+            mapping.unmap(&[&self.staging]);
+            return Some(None);
+        }
+
+        let [distance, x] = {
+            let data = self.staging.slice(..).get_mapped_range();
+            let words: &[u32] = bytemuck::cast_slice(&data);
+            [words[0], words[1]]
+        };
+        self.staging.unmap();
+
+        Some((distance != u32::MAX).then(|| f32::from_bits(x)))
+    }
+}
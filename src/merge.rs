@@ -0,0 +1,342 @@
+use crate::{Uniform, VertexLayout};
+
+const WORKGROUP_SIZE: u32 = 64;
+const MAX_WORKGROUPS: u32 = 65535;
+
+/// Draws the points as scatter markers, merging every point that falls in
+/// the same cell of the viewport (by default, the same pixel) into a single
+/// marker which grows and brightens with the number of points it stands
+/// for. Cells holding a single point draw it at its exact position, so
+/// zooming in goes back to individual markers. See
+/// [`GpuAcceleratedPlot::set_point_merge`](crate::GpuAcceleratedPlot::set_point_merge).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PointMerge {
+    /// The size of the cells points are merged within, in pixels.
+    pub cell_size: u32,
+    /// The diameter of a lone point's marker, in pixels.
+    pub marker_size: f32,
+    /// The diameter of the marker standing for the most points.
+    pub max_marker_size: f32,
+    /// Linear RGBA, at full brightness.
+    pub color: [f32; 4],
+}
+
+impl PointMerge {
+    pub fn new(color: [f32; 4]) -> PointMerge {
+        PointMerge {
+            cell_size: 1,
+            marker_size: 3.0,
+            max_marker_size: 9.0,
+            color,
+        }
+    }
+
+    pub fn with_cell_size(self, cell_size: u32) -> PointMerge {
+        PointMerge {
+            cell_size: cell_size.max(1),
+            ..self
+        }
+    }
+
+    pub fn with_marker_sizes(self, marker_size: f32, max_marker_size: f32) -> PointMerge {
+        PointMerge {
+            marker_size,
+            max_marker_size,
+            ..self
+        }
+    }
+}
+
+// Laid out to match `MergeParams` in the merge shaders.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+struct Params {
+    x_range: [f32; 2],
+    y_range: [f32; 2],
+    size: [f32; 2],
+    cells: [u32; 2],
+    stride: u32,
+    position: u32,
+    samples: u32,
+    cell_size: f32,
+    marker_size: f32,
+    max_marker_size: f32,
+    _padding: [f32; 2],
+    color: [f32; 4],
+}
+
+// Merges a plot's points into cells on the GPU on every render, and draws
+// one marker per occupied cell.
+pub(crate) struct MergeLayer {
+    merge: PointMerge,
+    marker_shader: wgpu::ShaderModule,
+    bind_group_layout: wgpu::BindGroupLayout,
+    clear: wgpu::ComputePipeline,
+    bin: wgpu::ComputePipeline,
+    pipeline: wgpu::RenderPipeline,
+    params: wgpu::Buffer,
+    cells: Option<wgpu::Buffer>,
+    cell_count: u32,
+    bind_groups: Option<(wgpu::BindGroup, wgpu::BindGroup)>,
+    written: Option<Params>,
+}
+
+impl MergeLayer {
+    pub fn new(
+        device: &wgpu::Device,
+        merge: PointMerge,
+        format: wgpu::TextureFormat,
+        sample_count: u32,
+    ) -> MergeLayer {
+        let merge_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("egui_plot_merge_shader"),
+            source: wgpu::ShaderSource::Wgsl(
+                concat!(
+                    include_str!("./merge.wgsl"),
+                    include_str!("./merge_shader.wgsl")
+                )
+                .into(),
+            ),
+        });
+        let marker_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("egui_plot_marker_shader"),
+            source: wgpu::ShaderSource::Wgsl(
+                concat!(
+                    include_str!("./merge.wgsl"),
+                    include_str!("./marker_shader.wgsl")
+                )
+                .into(),
+            ),
+        });
+
+        // Clearing only uses the cells, so give both entry points the same
+        // layout rather than deriving one for each.
+        let storage = |read_only| wgpu::BindingType::Buffer {
+            ty: wgpu::BufferBindingType::Storage { read_only },
+            has_dynamic_offset: false,
+            min_binding_size: None,
+        };
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("egui_plot_merge_bind_group_layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: storage(true),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: storage(false),
+                    count: None,
+                },
+            ],
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("egui_plot_merge_pipeline_layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let create = |entry_point| {
+            device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some("egui_plot_merge_pipeline"),
+                layout: Some(&pipeline_layout),
+                module: &merge_shader,
+                entry_point,
+            })
+        };
+        let clear = create("cs_clear");
+        let bin = create("cs_merge");
+
+        let params = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("egui_plot_merge_params"),
+            size: std::mem::size_of::<Params>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::UNIFORM,
+            mapped_at_creation: false,
+        });
+
+        MergeLayer {
+            merge,
+            pipeline: marker_pipeline(device, &marker_shader, format, sample_count),
+            marker_shader,
+            bind_group_layout,
+            clear,
+            bin,
+            params,
+            cells: None,
+            cell_count: 0,
+            bind_groups: None,
+            written: None,
+        }
+    }
+
+    pub fn merge(&self) -> &PointMerge {
+        &self.merge
+    }
+
+    // Follow changes to the plot's render format or sample count.
+    pub fn set_target(
+        &mut self,
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        sample_count: u32,
+    ) {
+        self.pipeline = marker_pipeline(device, &self.marker_shader, format, sample_count);
+    }
+
+    // Merge `count` vertices laid out as `layout` from byte `offset` of
+    // `points`, over `bounds` on a viewport of `size` pixels. Returns whether
+    // anything changed.
+    #[allow(clippy::too_many_arguments)]
+    pub fn update(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        bounds: &Uniform,
+        size: [u32; 2],
+        points: &wgpu::Buffer,
+        layout: VertexLayout,
+        offset: wgpu::BufferAddress,
+        count: u32,
+    ) -> bool {
+        let cell_size = self.merge.cell_size.max(1);
+        let cells = size.map(|n| ((n + cell_size - 1) / cell_size).max(1));
+        let cell_count = cells[0] * cells[1];
+
+        let params = Params {
+            x_range: bounds.x_bounds,
+            y_range: bounds.y_bounds,
+            size: size.map(|n| n.max(1) as f32),
+            cells,
+            stride: (layout.stride / 4) as u32,
+            position: ((offset + layout.position) / 4) as u32,
+            samples: count / 2,
+            cell_size: cell_size as f32,
+            marker_size: self.merge.marker_size,
+            max_marker_size: self.merge.max_marker_size,
+            _padding: [0.0; 2],
+            color: self.merge.color,
+        };
+
+        if cell_count != self.cell_count {
+            self.cell_count = cell_count;
+            self.cells = Some(device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("egui_plot_merge_cells"),
+                size: (2 * cell_count as u64 + 1) * std::mem::size_of::<u32>() as u64,
+                usage: wgpu::BufferUsages::STORAGE,
+                mapped_at_creation: false,
+            }));
+        }
+
+        // The points may have moved to another buffer, so bind them afresh.
+        if let Some(cells) = &self.cells {
+            let bind_group = |layout: &wgpu::BindGroupLayout| {
+                device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    label: Some("egui_plot_merge_bind_group"),
+                    layout,
+                    entries: &[
+                        wgpu::BindGroupEntry {
+                            binding: 0,
+                            resource: self.params.as_entire_binding(),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 1,
+                            resource: points.as_entire_binding(),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 2,
+                            resource: cells.as_entire_binding(),
+                        },
+                    ],
+                })
+            };
+            self.bind_groups = Some((
+                bind_group(&self.bind_group_layout),
+                bind_group(&self.pipeline.get_bind_group_layout(0)),
+            ));
+        }
+
+        if self.written == Some(params) {
+            return false;
+        }
+
+        queue.write_buffer(&self.params, 0, bytemuck::bytes_of(&params));
+        self.written = Some(params);
+        true
+    }
+
+    // Merge the points into their cells again.
+    pub fn dispatch(&self, encoder: &mut wgpu::CommandEncoder) {
+        let (bind_group, samples) = match (&self.bind_groups, self.written) {
+            (Some((bind_group, _)), Some(params)) => (bind_group, params.samples),
+            _ => return,
+        };
+        let workgroups =
+            |n: u32| ((n + WORKGROUP_SIZE - 1) / WORKGROUP_SIZE).clamp(1, MAX_WORKGROUPS);
+
+        let mut cpass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("egui_plot_merge_pass"),
+        });
+        cpass.set_bind_group(0, bind_group, &[]);
+
+        cpass.set_pipeline(&self.clear);
+        cpass.dispatch_workgroups(workgroups(2 * self.cell_count + 1), 1, 1);
+
+        cpass.set_pipeline(&self.bin);
+        cpass.dispatch_workgroups(workgroups(samples), 1, 1);
+    }
+
+    // Draw a marker for every occupied cell.
+    pub fn draw<'rp>(&'rp self, rpass: &mut wgpu::RenderPass<'rp>) {
+        if let Some((_, bind_group)) = &self.bind_groups {
+            rpass.set_pipeline(&self.pipeline);
+            rpass.set_bind_group(0, bind_group, &[]);
+            rpass.draw(0..6, 0..self.cell_count);
+        }
+    }
+}
+
+fn marker_pipeline(
+    device: &wgpu::Device,
+    shader: &wgpu::ShaderModule,
+    format: wgpu::TextureFormat,
+    sample_count: u32,
+) -> wgpu::RenderPipeline {
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("egui_plot_marker_pipeline"),
+        layout: None,
+        vertex: wgpu::VertexState {
+            module: shader,
+            entry_point: "vs_main",
+            buffers: &[],
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: shader,
+            entry_point: "fs_main",
+            targets: &[Some(wgpu::ColorTargetState {
+                format,
+                blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+        }),
+        primitive: wgpu::PrimitiveState::default(),
+        depth_stencil: None,
+        multisample: wgpu::MultisampleState {
+            count: sample_count,
+            ..Default::default()
+        },
+        multiview: None,
+    })
+}
@@ -0,0 +1,39 @@
+// Shared by the merge and marker shaders, which are each prefixed with this
+// file.
+
+struct MergeParams {
+    x_range: vec2<f32>,
+    y_range: vec2<f32>,
+    // The viewport's size, in pixels.
+    size: vec2<f32>,
+    cells: vec2<u32>,
+    // Vertex stride and position offset, in 4 byte words.
+    stride: u32,
+    position: u32,
+    samples: u32,
+    // Cell and marker sizes, in pixels.
+    cell_size: f32,
+    marker_size: f32,
+    max_marker_size: f32,
+    _padding: vec2<f32>,
+    color: vec4<f32>,
+};
+
+// Map a data position to 0..1 across the viewport.
+fn viewport_coords(p: MergeParams, position: vec2<f32>) -> vec2<f32> {
+    let origin = vec2<f32>(p.x_range[0], p.y_range[0]);
+    let size = vec2<f32>(p.x_range[1], p.y_range[1]) - origin;
+    return (position - origin) / size;
+}
+
+// The index of the cell containing a data position, or -1 outside of the
+// viewport (including NaNs).
+fn cell_index(p: MergeParams, position: vec2<f32>) -> i32 {
+    let c = viewport_coords(p, position) * p.size / p.cell_size;
+    let cells = vec2<f32>(p.cells);
+    if (!(c.x >= 0.0 && c.x < cells.x && c.y >= 0.0 && c.y < cells.y)) {
+        return -1;
+    }
+
+    return i32(floor(c.y)) * i32(p.cells.x) + i32(floor(c.x));
+}
@@ -0,0 +1,39 @@
+@group(0) @binding(0)
+var<uniform> params: MergeParams;
+@group(0) @binding(1)
+var<storage, read> points: array<f32>;
+// For each cell, the number of points in it and one plus the index of the
+// last of them, followed by the largest count.
+@group(0) @binding(2)
+var<storage, read_write> cells: array<atomic<u32>>;
+
+let WORKGROUP_SIZE: u32 = 64u;
+
+@compute @workgroup_size(64)
+fn cs_clear(@builtin(global_invocation_id) id: vec3<u32>,
+            @builtin(num_workgroups) groups: vec3<u32>) {
+    let total = groups.x * WORKGROUP_SIZE;
+    for (var i = id.x; i < arrayLength(&cells); i = i + total) {
+        atomicStore(&cells[i], 0u);
+    }
+}
+
+@compute @workgroup_size(64)
+fn cs_merge(@builtin(global_invocation_id) id: vec3<u32>,
+            @builtin(num_workgroups) groups: vec3<u32>) {
+    let total = groups.x * WORKGROUP_SIZE;
+    let max_index = arrayLength(&cells) - 1u;
+
+    for (var i = id.x; i < params.samples; i = i + total) {
+        // Vertices come in pairs with the same position, one for each normal.
+        let word = 2u * i * params.stride + params.position;
+        let position = vec2<f32>(points[word], points[word + 1u]);
+
+        let cell = cell_index(params, position);
+        if (cell >= 0) {
+            let count = atomicAdd(&cells[2 * cell], 1u) + 1u;
+            atomicMax(&cells[2 * cell + 1], i + 1u);
+            atomicMax(&cells[max_index], count);
+        }
+    }
+}
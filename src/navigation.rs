@@ -0,0 +1,252 @@
+use std::collections::VecDeque;
+
+use crate::Bounds;
+
+/// Views kept by default in each direction of a [`ZoomHistory`].
+pub const DEFAULT_ZOOM_HISTORY: usize = 64;
+
+/// Which axes a zoom scales, e.g. from the modifiers held while zooming
+/// (Ctrl for x only, Shift for y only).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ZoomAxes {
+    #[default]
+    Both,
+    X,
+    Y,
+}
+
+impl ZoomAxes {
+    pub fn x(self) -> bool {
+        self != ZoomAxes::Y
+    }
+
+    pub fn y(self) -> bool {
+        self != ZoomAxes::X
+    }
+}
+
+/// The views a plot was zoomed away from, to step back through them (and
+/// forward again) like a browser's history, e.g. in a deep zoom session.
+///
+/// Each zoom gesture is recorded once, however many steps it takes: report
+/// every step with [`ZoomHistory::zoomed`], and frames without one with
+/// [`ZoomHistory::settle`]. See `zoom_navigation` for the egui widget.
+#[derive(Clone, Debug)]
+pub struct ZoomHistory {
+    back: VecDeque<Bounds>,
+    forward: Vec<Bounds>,
+    capacity: usize,
+    zooming: bool,
+}
+
+impl Default for ZoomHistory {
+    fn default() -> ZoomHistory {
+        ZoomHistory::new(DEFAULT_ZOOM_HISTORY)
+    }
+}
+
+impl ZoomHistory {
+    pub fn new(capacity: usize) -> ZoomHistory {
+        ZoomHistory {
+            back: VecDeque::new(),
+            forward: Vec::new(),
+            capacity: capacity.max(1),
+            zooming: false,
+        }
+    }
+
+    /// Record a zoom step away from `before`. The first step of a gesture
+    /// saves `before` and clears the views ahead of it.
+    pub fn zoomed(&mut self, before: Bounds) {
+        if !self.zooming {
+            self.push(before);
+        }
+        self.zooming = true;
+    }
+
+    /// End the current zoom gesture, if any.
+    pub fn settle(&mut self) {
+        self.zooming = false;
+    }
+
+    /// Record a jump away from `before` which isn't a zoom gesture, e.g. a
+    /// reset or an autoscale.
+    pub fn push(&mut self, before: Bounds) {
+        if self.back.back() != Some(&before) {
+            self.save(before);
+        }
+        self.forward.clear();
+    }
+
+    // Save a view to step back to, forgetting the oldest past capacity.
+    fn save(&mut self, view: Bounds) {
+        self.back.push_back(view);
+        if self.back.len() > self.capacity {
+            self.back.pop_front();
+        }
+    }
+
+    /// The view before `current`, which becomes the next one forward.
+    pub fn zoom_back(&mut self, current: Bounds) -> Option<Bounds> {
+        let previous = self.back.pop_back()?;
+        self.forward.push(current);
+        self.zooming = false;
+        Some(previous)
+    }
+
+    /// The view `zoom_back` last left, which `current` comes before.
+    pub fn zoom_forward(&mut self, current: Bounds) -> Option<Bounds> {
+        let next = self.forward.pop()?;
+        self.save(current);
+        self.zooming = false;
+        Some(next)
+    }
+
+    pub fn can_zoom_back(&self) -> bool {
+        !self.back.is_empty()
+    }
+
+    pub fn can_zoom_forward(&self) -> bool {
+        !self.forward.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.back.clear();
+        self.forward.clear();
+        self.zooming = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn view(i: u32) -> Bounds {
+        Bounds::new([0.0, f64::from(i)], [0.0, 1.0])
+    }
+
+    // Step back through every view, returning them from newest to oldest.
+    fn back_views(history: &mut ZoomHistory, mut current: Bounds) -> Vec<Bounds> {
+        let mut views = Vec::new();
+        while let Some(previous) = history.zoom_back(current) {
+            views.push(previous);
+            current = previous;
+        }
+        views
+    }
+
+    #[test]
+    fn gestures_are_recorded_once() {
+        let mut history = ZoomHistory::default();
+        history.zoomed(view(0));
+        history.zoomed(view(1));
+        history.zoomed(view(2));
+        history.settle();
+        history.zoomed(view(3));
+        history.zoomed(view(4));
+
+        assert_eq!(back_views(&mut history, view(5)), [view(3), view(0)]);
+    }
+
+    #[test]
+    fn settling_without_a_gesture_records_nothing() {
+        let mut history = ZoomHistory::default();
+        history.settle();
+        history.settle();
+        assert!(!history.can_zoom_back());
+
+        history.zoomed(view(0));
+        history.settle();
+        history.settle();
+        history.zoomed(view(1));
+        assert_eq!(back_views(&mut history, view(2)), [view(1), view(0)]);
+    }
+
+    #[test]
+    fn stepping_ends_the_gesture() {
+        let mut history = ZoomHistory::default();
+        history.zoomed(view(0));
+        assert_eq!(history.zoom_back(view(1)), Some(view(0)));
+
+        // Zooming again, without settling, starts a new gesture.
+        history.zoomed(view(0));
+        assert!(!history.can_zoom_forward());
+        assert_eq!(history.zoom_back(view(2)), Some(view(0)));
+    }
+
+    #[test]
+    fn back_and_forward_retrace_the_views() {
+        let mut history = ZoomHistory::default();
+        history.push(view(0));
+        history.push(view(1));
+
+        assert_eq!(history.zoom_back(view(2)), Some(view(1)));
+        assert_eq!(history.zoom_back(view(1)), Some(view(0)));
+        assert_eq!(history.zoom_back(view(0)), None);
+        assert!(history.can_zoom_forward());
+
+        assert_eq!(history.zoom_forward(view(0)), Some(view(1)));
+        assert_eq!(history.zoom_forward(view(1)), Some(view(2)));
+        assert_eq!(history.zoom_forward(view(2)), None);
+        assert_eq!(back_views(&mut history, view(2)), [view(1), view(0)]);
+    }
+
+    #[test]
+    fn push_clears_forward() {
+        let mut history = ZoomHistory::default();
+        history.push(view(0));
+        history.push(view(1));
+        history.zoom_back(view(2));
+        assert!(history.can_zoom_forward());
+
+        history.push(view(1));
+        assert!(!history.can_zoom_forward());
+        assert_eq!(history.zoom_forward(view(3)), None);
+
+        // Starting a zoom gesture does too.
+        history.zoom_back(view(3));
+        assert!(history.can_zoom_forward());
+        history.zoomed(view(1));
+        assert!(!history.can_zoom_forward());
+    }
+
+    #[test]
+    fn push_skips_repeated_views() {
+        let mut history = ZoomHistory::default();
+        history.push(view(0));
+        history.push(view(0));
+        assert_eq!(back_views(&mut history, view(1)), [view(0)]);
+    }
+
+    #[test]
+    fn capacity_evicts_the_oldest() {
+        let mut history = ZoomHistory::new(3);
+        for i in 0..5 {
+            history.push(view(i));
+        }
+        assert_eq!(
+            back_views(&mut history, view(5)),
+            [view(4), view(3), view(2)]
+        );
+
+        // At least one view is always kept.
+        let mut history = ZoomHistory::new(0);
+        history.push(view(0));
+        history.push(view(1));
+        assert_eq!(back_views(&mut history, view(2)), [view(1)]);
+    }
+
+    #[test]
+    fn stepping_at_capacity_keeps_every_view() {
+        let mut history = ZoomHistory::new(2);
+        for i in 0..3 {
+            history.push(view(i));
+        }
+        assert_eq!(history.zoom_back(view(3)), Some(view(2)));
+        assert_eq!(history.zoom_back(view(2)), Some(view(1)));
+
+        assert_eq!(history.zoom_forward(view(1)), Some(view(2)));
+        assert_eq!(history.zoom_forward(view(2)), Some(view(3)));
+        assert_eq!(back_views(&mut history, view(3)), [view(2), view(1)]);
+    }
+}
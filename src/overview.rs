@@ -0,0 +1,149 @@
+use std::borrow::Cow;
+
+use crate::{
+    blit::Blitter,
+    budget,
+    target::{RenderTarget, TargetConfig},
+    Bounds, DecimationMode, Vertex, VertexBudget,
+};
+
+// The overview only needs to show the shape of the data, so it is drawn from
+// a decimated copy of the vertices, keeping the extremes so that spikes
+// still show.
+const OVERVIEW_MAX_POINTS: usize = 20_000;
+
+// A second viewport onto the plot's data, always showing the full extent of
+// the data at a coarse level of detail.
+pub(crate) struct Overview {
+    target: RenderTarget,
+
+    vertex_buffer: wgpu::Buffer,
+    vertex_count: u32,
+    data_bounds: Option<Bounds>,
+
+    invalidated: bool,
+    needs_render: bool,
+}
+
+impl Overview {
+    pub fn new(device: &wgpu::Device, blitter: &Blitter, config: TargetConfig) -> Overview {
+        let vertex_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("egui_plot_overview_vertices"),
+            size: (OVERVIEW_MAX_POINTS * std::mem::size_of::<Vertex>()) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::VERTEX,
+            mapped_at_creation: false,
+        });
+
+        let target = RenderTarget::new(
+            device,
+            blitter,
+            TargetConfig {
+                mipmaps: false,
+                ..config
+            },
+            1,
+            1,
+        );
+
+        Overview {
+            target,
+            vertex_buffer,
+            vertex_count: 0,
+            data_bounds: None,
+            invalidated: true,
+            needs_render: true,
+        }
+    }
+
+    pub fn data_bounds(&self) -> Option<Bounds> {
+        self.data_bounds
+    }
+
+    pub fn create_view(&self) -> wgpu::TextureView {
+        self.target.create_view()
+    }
+
+    // Rebuild the coarse copy of the data and its extents. Each segment
+    // between `boundaries` (e.g. where series start and end) is decimated
+    // separately, like the main plot's upload.
+    pub fn update_vertices(&mut self, queue: &wgpu::Queue, points: &[Vertex], boundaries: &[u32]) {
+        let budget = VertexBudget::new(OVERVIEW_MAX_POINTS);
+        let decimation = budget.decimation(points.len(), boundaries.len() + 1);
+        let coarse = if decimation > 1 {
+            let (decimated, _) =
+                budget::decimate_segments(points, boundaries, decimation, DecimationMode::MinMax);
+            Cow::Owned(decimated)
+        } else {
+            Cow::Borrowed(points)
+        };
+        // Many short segments can each round up past the budget.
+        let coarse = &coarse[..coarse.len().min(OVERVIEW_MAX_POINTS)];
+
+        self.vertex_count = coarse.len() as u32;
+        queue.write_buffer(&self.vertex_buffer, 0, bytemuck::cast_slice(coarse));
+
+        let mut min = [f64::INFINITY; 2];
+        let mut max = [f64::NEG_INFINITY; 2];
+        for p in points
+            .iter()
+            .filter(|p| p.position.iter().all(|c| c.is_finite()))
+        {
+            for ((lo, hi), &c) in min.iter_mut().zip(&mut max).zip(&p.position) {
+                *lo = lo.min(c as f64);
+                *hi = hi.max(c as f64);
+            }
+        }
+
+        self.data_bounds = (min[0] <= max[0]).then(|| Bounds::from_min_max(min, max));
+        self.invalidated = true;
+    }
+
+    // The overview bounds only follow the data, so it only needs to be
+    // rendered again after an upload or a resize.
+    pub fn prepare(&mut self, device: &wgpu::Device, blitter: &Blitter, dimensions: [u32; 2]) {
+        let resized = self.target.resize(device, blitter, dimensions);
+        self.needs_render = resized || std::mem::take(&mut self.invalidated);
+    }
+
+    // Follow changes to the main plot's target configuration, which the
+    // overview shares a pipeline with.
+    pub fn reconfigure(&mut self, device: &wgpu::Device, blitter: &Blitter, config: TargetConfig) {
+        let config = TargetConfig {
+            mipmaps: false,
+            ..config
+        };
+        self.invalidated |= self.target.reconfigure(device, blitter, config);
+    }
+
+    pub fn needs_render(&self) -> bool {
+        self.needs_render
+    }
+
+    pub fn render(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        pipeline: &wgpu::RenderPipeline,
+        uniforms: (&wgpu::BindGroup, wgpu::DynamicOffset),
+        series: (&wgpu::BindGroup, wgpu::DynamicOffset),
+        blitter: &Blitter,
+    ) {
+        {
+            let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("egui_plot_overview_pass"),
+                color_attachments: &[Some(
+                    self.target
+                        .color_attachment(wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT)),
+                )],
+                depth_stencil_attachment: None,
+            });
+
+            rpass.set_pipeline(pipeline);
+            rpass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+            rpass.set_bind_group(0, uniforms.0, &[uniforms.1]);
+            rpass.set_bind_group(1, series.0, &[series.1]);
+            rpass.draw(0..self.vertex_count, 0..1);
+        }
+
+        self.target.finish(encoder, blitter);
+    }
+}
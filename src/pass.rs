@@ -0,0 +1,52 @@
+use crate::PlotTransform;
+
+/// Identifies a pass added with
+/// [`GpuAcceleratedPlot::add_pass`](crate::GpuAcceleratedPlot::add_pass).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PassId(pub(crate) u64);
+
+/// What a [`PlotPass`] draws into, passed to [`PlotPass::prepare`].
+pub struct PassContext<'a> {
+    pub format: wgpu::TextureFormat,
+    pub sample_count: u32,
+    /// The layout of bind group 0, which is bound to the plot's bounds (a
+    /// [`Uniform`](crate::Uniform), with a dynamic offset) whenever
+    /// [`PlotPass::render`] is called. Use it as group 0 of the pass's
+    /// pipeline layout to transform data in its shaders as the lines are.
+    pub uniform_layout: &'a wgpu::BindGroupLayout,
+    pub transform: PlotTransform,
+    /// The plot as it was last rendered, single-sampled and in `format`,
+    /// while a pass returns true from [`PlotPass::reads_previous_frame`].
+    /// It alternates between the two textures of a
+    /// [`PingPong`](crate::PingPong), so bind it afresh in every prepare
+    /// (or keep a bind group for each). None on the first frame after the
+    /// plot is resized or reconfigured.
+    pub previous_frame: Option<&'a wgpu::TextureView>,
+}
+
+/// A custom GPU pass rendered into the plot's target along with the lines,
+/// e.g. map tiles underneath the data or markers of its own. See
+/// [`GpuAcceleratedPlot::add_pass`](crate::GpuAcceleratedPlot::add_pass).
+pub trait PlotPass: Send + Sync {
+    /// Called at the end of each
+    /// [`GpuAcceleratedPlot::prepare`](crate::GpuAcceleratedPlot::prepare),
+    /// to create pipelines for the target and upload data. Returns whether
+    /// anything changed, so that the plot is rendered again.
+    fn prepare(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        context: &PassContext,
+    ) -> bool;
+
+    /// Draw into the plot's render pass, with the bounds bound as group 0.
+    /// Any other state may be left as it was.
+    fn render<'rp>(&'rp self, rpass: &mut wgpu::RenderPass<'rp>);
+
+    /// Whether the pass samples [`PassContext::previous_frame`], e.g. for
+    /// feedback effects. The plot then keeps a copy of each frame, and is
+    /// rendered on every prepare so that the copies stay a frame apart.
+    fn reads_previous_frame(&self) -> bool {
+        false
+    }
+}
@@ -0,0 +1,105 @@
+use std::collections::VecDeque;
+
+use crate::{worker, Bounds, Trail, Vertex};
+
+/// An XY phase plot of a live dynamical system, e.g. the Lorenz attractor:
+/// the most recent points of its trajectory, oldest first, drawn with a
+/// [`Trail`] so that the head is solid and its history fades out.
+///
+/// Upload [`PhaseSpace::vertices`] as a series (passing the points as dirty
+/// after each push), then give it the trail with
+/// [`GpuAcceleratedPlot::set_trail`](crate::GpuAcceleratedPlot::set_trail),
+/// and [`GpuAcceleratedPlot::set_glow`](crate::GpuAcceleratedPlot::set_glow)
+/// for a glowing head.
+#[derive(Clone, Debug)]
+pub struct PhaseSpace {
+    points: VecDeque<[f64; 2]>,
+    /// Points kept, and faded over.
+    pub length: usize,
+    pub falloff: f32,
+    pub color: [f32; 4],
+}
+
+impl PhaseSpace {
+    pub fn new(length: usize) -> PhaseSpace {
+        PhaseSpace {
+            points: VecDeque::with_capacity(length),
+            length,
+            falloff: 2.0,
+            color: [0.4, 0.8, 1.0, 1.0],
+        }
+    }
+
+    pub fn with_falloff(self, falloff: f32) -> PhaseSpace {
+        PhaseSpace { falloff, ..self }
+    }
+
+    pub fn with_color(self, color: [f32; 4]) -> PhaseSpace {
+        PhaseSpace { color, ..self }
+    }
+
+    /// Append the newest point, dropping the oldest once there are
+    /// `length` of them.
+    pub fn push(&mut self, point: [f64; 2]) {
+        self.points.push_back(point);
+        while self.points.len() > self.length.max(1) {
+            self.points.pop_front();
+        }
+    }
+
+    pub fn extend(&mut self, points: impl IntoIterator<Item = [f64; 2]>) {
+        for point in points {
+            self.push(point);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.points.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    pub fn head(&self) -> Option<[f64; 2]> {
+        self.points.back().copied()
+    }
+
+    pub fn trail(&self) -> Trail {
+        Trail::new(self.length as u32).with_falloff(self.falloff)
+    }
+
+    pub fn vertices(&self) -> Vec<Vertex> {
+        let (front, back) = self.points.as_slices();
+        if back.is_empty() {
+            worker::expand(front, self.color)
+        } else {
+            let points: Vec<_> = self.points.iter().copied().collect();
+            worker::expand(&points, self.color)
+        }
+    }
+
+    /// The bounds of the points, with a tenth either side, e.g. to pass
+    /// through [`Bounds::with_data_aspect`] so that the phase portrait keeps
+    /// its shape.
+    pub fn bounds(&self) -> Option<Bounds> {
+        let (first, rest) = (self.points.front()?, self.points.iter().skip(1));
+        let [mut min, mut max] = [*first; 2];
+        for point in rest {
+            for i in 0..2 {
+                min[i] = min[i].min(point[i]);
+                max[i] = max[i].max(point[i]);
+            }
+        }
+
+        let pad = |i: usize| ((max[i] - min[i]) * 0.1).max(f64::EPSILON);
+        Some(Bounds::new(
+            [min[0] - pad(0), max[0] + pad(0)],
+            [min[1] - pad(1), max[1] + pad(1)],
+        ))
+    }
+}
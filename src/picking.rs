@@ -0,0 +1,89 @@
+use crate::{Bounds, Vertex};
+
+/// A single data sample found by picking.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sample {
+    /// Index of the sample's first vertex in the points passed to the plot.
+    pub index: usize,
+    pub position: [f32; 2],
+}
+
+/// The samples under one pixel column of the plot.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ColumnPick {
+    /// The sample with the lowest y value.
+    pub min: Sample,
+    /// The sample with the highest y value.
+    pub max: Sample,
+    /// Number of samples in the column.
+    pub count: usize,
+}
+
+impl ColumnPick {
+    /// Whichever extreme is closest to `y`, e.g. the cursor's position.
+    pub fn nearest(&self, y: f64) -> Sample {
+        if (self.max.position[1] as f64 - y).abs() < (self.min.position[1] as f64 - y).abs() {
+            self.max
+        } else {
+            self.min
+        }
+    }
+}
+
+/// Find the true extremes of the data under the pixel column containing
+/// data position `x`, for a plot `width` pixels wide showing `bounds`. Pass
+/// the width of the rendered texture, from
+/// [`GpuAcceleratedPlot::texture_size`](crate::GpuAcceleratedPlot::texture_size),
+/// for the columns to match the drawn pixels at any scale factor.
+///
+/// This searches the full data rather than what was drawn, so tooltips stay
+/// accurate while the render budget decimates the plot. It is a linear scan,
+/// so only call it when the cursor moves.
+pub fn pick_column(points: &[Vertex], bounds: &Bounds, width: u32, x: f64) -> Option<ColumnPick> {
+    if width == 0 || bounds.width() == 0.0 {
+        return None;
+    }
+
+    // Columns are counted from the low end, even if the axis is reversed.
+    let low = bounds.x[0].min(bounds.x[1]);
+    let pixel = bounds.width().abs() / width as f64;
+    let column = ((x - low) / pixel).floor();
+    let x0 = low + column * pixel;
+    let x1 = x0 + pixel;
+
+    let mut pick: Option<ColumnPick> = None;
+
+    // Vertices come in pairs with the same position, one for each normal.
+    for (pair, vertices) in points.chunks(2).enumerate() {
+        let position = vertices[0].position;
+        let px = position[0] as f64;
+        if px < x0 || px >= x1 || !position[1].is_finite() {
+            continue;
+        }
+
+        let sample = Sample {
+            index: pair * 2,
+            position,
+        };
+        match &mut pick {
+            None => {
+                pick = Some(ColumnPick {
+                    min: sample,
+                    max: sample,
+                    count: 1,
+                })
+            }
+            Some(pick) => {
+                if position[1] < pick.min.position[1] {
+                    pick.min = sample;
+                }
+                if position[1] > pick.max.position[1] {
+                    pick.max = sample;
+                }
+                pick.count += 1;
+            }
+        }
+    }
+
+    pick
+}
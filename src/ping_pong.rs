@@ -0,0 +1,128 @@
+use crate::{
+    blit::{BlitFilter, Blitter},
+    target::{create_texture, RenderTarget, TargetConfig},
+};
+
+/// A pair of same-sized textures which take turns being drawn into and
+/// read from, for effects that build on their own last output, e.g. trails
+/// fading a little more every frame. Both textures can be drawn into and
+/// sampled.
+pub struct PingPong {
+    textures: [(wgpu::Texture, wgpu::TextureView); 2],
+    current: usize,
+    format: wgpu::TextureFormat,
+    size: [u32; 2],
+}
+
+impl PingPong {
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat, size: [u32; 2]) -> PingPong {
+        let [width, height] = size.map(|n| n.max(1));
+        PingPong {
+            textures: [(); 2].map(|_| create_texture(device, format, 1, width, height, 1)),
+            current: 0,
+            format,
+            size,
+        }
+    }
+
+    pub fn format(&self) -> wgpu::TextureFormat {
+        self.format
+    }
+
+    pub fn size(&self) -> [u32; 2] {
+        self.size
+    }
+
+    /// Whether the pair can be kept for a target of `format` and `size`,
+    /// rather than allocating another.
+    pub fn is_compatible(&self, format: wgpu::TextureFormat, size: [u32; 2]) -> bool {
+        self.format == format && self.size == size
+    }
+
+    /// The texture to draw into this time.
+    pub fn current(&self) -> &wgpu::TextureView {
+        &self.textures[self.current].1
+    }
+
+    /// The texture drawn into last time, until the next swap.
+    pub fn previous(&self) -> &wgpu::TextureView {
+        &self.textures[1 - self.current].1
+    }
+
+    /// Which of the two textures is current, e.g. to keep a bind group for
+    /// each rather than creating one after every swap.
+    pub fn index(&self) -> usize {
+        self.current
+    }
+
+    pub fn texture(&self, index: usize) -> &wgpu::Texture {
+        &self.textures[index % 2].0
+    }
+
+    pub fn view(&self, index: usize) -> &wgpu::TextureView {
+        &self.textures[index % 2].1
+    }
+
+    /// Make the current texture the previous one, and draw into the other.
+    pub fn swap(&mut self) {
+        self.current = 1 - self.current;
+    }
+}
+
+// The plot's last rendered frames, for passes which read the previous one.
+// Each render copies the plot into the current texture.
+pub(crate) struct FrameHistory {
+    frames: Option<(([u32; 2], TargetConfig), PingPong, wgpu::BindGroup)>,
+    // Whether the previous texture holds a frame yet.
+    swapped: bool,
+}
+
+impl FrameHistory {
+    pub fn new() -> FrameHistory {
+        FrameHistory {
+            frames: None,
+            swapped: false,
+        }
+    }
+
+    // Follow changes to the plot's size and format, then start a new frame.
+    pub fn prepare(&mut self, device: &wgpu::Device, blitter: &Blitter, target: &RenderTarget) {
+        let key = (target.size(), target.config());
+        match &mut self.frames {
+            Some((k, frames, _)) if *k == key => {
+                frames.swap();
+                self.swapped = true;
+            }
+            _ => {
+                let format = key.1.render_format;
+                blitter.prepare_with(device, format, BlitFilter::Exact);
+                let frames = PingPong::new(device, format, key.0);
+                let copy = blitter.create_bind_group(device, target.color_view());
+                self.frames = Some((key, frames, copy));
+                self.swapped = false;
+            }
+        }
+    }
+
+    // The frame rendered before this one, if the size and format haven't
+    // changed since.
+    pub fn previous(&self) -> Option<&wgpu::TextureView> {
+        self.frames
+            .as_ref()
+            .filter(|_| self.swapped)
+            .map(|(_, frames, _)| frames.previous())
+    }
+
+    // Keep the frame just rendered.
+    pub fn record(&self, encoder: &mut wgpu::CommandEncoder, blitter: &Blitter) {
+        if let Some((_, frames, copy)) = &self.frames {
+            blitter.blit_with(
+                encoder,
+                copy,
+                frames.current(),
+                frames.format(),
+                BlitFilter::Exact,
+            );
+        }
+    }
+}
@@ -0,0 +1,338 @@
+use std::ops::Range;
+
+use crate::SeriesId;
+#[cfg(feature = "gpu")]
+use crate::{Uniform, VertexLayout};
+
+/// A marker travelling along a series by the time of each of its samples,
+/// to replay a simulation or a recorded trajectory on top of its full
+/// path. The marker is placed on the GPU, between the samples either side
+/// of the current time, so it moves smoothly however sparse the samples
+/// are. See [`GpuAcceleratedPlot::set_time_cursor`](crate::GpuAcceleratedPlot::set_time_cursor).
+#[derive(Clone, Debug, PartialEq)]
+pub struct TimeCursor {
+    /// The series to follow, or None for all of the points.
+    pub series: Option<SeriesId>,
+    /// The time of each of the series' samples, ascending.
+    pub times: Vec<f64>,
+    /// The current time, in the units of `times`.
+    pub time: f64,
+    /// How much `time` advances per second of playback.
+    pub speed: f64,
+    pub playing: bool,
+    /// Whether to start over from the first sample past the last one,
+    /// rather than pausing on it.
+    pub looping: bool,
+    /// In pixels.
+    pub radius: f32,
+    /// Linear RGBA.
+    pub color: [f32; 4],
+}
+
+impl TimeCursor {
+    /// A paused cursor at the first sample of `series`.
+    pub fn new(series: Option<SeriesId>, times: Vec<f64>) -> TimeCursor {
+        TimeCursor {
+            series,
+            time: times.first().copied().unwrap_or(0.0),
+            times,
+            speed: 1.0,
+            playing: false,
+            looping: false,
+            radius: 5.0,
+            color: [1.0, 1.0, 1.0, 1.0],
+        }
+    }
+
+    pub fn with_color(self, color: [f32; 4]) -> TimeCursor {
+        TimeCursor { color, ..self }
+    }
+
+    pub fn with_radius(self, radius: f32) -> TimeCursor {
+        TimeCursor { radius, ..self }
+    }
+
+    pub fn with_speed(self, speed: f64) -> TimeCursor {
+        TimeCursor { speed, ..self }
+    }
+
+    pub fn with_looping(self, looping: bool) -> TimeCursor {
+        TimeCursor { looping, ..self }
+    }
+
+    pub fn play(&mut self) {
+        // Playing from the end starts over.
+        if let Some(range) = self.range() {
+            if self.speed > 0.0 && self.time >= range.end {
+                self.time = range.start;
+            } else if self.speed < 0.0 && self.time <= range.start {
+                self.time = range.end;
+            }
+        }
+        self.playing = true;
+    }
+
+    pub fn pause(&mut self) {
+        self.playing = false;
+    }
+
+    pub fn toggle(&mut self) {
+        if self.playing {
+            self.pause();
+        } else {
+            self.play();
+        }
+    }
+
+    /// Jump to `time`, clamped to the samples' times.
+    pub fn seek(&mut self, time: f64) {
+        self.time = match self.range() {
+            Some(range) => time.clamp(range.start, range.end),
+            None => time,
+        };
+    }
+
+    /// Negative speeds play backwards.
+    pub fn set_speed(&mut self, speed: f64) {
+        self.speed = speed;
+    }
+
+    /// The times of the first and last samples.
+    pub fn range(&self) -> Option<Range<f64>> {
+        match (self.times.first(), self.times.last()) {
+            (Some(&first), Some(&last)) => Some(first..last),
+            _ => None,
+        }
+    }
+
+    /// Advance the time by `dt` seconds of playback, e.g. the frame time.
+    /// Returns whether the cursor is still playing, i.e. whether to request
+    /// another frame.
+    pub fn advance(&mut self, dt: f64) -> bool {
+        let range = match self.range() {
+            Some(range) if self.playing => range,
+            _ => return false,
+        };
+
+        let time = self.time + dt * self.speed;
+        let span = range.end - range.start;
+        self.time = if (range.start..=range.end).contains(&time) {
+            time
+        } else if self.looping && span > 0.0 {
+            range.start + (time - range.start).rem_euclid(span)
+        } else {
+            self.playing = false;
+            time.clamp(range.start, range.end)
+        };
+        self.playing
+    }
+
+    /// How far along the samples the current time is, interpolated between
+    /// the samples either side of it, e.g. 2.5 halfway between the third and
+    /// fourth.
+    pub fn sample_position(&self) -> Option<f32> {
+        let range = self.range()?;
+        let time = self.time.clamp(range.start, range.end);
+
+        // The first sample past the time.
+        let next = self.times.partition_point(|&t| t <= time);
+        if next == 0 || next >= self.times.len() {
+            return Some(next.saturating_sub(1) as f32);
+        }
+
+        let (a, b) = (self.times[next - 1], self.times[next]);
+        let t = if b > a { (time - a) / (b - a) } else { 0.0 };
+        Some((next - 1) as f32 + t as f32)
+    }
+}
+
+// Laid out to match `CursorParams` in the cursor shader.
+#[cfg(feature = "gpu")]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+struct Params {
+    x_range: [f32; 2],
+    y_range: [f32; 2],
+    size: [f32; 2],
+    stride: u32,
+    position: u32,
+    first: u32,
+    samples: u32,
+    sample: f32,
+    radius: f32,
+    color: [f32; 4],
+}
+
+// Draws a time cursor's marker, reading its samples straight from the
+// plot's points.
+#[cfg(feature = "gpu")]
+pub(crate) struct CursorLayer {
+    cursor: TimeCursor,
+    shader: wgpu::ShaderModule,
+    pipeline: wgpu::RenderPipeline,
+    params: wgpu::Buffer,
+    bind_group: Option<wgpu::BindGroup>,
+    written: Option<Params>,
+}
+
+#[cfg(feature = "gpu")]
+impl CursorLayer {
+    pub fn new(
+        device: &wgpu::Device,
+        cursor: TimeCursor,
+        format: wgpu::TextureFormat,
+        sample_count: u32,
+    ) -> CursorLayer {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("egui_plot_cursor_shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("./cursor_shader.wgsl").into()),
+        });
+        let params = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("egui_plot_cursor_params"),
+            size: std::mem::size_of::<Params>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::UNIFORM,
+            mapped_at_creation: false,
+        });
+
+        CursorLayer {
+            cursor,
+            pipeline: cursor_pipeline(device, &shader, format, sample_count),
+            shader,
+            params,
+            bind_group: None,
+            written: None,
+        }
+    }
+
+    pub fn cursor(&self) -> &TimeCursor {
+        &self.cursor
+    }
+
+    pub fn cursor_mut(&mut self) -> &mut TimeCursor {
+        &mut self.cursor
+    }
+
+    // Follow changes to the plot's render format or sample count.
+    pub fn set_target(
+        &mut self,
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        sample_count: u32,
+    ) {
+        self.pipeline = cursor_pipeline(device, &self.shader, format, sample_count);
+    }
+
+    // Place the cursor on `vertices` of `points`, laid out as `layout` from
+    // byte `offset`, over `bounds` on a viewport of `size` pixels. Returns
+    // whether anything changed.
+    #[allow(clippy::too_many_arguments)]
+    pub fn update(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        bounds: &Uniform,
+        size: [u32; 2],
+        points: &wgpu::Buffer,
+        layout: VertexLayout,
+        offset: wgpu::BufferAddress,
+        vertices: Range<u32>,
+    ) -> bool {
+        let samples = vertices.end.saturating_sub(vertices.start) / 2;
+        let position = match self.cursor.sample_position() {
+            Some(position) if samples > 0 => position,
+            _ => {
+                let changed = self.bind_group.is_some();
+                self.bind_group = None;
+                self.written = None;
+                return changed;
+            }
+        };
+
+        // The drawn samples may be decimated, so scale the position to them.
+        let times = self.cursor.times.len();
+        let sample = if times > 1 {
+            position * (samples - 1) as f32 / (times - 1) as f32
+        } else {
+            0.0
+        };
+
+        let params = Params {
+            x_range: bounds.x_bounds,
+            y_range: bounds.y_bounds,
+            size: size.map(|n| n.max(1) as f32),
+            stride: (layout.stride / 4) as u32,
+            position: ((offset + layout.position) / 4) as u32,
+            first: vertices.start / 2,
+            samples,
+            sample,
+            radius: self.cursor.radius,
+            color: self.cursor.color,
+        };
+
+        // The points may have moved to another buffer, so bind them afresh.
+        self.bind_group = Some(device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("egui_plot_cursor_bind_group"),
+            layout: &self.pipeline.get_bind_group_layout(0),
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: self.params.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: points.as_entire_binding(),
+                },
+            ],
+        }));
+
+        if self.written == Some(params) {
+            return false;
+        }
+
+        queue.write_buffer(&self.params, 0, bytemuck::bytes_of(&params));
+        self.written = Some(params);
+        true
+    }
+
+    pub fn draw<'rp>(&'rp self, rpass: &mut wgpu::RenderPass<'rp>) {
+        if let Some(bind_group) = &self.bind_group {
+            rpass.set_pipeline(&self.pipeline);
+            rpass.set_bind_group(0, bind_group, &[]);
+            rpass.draw(0..6, 0..1);
+        }
+    }
+}
+
+#[cfg(feature = "gpu")]
+fn cursor_pipeline(
+    device: &wgpu::Device,
+    shader: &wgpu::ShaderModule,
+    format: wgpu::TextureFormat,
+    sample_count: u32,
+) -> wgpu::RenderPipeline {
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("egui_plot_cursor_pipeline"),
+        layout: None,
+        vertex: wgpu::VertexState {
+            module: shader,
+            entry_point: "vs_main",
+            buffers: &[],
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: shader,
+            entry_point: "fs_main",
+            targets: &[Some(wgpu::ColorTargetState {
+                format,
+                blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+        }),
+        primitive: wgpu::PrimitiveState::default(),
+        depth_stencil: None,
+        multisample: wgpu::MultisampleState {
+            count: sample_count,
+            ..Default::default()
+        },
+        multiview: None,
+    })
+}
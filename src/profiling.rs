@@ -0,0 +1,13 @@
+// Open a profiling scope lasting until the end of the enclosing block: a
+// `tracing` span with the given fields, and a puffin scope, for whichever
+// of those features are enabled. The fields aren't evaluated otherwise.
+macro_rules! profile_scope {
+    ($name:literal $(, $field:ident = $value:expr)* $(,)?) => {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!($name $(, $field = $value)*).entered();
+        #[cfg(feature = "puffin")]
+        puffin::profile_scope!($name);
+    };
+}
+
+pub(crate) use profile_scope;
@@ -0,0 +1,181 @@
+use std::sync::{
+    atomic::{AtomicU8, Ordering},
+    Arc,
+};
+
+use crate::target::RenderTarget;
+#[cfg(feature = "compute")]
+use crate::{ColumnExtrema, Fit, Hit, SelectionEvent};
+
+const PENDING: u8 = 0;
+const MAPPED: u8 = 1;
+const FAILED: u8 = 2;
+
+/// A finished readback, from
+/// [`GpuAcceleratedPlot::poll_result`](crate::GpuAcceleratedPlot::poll_result).
+#[derive(Clone, Debug, PartialEq)]
+pub enum ReadbackResult {
+    /// From `request_hit_test`, `None` if nothing was close enough.
+    #[cfg(feature = "compute")]
+    Hit(Option<Hit>),
+    /// From `request_selection`, also passed to the selection callback.
+    #[cfg(feature = "compute")]
+    Selection(SelectionEvent),
+    /// From `request_breaches`.
+    #[cfg(feature = "compute")]
+    Breaches(Vec<u32>),
+    /// From `request_fit`, `None` if there were too few samples.
+    #[cfg(feature = "compute")]
+    Fit(Option<Fit>),
+    /// From `request_zero_crossing`, `None` if there was none close enough.
+    #[cfg(feature = "compute")]
+    ZeroCrossing(Option<f32>),
+    /// From `request_column_extrema`.
+    #[cfg(feature = "compute")]
+    ColumnExtrema(ColumnExtrema),
+    /// From `request_image`: the size and rows of 8 bit RGBA pixels, as
+    /// from `read_rgba`, or `None` if the read failed.
+    Image(Option<([u32; 2], Vec<u8>)>),
+}
+
+// Staging buffers being mapped for reading without blocking. The maps
+// complete within `device.poll` on native, and once control returns to the
+// browser on the web, so the results are checked for each frame rather
+// than waited for.
+pub(crate) struct Mapping {
+    states: Vec<Arc<AtomicU8>>,
+}
+
+impl Mapping {
+    pub fn start(slices: &[wgpu::BufferSlice]) -> Mapping {
+        let states = slices
+            .iter()
+            .map(|slice| {
+                let state = Arc::new(AtomicU8::new(PENDING));
+                let callback_state = Arc::clone(&state);
+                slice.map_async(wgpu::MapMode::Read, move |result| {
+                    let mapped = if result.is_ok() { MAPPED } else { FAILED };
+                    callback_state.store(mapped, Ordering::Release);
+                });
+                state
+            })
+            .collect();
+
+        Mapping { states }
+    }
+
+    // None while any map is pending, then whether all succeeded.
+    pub fn state(&self) -> Option<bool> {
+        let states: Vec<u8> = self
+            .states
+            .iter()
+            .map(|state| state.load(Ordering::Acquire))
+            .collect();
+        (!states.contains(&PENDING)).then(|| states.iter().all(|&state| state == MAPPED))
+    }
+
+    // Unmap whichever of `buffers`, in the order their slices were passed
+    // to `start`, were mapped, so that they can be copied into again.
+    pub fn unmap(&self, buffers: &[&wgpu::Buffer]) {
+        for (state, buffer) in self.states.iter().zip(buffers) {
+            if state.load(Ordering::Acquire) == MAPPED {
+                buffer.unmap();
+            }
+        }
+    }
+}
+
+// The plot texture being copied into a staging buffer, to be read back as
+// 8 bit RGBA pixels.
+pub(crate) struct ImageReadback {
+    buffer: wgpu::Buffer,
+    size: [u32; 2],
+    padded_row: u32,
+    bgra: bool,
+    mapping: Mapping,
+}
+
+impl ImageReadback {
+    // Returns None if the texture isn't 8 bit RGBA or BGRA.
+    pub fn start(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        target: &RenderTarget,
+    ) -> Option<ImageReadback> {
+        let bgra = match target.config().view_format {
+            wgpu::TextureFormat::Rgba8Unorm | wgpu::TextureFormat::Rgba8UnormSrgb => false,
+            wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb => true,
+            _ => return None,
+        };
+
+        let [width, height] = target.size();
+        let alignment = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let padded_row = (4 * width + alignment - 1) / alignment * alignment;
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("egui_plot_image_readback"),
+            size: (padded_row * height) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder =
+            device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        encoder.copy_texture_to_buffer(
+            wgpu::ImageCopyTexture {
+                texture: target.output_texture(),
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::ImageCopyBuffer {
+                buffer: &buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: std::num::NonZeroU32::new(padded_row),
+                    rows_per_image: None,
+                },
+            },
+            wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+        );
+        queue.submit(std::iter::once(encoder.finish()));
+
+        let mapping = Mapping::start(&[buffer.slice(..)]);
+        Some(ImageReadback {
+            buffer,
+            size: [width, height],
+            padded_row,
+            bgra,
+            mapping,
+        })
+    }
+
+    // The pixels once read back, or None inside if the read failed.
+    pub fn finish(&self) -> Option<Option<([u32; 2], Vec<u8>)>> {
+        if !self.mapping.state()? {
+            self.mapping.unmap(&[&self.buffer]);
+            return Some(None);
+        }
+
+        let [width, height] = self.size;
+        let row = 4 * width as usize;
+        let mut pixels = Vec::with_capacity(row * height as usize);
+        {
+            let data = self.buffer.slice(..).get_mapped_range();
+            for padded in data.chunks_exact(self.padded_row as usize) {
+                pixels.extend_from_slice(&padded[..row]);
+            }
+        }
+        self.buffer.unmap();
+
+        if self.bgra {
+            for pixel in pixels.chunks_exact_mut(4) {
+                pixel.swap(0, 2);
+            }
+        }
+        Some(Some((self.size, pixels)))
+    }
+}
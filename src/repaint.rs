@@ -0,0 +1,80 @@
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+/// Wakes up egui when new data arrives for a plot, so that idle plots don't
+/// need to repaint continuously.
+///
+/// Clone it into data producers (e.g. an acquisition thread) and call
+/// [`DataNotifier::notify`] after each batch of samples. On the UI side, a
+/// [`DataWatcher`] reports whether anything arrived since the last frame,
+/// which can be passed straight through as the `dirty` flag.
+#[derive(Clone)]
+pub struct DataNotifier {
+    ctx: egui::Context,
+    generation: Arc<AtomicU64>,
+}
+
+impl DataNotifier {
+    pub fn new(ctx: &egui::Context) -> DataNotifier {
+        DataNotifier {
+            ctx: ctx.clone(),
+            generation: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Mark the data as changed and request a repaint.
+    pub fn notify(&self) {
+        self.generation.fetch_add(1, Ordering::Release);
+        self.ctx.request_repaint();
+    }
+
+    /// Mark the data as changed, but let egui coalesce the repaint with
+    /// anything else happening within `delay`. Useful for high-rate streams
+    /// where repainting on every sample would be wasteful.
+    pub fn notify_after(&self, delay: Duration) {
+        self.generation.fetch_add(1, Ordering::Release);
+        self.ctx.request_repaint_after(delay);
+    }
+
+    pub fn watch(&self) -> DataWatcher {
+        DataWatcher {
+            generation: Arc::clone(&self.generation),
+            seen: None,
+        }
+    }
+}
+
+pub struct DataWatcher {
+    generation: Arc<AtomicU64>,
+    seen: Option<u64>,
+}
+
+impl DataWatcher {
+    /// Whether the data has changed since the last call. Always true the
+    /// first time, so that the initial data is uploaded.
+    pub fn take_changed(&mut self) -> bool {
+        let generation = self.generation.load(Ordering::Acquire);
+        let changed = self.seen != Some(generation);
+        self.seen = Some(generation);
+        changed
+    }
+}
+
+/// Keep repainting while an animation is running (e.g. a fade or a follow
+/// mode scroll), at most once per `interval`. Does nothing once `active` is
+/// false, letting the UI go idle.
+pub fn request_animation_frame(ctx: &egui::Context, active: bool, interval: Option<Duration>) {
+    if !active {
+        return;
+    }
+
+    match interval {
+        Some(interval) => ctx.request_repaint_after(interval),
+        None => ctx.request_repaint(),
+    }
+}
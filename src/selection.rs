@@ -0,0 +1,380 @@
+use std::ops::Range;
+
+use crate::SeriesId;
+#[cfg(feature = "compute")]
+use crate::{readback::Mapping, ValueMapping, VertexLayout};
+
+#[cfg(feature = "compute")]
+const WORKGROUPS: u32 = 256;
+#[cfg(feature = "compute")]
+const STATS: usize = 5;
+
+#[cfg(feature = "compute")]
+const NONE: u32 = u32::MAX;
+
+/// A region of the plot selected by the user, in axis units.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Region {
+    pub x: [f64; 2],
+    /// `None` for an x range selection, spanning every value.
+    pub y: Option<[f64; 2]>,
+}
+
+impl Region {
+    pub fn x_range(x: [f64; 2]) -> Region {
+        Region {
+            x: [x[0].min(x[1]), x[0].max(x[1])],
+            y: None,
+        }
+    }
+
+    pub fn rect(x: [f64; 2], y: [f64; 2]) -> Region {
+        Region {
+            y: Some([y[0].min(y[1]), y[0].max(y[1])]),
+            ..Region::x_range(x)
+        }
+    }
+}
+
+/// Basic statistics of the samples of one series within a [`Region`], in
+/// the series' plotted units (after value mapping).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RegionStats {
+    pub count: u64,
+    pub mean: f64,
+    pub std_dev: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+/// The samples of one series within a [`Region`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct SeriesSelection {
+    /// `None` if the plot has no series.
+    pub series_id: Option<SeriesId>,
+    /// From the first to the last selected sample, as vertex indices.
+    pub vertices: Range<u32>,
+    /// The ids of the samples spanned by `vertices`, if ids were attached
+    /// with [`GpuAcceleratedPlot::set_point_ids`](crate::GpuAcceleratedPlot::set_point_ids).
+    pub ids: Option<Vec<u32>>,
+    pub stats: RegionStats,
+}
+
+/// Emitted when a selection is completed, see
+/// [`GpuAcceleratedPlot::select_region`](crate::GpuAcceleratedPlot::select_region).
+/// Series without samples in the region are left out.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SelectionEvent {
+    pub region: Region,
+    pub series: Vec<SeriesSelection>,
+}
+
+/// How a [`RegionSelector`] selects.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SelectionMode {
+    #[default]
+    Box,
+    XRange,
+}
+
+/// The state of dragging out a selection.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RegionSelector {
+    pub mode: SelectionMode,
+    // Where the drag started, in axis units.
+    start: Option<[f64; 2]>,
+}
+
+impl RegionSelector {
+    pub fn new(mode: SelectionMode) -> RegionSelector {
+        RegionSelector { mode, start: None }
+    }
+
+    pub fn begin(&mut self, position: [f64; 2]) {
+        self.start = Some(position);
+    }
+
+    /// The region between the start of the drag and `position`, if
+    /// dragging.
+    pub fn region(&self, position: [f64; 2]) -> Option<Region> {
+        let start = self.start?;
+        let x = [start[0], position[0]];
+        Some(match self.mode {
+            SelectionMode::Box => Region::rect(x, [start[1], position[1]]),
+            SelectionMode::XRange => Region::x_range(x),
+        })
+    }
+
+    /// Finish the drag at `position`, returning the selected region.
+    pub fn end(&mut self, position: [f64; 2]) -> Option<Region> {
+        let region = self.region(position);
+        self.start = None;
+        region
+    }
+}
+
+// Laid out to match `Params` in the stats shader.
+#[cfg(feature = "compute")]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+struct Params {
+    x_range: [f32; 2],
+    y_range: [f32; 2],
+    stride: u32,
+    position: u32,
+    first: u32,
+    end: u32,
+    scale: f32,
+    offset: f32,
+    transfer: u32,
+    y_center: f32,
+    slot: u32,
+    _padding: [u32; 3],
+}
+
+// A range of vertices to reduce, drawn with a value mapping.
+#[cfg(feature = "compute")]
+pub(crate) struct StatsSlot {
+    pub vertices: Range<u32>,
+    pub mapping: ValueMapping,
+}
+
+// Reduces the samples within a region to statistics on the GPU.
+#[cfg(feature = "compute")]
+pub(crate) struct StatsReducer {
+    pipeline: wgpu::ComputePipeline,
+    params: wgpu::Buffer,
+    // Sums, indices and a staging buffer for each.
+    buffers: Option<[wgpu::Buffer; 4]>,
+    slots: usize,
+    // The reduction being read back, its slot count and y center.
+    pending: Option<(Mapping, usize, f32)>,
+}
+
+#[cfg(feature = "compute")]
+impl StatsReducer {
+    pub fn new(device: &wgpu::Device) -> StatsReducer {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("egui_plot_stats_shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("./stats_shader.wgsl").into()),
+        });
+
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("egui_plot_stats_pipeline"),
+            layout: None,
+            module: &shader,
+            entry_point: "cs_stats",
+        });
+
+        let params = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("egui_plot_stats_params"),
+            size: std::mem::size_of::<Params>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::UNIFORM,
+            mapped_at_creation: false,
+        });
+
+        StatsReducer {
+            pipeline,
+            params,
+            buffers: None,
+            slots: 0,
+            pending: None,
+        }
+    }
+
+    // Start reducing each slot's samples within `x_range` and `y_range`,
+    // among the vertices laid out as `layout` from byte `offset` of
+    // `points`. Returns false if there's nothing to reduce, or the last
+    // reduction is still being read back.
+    #[allow(clippy::too_many_arguments)]
+    pub fn submit(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        points: &wgpu::Buffer,
+        layout: VertexLayout,
+        offset: wgpu::BufferAddress,
+        x_range: [f32; 2],
+        y_range: [f32; 2],
+        slots: &[StatsSlot],
+    ) -> bool {
+        if slots.is_empty() || self.pending.is_some() {
+            return false;
+        }
+
+        let sums_size = |slots: usize| {
+            (slots * WORKGROUPS as usize * STATS * std::mem::size_of::<f32>())
+                as wgpu::BufferAddress
+        };
+        let indices_size =
+            |slots: usize| (slots * 2 * std::mem::size_of::<u32>()) as wgpu::BufferAddress;
+
+        if self.buffers.is_none() || slots.len() > self.slots {
+            self.slots = slots.len().next_power_of_two();
+            let create = |label, size, usage| {
+                device.create_buffer(&wgpu::BufferDescriptor {
+                    label: Some(label),
+                    size,
+                    usage,
+                    mapped_at_creation: false,
+                })
+            };
+            let storage = wgpu::BufferUsages::STORAGE
+                | wgpu::BufferUsages::COPY_DST
+                | wgpu::BufferUsages::COPY_SRC;
+            let staging = wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST;
+            self.buffers = Some([
+                create("egui_plot_stats_sums", sums_size(self.slots), storage),
+                create("egui_plot_stats_indices", indices_size(self.slots), storage),
+                create(
+                    "egui_plot_stats_sums_staging",
+                    sums_size(self.slots),
+                    staging,
+                ),
+                create(
+                    "egui_plot_stats_indices_staging",
+                    indices_size(self.slots),
+                    staging,
+                ),
+            ]);
+        }
+        let [sums, indices, sums_staging, indices_staging] = match &self.buffers {
+            Some(buffers) => buffers,
+            None => return false,
+        };
+
+        let initial: Vec<u32> = slots.iter().flat_map(|_| [NONE, 0]).collect();
+        queue.write_buffer(indices, 0, bytemuck::cast_slice(&initial));
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("egui_plot_stats_bind_group"),
+            layout: &self.pipeline.get_bind_group_layout(0),
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: self.params.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: points.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: sums.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: indices.as_entire_binding(),
+                },
+            ],
+        });
+
+        // Each slot has its own parameters, so each is a submission of its
+        // own: writes to the parameters land before the next submission.
+        let y_center = (y_range[0] * 0.5 + y_range[1] * 0.5).clamp(-1e30, 1e30);
+        for (slot, stats_slot) in slots.iter().enumerate() {
+            let params = Params {
+                x_range,
+                y_range,
+                stride: (layout.stride / 4) as u32,
+                position: ((offset + layout.position) / 4) as u32,
+                first: stats_slot.vertices.start / 2,
+                end: stats_slot.vertices.end / 2,
+                scale: stats_slot.mapping.scale,
+                offset: stats_slot.mapping.offset,
+                transfer: stats_slot.mapping.transfer.code(),
+                y_center,
+                slot: slot as u32,
+                _padding: [0; 3],
+            };
+            queue.write_buffer(&self.params, 0, bytemuck::bytes_of(&params));
+
+            let mut encoder =
+                device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+            {
+                let mut cpass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                    label: Some("egui_plot_stats_pass"),
+                });
+                cpass.set_pipeline(&self.pipeline);
+                cpass.set_bind_group(0, &bind_group, &[]);
+                cpass.dispatch_workgroups(WORKGROUPS, 1, 1);
+            }
+            queue.submit(std::iter::once(encoder.finish()));
+        }
+
+        let mut encoder =
+            device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        encoder.copy_buffer_to_buffer(sums, 0, sums_staging, 0, sums_size(slots.len()));
+        encoder.copy_buffer_to_buffer(indices, 0, indices_staging, 0, indices_size(slots.len()));
+        queue.submit(std::iter::once(encoder.finish()));
+
+        let mapping = Mapping::start(&[
+            sums_staging.slice(..sums_size(slots.len())),
+            indices_staging.slice(..indices_size(slots.len())),
+        ]);
+        self.pending = Some((mapping, slots.len(), y_center));
+        true
+    }
+
+    // The results of the last reduction submitted, once read back: each
+    // slot's statistics and selected vertices, if it had any samples in the
+    // region.
+    pub fn finish(&mut self) -> Option<Vec<Option<(RegionStats, Range<u32>)>>> {
+        self.pending.as_ref()?.0.state()?;
+        let (mapping, slots, y_center) = self.pending.take()?;
+        let [_, _, sums_staging, indices_staging] = self.buffers.as_ref()?;
+        if mapping.state() != Some(true) {
+            mapping.unmap(&[sums_staging, indices_staging]);
+            return Some(vec![None; slots]);
+        }
+
+        let sums_size = slots * WORKGROUPS as usize * STATS * std::mem::size_of::<f32>();
+        let indices_size = slots * 2 * std::mem::size_of::<u32>();
+        let results = {
+            let sums = sums_staging
+                .slice(..sums_size as wgpu::BufferAddress)
+                .get_mapped_range();
+            let indices = indices_staging
+                .slice(..indices_size as wgpu::BufferAddress)
+                .get_mapped_range();
+            let sums: &[f32] = bytemuck::cast_slice(&sums);
+            let indices: &[u32] = bytemuck::cast_slice(&indices);
+
+            sums.chunks(WORKGROUPS as usize * STATS)
+                .zip(indices.chunks(2))
+                .map(|(partials, range)| {
+                    // Accumulate the workgroups' partial sums in double
+                    // precision.
+                    let mut total = [0.0, 0.0, 0.0, f64::INFINITY, f64::NEG_INFINITY];
+                    for s in partials.chunks(STATS) {
+                        total[0] += s[0] as f64;
+                        total[1] += s[1] as f64;
+                        total[2] += s[2] as f64;
+                        total[3] = total[3].min(s[3] as f64);
+                        total[4] = total[4].max(s[4] as f64);
+                    }
+
+                    let count = total[0];
+                    if count < 1.0 || range[0] == NONE {
+                        return None;
+                    }
+
+                    let mean = total[1] / count;
+                    let variance = (total[2] / count - mean * mean).max(0.0);
+                    let stats = RegionStats {
+                        count: count as u64,
+                        mean: mean + y_center as f64,
+                        std_dev: variance.sqrt(),
+                        min: total[3],
+                        max: total[4],
+                    };
+                    Some((stats, 2 * range[0]..2 * range[1] + 2))
+                })
+                .collect()
+        };
+        sums_staging.unmap();
+        indices_staging.unmap();
+
+        Some(results)
+    }
+}
@@ -0,0 +1,441 @@
+#[cfg(feature = "gpu")]
+use std::{ops::Range, sync::Arc};
+
+#[cfg(feature = "gpu")]
+use crate::groups::GroupState;
+
+/// Identifies a series added with
+/// [`GpuAcceleratedPlot::add_series`](crate::GpuAcceleratedPlot::add_series).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SeriesId(pub(crate) usize);
+
+/// Identifies a style shared between series, added with
+/// [`GpuAcceleratedPlot::add_style`](crate::GpuAcceleratedPlot::add_style).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct StyleHandle(pub(crate) usize);
+
+/// A function applied to each value before scaling.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Transfer {
+    #[default]
+    Identity,
+    /// `20 * log10(y)`, for amplitudes.
+    Decibels,
+    /// `10 * log10(y)`, for powers.
+    PowerDecibels,
+}
+
+impl Transfer {
+    // As numbered in the shaders.
+    pub(crate) fn code(self) -> u32 {
+        match self {
+            Transfer::Identity => 0,
+            Transfer::Decibels => 1,
+            Transfer::PowerDecibels => 2,
+        }
+    }
+}
+
+/// Maps a series' y values to plotted values as `scale * transfer(y) +
+/// offset`, in the shader, so that switching units doesn't require uploading
+/// the data again.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ValueMapping {
+    pub scale: f32,
+    pub offset: f32,
+    pub transfer: Transfer,
+}
+
+impl Default for ValueMapping {
+    fn default() -> Self {
+        ValueMapping {
+            scale: 1.0,
+            offset: 0.0,
+            transfer: Transfer::Identity,
+        }
+    }
+}
+
+impl ValueMapping {
+    pub fn linear(scale: f32, offset: f32) -> ValueMapping {
+        ValueMapping {
+            scale,
+            offset,
+            transfer: Transfer::Identity,
+        }
+    }
+
+    pub fn with_transfer(self, transfer: Transfer) -> ValueMapping {
+        ValueMapping { transfer, ..self }
+    }
+}
+
+/// Rescales a series' mapped values in the shader, so that channels in
+/// different units can be overlaid, see
+/// [`GpuAcceleratedPlot::set_normalization`](crate::GpuAcceleratedPlot::set_normalization).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Normalization {
+    /// The smallest to largest visible value spans 0 to 1.
+    MinMax,
+    /// Values are shown as standard deviations from the visible mean.
+    ZScore,
+    /// `range` spans 0 to 1, wherever the plot is scrolled.
+    Fixed { range: [f32; 2] },
+}
+
+impl Normalization {
+    // Whether the reference depends on the visible values, which are reduced
+    // on the GPU.
+    pub(crate) fn is_visible_range(self) -> bool {
+        !matches!(self, Normalization::Fixed { .. })
+    }
+
+    // The value shown at 0 and the span shown as 1, from the statistics of
+    // the visible values.
+    #[cfg(feature = "compute")]
+    pub(crate) fn reference(self, stats: &crate::RegionStats) -> [f32; 2] {
+        match self {
+            Normalization::MinMax => [stats.min as f32, (stats.max - stats.min) as f32],
+            Normalization::ZScore => [stats.mean as f32, stats.std_dev as f32],
+            Normalization::Fixed { range: [lo, hi] } => [lo, hi - lo],
+        }
+    }
+}
+
+/// The most color stops a [`Gradient`] is drawn with. Longer colormaps are
+/// resampled down to this many.
+pub const MAX_GRADIENT_STOPS: usize = 8;
+
+/// What a [`Gradient`] is keyed on.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GradientKey {
+    /// Position within the series, from its first vertex to its last.
+    Index,
+    /// The x value of each point (e.g. a timestamp), mapped from `range`.
+    X { range: [f32; 2] },
+}
+
+/// Colors a series along its length in the shader, replacing the vertex
+/// colors, so progression along a trajectory is visible without uploading
+/// per-vertex colors.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Gradient {
+    pub key: GradientKey,
+    /// Evenly spaced linear RGBA colors, from the start of the key range to
+    /// the end.
+    pub stops: Vec<[f32; 4]>,
+}
+
+impl Gradient {
+    pub fn two_color(start: [f32; 4], end: [f32; 4]) -> Gradient {
+        Gradient::colormap(&[start, end])
+    }
+
+    pub fn colormap(stops: &[[f32; 4]]) -> Gradient {
+        Gradient {
+            key: GradientKey::Index,
+            stops: stops.to_vec(),
+        }
+    }
+
+    pub fn with_key(self, key: GradientKey) -> Gradient {
+        Gradient { key, ..self }
+    }
+
+    // The stops to upload, resampled if there are too many.
+    pub(crate) fn resampled(&self) -> Vec<[f32; 4]> {
+        if self.stops.len() <= MAX_GRADIENT_STOPS {
+            return self.stops.clone();
+        }
+
+        let last = (self.stops.len() - 1) as f32;
+        (0..MAX_GRADIENT_STOPS)
+            .map(|i| {
+                let t = i as f32 / (MAX_GRADIENT_STOPS - 1) as f32 * last;
+                let a = self.stops[t.floor() as usize];
+                let b = self.stops[t.ceil() as usize];
+                let f = t.fract();
+                [0, 1, 2, 3].map(|c| a[c] + (b[c] - a[c]) * f)
+            })
+            .collect()
+    }
+}
+
+/// Folds x values onto `0..period`, see
+/// [`GpuAcceleratedPlot::set_fold`](crate::GpuAcceleratedPlot::set_fold).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Fold {
+    pub period: f32,
+    pub phase: f32,
+}
+
+impl Fold {
+    pub fn new(period: f32) -> Fold {
+        Fold { period, phase: 0.0 }
+    }
+
+    pub fn with_phase(self, phase: f32) -> Fold {
+        Fold { phase, ..self }
+    }
+}
+
+/// Fades a series out towards its first vertices, leaving the last `length`
+/// points (pairs of vertices) visible, e.g. as the trail of a moving point.
+/// See [`GpuAcceleratedPlot::set_trail`](crate::GpuAcceleratedPlot::set_trail).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Trail {
+    pub length: u32,
+    /// How quickly the trail fades: 1 is linear, higher keeps less of it.
+    pub falloff: f32,
+}
+
+impl Trail {
+    pub fn new(length: u32) -> Trail {
+        Trail {
+            length,
+            falloff: 1.0,
+        }
+    }
+
+    pub fn with_falloff(self, falloff: f32) -> Trail {
+        Trail { falloff, ..self }
+    }
+}
+
+/// How a series' points are placed in the plot, see
+/// [`GpuAcceleratedPlot::set_coordinates`](crate::GpuAcceleratedPlot::set_coordinates).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Coordinates {
+    /// x and y as they are.
+    #[default]
+    Cartesian,
+    /// x and y are the resistance and reactance of a normalized impedance,
+    /// drawn at its reflection coefficient `(z - 1) / (z + 1)`, as on a
+    /// [`SmithChart`](crate::SmithChart).
+    Impedance,
+}
+
+impl Coordinates {
+    // As numbered in the line shader.
+    pub(crate) fn code(self) -> u32 {
+        match self {
+            Coordinates::Cartesian => 0,
+            Coordinates::Impedance => 1,
+        }
+    }
+}
+
+// Per-series shader parameters, laid out to match `Series` in the line
+// shader.
+#[cfg(feature = "gpu")]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub(crate) struct SeriesUniform {
+    scale: f32,
+    offset: f32,
+    transfer: u32,
+    alpha: f32,
+    // Zero stops means the vertex colors are used.
+    gradient_key: u32,
+    stop_count: u32,
+    key_range: [f32; 2],
+    stops: [[f32; 4]; MAX_GRADIENT_STOPS],
+    // Period and phase, with a zero period meaning no folding.
+    fold: [f32; 2],
+    width: f32,
+    coordinates: u32,
+    // Last vertex, length in vertices and falloff, with a zero length
+    // meaning no trail.
+    trail: [f32; 4],
+}
+
+#[cfg(feature = "gpu")]
+impl Default for SeriesUniform {
+    fn default() -> Self {
+        SeriesUniform::new(&SeriesStyle::default(), &(0..0))
+    }
+}
+
+#[cfg(feature = "gpu")]
+impl SeriesUniform {
+    pub fn new(style: &SeriesStyle, vertices: &Range<u32>) -> SeriesUniform {
+        let mut uniform = SeriesUniform {
+            scale: style.mapping.scale,
+            offset: style.mapping.offset,
+            transfer: style.mapping.transfer.code(),
+            alpha: style.alpha,
+            gradient_key: 0,
+            stop_count: 0,
+            key_range: [0.0, 1.0],
+            stops: [[0.0; 4]; MAX_GRADIENT_STOPS],
+            fold: style
+                .fold
+                .filter(|fold| fold.period > 0.0)
+                .map_or([0.0, 0.0], |fold| [fold.period, fold.phase]),
+            width: style.width,
+            coordinates: style.coordinates.code(),
+            trail: style
+                .trail
+                .filter(|trail| trail.length > 0)
+                .map_or([0.0; 4], |trail| {
+                    let last = vertices.end.saturating_sub(1) as f32;
+                    [last, 2.0 * trail.length as f32, trail.falloff, 0.0]
+                }),
+        };
+
+        if let Some(gradient) = style.gradient.as_ref().filter(|g| !g.stops.is_empty()) {
+            let stops = gradient.resampled();
+            uniform.stop_count = stops.len() as u32;
+            uniform.stops[..stops.len()].copy_from_slice(&stops);
+
+            (uniform.gradient_key, uniform.key_range) = match gradient.key {
+                GradientKey::Index => (
+                    0,
+                    [vertices.start as f32, vertices.end.saturating_sub(1) as f32],
+                ),
+                GradientKey::X { range } => (1, range),
+            };
+        }
+
+        uniform
+    }
+}
+
+/// How a series is drawn. Each series has its own, set piecewise with e.g.
+/// [`GpuAcceleratedPlot::set_alpha`](crate::GpuAcceleratedPlot::set_alpha),
+/// or can share one with other series through a [`StyleHandle`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct SeriesStyle {
+    pub mapping: ValueMapping,
+    /// Multiplies the opacity, from 0 to 1.
+    pub alpha: f32,
+    pub gradient: Option<Gradient>,
+    pub glow: bool,
+    pub fold: Option<Fold>,
+    /// Multiplies the line width, on top of the theme's.
+    pub width: f32,
+    pub coordinates: Coordinates,
+    pub trail: Option<Trail>,
+}
+
+impl Default for SeriesStyle {
+    fn default() -> Self {
+        SeriesStyle {
+            mapping: ValueMapping::default(),
+            alpha: 1.0,
+            gradient: None,
+            glow: false,
+            fold: None,
+            width: 1.0,
+            coordinates: Coordinates::Cartesian,
+            trail: None,
+        }
+    }
+}
+
+/// Conventional z orders for composing different kinds of series. Series
+/// with a higher z order are drawn on top, and series with equal z orders
+/// are drawn in the order they were added.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Layer {
+    /// Fills, heatmaps and other backdrops.
+    Background,
+    Lines,
+    /// Markers and annotations.
+    Foreground,
+}
+
+impl From<Layer> for i32 {
+    fn from(layer: Layer) -> Self {
+        match layer {
+            Layer::Background => -100,
+            Layer::Lines => 0,
+            Layer::Foreground => 100,
+        }
+    }
+}
+
+/// One copy of an instanced series: its points are scaled, then offset, and
+/// its colors are multiplied by `color`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct Instance {
+    pub offset: [f32; 2],
+    pub scale: [f32; 2],
+    pub color: [f32; 4],
+}
+
+impl Default for Instance {
+    fn default() -> Self {
+        Instance {
+            offset: [0.0, 0.0],
+            scale: [1.0, 1.0],
+            color: [1.0, 1.0, 1.0, 1.0],
+        }
+    }
+}
+
+#[cfg(feature = "gpu")]
+pub(crate) struct Instances {
+    pub buffer: wgpu::Buffer,
+    pub capacity: usize,
+    pub count: u32,
+    // The instanced variant of the pipeline the series is drawn with.
+    pub pipeline: Arc<wgpu::RenderPipeline>,
+}
+
+// A range of the plot's vertices drawn with its own style, or a shared one.
+#[cfg(feature = "gpu")]
+pub(crate) struct Series {
+    pub vertices: Range<u32>,
+    pub style: SeriesStyle,
+    pub shared: Option<StyleHandle>,
+    pub name: Option<String>,
+    pub group: GroupState,
+    pub normalization: Option<Normalization>,
+    // The value shown at 0 and the span shown as 1, as last reduced for a
+    // visible range normalization.
+    pub reference: Option<[f32; 2]>,
+    pub z_order: i32,
+    pub instances: Option<Instances>,
+}
+
+#[cfg(feature = "gpu")]
+impl Series {
+    // The style the series is drawn with, given the plot's shared styles.
+    pub fn effective_style<'a>(&'a self, styles: &'a [SeriesStyle]) -> &'a SeriesStyle {
+        self.shared
+            .and_then(|handle| styles.get(handle.0))
+            .unwrap_or(&self.style)
+    }
+
+    // The effective style with the normalization and group state applied.
+    pub fn grouped_style(&self, styles: &[SeriesStyle]) -> SeriesStyle {
+        let mut style = self.effective_style(styles).clone();
+
+        let reference = match self.normalization {
+            Some(Normalization::Fixed { range: [lo, hi] }) => Some([lo, hi - lo]),
+            Some(_) => self.reference,
+            None => None,
+        };
+        // Until the first reduction arrives (or for a constant series), the
+        // values are shown as they are.
+        if let Some([center, span]) = reference.filter(|[c, s]| c.is_finite() && s.is_normal()) {
+            style.mapping.scale /= span;
+            style.mapping.offset = (style.mapping.offset - center) / span;
+        }
+
+        style.alpha *= self.group.alpha;
+        style.mapping.offset += self.group.offset;
+        style
+    }
+
+    // Whether the series can be drawn with its shared style's uniform, i.e.
+    // it has one and isn't normalized, or faded or offset by a group.
+    pub fn uses_shared_slot(&self) -> bool {
+        self.shared.is_some()
+            && self.normalization.is_none()
+            && self.group.alpha == 1.0
+            && self.group.offset == 0.0
+    }
+}
@@ -0,0 +1,140 @@
+#[cfg(feature = "gpu")]
+use wgpu::util::DeviceExt;
+
+use crate::Bounds;
+#[cfg(feature = "gpu")]
+use crate::{worker, LineBatch};
+
+// Segments in each full circle of the grid.
+const SEGMENTS: usize = 128;
+
+/// The grid of a Smith chart: the unit circle of the reflection coefficient,
+/// with circles of constant resistance and arcs of constant reactance, drawn
+/// behind the series. See
+/// [`GpuAcceleratedPlot::set_smith_chart`](crate::GpuAcceleratedPlot::set_smith_chart).
+///
+/// Draw S-parameters as they are, with the real part as x and the imaginary
+/// part as y, and impedances with [`Coordinates::Impedance`](crate::Coordinates::Impedance).
+#[derive(Clone, Debug, PartialEq)]
+pub struct SmithChart {
+    /// Normalized resistances with a circle each.
+    pub resistances: Vec<f32>,
+    /// Normalized reactances with an arc each, above and below the real
+    /// axis.
+    pub reactances: Vec<f32>,
+    pub color: [f32; 4],
+    /// Space around the unit circle, as a fraction of its radius.
+    pub margin: f32,
+}
+
+impl SmithChart {
+    pub fn new() -> SmithChart {
+        SmithChart::default()
+    }
+
+    pub fn with_resistances(self, resistances: Vec<f32>) -> SmithChart {
+        SmithChart {
+            resistances,
+            ..self
+        }
+    }
+
+    pub fn with_reactances(self, reactances: Vec<f32>) -> SmithChart {
+        SmithChart { reactances, ..self }
+    }
+
+    pub fn with_color(self, color: [f32; 4]) -> SmithChart {
+        SmithChart { color, ..self }
+    }
+
+    pub fn with_margin(self, margin: f32) -> SmithChart {
+        SmithChart { margin, ..self }
+    }
+
+    /// The square view around the unit circle, before being widened to the
+    /// plot's aspect ratio.
+    pub fn bounds(&self) -> Bounds {
+        let e = 1.0 + self.margin.max(0.0) as f64;
+        Bounds::new([-e, e], [-e, e])
+    }
+
+    // The unit circle, the real axis and the grid, as line vertices.
+    #[cfg(feature = "gpu")]
+    pub(crate) fn grid_vertices(&self, device: &wgpu::Device) -> (wgpu::Buffer, u32) {
+        let lines = 2 + self.resistances.len() + 2 * self.reactances.len();
+        let mut batch = LineBatch::with_capacity(lines, 2 * (SEGMENTS + 1));
+
+        batch.push(&worker::expand(&circle([0.0, 0.0], 1.0), self.color));
+        batch.push(&worker::expand(&[[-1.0, 0.0], [1.0, 0.0]], self.color));
+        for &r in self.resistances.iter().filter(|r| **r > 0.0) {
+            let r = r as f64;
+            let circle = circle([r / (1.0 + r), 0.0], 1.0 / (1.0 + r));
+            batch.push(&worker::expand(&circle, self.color));
+        }
+        for &x in self.reactances.iter().filter(|x| **x != 0.0) {
+            for x in [x as f64, -x as f64] {
+                // Sweep the resistance from zero to infinity, which keeps the
+                // arc inside the unit circle.
+                let arc: Vec<_> = (0..=SEGMENTS)
+                    .map(|i| {
+                        let t = i as f64 / SEGMENTS as f64;
+                        if t < 1.0 {
+                            reflection([t / (1.0 - t), x])
+                        } else {
+                            [1.0, 0.0]
+                        }
+                    })
+                    .collect();
+                batch.push(&worker::expand(&arc, self.color));
+            }
+        }
+
+        let vertices = batch.vertices();
+        let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("egui_plot_smith_grid"),
+            contents: bytemuck::cast_slice(vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        (buffer, vertices.len() as u32)
+    }
+}
+
+impl Default for SmithChart {
+    fn default() -> Self {
+        SmithChart {
+            resistances: vec![0.2, 0.5, 1.0, 2.0, 5.0],
+            reactances: vec![0.2, 0.5, 1.0, 2.0, 5.0],
+            color: [0.5, 0.5, 0.5, 0.5],
+            margin: 0.05,
+        }
+    }
+}
+
+/// The reflection coefficient `(z - 1) / (z + 1)` of a normalized impedance
+/// `z`, both as `[real, imaginary]`, as the line shader places
+/// [`Coordinates::Impedance`](crate::Coordinates::Impedance) points.
+pub fn reflection(z: [f64; 2]) -> [f64; 2] {
+    let [a, b] = z;
+    let d = (a + 1.0) * (a + 1.0) + b * b;
+    [(a * a + b * b - 1.0) / d, 2.0 * b / d]
+}
+
+/// The normalized impedance `(1 + Γ) / (1 - Γ)` with reflection coefficient
+/// `gamma`, e.g. to label the point under the cursor.
+pub fn impedance(gamma: [f64; 2]) -> [f64; 2] {
+    let [a, b] = gamma;
+    let d = (1.0 - a) * (1.0 - a) + b * b;
+    [(1.0 - a * a - b * b) / d, 2.0 * b / d]
+}
+
+fn circle(center: [f64; 2], radius: f64) -> Vec<[f64; 2]> {
+    (0..=SEGMENTS)
+        .map(|i| {
+            let angle = i as f64 / SEGMENTS as f64 * std::f64::consts::TAU;
+            [
+                center[0] + radius * angle.cos(),
+                center[1] + radius * angle.sin(),
+            ]
+        })
+        .collect()
+}
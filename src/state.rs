@@ -0,0 +1,136 @@
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, PoisonError,
+    },
+};
+
+use crate::{worker, Vertex};
+
+/// The samples of a plot's series, shared between the threads acquiring
+/// them and the one preparing the plot, so that producers don't need to
+/// route every batch through the UI's update loop.
+///
+/// Threading model: clone the state into each producer, which can push
+/// samples from any thread. Every change takes a single short lock over the
+/// samples and bumps a generation counter, and nothing else is locked.
+/// [`GpuAcceleratedPlot::prepare_shared`](crate::GpuAcceleratedPlot::prepare_shared)
+/// copies the samples out under the same lock when the generation moved,
+/// then expands and uploads them outside of it, so producers only ever wait
+/// for a copy. The plot itself is `Send + Sync` but is only updated through
+/// `&mut`, on whichever thread renders it (e.g. inside egui's paint
+/// callbacks).
+#[derive(Clone, Default)]
+pub struct GpuPlotState {
+    inner: Arc<StateInner>,
+}
+
+#[derive(Default)]
+struct StateInner {
+    series: Mutex<Vec<StateSeries>>,
+    generation: AtomicU64,
+}
+
+struct StateSeries {
+    samples: VecDeque<[f64; 2]>,
+    capacity: usize,
+    color: [f32; 4],
+}
+
+impl GpuPlotState {
+    pub fn new() -> GpuPlotState {
+        GpuPlotState::default()
+    }
+
+    /// Add a series keeping at most `capacity` samples, dropping the oldest
+    /// past it, and return its index. Series are drawn as the plot's first
+    /// series, in the order they were added.
+    pub fn add_series(&self, color: [f32; 4], capacity: usize) -> usize {
+        self.update(|series| {
+            series.push(StateSeries {
+                samples: VecDeque::new(),
+                capacity: capacity.max(1),
+                color,
+            });
+            series.len() - 1
+        })
+    }
+
+    pub fn push(&self, series: usize, sample: [f64; 2]) {
+        self.extend(series, [sample]);
+    }
+
+    pub fn extend(&self, series: usize, samples: impl IntoIterator<Item = [f64; 2]>) {
+        self.update(|all| {
+            if let Some(series) = all.get_mut(series) {
+                series.samples.extend(samples);
+                let excess = series.samples.len().saturating_sub(series.capacity);
+                series.samples.drain(..excess);
+            }
+        });
+    }
+
+    /// Replace all of a series' samples, e.g. with a new trace.
+    pub fn replace(&self, series: usize, samples: &[[f64; 2]]) {
+        self.update(|all| {
+            if let Some(series) = all.get_mut(series) {
+                let start = samples.len().saturating_sub(series.capacity);
+                series.samples.clear();
+                series.samples.extend(&samples[start..]);
+            }
+        });
+    }
+
+    pub fn clear(&self, series: usize) {
+        self.replace(series, &[]);
+    }
+
+    pub fn series_count(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Incremented by every change, e.g. to compare against the last one
+    /// seen.
+    pub fn generation(&self) -> u64 {
+        self.inner.generation.load(Ordering::Acquire)
+    }
+
+    // The vertices of every series, one after the other, and the range each
+    // series covers, along with the generation they're from.
+    pub(crate) fn vertices(&self) -> (u64, Vec<Vertex>, Vec<std::ops::Range<u32>>) {
+        let (generation, copies) = {
+            let series = self.lock();
+            let copies: Vec<_> = series
+                .iter()
+                .map(|s| (s.samples.iter().copied().collect::<Vec<_>>(), s.color))
+                .collect();
+            (self.generation(), copies)
+        };
+
+        let mut vertices = Vec::new();
+        let mut ranges = Vec::with_capacity(copies.len());
+        for (samples, color) in copies {
+            let start = vertices.len() as u32;
+            if !samples.is_empty() {
+                vertices.extend(worker::expand(&samples, color));
+            }
+            ranges.push(start..vertices.len() as u32);
+        }
+        (generation, vertices, ranges)
+    }
+
+    fn update<R>(&self, change: impl FnOnce(&mut Vec<StateSeries>) -> R) -> R {
+        let mut series = self.lock();
+        let result = change(&mut series);
+        self.inner.generation.fetch_add(1, Ordering::AcqRel);
+        result
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<StateSeries>> {
+        self.inner
+            .series
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
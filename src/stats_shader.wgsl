@@ -0,0 +1,118 @@
+struct Params {
+    // The region, in data units after value mapping.
+    x_range: vec2<f32>,
+    y_range: vec2<f32>,
+    // Vertex stride and position offset, in 4 byte words.
+    stride: u32,
+    position: u32,
+    // The range of samples to reduce.
+    first: u32,
+    end: u32,
+    // The series' value mapping, as in the line shader.
+    scale: f32,
+    offset: f32,
+    transfer: u32,
+    // Subtracted from values to keep the sums of squares small.
+    y_center: f32,
+    // Which result slot the series writes to.
+    slot: u32,
+    _padding0: u32,
+    _padding1: u32,
+    _padding2: u32,
+};
+
+@group(0) @binding(0)
+var<uniform> params: Params;
+@group(0) @binding(1)
+var<storage, read> points: array<f32>;
+// STATS sums per workgroup per slot.
+@group(0) @binding(2)
+var<storage, read_write> sums: array<f32>;
+// The first and last sample in the region, per slot.
+@group(0) @binding(3)
+var<storage, read_write> indices: array<atomic<u32>>;
+
+let WORKGROUP_SIZE: u32 = 64u;
+let WORKGROUPS: u32 = 256u;
+
+// The count, sum, sum of squares, minimum and maximum.
+let STATS: u32 = 5u;
+
+let LOG10_2: f32 = 0.30102999566;
+
+let TRANSFER_DECIBELS: u32 = 1u;
+let TRANSFER_POWER_DECIBELS: u32 = 2u;
+
+var<workgroup> partial: array<array<f32, 5>, 64>;
+
+fn map_value(y: f32) -> f32 {
+    var value = y;
+    if (params.transfer == TRANSFER_DECIBELS) {
+        value = 20.0 * LOG10_2 * log2(y);
+    } else if (params.transfer == TRANSFER_POWER_DECIBELS) {
+        value = 10.0 * LOG10_2 * log2(y);
+    }
+    return params.scale * value + params.offset;
+}
+
+@compute @workgroup_size(64)
+fn cs_stats(@builtin(global_invocation_id) id: vec3<u32>,
+            @builtin(local_invocation_index) local: u32,
+            @builtin(workgroup_id) group: vec3<u32>) {
+    var s = array<f32, 5>(0.0, 0.0, 0.0, 3.4e38, -3.4e38);
+    var first = 0xffffffffu;
+    var last = 0u;
+
+    let total = WORKGROUPS * WORKGROUP_SIZE;
+    for (var i = params.first + id.x; i < params.end; i = i + total) {
+        // Vertices come in pairs with the same position, one for each normal.
+        let word = 2u * i * params.stride + params.position;
+        let x = points[word];
+        let y = map_value(points[word + 1u]);
+
+        // Also skips NaN gaps.
+        if (!(x >= params.x_range[0] && x <= params.x_range[1]
+              && y >= params.y_range[0] && y <= params.y_range[1])) {
+            continue;
+        }
+
+        let d = y - params.y_center;
+        s[0] = s[0] + 1.0;
+        s[1] = s[1] + d;
+        s[2] = s[2] + d * d;
+        s[3] = min(s[3], y);
+        s[4] = max(s[4], y);
+        first = min(first, i);
+        last = max(last, i);
+    }
+
+    if (s[0] > 0.0) {
+        atomicMin(&indices[2u * params.slot], first);
+        atomicMax(&indices[2u * params.slot + 1u], last);
+    }
+
+    partial[local] = s;
+    workgroupBarrier();
+
+    for (var stride = WORKGROUP_SIZE / 2u; stride > 0u; stride = stride / 2u) {
+        if (local < stride) {
+            let a = partial[local];
+            let b = partial[local + stride];
+            partial[local] = array<f32, 5>(
+                a[0] + b[0],
+                a[1] + b[1],
+                a[2] + b[2],
+                min(a[3], b[3]),
+                max(a[4], b[4]),
+            );
+        }
+        workgroupBarrier();
+    }
+
+    if (local == 0u) {
+        let base = (params.slot * WORKGROUPS + group.x) * STATS;
+        for (var k = 0u; k < STATS; k = k + 1u) {
+            sums[base + k] = partial[0][k];
+        }
+    }
+}
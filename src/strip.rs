@@ -0,0 +1,207 @@
+use std::collections::VecDeque;
+
+use crate::{worker, Bounds, Vertex};
+
+/// Samples kept by default, at most, whatever the retention.
+pub const DEFAULT_STRIP_CAPACITY: usize = 1 << 20;
+
+/// A live chart of one value over time, configured for monitoring: samples
+/// are appended to a ring buffer which drops those older than the retention
+/// (or past its capacity), the view follows the newest sample, and the
+/// visible samples are decimated to a minimum and maximum per pixel column,
+/// so that a long window stays as cheap to draw as a short one while still
+/// showing every spike.
+///
+/// Time is in seconds, or any other unit as long as it's used throughout.
+/// Call [`StripChart::vertices`] again (and pass the points as dirty) when
+/// samples were pushed or the view changed, which
+/// [`StripChart::take_dirty`] tracks for the view that follows.
+#[derive(Clone, Debug)]
+pub struct StripChart {
+    samples: VecDeque<[f64; 2]>,
+    /// The width of the view when following, in time units.
+    pub window: f64,
+    /// How long samples are kept, at least `window` to fill the view.
+    pub retention: f64,
+    pub capacity: usize,
+    /// Space left after the newest sample when following.
+    pub lead: f64,
+    pub color: [f32; 4],
+    dirty: bool,
+}
+
+impl StripChart {
+    pub fn new(window: f64) -> StripChart {
+        StripChart {
+            samples: VecDeque::new(),
+            window,
+            retention: window,
+            capacity: DEFAULT_STRIP_CAPACITY,
+            lead: 0.0,
+            color: [0.3, 0.8, 0.4, 1.0],
+            dirty: true,
+        }
+    }
+
+    /// Keep samples for longer than the window, e.g. to scroll back while
+    /// paused.
+    pub fn with_retention(self, retention: f64) -> StripChart {
+        StripChart { retention, ..self }
+    }
+
+    pub fn with_capacity(self, capacity: usize) -> StripChart {
+        StripChart { capacity, ..self }
+    }
+
+    pub fn with_lead(self, lead: f64) -> StripChart {
+        StripChart { lead, ..self }
+    }
+
+    pub fn with_color(self, color: [f32; 4]) -> StripChart {
+        StripChart { color, ..self }
+    }
+
+    /// Append a sample. Samples older than the newest one are dropped, as a
+    /// monitored value only moves forward in time.
+    pub fn push(&mut self, time: f64, value: f64) {
+        if self.samples.back().map_or(false, |&[t, _]| time < t) || !time.is_finite() {
+            return;
+        }
+
+        self.samples.push_back([time, value]);
+        let oldest = time - self.retention.max(self.window);
+        while self.samples.len() > self.capacity.max(1)
+            || self.samples.front().map_or(false, |&[t, _]| t < oldest)
+        {
+            self.samples.pop_front();
+        }
+        self.dirty = true;
+    }
+
+    pub fn extend(&mut self, samples: impl IntoIterator<Item = [f64; 2]>) {
+        for [time, value] in samples {
+            self.push(time, value);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.samples.clear();
+        self.dirty = true;
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    pub fn latest(&self) -> Option<[f64; 2]> {
+        self.samples.back().copied()
+    }
+
+    /// Whether samples were pushed since the last call, i.e. whether the
+    /// following view's vertices need uploading again.
+    pub fn take_dirty(&mut self) -> bool {
+        std::mem::take(&mut self.dirty)
+    }
+
+    /// The view following the newest sample, with the y range fit to the
+    /// samples in it.
+    pub fn bounds(&self) -> Bounds {
+        let end = self.latest().map_or(0.0, |[t, _]| t) + self.lead;
+        let x = [end - self.window, end];
+        let y = self.visible(x).filter(|[_, y]| y.is_finite()).fold(
+            None::<[f64; 2]>,
+            |range, [_, y]| match range {
+                None => Some([y, y]),
+                Some([lo, hi]) => Some([f64::min(lo, y), f64::max(hi, y)]),
+            },
+        );
+
+        let y = match y {
+            Some([lo, hi]) if hi > lo => [lo - (hi - lo) * 0.05, hi + (hi - lo) * 0.05],
+            Some([v, _]) => [v - 1.0, v + 1.0],
+            None => [-1.0, 1.0],
+        };
+        Bounds::new(x, y)
+    }
+
+    /// The vertices showing the times `x` at `width` pixels across: every
+    /// sample while there are fewer than two per pixel, and otherwise the
+    /// minimum and maximum of each pixel's samples, in the order they
+    /// occurred.
+    pub fn vertices(&self, x: [f64; 2], width: u32) -> Vec<Vertex> {
+        let [lo, hi] = [x[0].min(x[1]), x[0].max(x[1])];
+        // One sample either side, so that the line runs off the edges.
+        let start = self
+            .samples
+            .partition_point(|&[t, _]| t < lo)
+            .saturating_sub(1);
+        let end = (self.samples.partition_point(|&[t, _]| t <= hi) + 1).min(self.samples.len());
+        let visible = self.samples.range(start..end.max(start));
+
+        let columns = width.max(1) as f64;
+        if (end.saturating_sub(start) as f64) < 2.0 * columns {
+            let samples: Vec<_> = visible.copied().collect();
+            return worker::expand(&samples, self.color);
+        }
+
+        let column = |t: f64| ((t - lo) / (hi - lo) * columns).floor() as i64;
+        let mut decimated = Vec::with_capacity(2 * width as usize + 2);
+        let mut current: Option<(i64, [f64; 2], [f64; 2])> = None;
+        for &sample in visible {
+            let c = column(sample[0]);
+            current = match current {
+                Some((col, min, max)) if col == c => Some((
+                    col,
+                    if sample[1] < min[1] { sample } else { min },
+                    if sample[1] > max[1] { sample } else { max },
+                )),
+                previous => {
+                    if let Some((_, min, max)) = previous {
+                        push_extremes(&mut decimated, min, max);
+                    }
+                    Some((c, sample, sample))
+                }
+            };
+        }
+        if let Some((_, min, max)) = current {
+            push_extremes(&mut decimated, min, max);
+        }
+
+        worker::expand(&decimated, self.color)
+    }
+
+    /// The sample nearest to time `x`, e.g. for a readout under the cursor.
+    pub fn readout(&self, x: f64) -> Option<[f64; 2]> {
+        let i = self.samples.partition_point(|&[t, _]| t < x);
+        let after = self.samples.get(i);
+        let before = i.checked_sub(1).and_then(|i| self.samples.get(i));
+        match (before, after) {
+            (Some(b), Some(a)) => Some(if x - b[0] <= a[0] - x { *b } else { *a }),
+            (Some(s), None) | (None, Some(s)) => Some(*s),
+            (None, None) => None,
+        }
+    }
+
+    fn visible(&self, x: [f64; 2]) -> impl Iterator<Item = [f64; 2]> + '_ {
+        let start = self.samples.partition_point(|&[t, _]| t < x[0]);
+        self.samples
+            .range(start..)
+            .take_while(move |&&[t, _]| t <= x[1])
+            .copied()
+    }
+}
+
+// A column's extremes, in the order they occurred.
+fn push_extremes(decimated: &mut Vec<[f64; 2]>, min: [f64; 2], max: [f64; 2]) {
+    if min == max {
+        decimated.push(min);
+    } else if min[0] <= max[0] {
+        decimated.extend([min, max]);
+    } else {
+        decimated.extend([max, min]);
+    }
+}
@@ -0,0 +1,145 @@
+use std::ops::Range;
+
+use crate::Bounds;
+
+/// A grid of subplots rendered into a single plot texture.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SubplotLayout {
+    pub rows: u32,
+    pub columns: u32,
+    /// Gap between neighboring cells, in pixels.
+    pub spacing: u32,
+}
+
+impl SubplotLayout {
+    pub fn grid(rows: u32, columns: u32) -> SubplotLayout {
+        SubplotLayout {
+            rows,
+            columns,
+            spacing: 0,
+        }
+    }
+
+    pub fn with_spacing(self, spacing: u32) -> SubplotLayout {
+        SubplotLayout { spacing, ..self }
+    }
+
+    pub fn len(&self) -> usize {
+        (self.rows * self.columns) as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The pixel rectangle of the cell at `index` (in row-major order) for a
+    /// texture of the given size.
+    pub fn cell(&self, index: usize, size: [u32; 2]) -> Option<CellRect> {
+        if index >= self.len() {
+            return None;
+        }
+
+        let column = index as u32 % self.columns;
+        let row = index as u32 / self.columns;
+
+        let gaps = [
+            self.spacing * (self.columns - 1),
+            self.spacing * (self.rows - 1),
+        ];
+        let width = size[0].saturating_sub(gaps[0]) / self.columns;
+        let height = size[1].saturating_sub(gaps[1]) / self.rows;
+
+        Some(CellRect {
+            x: column * (width + self.spacing),
+            y: row * (height + self.spacing),
+            width,
+            height,
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CellRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl CellRect {
+    pub fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+}
+
+/// The contents of a single subplot: a range of the uploaded vertices and
+/// the data bounds to display them with.
+#[derive(Clone, Debug)]
+pub struct Subplot {
+    pub bounds: Bounds,
+    pub vertices: Range<u32>,
+}
+
+/// Rows of subplots stacked on top of each other, all sharing one x range,
+/// e.g. for recordings from several sensors. Rendered as a one column grid
+/// in a single render pass, see
+/// [`GpuAcceleratedPlot::prepare_subplots`](crate::GpuAcceleratedPlot::prepare_subplots).
+#[derive(Clone, Debug)]
+pub struct StackedRows {
+    pub x: [f64; 2],
+    pub rows: Vec<StackedRow>,
+    /// Gap between neighboring rows, in pixels.
+    pub spacing: u32,
+}
+
+/// One row of [`StackedRows`]: a range of the uploaded vertices and its own
+/// y range.
+#[derive(Clone, Debug)]
+pub struct StackedRow {
+    pub y: [f64; 2],
+    pub vertices: Range<u32>,
+}
+
+impl StackedRows {
+    pub fn new(x: [f64; 2]) -> StackedRows {
+        StackedRows {
+            x,
+            rows: Vec::new(),
+            spacing: 0,
+        }
+    }
+
+    pub fn with_spacing(self, spacing: u32) -> StackedRows {
+        StackedRows { spacing, ..self }
+    }
+
+    pub fn push(&mut self, y: [f64; 2], vertices: Range<u32>) {
+        self.rows.push(StackedRow { y, vertices });
+    }
+
+    pub fn layout(&self) -> SubplotLayout {
+        SubplotLayout::grid(self.rows.len() as u32, 1).with_spacing(self.spacing)
+    }
+
+    /// The subplots to render, top to bottom.
+    pub fn subplots(&self) -> Vec<Subplot> {
+        self.rows
+            .iter()
+            .map(|row| Subplot {
+                bounds: Bounds::new(self.x, row.y),
+                vertices: row.vertices.clone(),
+            })
+            .collect()
+    }
+
+    /// The row containing pixel `y` from the top of a texture of the given
+    /// size, if any.
+    pub fn row_at(&self, y: u32, size: [u32; 2]) -> Option<usize> {
+        let layout = self.layout();
+        (0..self.rows.len()).find(|&i| {
+            layout
+                .cell(i, size)
+                .map_or(false, |cell| y >= cell.y && y < cell.y + cell.height)
+        })
+    }
+}
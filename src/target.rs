@@ -0,0 +1,362 @@
+use std::{fmt, num::NonZeroU32};
+
+use wgpu::TextureViewDescriptor;
+
+use crate::blit::{BlitFilter, Blitter};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FormatError {
+    /// The format requires device features which were not enabled.
+    MissingFeatures(wgpu::Features),
+    /// The format can't be used as a render attachment or sampled afterwards.
+    NotRenderable(wgpu::TextureFormat),
+    /// The format can't be blended or filtered, e.g. integer formats.
+    NotBlendable(wgpu::TextureFormat),
+    /// The format has no sRGB (or linear) counterpart to be viewed as.
+    NoSrgbView(wgpu::TextureFormat),
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FormatError::MissingFeatures(features) => {
+                write!(f, "render format requires missing features {:?}", features)
+            }
+            FormatError::NotRenderable(format) => {
+                write!(f, "{:?} is not usable as a render target", format)
+            }
+            FormatError::NotBlendable(format) => {
+                write!(f, "{:?} does not support blending or filtering", format)
+            }
+            FormatError::NoSrgbView(format) => {
+                write!(f, "{:?} has no sRGB counterpart to be viewed as", format)
+            }
+        }
+    }
+}
+
+impl std::error::Error for FormatError {}
+
+/// Check that the device can render plots into the given format, blend
+/// lines onto it, and sample it back for compositing.
+pub fn validate_render_format(
+    device: &wgpu::Device,
+    format: wgpu::TextureFormat,
+) -> Result<(), FormatError> {
+    let info = format.describe();
+
+    let missing = info.required_features - device.features();
+    if !missing.is_empty() {
+        return Err(FormatError::MissingFeatures(missing));
+    }
+
+    let usages = wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING;
+    if !info
+        .guaranteed_format_features
+        .allowed_usages
+        .contains(usages)
+    {
+        return Err(FormatError::NotRenderable(format));
+    }
+
+    if info.sample_type != (wgpu::TextureSampleType::Float { filterable: true }) {
+        return Err(FormatError::NotBlendable(format));
+    }
+
+    Ok(())
+}
+
+// The same format with the sRGB transfer function toggled, e.g.
+// `Rgba8UnormSrgb` for `Rgba8Unorm` and the other way around.
+pub(crate) fn srgb_counterpart(format: wgpu::TextureFormat) -> Option<wgpu::TextureFormat> {
+    use wgpu::TextureFormat::*;
+
+    match format {
+        Rgba8Unorm => Some(Rgba8UnormSrgb),
+        Rgba8UnormSrgb => Some(Rgba8Unorm),
+        Bgra8Unorm => Some(Bgra8UnormSrgb),
+        Bgra8UnormSrgb => Some(Bgra8Unorm),
+        _ => None,
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct TargetConfig {
+    pub render_format: wgpu::TextureFormat,
+    pub output_format: wgpu::TextureFormat,
+    // The format the displayed texture is sampled as: the output format, or
+    // its sRGB counterpart. wgpu 0.13 can't view a texture as another format
+    // (`view_formats`), so the texture is created in this format instead,
+    // and the conversion stores what the output format would have held.
+    pub view_format: wgpu::TextureFormat,
+    pub sample_count: u32,
+    pub mipmaps: bool,
+    // Convert and downsample with exact texel loads instead of the sampler.
+    pub deterministic: bool,
+}
+
+// The set of textures a plot renders into. Lines are drawn into `color` in
+// the internal render format (through `multisampled` when MSAA is enabled),
+// then converted into `output` when the display or view format differs. When
+// mipmaps are enabled, the displayed texture carries a full mip chain which
+// is regenerated after each render.
+pub(crate) struct RenderTarget {
+    config: TargetConfig,
+
+    color: (wgpu::Texture, wgpu::TextureView),
+    multisampled: Option<(wgpu::Texture, wgpu::TextureView)>,
+    output: Option<((wgpu::Texture, wgpu::TextureView), wgpu::BindGroup)>,
+    // For each mip level past the first: the destination view, and a bind
+    // group sampling the level above it.
+    mip_chain: Vec<(wgpu::TextureView, wgpu::BindGroup)>,
+
+    width: u32,
+    height: u32,
+}
+
+impl RenderTarget {
+    pub fn new(
+        device: &wgpu::Device,
+        blitter: &Blitter,
+        config: TargetConfig,
+        width: u32,
+        height: u32,
+    ) -> RenderTarget {
+        let converted = config.render_format != config.output_format
+            || config.view_format != config.output_format;
+        if converted {
+            blitter.prepare_with(device, config.view_format, config.conversion_filter());
+        }
+        if config.mipmaps {
+            blitter.prepare_with(device, config.view_format, config.mip_filter());
+        }
+
+        let mip_level_count = if config.mipmaps {
+            mip_level_count(width, height)
+        } else {
+            1
+        };
+
+        // Only the displayed texture carries the mip chain.
+        let color = create_texture(
+            device,
+            config.render_format,
+            1,
+            width,
+            height,
+            if converted { 1 } else { mip_level_count },
+        );
+        let multisampled = (config.sample_count > 1).then(|| {
+            create_texture(
+                device,
+                config.render_format,
+                config.sample_count,
+                width,
+                height,
+                1,
+            )
+        });
+        let output = converted.then(|| {
+            let texture = create_texture(
+                device,
+                config.view_format,
+                1,
+                width,
+                height,
+                mip_level_count,
+            );
+            let bind_group = blitter.create_bind_group(device, &color.1);
+            (texture, bind_group)
+        });
+
+        let display = match &output {
+            Some(((texture, _), _)) => texture,
+            None => &color.0,
+        };
+        let mip_chain = (1..mip_level_count)
+            .map(|level| {
+                let source = mip_view(display, level - 1);
+                let destination = mip_view(display, level);
+                (destination, blitter.create_bind_group(device, &source))
+            })
+            .collect();
+
+        RenderTarget {
+            config,
+            color,
+            multisampled,
+            output,
+            mip_chain,
+            width,
+            height,
+        }
+    }
+
+    pub fn size(&self) -> [u32; 2] {
+        [self.width, self.height]
+    }
+
+    pub fn config(&self) -> TargetConfig {
+        self.config
+    }
+
+    pub fn mip_level_count(&self) -> u32 {
+        self.mip_chain.len() as u32 + 1
+    }
+
+    // Re-allocate the textures if the requested dimensions have changed.
+    // Returns whether the textures were re-allocated (and so need to be
+    // rendered again).
+    pub fn resize(&mut self, device: &wgpu::Device, blitter: &Blitter, size: [u32; 2]) -> bool {
+        let resized = size != self.size();
+        if resized {
+            *self = RenderTarget::new(device, blitter, self.config, size[0], size[1]);
+        }
+        resized
+    }
+
+    pub fn reconfigure(
+        &mut self,
+        device: &wgpu::Device,
+        blitter: &Blitter,
+        config: TargetConfig,
+    ) -> bool {
+        let changed = config != self.config;
+        if changed {
+            *self = RenderTarget::new(device, blitter, config, self.width, self.height);
+        }
+        changed
+    }
+
+    // The texture which is handed to egui for display.
+    pub fn output_texture(&self) -> &wgpu::Texture {
+        match &self.output {
+            Some(((texture, _), _)) => texture,
+            None => &self.color.0,
+        }
+    }
+
+    pub fn create_view(&self) -> wgpu::TextureView {
+        self.output_texture()
+            .create_view(&TextureViewDescriptor::default())
+    }
+
+    // A view of a single mip level of the displayed texture, for sampling.
+    pub fn create_mip_view(&self, level: u32) -> wgpu::TextureView {
+        mip_view(self.output_texture(), level)
+    }
+
+    // The single-sampled texture lines end up in, for passes which draw on
+    // top of them after the main pass.
+    pub fn color_view(&self) -> &wgpu::TextureView {
+        &self.color.1
+    }
+
+    // Render directly to the texture if no MSAA, or use the multisampled
+    // buffer and resolve to the texture if using MSAA.
+    pub fn color_attachment(
+        &self,
+        load: wgpu::LoadOp<wgpu::Color>,
+    ) -> wgpu::RenderPassColorAttachment<'_> {
+        match &self.multisampled {
+            None => wgpu::RenderPassColorAttachment {
+                view: &self.color.1,
+                resolve_target: None,
+                ops: wgpu::Operations { load, store: true },
+            },
+            Some((_, msaa_view)) => wgpu::RenderPassColorAttachment {
+                view: msaa_view,
+                resolve_target: Some(&self.color.1),
+                ops: wgpu::Operations { load, store: false },
+            },
+        }
+    }
+
+    // Convert the rendered plot into the output format, if required, then
+    // downsample each mip level from the one above it.
+    pub fn finish(&self, encoder: &mut wgpu::CommandEncoder, blitter: &Blitter) {
+        let format = self.config.view_format;
+        if let Some(((_, view), bind_group)) = &self.output {
+            let filter = self.config.conversion_filter();
+            blitter.blit_with(encoder, bind_group, view, format, filter);
+        }
+
+        for (destination, source) in &self.mip_chain {
+            let filter = self.config.mip_filter();
+            blitter.blit_with(encoder, source, destination, format, filter);
+        }
+    }
+}
+
+impl TargetConfig {
+    fn conversion_filter(&self) -> BlitFilter {
+        let srgb = |format: wgpu::TextureFormat| format.describe().srgb;
+        if self.view_format != self.output_format {
+            // Store the bytes the output format would have held, so that
+            // they're reinterpreted when sampled.
+            if srgb(self.view_format) {
+                BlitFilter::DecodeSrgb
+            } else {
+                BlitFilter::EncodeSrgb
+            }
+        } else if self.deterministic {
+            BlitFilter::Exact
+        } else {
+            BlitFilter::Linear
+        }
+    }
+
+    fn mip_filter(&self) -> BlitFilter {
+        if self.deterministic {
+            BlitFilter::Box
+        } else {
+            BlitFilter::Linear
+        }
+    }
+}
+
+fn mip_level_count(width: u32, height: u32) -> u32 {
+    32 - width.max(height).max(1).leading_zeros()
+}
+
+fn mip_view(texture: &wgpu::Texture, level: u32) -> wgpu::TextureView {
+    texture.create_view(&TextureViewDescriptor {
+        base_mip_level: level,
+        mip_level_count: NonZeroU32::new(1),
+        ..Default::default()
+    })
+}
+
+// Create a texture and a view of its first mip level, suitable for use as a
+// render attachment.
+pub(crate) fn create_texture(
+    device: &wgpu::Device,
+    format: wgpu::TextureFormat,
+    sample_count: u32,
+    width: u32,
+    height: u32,
+    mip_level_count: u32,
+) -> (wgpu::Texture, wgpu::TextureView) {
+    // Single-sampled textures can be read back, see `read_rgba`.
+    let mut usage = wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::RENDER_ATTACHMENT;
+    if sample_count == 1 {
+        usage |= wgpu::TextureUsages::COPY_SRC;
+    }
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("egui_plot_texture"),
+        size: wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count,
+        sample_count,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage,
+    });
+
+    let view = mip_view(&texture, 0);
+
+    (texture, view)
+}
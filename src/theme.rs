@@ -0,0 +1,67 @@
+/// How the plot texture is styled as a whole, switchable at runtime with
+/// [`GpuAcceleratedPlot::set_theme`](crate::GpuAcceleratedPlot::set_theme).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Theme {
+    /// Linear RGBA the plot texture is cleared to before drawing.
+    pub background: [f32; 4],
+    /// Draws every series without a gradient in this linear RGBA color,
+    /// instead of by its vertex colors.
+    pub line_color: Option<[f32; 4]>,
+    /// Multiplies the width of every line.
+    pub line_width: f32,
+    /// Whether series set to glow do. Glow is additive, so it's best left
+    /// off on light backgrounds.
+    pub glow: bool,
+    /// How much to enlarge text labeled around the plot, e.g. axis ticks
+    /// drawn for an exported image, relative to the UI's font size.
+    pub label_scale: f32,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::screen()
+    }
+}
+
+impl Theme {
+    /// A transparent background over the UI, lines in their own colors.
+    pub fn screen() -> Theme {
+        Theme {
+            background: [0.0; 4],
+            line_color: None,
+            line_width: 1.0,
+            glow: true,
+            label_scale: 1.0,
+        }
+    }
+
+    /// Publication style: thin dark lines on white, without glow, and
+    /// larger labels.
+    pub fn print() -> Theme {
+        Theme {
+            background: [1.0; 4],
+            line_color: Some([0.01, 0.01, 0.01, 1.0]),
+            line_width: 0.6,
+            glow: false,
+            label_scale: 1.25,
+        }
+    }
+
+    pub fn with_background(self, background: [f32; 4]) -> Theme {
+        Theme { background, ..self }
+    }
+
+    pub fn with_line_color(self, line_color: Option<[f32; 4]>) -> Theme {
+        Theme { line_color, ..self }
+    }
+
+    pub fn with_line_width(self, line_width: f32) -> Theme {
+        Theme { line_width, ..self }
+    }
+
+    #[cfg(feature = "gpu")]
+    pub(crate) fn clear_color(&self) -> wgpu::Color {
+        let [r, g, b, a] = self.background.map(f64::from);
+        wgpu::Color { r, g, b, a }
+    }
+}
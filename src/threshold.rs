@@ -0,0 +1,318 @@
+#[cfg(feature = "compute")]
+use crate::{readback::Mapping, VertexLayout};
+use crate::{LineBatch, Uniform, Vertex};
+
+/// The most breaches [`GpuAcceleratedPlot::find_breaches`](crate::GpuAcceleratedPlot::find_breaches)
+/// returns at once.
+#[cfg(feature = "compute")]
+pub const MAX_BREACHES: usize = 4096;
+
+#[cfg(feature = "compute")]
+const WORKGROUP_SIZE: u32 = 64;
+#[cfg(feature = "compute")]
+const MAX_WORKGROUPS: u32 = 65535;
+
+/// A horizontal alarm threshold: values outside of `lower..=upper` breach
+/// it. The limits are drawn as lines, and the breaching regions shaded.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Threshold {
+    pub lower: f32,
+    pub upper: f32,
+    pub line_color: [f32; 4],
+    pub band_color: [f32; 4],
+}
+
+impl Threshold {
+    /// Breached by values above `level`.
+    pub fn above(level: f32) -> Threshold {
+        Threshold::band(f32::NEG_INFINITY, level)
+    }
+
+    /// Breached by values below `level`.
+    pub fn below(level: f32) -> Threshold {
+        Threshold::band(level, f32::INFINITY)
+    }
+
+    /// Breached by values outside of `lower..=upper`.
+    pub fn band(lower: f32, upper: f32) -> Threshold {
+        Threshold {
+            lower,
+            upper,
+            line_color: [1.0, 0.2, 0.2, 1.0],
+            band_color: [1.0, 0.2, 0.2, 0.15],
+        }
+    }
+
+    pub fn with_colors(self, line_color: [f32; 4], band_color: [f32; 4]) -> Threshold {
+        Threshold {
+            line_color,
+            band_color,
+            ..self
+        }
+    }
+
+    pub fn is_breached(&self, y: f32) -> bool {
+        y < self.lower || y > self.upper
+    }
+
+    fn push_vertices(&self, batch: &mut LineBatch, bounds: &Uniform) {
+        let ([x0, x1], [y0, y1]) = bounds.data_bounds();
+
+        // The breaching regions, as solid quads (zero normals).
+        for [lo, hi] in [[y0, self.lower.min(y1)], [self.upper.max(y0), y1]] {
+            if lo < hi {
+                batch.push(
+                    &[[x0, lo], [x1, lo], [x0, hi], [x1, hi]].map(|position| Vertex {
+                        position,
+                        normal: [0.0, 0.0],
+                        color: self.band_color,
+                    }),
+                );
+            }
+        }
+
+        for level in [self.lower, self.upper] {
+            if level.is_finite() {
+                batch.push(
+                    &[[x0, 1.0], [x0, -1.0], [x1, 1.0], [x1, -1.0]].map(|[x, n]| Vertex {
+                        position: [x, level],
+                        normal: [0.0, n],
+                        color: self.line_color,
+                    }),
+                );
+            }
+        }
+    }
+}
+
+// The vertices of a plot's thresholds, rebuilt whenever the thresholds or
+// the bounds they span change.
+#[cfg(feature = "gpu")]
+pub(crate) struct ThresholdLayer {
+    thresholds: Vec<Threshold>,
+    vertex_buffer: Option<wgpu::Buffer>,
+    capacity: usize,
+    vertex_count: u32,
+    // The bounds the vertices were built for, or None if they are stale.
+    bounds: Option<Uniform>,
+}
+
+#[cfg(feature = "gpu")]
+impl ThresholdLayer {
+    pub fn new() -> ThresholdLayer {
+        ThresholdLayer {
+            thresholds: Vec::new(),
+            vertex_buffer: None,
+            capacity: 0,
+            vertex_count: 0,
+            bounds: None,
+        }
+    }
+
+    pub fn set(&mut self, thresholds: Vec<Threshold>) {
+        self.thresholds = thresholds;
+        self.bounds = None;
+    }
+
+    // Returns whether the vertices changed.
+    pub fn update(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, bounds: &Uniform) -> bool {
+        if self.bounds.as_ref() == Some(bounds) {
+            return false;
+        }
+        self.bounds = Some(*bounds);
+
+        let mut batch = LineBatch::new();
+        for threshold in &self.thresholds {
+            threshold.push_vertices(&mut batch, bounds);
+        }
+        let vertices = batch.vertices();
+
+        if vertices.len() > self.capacity {
+            self.capacity = vertices.len().next_power_of_two();
+            self.vertex_buffer = Some(device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("egui_plot_threshold_vertices"),
+                size: (self.capacity * std::mem::size_of::<Vertex>()) as wgpu::BufferAddress,
+                usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::VERTEX,
+                mapped_at_creation: false,
+            }));
+        }
+
+        if let Some(buffer) = &self.vertex_buffer {
+            queue.write_buffer(buffer, 0, bytemuck::cast_slice(vertices));
+        }
+        self.vertex_count = vertices.len() as u32;
+        true
+    }
+
+    // The vertex buffer and vertex count, if there's anything to draw.
+    pub fn vertices(&self) -> Option<(&wgpu::Buffer, u32)> {
+        self.vertex_buffer
+            .as_ref()
+            .filter(|_| self.vertex_count > 0)
+            .map(|buffer| (buffer, self.vertex_count))
+    }
+}
+
+#[cfg(feature = "compute")]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct Params {
+    x_range: [f32; 2],
+    limits: [f32; 2],
+    stride: u32,
+    position: u32,
+    samples: u32,
+    capacity: u32,
+}
+
+// Finds where the samples in a vertex buffer breach a threshold, on the GPU.
+#[cfg(feature = "compute")]
+pub(crate) struct BreachFinder {
+    pipeline: wgpu::ComputePipeline,
+    params: wgpu::Buffer,
+    breaches: wgpu::Buffer,
+    staging: wgpu::Buffer,
+    // The search being read back.
+    pending: Option<Mapping>,
+}
+
+#[cfg(feature = "compute")]
+impl BreachFinder {
+    pub fn new(device: &wgpu::Device) -> BreachFinder {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("egui_plot_threshold_shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("./threshold_shader.wgsl").into()),
+        });
+
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("egui_plot_threshold_pipeline"),
+            layout: None,
+            module: &shader,
+            entry_point: "cs_breaches",
+        });
+
+        let params = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("egui_plot_threshold_params"),
+            size: std::mem::size_of::<Params>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::UNIFORM,
+            mapped_at_creation: false,
+        });
+
+        // The count, then the indices.
+        let size = ((1 + MAX_BREACHES) * std::mem::size_of::<u32>()) as wgpu::BufferAddress;
+        let breaches = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("egui_plot_threshold_breaches"),
+            size,
+            usage: wgpu::BufferUsages::STORAGE
+                | wgpu::BufferUsages::COPY_DST
+                | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let staging = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("egui_plot_threshold_staging"),
+            size,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        BreachFinder {
+            pipeline,
+            params,
+            breaches,
+            staging,
+            pending: None,
+        }
+    }
+
+    // Start finding breaches among `count` vertices laid out as `layout`
+    // from byte `offset` of `points`, within `x_range`. Returns false if the
+    // last search is still being read back.
+    #[allow(clippy::too_many_arguments)]
+    pub fn submit(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        points: &wgpu::Buffer,
+        layout: VertexLayout,
+        offset: wgpu::BufferAddress,
+        count: u32,
+        x_range: [f32; 2],
+        threshold: &Threshold,
+    ) -> bool {
+        if self.pending.is_some() {
+            return false;
+        }
+
+        let samples = count / 2;
+        let params = Params {
+            x_range,
+            limits: [threshold.lower, threshold.upper],
+            stride: (layout.stride / 4) as u32,
+            position: ((offset + layout.position) / 4) as u32,
+            samples,
+            capacity: MAX_BREACHES as u32,
+        };
+        queue.write_buffer(&self.params, 0, bytemuck::bytes_of(&params));
+        queue.write_buffer(&self.breaches, 0, bytemuck::bytes_of(&0u32));
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("egui_plot_threshold_bind_group"),
+            layout: &self.pipeline.get_bind_group_layout(0),
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: self.params.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: points.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: self.breaches.as_entire_binding(),
+                },
+            ],
+        });
+
+        let workgroups = ((samples + WORKGROUP_SIZE - 1) / WORKGROUP_SIZE).clamp(1, MAX_WORKGROUPS);
+
+        let mut encoder =
+            device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        {
+            let mut cpass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("egui_plot_threshold_pass"),
+            });
+            cpass.set_pipeline(&self.pipeline);
+            cpass.set_bind_group(0, &bind_group, &[]);
+            cpass.dispatch_workgroups(workgroups, 1, 1);
+        }
+        let size = ((1 + MAX_BREACHES) * std::mem::size_of::<u32>()) as wgpu::BufferAddress;
+        encoder.copy_buffer_to_buffer(&self.breaches, 0, &self.staging, 0, size);
+        queue.submit(std::iter::once(encoder.finish()));
+
+        self.pending = Some(Mapping::start(&[self.staging.slice(..)]));
+        true
+    }
+
+    // The sorted vertex indices found by the last search submitted, once
+    // read back.
+    pub fn finish(&mut self) -> Option<Vec<u32>> {
+        let mapped = self.pending.as_ref()?.state()?;
+        let mapping = self.pending.take()?;
+        if !mapped {
+            mapping.unmap(&[&self.staging]);
+            return Some(Vec::new());
+        }
+
+        let mut indices = {
+            let data = self.staging.slice(..).get_mapped_range();
+            let words: &[u32] = bytemuck::cast_slice(&data);
+            let found = (words[0] as usize).min(MAX_BREACHES);
+            words[1..=found].to_vec()
+        };
+        self.staging.unmap();
+
+        indices.sort_unstable();
+        Some(indices)
+    }
+}
@@ -0,0 +1,64 @@
+struct Params {
+    x_range: vec2<f32>,
+    // Values below the first or above the second limit breach the threshold.
+    limits: vec2<f32>,
+    // Vertex stride and position offset, in 4 byte words.
+    stride: u32,
+    position: u32,
+    samples: u32,
+    capacity: u32,
+};
+
+// Found breaches, in no particular order. `count` may exceed the capacity.
+struct Breaches {
+    count: atomic<u32>,
+    indices: array<u32>,
+};
+
+@group(0) @binding(0)
+var<uniform> params: Params;
+@group(0) @binding(1)
+var<storage, read> points: array<f32>;
+@group(0) @binding(2)
+var<storage, read_write> breaches: Breaches;
+
+let WORKGROUP_SIZE: u32 = 64u;
+
+// Vertices come in pairs with the same position, one for each normal.
+fn sample(i: u32) -> vec2<f32> {
+    let word = 2u * i * params.stride + params.position;
+    return vec2<f32>(points[word], points[word + 1u]);
+}
+
+// NaN gaps never breach.
+fn breached(y: f32) -> bool {
+    return y < params.limits[0] || y > params.limits[1];
+}
+
+@compute @workgroup_size(64)
+fn cs_breaches(@builtin(global_invocation_id) id: vec3<u32>,
+               @builtin(num_workgroups) groups: vec3<u32>) {
+    let total = groups.x * WORKGROUP_SIZE;
+
+    for (var i = id.x; i < params.samples; i = i + total) {
+        let p = sample(i);
+        if (p.x < params.x_range[0] || p.x > params.x_range[1] || !breached(p.y)) {
+            continue;
+        }
+
+        // Only report where a breach starts, or where it enters the visible
+        // range.
+        var starts = i == 0u;
+        if (!starts) {
+            let previous = sample(i - 1u);
+            starts = !breached(previous.y) || previous.x < params.x_range[0];
+        }
+
+        if (starts) {
+            let slot = atomicAdd(&breaches.count, 1u);
+            if (slot < params.capacity) {
+                breaches.indices[slot] = 2u * i;
+            }
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::{
+    blit::Blitter,
+    target::{self, RenderTarget},
+};
+
+// A small copy of the plot, refreshed after renders which follow a change to
+// the data. It is downsampled from the plot texture's mip chain, so once the
+// lines have been resolved from MSAA it costs a single blit of a level at
+// most twice the thumbnail's size.
+pub(crate) struct Thumbnail {
+    size: [u32; 2],
+    format: wgpu::TextureFormat,
+    texture: (wgpu::Texture, wgpu::TextureView),
+    // The mip level sampled, for the plot size and mip count it was picked
+    // for.
+    source: Option<((u32, [u32; 2], u32), wgpu::BindGroup)>,
+    stale: AtomicBool,
+}
+
+impl Thumbnail {
+    pub fn new(
+        device: &wgpu::Device,
+        blitter: &Blitter,
+        format: wgpu::TextureFormat,
+        size: [u32; 2],
+    ) -> Thumbnail {
+        blitter.prepare(device, format);
+        let size = size.map(|n| n.max(1));
+
+        Thumbnail {
+            size,
+            format,
+            texture: target::create_texture(device, format, 1, size[0], size[1], 1),
+            source: None,
+            stale: AtomicBool::new(true),
+        }
+    }
+
+    pub fn size(&self) -> [u32; 2] {
+        self.size
+    }
+
+    pub fn format(&self) -> wgpu::TextureFormat {
+        self.format
+    }
+
+    pub fn create_view(&self) -> wgpu::TextureView {
+        self.texture
+            .0
+            .create_view(&wgpu::TextureViewDescriptor::default())
+    }
+
+    // Refresh the thumbnail after the next render.
+    pub fn invalidate(&self) {
+        self.stale.store(true, Ordering::Relaxed);
+    }
+
+    // Pick the smallest mip level of the plot texture which is still at
+    // least as large as the thumbnail, if the plot texture has changed.
+    pub fn prepare(&mut self, device: &wgpu::Device, blitter: &Blitter, target: &RenderTarget) {
+        let size = target.size();
+        let scale = (size[0] / self.size[0]).min(size[1] / self.size[1]).max(1);
+        let level = (31 - scale.leading_zeros()).min(target.mip_level_count() - 1);
+
+        let key = (level, size, target.mip_level_count());
+        if self.source.as_ref().map(|(k, _)| *k) != Some(key) {
+            let view = target.create_mip_view(level);
+            self.source = Some((key, blitter.create_bind_group(device, &view)));
+            self.invalidate();
+        }
+    }
+
+    // Downsample the plot into the thumbnail, if the data has changed since
+    // it was last refreshed.
+    pub fn update(&self, encoder: &mut wgpu::CommandEncoder, blitter: &Blitter) {
+        if let Some((_, source)) = &self.source {
+            if self.stale.swap(false, Ordering::Relaxed) {
+                blitter.blit(encoder, source, &self.texture.1, self.format);
+            }
+        }
+    }
+}
@@ -0,0 +1,320 @@
+#[cfg(feature = "gpu")]
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+#[cfg(feature = "gpu")]
+use crate::Uniform;
+
+/// A span of time on one lane of a [`Timeline`], e.g. a scheduled task or
+/// an annotated region. `id` is returned by [`Timeline::pick`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Interval {
+    pub start: f32,
+    pub end: f32,
+    pub lane: u32,
+    pub color: [f32; 4],
+    pub id: u64,
+}
+
+/// Gantt-style lanes of intervals, drawn as rounded rectangles in the main
+/// viewport. Lane `i` spans `origin + i * lane_height` up to the next
+/// lane in the plot's y units, so lanes can sit alongside time series in
+/// the same plot.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Timeline {
+    pub origin: f32,
+    pub lane_height: f32,
+    /// Fraction of each lane left empty between neighboring lanes.
+    pub gap: f32,
+    /// Corner radius, in pixels.
+    pub radius: f32,
+    pub intervals: Vec<Interval>,
+}
+
+impl Timeline {
+    pub fn new(origin: f32, lane_height: f32) -> Timeline {
+        Timeline {
+            origin,
+            lane_height,
+            gap: 0.2,
+            radius: 4.0,
+            intervals: Vec::new(),
+        }
+    }
+
+    pub fn with_gap(self, gap: f32) -> Timeline {
+        Timeline { gap, ..self }
+    }
+
+    pub fn with_radius(self, radius: f32) -> Timeline {
+        Timeline { radius, ..self }
+    }
+
+    pub fn push(&mut self, interval: Interval) {
+        self.intervals.push(interval);
+    }
+
+    /// The y range an interval on `lane` covers.
+    pub fn lane_span(&self, lane: u32) -> [f32; 2] {
+        let bottom = self.origin + lane as f32 * self.lane_height;
+        let margin = self.lane_height * self.gap.clamp(0.0, 1.0) * 0.5;
+        [bottom + margin, bottom + self.lane_height - margin]
+    }
+
+    /// The id of the interval under data position `(x, y)`, e.g. where the
+    /// plot was clicked. Later intervals are drawn on top, so win ties.
+    pub fn pick(&self, x: f64, y: f64) -> Option<u64> {
+        self.intervals
+            .iter()
+            .rev()
+            .find(|interval| {
+                let [y0, y1] = self.lane_span(interval.lane);
+                let (x0, x1) = (
+                    interval.start.min(interval.end),
+                    interval.start.max(interval.end),
+                );
+                (x0 as f64..=x1 as f64).contains(&x)
+                    && (y0.min(y1) as f64..=y0.max(y1) as f64).contains(&y)
+            })
+            .map(|interval| interval.id)
+    }
+
+    #[cfg(feature = "gpu")]
+    fn instances(&self) -> impl Iterator<Item = IntervalInstance> + '_ {
+        self.intervals.iter().map(|interval| IntervalInstance {
+            x: [interval.start, interval.end],
+            y: self.lane_span(interval.lane),
+            color: interval.color,
+        })
+    }
+}
+
+#[cfg(feature = "gpu")]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct IntervalInstance {
+    x: [f32; 2],
+    y: [f32; 2],
+    color: [f32; 4],
+}
+
+// Laid out to match `Params` in the timeline shader.
+#[cfg(feature = "gpu")]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+struct Params {
+    x_range: [f32; 2],
+    y_range: [f32; 2],
+    pixel: [f32; 2],
+    radius: f32,
+    _padding: f32,
+}
+
+// The timeline pipelines, shared between plots like the line pipelines.
+#[cfg(feature = "gpu")]
+pub(crate) struct TimelinePipelines {
+    shader: wgpu::ShaderModule,
+    bind_group_layout: wgpu::BindGroupLayout,
+    pipeline_layout: wgpu::PipelineLayout,
+    pipelines: Mutex<HashMap<(wgpu::TextureFormat, u32), Arc<wgpu::RenderPipeline>>>,
+}
+
+#[cfg(feature = "gpu")]
+impl TimelinePipelines {
+    pub fn new(device: &wgpu::Device) -> TimelinePipelines {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("egui_plot_timeline_shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("./timeline_shader.wgsl").into()),
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("egui_plot_timeline_bind_group_layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("egui_plot_timeline_pipeline_layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        TimelinePipelines {
+            shader,
+            bind_group_layout,
+            pipeline_layout,
+            pipelines: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn pipeline(
+        &self,
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        sample_count: u32,
+    ) -> Arc<wgpu::RenderPipeline> {
+        let mut pipelines = self
+            .pipelines
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let pipeline = pipelines.entry((format, sample_count)).or_insert_with(|| {
+            Arc::new(
+                device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                    label: Some("egui_plot_timeline_pipeline"),
+                    layout: Some(&self.pipeline_layout),
+                    vertex: wgpu::VertexState {
+                        module: &self.shader,
+                        entry_point: "vs_main",
+                        buffers: &[wgpu::VertexBufferLayout {
+                            array_stride: std::mem::size_of::<IntervalInstance>()
+                                as wgpu::BufferAddress,
+                            step_mode: wgpu::VertexStepMode::Instance,
+                            attributes: &wgpu::vertex_attr_array![
+                                0 => Float32x2,
+                                1 => Float32x2,
+                                2 => Float32x4,
+                            ],
+                        }],
+                    },
+                    fragment: Some(wgpu::FragmentState {
+                        module: &self.shader,
+                        entry_point: "fs_main",
+                        targets: &[Some(wgpu::ColorTargetState {
+                            format,
+                            blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                            write_mask: wgpu::ColorWrites::ALL,
+                        })],
+                    }),
+                    primitive: wgpu::PrimitiveState::default(),
+                    depth_stencil: None,
+                    multisample: wgpu::MultisampleState {
+                        count: sample_count,
+                        ..Default::default()
+                    },
+                    multiview: None,
+                }),
+            )
+        });
+
+        Arc::clone(pipeline)
+    }
+}
+
+// A plot's timeline, as one instance per interval.
+#[cfg(feature = "gpu")]
+pub(crate) struct TimelineLayer {
+    pub pipeline: Arc<wgpu::RenderPipeline>,
+    instances: Option<wgpu::Buffer>,
+    capacity: usize,
+    count: u32,
+    radius: f32,
+    params: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    written: Option<Params>,
+}
+
+#[cfg(feature = "gpu")]
+impl TimelineLayer {
+    pub fn new(
+        device: &wgpu::Device,
+        shared: &TimelinePipelines,
+        format: wgpu::TextureFormat,
+        sample_count: u32,
+    ) -> TimelineLayer {
+        let params = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("egui_plot_timeline_params"),
+            size: std::mem::size_of::<Params>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::UNIFORM,
+            mapped_at_creation: false,
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("egui_plot_timeline_bind_group"),
+            layout: &shared.bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: params.as_entire_binding(),
+            }],
+        });
+
+        TimelineLayer {
+            pipeline: shared.pipeline(device, format, sample_count),
+            instances: None,
+            capacity: 0,
+            count: 0,
+            radius: 0.0,
+            params,
+            bind_group,
+            written: None,
+        }
+    }
+
+    pub fn set_timeline(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        timeline: &Timeline,
+    ) {
+        let instances: Vec<IntervalInstance> = timeline.instances().collect();
+
+        if instances.len() > self.capacity {
+            self.capacity = instances.len().next_power_of_two();
+            self.instances = Some(device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("egui_plot_timeline_instances"),
+                size: (self.capacity * std::mem::size_of::<IntervalInstance>())
+                    as wgpu::BufferAddress,
+                usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::VERTEX,
+                mapped_at_creation: false,
+            }));
+        }
+
+        if let Some(buffer) = &self.instances {
+            queue.write_buffer(buffer, 0, bytemuck::cast_slice(&instances));
+        }
+        self.count = instances.len() as u32;
+        self.radius = timeline.radius;
+    }
+
+    // Follow the plot's bounds and size. Returns whether they changed.
+    pub fn update(&mut self, queue: &wgpu::Queue, bounds: &Uniform, size: [u32; 2]) -> bool {
+        let [x0, x1] = bounds.x_bounds;
+        let [y0, y1] = bounds.y_bounds;
+        let params = Params {
+            x_range: bounds.x_bounds,
+            y_range: bounds.y_bounds,
+            pixel: [
+                (x1 - x0) / size[0].max(1) as f32,
+                (y1 - y0) / size[1].max(1) as f32,
+            ],
+            radius: self.radius,
+            _padding: 0.0,
+        };
+
+        if self.written == Some(params) {
+            return false;
+        }
+
+        queue.write_buffer(&self.params, 0, bytemuck::bytes_of(&params));
+        self.written = Some(params);
+        true
+    }
+
+    pub fn draw<'rp>(&'rp self, rpass: &mut wgpu::RenderPass<'rp>) {
+        if let Some(instances) = self.instances.as_ref().filter(|_| self.count > 0) {
+            rpass.set_pipeline(&self.pipeline);
+            rpass.set_bind_group(0, &self.bind_group, &[]);
+            rpass.set_vertex_buffer(0, instances.slice(..));
+            rpass.draw(0..6, 0..self.count);
+        }
+    }
+}
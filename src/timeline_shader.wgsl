@@ -0,0 +1,69 @@
+struct VertexOut {
+    @location(0) color: vec4<f32>,
+    // Position relative to the interval's center, and its half size, in
+    // pixels.
+    @location(1) local: vec2<f32>,
+    @location(2) half_size: vec2<f32>,
+    @builtin(position) position: vec4<f32>,
+};
+
+struct Params {
+    x_range: vec2<f32>,
+    y_range: vec2<f32>,
+    // The size of one pixel, in data units.
+    pixel: vec2<f32>,
+    // Corner radius, in pixels.
+    radius: f32,
+    _padding: f32,
+};
+
+@group(0) @binding(0)
+var<uniform> params: Params;
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32,
+           @location(0) x: vec2<f32>,
+           @location(1) y: vec2<f32>,
+           @location(2) color: vec4<f32>) -> VertexOut {
+    var out: VertexOut;
+
+    let c = index % 6u;
+    let corner = vec2<f32>(
+        select(-1.0, 1.0, c == 1u || c == 2u || c == 4u),
+        select(-1.0, 1.0, c == 2u || c == 4u || c == 5u),
+    );
+
+    let pixel = abs(params.pixel);
+    let center = vec2<f32>(x[0] + x[1], y[0] + y[1]) * 0.5;
+    let half_size = abs(vec2<f32>(x[1] - x[0], y[1] - y[0])) * 0.5 / pixel;
+
+    // Grow the quad by a pixel to leave room for antialiasing.
+    let local = corner * (half_size + 1.0);
+    let p = center + local * pixel;
+
+    let origin = vec2<f32>(params.x_range[0], params.y_range[0]);
+    let size = vec2<f32>(params.x_range[1], params.y_range[1]) - origin;
+    let ndc = (p - origin) / size * 2.0 - 1.0;
+
+    out.color = color;
+    out.local = local;
+    out.half_size = half_size;
+    out.position = vec4<f32>(ndc, 0.0, 1.0);
+
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOut) -> @location(0) vec4<f32> {
+    // Signed distance to the rounded rectangle, in pixels.
+    let radius = min(params.radius, min(in.half_size.x, in.half_size.y));
+    let q = abs(in.local) - in.half_size + radius;
+    let d = length(max(q, vec2<f32>(0.0))) + min(max(q.x, q.y), 0.0) - radius;
+
+    let alpha = clamp(0.5 - d, 0.0, 1.0) * in.color.w;
+    if (alpha <= 0.0) {
+        discard;
+    }
+
+    return vec4<f32>(in.color.xyz, alpha);
+}
@@ -0,0 +1,104 @@
+use crate::{AxisBreaks, Bounds, Transfer, ValueMapping};
+
+/// The transform the line shader applies to data, on the CPU, for placing
+/// overlays (labels, buttons, custom markers) exactly on top of the plot.
+/// Get the plot's current one from
+/// [`GpuAcceleratedPlot::transform`](crate::GpuAcceleratedPlot::transform).
+///
+/// Pixels are counted from the top left of the plot, in the units of
+/// `size`. Folding isn't taken into account.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlotTransform {
+    /// In axis units, ordered as drawn: high to low on a reversed axis.
+    pub bounds: Bounds,
+    pub breaks: [AxisBreaks; 2],
+    /// Applied to y values, as for a series.
+    pub mapping: ValueMapping,
+    pub size: [f32; 2],
+}
+
+impl PlotTransform {
+    pub fn new(bounds: Bounds, size: [f32; 2]) -> PlotTransform {
+        PlotTransform {
+            bounds,
+            breaks: Default::default(),
+            mapping: ValueMapping::default(),
+            size,
+        }
+    }
+
+    pub fn with_breaks(self, x: AxisBreaks, y: AxisBreaks) -> PlotTransform {
+        PlotTransform {
+            breaks: [x, y],
+            ..self
+        }
+    }
+
+    /// Transform the values of a series with its own mapping.
+    pub fn with_value_mapping(self, mapping: ValueMapping) -> PlotTransform {
+        PlotTransform { mapping, ..self }
+    }
+
+    /// Place the same view in a viewport of another size, e.g. the rect of
+    /// the widget showing the plot.
+    pub fn with_size(self, size: [f32; 2]) -> PlotTransform {
+        PlotTransform { size, ..self }
+    }
+
+    /// Normalized device coordinates, -1..1 over the bounds with y up.
+    pub fn data_to_ndc(&self, data: [f64; 2]) -> [f64; 2] {
+        let axis = [
+            self.breaks[0].to_axis(data[0]),
+            self.breaks[1].to_axis(map_value(&self.mapping, data[1])),
+        ];
+        let range = [self.bounds.x, self.bounds.y];
+        [0, 1].map(|i| {
+            let [lo, hi] = range[i];
+            (axis[i] - lo) / (hi - lo) * 2.0 - 1.0
+        })
+    }
+
+    pub fn ndc_to_data(&self, ndc: [f64; 2]) -> [f64; 2] {
+        let range = [self.bounds.x, self.bounds.y];
+        let axis = [0, 1].map(|i| {
+            let [lo, hi] = range[i];
+            lo + (ndc[i] + 1.0) * 0.5 * (hi - lo)
+        });
+        [
+            self.breaks[0].to_data(axis[0]),
+            unmap_value(&self.mapping, self.breaks[1].to_data(axis[1])),
+        ]
+    }
+
+    pub fn data_to_px(&self, data: [f64; 2]) -> [f32; 2] {
+        let [x, y] = self.data_to_ndc(data);
+        [
+            ((x + 1.0) * 0.5 * self.size[0] as f64) as f32,
+            ((1.0 - y) * 0.5 * self.size[1] as f64) as f32,
+        ]
+    }
+
+    pub fn px_to_data(&self, px: [f32; 2]) -> [f64; 2] {
+        let [w, h] = self.size.map(|s| s.max(f32::MIN_POSITIVE) as f64);
+        self.ndc_to_data([px[0] as f64 / w * 2.0 - 1.0, 1.0 - px[1] as f64 / h * 2.0])
+    }
+}
+
+// As `map_value` in the line shader.
+fn map_value(mapping: &ValueMapping, y: f64) -> f64 {
+    let value = match mapping.transfer {
+        Transfer::Identity => y,
+        Transfer::Decibels => 20.0 * y.log10(),
+        Transfer::PowerDecibels => 10.0 * y.log10(),
+    };
+    mapping.scale as f64 * value + mapping.offset as f64
+}
+
+fn unmap_value(mapping: &ValueMapping, value: f64) -> f64 {
+    let value = (value - mapping.offset as f64) / mapping.scale as f64;
+    match mapping.transfer {
+        Transfer::Identity => value,
+        Transfer::Decibels => 10f64.powf(value / 20.0),
+        Transfer::PowerDecibels => 10f64.powf(value / 10.0),
+    }
+}
@@ -0,0 +1,134 @@
+use std::{marker::PhantomData, num::NonZeroU64, ops::Range};
+
+// Past this many changed fields, one write of every slot is cheaper.
+const MAX_FIELD_WRITES: usize = 8;
+
+// A uniform buffer holding many `T`s, each padded out to the device's
+// dynamic offset alignment. All slots are written in a single buffer write,
+// and a single bind group is selected between with dynamic offsets.
+pub(crate) struct UniformSlots<T> {
+    buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    stride: wgpu::BufferAddress,
+    capacity: usize,
+    staging: Vec<u8>,
+    _uniform: PhantomData<T>,
+}
+
+impl<T: bytemuck::Pod> UniformSlots<T> {
+    pub fn new(
+        device: &wgpu::Device,
+        bind_group_layout: &wgpu::BindGroupLayout,
+        capacity: usize,
+    ) -> UniformSlots<T> {
+        let alignment = device.limits().min_uniform_buffer_offset_alignment as wgpu::BufferAddress;
+        let size = std::mem::size_of::<T>() as wgpu::BufferAddress;
+        let stride = (size + alignment - 1) / alignment * alignment;
+        let capacity = capacity.max(1);
+
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("egui_plot_uniform_slots"),
+            size: stride * capacity as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::UNIFORM,
+            mapped_at_creation: false,
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("egui_plot_uniform_slots_bind_group"),
+            layout: bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                    buffer: &buffer,
+                    offset: 0,
+                    size: NonZeroU64::new(size),
+                }),
+            }],
+        });
+
+        UniformSlots {
+            buffer,
+            bind_group,
+            stride,
+            capacity,
+            staging: Vec::new(),
+            _uniform: PhantomData,
+        }
+    }
+
+    // Write all of the uniforms, growing the buffer (and re-creating the bind
+    // group) if there are more than fit.
+    pub fn write(
+        &mut self,
+        device: &wgpu::Device,
+        bind_group_layout: &wgpu::BindGroupLayout,
+        queue: &wgpu::Queue,
+        uniforms: &[T],
+    ) {
+        if uniforms.len() > self.capacity {
+            let capacity = uniforms.len().next_power_of_two();
+            *self = UniformSlots::new(device, bind_group_layout, capacity);
+        }
+
+        let stride = self.stride as usize;
+        self.staging.clear();
+        self.staging.resize(stride * uniforms.len(), 0);
+        for (chunk, uniform) in self.staging.chunks_exact_mut(stride).zip(uniforms) {
+            let bytes = bytemuck::bytes_of(uniform);
+            chunk[..bytes.len()].copy_from_slice(bytes);
+        }
+
+        if !self.staging.is_empty() {
+            queue.write_buffer(&self.buffer, 0, &self.staging);
+        }
+    }
+
+    // Write just the `fields` (byte ranges of `T`) of `uniforms` which differ
+    // from `written`, e.g. the bounds of a single axis while panning. Returns
+    // false, writing nothing, when the slots don't line up or so much changed
+    // that `write` is the better choice.
+    pub fn write_changed(
+        &self,
+        queue: &wgpu::Queue,
+        uniforms: &[T],
+        written: &[T],
+        fields: &[Range<usize>],
+    ) -> bool {
+        if uniforms.len() != written.len() || uniforms.len() > self.capacity {
+            return false;
+        }
+
+        let mut changed = Vec::new();
+        for (slot, (uniform, old)) in uniforms.iter().zip(written).enumerate() {
+            let (new, old) = (bytemuck::bytes_of(uniform), bytemuck::bytes_of(old));
+            for field in fields {
+                if new[field.clone()] != old[field.clone()] {
+                    changed.push((slot, field.clone()));
+                }
+            }
+        }
+        if changed.len() > MAX_FIELD_WRITES {
+            return false;
+        }
+
+        for (slot, field) in changed {
+            let bytes = &bytemuck::bytes_of(&uniforms[slot])[field.clone()];
+            let offset =
+                self.stride * slot as wgpu::BufferAddress + field.start as wgpu::BufferAddress;
+            queue.write_buffer(&self.buffer, offset, bytes);
+        }
+        true
+    }
+
+    pub fn bind_group(&self) -> &wgpu::BindGroup {
+        &self.bind_group
+    }
+
+    pub fn buffer(&self) -> &wgpu::Buffer {
+        &self.buffer
+    }
+
+    pub fn offset(&self, index: usize) -> wgpu::DynamicOffset {
+        (self.stride * index as wgpu::BufferAddress) as wgpu::DynamicOffset
+    }
+}
@@ -0,0 +1,338 @@
+use std::borrow::Cow;
+
+use crate::Vertex;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ValidationMode {
+    /// Upload vertices as provided.
+    #[default]
+    Off,
+    /// Scan vertices and record problems in a [`ValidationReport`], but
+    /// upload them unchanged.
+    Report,
+    /// Scan vertices and repair them before upload, keeping every vertex's
+    /// index: non-finite positions become gaps in the strip, bad normals are
+    /// replaced, and colors are clamped to `0..=1`.
+    Sanitize,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ValidationReport {
+    /// Number of vertices with a NaN or infinite position.
+    pub non_finite_positions: usize,
+    /// Number of vertices with a zero-length or non-finite normal.
+    pub bad_normals: usize,
+    /// Number of vertices with a color component outside of `0..=1`.
+    pub out_of_range_colors: usize,
+    /// Number of gaps made in the triangle strip between finite samples
+    /// (sanitize only).
+    pub gaps_inserted: usize,
+    /// Index of the first offending vertex, if any.
+    pub first_invalid_index: Option<usize>,
+}
+
+impl ValidationReport {
+    pub fn is_clean(&self) -> bool {
+        self.non_finite_positions == 0 && self.bad_normals == 0 && self.out_of_range_colors == 0
+    }
+
+    fn flag(&mut self, index: usize) {
+        self.first_invalid_index.get_or_insert(index);
+    }
+}
+
+fn position_is_finite(v: &Vertex) -> bool {
+    v.position.iter().all(|c| c.is_finite())
+}
+
+fn normal_is_valid(v: &Vertex) -> bool {
+    let [x, y] = v.normal;
+    x.is_finite() && y.is_finite() && (x != 0.0 || y != 0.0)
+}
+
+fn color_in_range(v: &Vertex) -> bool {
+    v.color.iter().all(|c| (0.0..=1.0).contains(c))
+}
+
+/// Scan the vertices for problems without modifying them.
+pub fn validate(points: &[Vertex]) -> ValidationReport {
+    let mut report = ValidationReport::default();
+
+    for (i, v) in points.iter().enumerate() {
+        if !position_is_finite(v) {
+            report.non_finite_positions += 1;
+            report.flag(i);
+        }
+        if !normal_is_valid(v) {
+            report.bad_normals += 1;
+            report.flag(i);
+        }
+        if !color_in_range(v) {
+            report.out_of_range_colors += 1;
+            report.flag(i);
+        }
+    }
+
+    report
+}
+
+/// Repair the vertices so that they can be safely drawn as a triangle strip.
+///
+/// Every vertex keeps its index, so series ranges, hits and selections still
+/// line up with the input. Samples (pairs of vertices) with a non-finite
+/// position break the strip instead: their vertices repeat the last vertex
+/// before the hole, and the last of them the first vertex after it, so they
+/// only form zero-area triangles and nothing is drawn across the gap.
+pub fn sanitize(points: &[Vertex]) -> (Vec<Vertex>, ValidationReport) {
+    sanitize_with_report(points, validate(points))
+}
+
+fn sanitize_with_report(
+    points: &[Vertex],
+    report: ValidationReport,
+) -> (Vec<Vertex>, ValidationReport) {
+    let mut out = points.to_vec();
+    let len = points.len();
+    let pairs = |range: std::ops::Range<usize>| range.start * 2..(range.end * 2).min(len);
+    let pair_count = (len + 1) / 2;
+    let finite = |p: usize| points[pairs(p..p + 1)].iter().all(position_is_finite);
+
+    // The last valid normal on each side of the line, by vertex parity.
+    let mut last_normals: [Option<[f32; 2]>; 2] = [None; 2];
+    for (i, v) in out.iter_mut().enumerate() {
+        for c in v.color.iter_mut() {
+            *c = if c.is_nan() { 0.0 } else { c.clamp(0.0, 1.0) };
+        }
+        if !finite(i / 2) {
+            continue;
+        }
+
+        if normal_is_valid(v) {
+            last_normals[i % 2] = Some(v.normal);
+            continue;
+        }
+        // Mirror the other vertex of the pair, or reuse the previous normal
+        // on the same side, or fall back to an arbitrary vertical normal.
+        // The sign alternates so the strip keeps its width.
+        let partner = points.get(i ^ 1).filter(|p| normal_is_valid(p));
+        v.normal = partner
+            .map(|p| p.normal.map(|n| -n))
+            .or(last_normals[i % 2])
+            .unwrap_or(if i % 2 == 0 { [0.0, 1.0] } else { [0.0, -1.0] });
+        last_normals[i % 2] = Some(v.normal);
+    }
+
+    let mut gaps_inserted = 0;
+    let mut p = 0;
+    while p < pair_count {
+        if finite(p) {
+            p += 1;
+            continue;
+        }
+        let run_start = p;
+        while p < pair_count && !finite(p) {
+            p += 1;
+        }
+
+        let before = (run_start * 2).checked_sub(1).map(|i| out[i]);
+        let after = (p < pair_count).then(|| out[p * 2]);
+        let hole = pairs(run_start..p);
+        match (before, after) {
+            (Some(before), Some(after)) => {
+                out[hole.clone()].fill(before);
+                out[hole.end - 1] = after;
+                gaps_inserted += 1;
+            }
+            (Some(fill), None) | (None, Some(fill)) => out[hole].fill(fill),
+            // Nothing to draw at all: identical vertices draw nothing.
+            (None, None) => out[hole].fill(Vertex::default()),
+        }
+    }
+
+    (
+        out,
+        ValidationReport {
+            gaps_inserted,
+            ..report
+        },
+    )
+}
+
+/// Apply the given validation mode, borrowing the input when no changes are
+/// required.
+pub fn apply(mode: ValidationMode, points: &[Vertex]) -> (Cow<'_, [Vertex]>, ValidationReport) {
+    match mode {
+        ValidationMode::Off => (Cow::Borrowed(points), ValidationReport::default()),
+        ValidationMode::Report => (Cow::Borrowed(points), validate(points)),
+        ValidationMode::Sanitize => {
+            let report = validate(points);
+            if report.is_clean() {
+                (Cow::Borrowed(points), report)
+            } else {
+                let (points, report) = sanitize_with_report(points, report);
+                (Cow::Owned(points), report)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A sample's pair of vertices, one either side of the line.
+    fn pair(x: f32, y: f32) -> [Vertex; 2] {
+        let vertex = |normal| Vertex {
+            position: [x, y],
+            normal,
+            color: [1.0; 4],
+        };
+        [vertex([0.0, 1.0]), vertex([0.0, -1.0])]
+    }
+
+    fn strip(samples: &[[f32; 2]]) -> Vec<Vertex> {
+        samples.iter().flat_map(|&[x, y]| pair(x, y)).collect()
+    }
+
+    fn positions(points: &[Vertex]) -> Vec<[f32; 2]> {
+        points.iter().map(|v| v.position).collect()
+    }
+
+    // Twice the signed area of each triangle of the strip, ignoring the
+    // normals.
+    fn areas(points: &[Vertex]) -> Vec<f32> {
+        points
+            .windows(3)
+            .map(|t| {
+                let [a, b, c] = [t[0].position, t[1].position, t[2].position];
+                (b[0] - a[0]) * (c[1] - a[1]) - (c[0] - a[0]) * (b[1] - a[1])
+            })
+            .collect()
+    }
+
+    #[test]
+    fn validate_counts_each_problem() {
+        let mut points = strip(&[[0.0, 0.0], [1.0, f32::NAN], [2.0, 0.0]]);
+        points[4].normal = [0.0, 0.0];
+        points[5].color[0] = 2.0;
+
+        let report = validate(&points);
+        assert_eq!(report.non_finite_positions, 2);
+        assert_eq!(report.bad_normals, 1);
+        assert_eq!(report.out_of_range_colors, 1);
+        assert_eq!(report.first_invalid_index, Some(2));
+        assert!(!report.is_clean());
+        assert!(validate(&strip(&[[0.0, 0.0], [1.0, 1.0]])).is_clean());
+    }
+
+    #[test]
+    fn sanitize_keeps_indices() {
+        let points = strip(&[[0.0, 0.0], [1.0, f32::NAN], [2.0, 2.0], [3.0, 3.0]]);
+        let (out, report) = sanitize(&points);
+
+        assert_eq!(out.len(), points.len());
+        assert_eq!(report.gaps_inserted, 1);
+        assert_eq!(
+            positions(&out),
+            [
+                [0.0, 0.0],
+                [0.0, 0.0],
+                [0.0, 0.0],
+                [2.0, 2.0],
+                [2.0, 2.0],
+                [2.0, 2.0],
+                [3.0, 3.0],
+                [3.0, 3.0]
+            ]
+        );
+        // The samples after the gap are where they were.
+        for i in 4..8 {
+            assert_eq!(out[i].position, points[i].position);
+            assert_eq!(out[i].normal, points[i].normal);
+        }
+    }
+
+    #[test]
+    fn sanitize_draws_nothing_across_gaps() {
+        let points = strip(&[
+            [0.0, 0.0],
+            [1.0, 5.0],
+            [2.0, f32::INFINITY],
+            [3.0, f32::NAN],
+            [4.0, 1.0],
+            [5.0, 0.0],
+        ]);
+        let (out, report) = sanitize(&points);
+
+        assert_eq!(report.gaps_inserted, 1);
+        // The triangles from the last vertex of the second sample to the
+        // first of the fifth.
+        for (i, area) in areas(&out).into_iter().enumerate().skip(3).take(6) {
+            assert_eq!(area, 0.0, "triangle {}", i);
+        }
+        // Whole vertices are repeated, normals included, so the shader
+        // can't widen the gap's triangles either.
+        assert_eq!(out[4].normal, out[3].normal);
+        assert_eq!(out[7].normal, out[8].normal);
+    }
+
+    #[test]
+    fn sanitize_replaces_whole_pairs() {
+        let mut points = strip(&[[0.0, 0.0], [1.0, 1.0], [2.0, 2.0]]);
+        points[3].position[1] = f32::NAN;
+        let (out, _) = sanitize(&points);
+
+        assert_eq!(out[2].position, [0.0, 0.0]);
+        assert_eq!(out[3].position, [2.0, 2.0]);
+    }
+
+    #[test]
+    fn sanitize_fills_holes_at_the_ends() {
+        let points = strip(&[[f32::NAN, 0.0], [1.0, 1.0], [2.0, f32::NAN]]);
+        let (out, report) = sanitize(&points);
+
+        assert_eq!(report.gaps_inserted, 0);
+        assert_eq!(positions(&out[..2]), [[1.0, 1.0]; 2]);
+        assert_eq!(positions(&out[4..]), [[1.0, 1.0]; 2]);
+        assert!(areas(&out).iter().all(|&area| area == 0.0));
+
+        let (out, _) = sanitize(&strip(&[[f32::NAN, 0.0]]));
+        assert_eq!(positions(&out), [[0.0, 0.0]; 2]);
+    }
+
+    #[test]
+    fn sanitize_repairs_normals_on_their_side() {
+        let mut points = strip(&[[0.0, 0.0], [1.0, f32::NAN], [2.0, 0.0], [3.0, 0.0]]);
+        // Both of the third sample's normals, and one of the fourth's.
+        points[4].normal = [f32::NAN, 0.0];
+        points[5].normal = [0.0, 0.0];
+        points[7].normal = [0.0, 0.0];
+        let (out, report) = sanitize(&points);
+
+        assert_eq!(report.bad_normals, 3);
+        assert_eq!(out[4].normal, [0.0, 1.0]);
+        assert_eq!(out[5].normal, [0.0, -1.0]);
+        assert_eq!(out[7].normal, [0.0, -1.0]);
+    }
+
+    #[test]
+    fn sanitize_clamps_colors() {
+        let mut points = strip(&[[0.0, 0.0]]);
+        points[0].color = [f32::NAN, -1.0, 2.0, 0.5];
+        let (out, _) = sanitize(&points);
+
+        assert_eq!(out[0].color, [0.0, 0.0, 1.0, 0.5]);
+    }
+
+    #[test]
+    fn apply_borrows_clean_points() {
+        let points = strip(&[[0.0, 0.0], [1.0, 1.0]]);
+        let (out, _) = apply(ValidationMode::Sanitize, &points);
+        assert!(matches!(out, Cow::Borrowed(_)));
+
+        let points = strip(&[[0.0, f32::NAN]]);
+        let (out, report) = apply(ValidationMode::Report, &points);
+        assert!(matches!(out, Cow::Borrowed(_)));
+        assert_eq!(report.non_finite_positions, 2);
+    }
+}
@@ -0,0 +1,89 @@
+use std::sync::Arc;
+
+use crate::Vertex;
+
+/// A plot's points, shared copy-on-write so that snapshots of them are
+/// cheap, e.g. for undo and redo in an editor.
+///
+/// Taking a [`SeriesVersion`] only clones a reference. The points are
+/// copied the first time they're edited while a snapshot still shares them,
+/// and the GPU only catches up when [`SeriesData::sync`] next reports them
+/// dirty, so restoring a version costs no more than an ordinary upload.
+#[derive(Clone, Default)]
+pub struct SeriesData {
+    points: Arc<Vec<Vertex>>,
+    version: u64,
+    // The version last reported by `sync`.
+    synced: Option<u64>,
+    // Counts up across every edit and restore, to tell versions apart.
+    next_version: u64,
+}
+
+/// An immutable snapshot of a [`SeriesData`], from
+/// [`SeriesData::snapshot`].
+#[derive(Clone)]
+pub struct SeriesVersion {
+    points: Arc<Vec<Vertex>>,
+    version: u64,
+}
+
+impl SeriesVersion {
+    pub fn points(&self) -> &[Vertex] {
+        &self.points
+    }
+
+    /// Identifies the version, unique within the data it was taken from.
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+}
+
+impl SeriesData {
+    pub fn new(points: Vec<Vertex>) -> SeriesData {
+        SeriesData {
+            points: Arc::new(points),
+            ..Default::default()
+        }
+    }
+
+    /// The points, as passed to the paint callbacks.
+    pub fn points(&self) -> Arc<Vec<Vertex>> {
+        Arc::clone(&self.points)
+    }
+
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    pub fn snapshot(&self) -> SeriesVersion {
+        SeriesVersion {
+            points: Arc::clone(&self.points),
+            version: self.version,
+        }
+    }
+
+    /// Go back (or forward) to a snapshot, without copying it.
+    pub fn restore(&mut self, snapshot: &SeriesVersion) {
+        if snapshot.version != self.version {
+            self.points = Arc::clone(&snapshot.points);
+            self.version = snapshot.version;
+        }
+    }
+
+    /// The points for editing, e.g. with a
+    /// [`PointEditor`](crate::PointEditor), copied first if a snapshot or
+    /// callback still shares them. Starts a new version.
+    pub fn edit(&mut self) -> &mut Vec<Vertex> {
+        self.next_version += 1;
+        self.version = self.next_version;
+        Arc::make_mut(&mut self.points)
+    }
+
+    /// Whether the points changed since the last call, to pass as `dirty`
+    /// when preparing the plot.
+    pub fn sync(&mut self) -> bool {
+        let dirty = self.synced != Some(self.version);
+        self.synced = Some(self.version);
+        dirty
+    }
+}
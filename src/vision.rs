@@ -0,0 +1,225 @@
+#[cfg(feature = "gpu")]
+use wgpu::util::DeviceExt;
+
+#[cfg(feature = "gpu")]
+use crate::{
+    blit::Blitter,
+    target::{create_texture, RenderTarget, TargetConfig},
+};
+
+/// A color vision deficiency to simulate over the rendered plot, see
+/// [`GpuAcceleratedPlot::set_color_vision`](crate::GpuAcceleratedPlot::set_color_vision).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ColorVision {
+    /// No red cones.
+    Protanopia,
+    /// No green cones.
+    Deuteranopia,
+    /// No blue cones.
+    Tritanopia,
+}
+
+impl ColorVision {
+    // The full-severity simulation matrices of Machado, Oliveira and
+    // Fernandes (2009), applied to linear RGB.
+    fn matrix(self) -> [[f32; 4]; 3] {
+        match self {
+            ColorVision::Protanopia => [
+                [0.152286, 1.052583, -0.204868, 0.0],
+                [0.114503, 0.786281, 0.099216, 0.0],
+                [-0.003882, -0.048116, 1.051998, 0.0],
+            ],
+            ColorVision::Deuteranopia => [
+                [0.367322, 0.860646, -0.227968, 0.0],
+                [0.280085, 0.672501, 0.047413, 0.0],
+                [-0.011820, 0.042940, 0.968881, 0.0],
+            ],
+            ColorVision::Tritanopia => [
+                [1.255528, -0.076749, -0.178779, 0.0],
+                [-0.078411, 0.930809, 0.147602, 0.0],
+                [0.004733, 0.691367, 0.303900, 0.0],
+            ],
+        }
+    }
+}
+
+/// Series colors which stay distinguishable under each kind of color vision
+/// deficiency (the Okabe-Ito palette, with grey in place of black), in
+/// linear RGB. See
+/// [`GpuAcceleratedPlot::set_cvd_safe_palette`](crate::GpuAcceleratedPlot::set_cvd_safe_palette).
+pub const CVD_SAFE_PALETTE: [[f32; 4]; 8] = [
+    [0.791, 0.347, 0.000, 1.0],
+    [0.093, 0.456, 0.815, 1.0],
+    [0.000, 0.342, 0.171, 1.0],
+    [0.871, 0.776, 0.054, 1.0],
+    [0.000, 0.168, 0.445, 1.0],
+    [0.665, 0.112, 0.000, 1.0],
+    [0.604, 0.191, 0.386, 1.0],
+    [0.497, 0.497, 0.497, 1.0],
+];
+
+// Applies a color vision simulation to the plot after each render. The plot
+// is filtered into a scratch texture, then copied back, since a texture
+// can't be sampled while it's being rendered to.
+#[cfg(feature = "gpu")]
+pub(crate) struct VisionFilter {
+    vision: ColorVision,
+    shader: wgpu::ShaderModule,
+    params: wgpu::Buffer,
+    pipeline: Option<(wgpu::TextureFormat, wgpu::RenderPipeline)>,
+    // The scratch texture, and bind groups filtering into it and copying it
+    // back, for the target they were created for.
+    scratch: Option<(
+        ([u32; 2], TargetConfig),
+        (wgpu::Texture, wgpu::TextureView),
+        wgpu::BindGroup,
+        wgpu::BindGroup,
+    )>,
+}
+
+#[cfg(feature = "gpu")]
+impl VisionFilter {
+    pub fn new(device: &wgpu::Device, vision: ColorVision) -> VisionFilter {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("egui_plot_vision_shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("./vision_shader.wgsl").into()),
+        });
+
+        let params = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("egui_plot_vision_params"),
+            contents: bytemuck::cast_slice(&vision.matrix()),
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::UNIFORM,
+        });
+
+        VisionFilter {
+            vision,
+            shader,
+            params,
+            pipeline: None,
+            scratch: None,
+        }
+    }
+
+    pub fn vision(&self) -> ColorVision {
+        self.vision
+    }
+
+    // Returns whether the simulation changed.
+    pub fn set_vision(&mut self, queue: &wgpu::Queue, vision: ColorVision) -> bool {
+        let changed = vision != self.vision;
+        if changed {
+            self.vision = vision;
+            queue.write_buffer(&self.params, 0, bytemuck::cast_slice(&vision.matrix()));
+        }
+        changed
+    }
+
+    // Follow changes to the plot's size and format.
+    pub fn prepare(&mut self, device: &wgpu::Device, blitter: &Blitter, target: &RenderTarget) {
+        let config = target.config();
+        if self.pipeline.as_ref().map(|(format, _)| *format) != Some(config.render_format) {
+            self.pipeline = Some((
+                config.render_format,
+                filter_pipeline(device, &self.shader, config.render_format),
+            ));
+            self.scratch = None;
+        }
+
+        let key = (target.size(), config);
+        if self.scratch.as_ref().map(|(k, ..)| *k) == Some(key) {
+            return;
+        }
+        let pipeline = match &self.pipeline {
+            Some((_, pipeline)) => pipeline,
+            None => return,
+        };
+
+        blitter.prepare(device, config.render_format);
+        let [width, height] = target.size();
+        let scratch = create_texture(device, config.render_format, 1, width, height, 1);
+        let filter = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("egui_plot_vision_bind_group"),
+            layout: &pipeline.get_bind_group_layout(0),
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(target.color_view()),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: self.params.as_entire_binding(),
+                },
+            ],
+        });
+        let copy = blitter.create_bind_group(device, &scratch.1);
+        self.scratch = Some((key, scratch, filter, copy));
+    }
+
+    pub fn apply(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        blitter: &Blitter,
+        target: &RenderTarget,
+    ) {
+        let (pipeline, (_, (_, scratch), filter, copy)) = match (&self.pipeline, &self.scratch) {
+            (Some((_, pipeline)), Some(scratch)) => (pipeline, scratch),
+            _ => return,
+        };
+
+        {
+            let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("egui_plot_vision_pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: scratch,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                        store: true,
+                    },
+                })],
+                depth_stencil_attachment: None,
+            });
+
+            rpass.set_pipeline(pipeline);
+            rpass.set_bind_group(0, filter, &[]);
+            rpass.draw(0..3, 0..1);
+        }
+
+        blitter.blit(
+            encoder,
+            copy,
+            target.color_view(),
+            target.config().render_format,
+        );
+    }
+}
+
+#[cfg(feature = "gpu")]
+fn filter_pipeline(
+    device: &wgpu::Device,
+    shader: &wgpu::ShaderModule,
+    format: wgpu::TextureFormat,
+) -> wgpu::RenderPipeline {
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("egui_plot_vision_pipeline"),
+        layout: None,
+        vertex: wgpu::VertexState {
+            module: shader,
+            entry_point: "vs_main",
+            buffers: &[],
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: shader,
+            entry_point: "fs_main",
+            targets: &[Some(wgpu::ColorTargetState {
+                format,
+                blend: None,
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+        }),
+        primitive: wgpu::PrimitiveState::default(),
+        depth_stencil: None,
+        multisample: wgpu::MultisampleState::default(),
+        multiview: None,
+    })
+}
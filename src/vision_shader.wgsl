@@ -0,0 +1,38 @@
+// Rows of the matrix applied to linear RGB.
+struct Params {
+    rows: array<vec4<f32>, 3>,
+};
+
+struct VertexOut {
+    @builtin(position) position: vec4<f32>,
+};
+
+@group(0) @binding(0)
+var source_texture: texture_2d<f32>;
+@group(0) @binding(1)
+var<uniform> params: Params;
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOut {
+    var out: VertexOut;
+
+    // A single triangle covering the whole viewport, as in the blit shader.
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    out.position = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOut) -> @location(0) vec4<f32> {
+    // The source is the same size as the destination, so read texels
+    // directly rather than filtering.
+    let color = textureLoad(source_texture, vec2<i32>(in.position.xy), 0);
+    let rgb = vec3<f32>(
+        dot(params.rows[0].xyz, color.rgb),
+        dot(params.rows[1].xyz, color.rgb),
+        dot(params.rows[2].xyz, color.rgb),
+    );
+
+    return vec4<f32>(max(rgb, vec3<f32>(0.0)), color.a);
+}
@@ -0,0 +1,100 @@
+use std::ops::Range;
+
+use crate::{Transfer, ValueMapping, Vertex};
+
+/// Stacks every visible series in its own row of one y axis, as in EEG or
+/// seismic viewers, by adjusting each series' value mapping in the shader.
+/// The first series is drawn at the top, at `spacing * (rows - 1)`, and the
+/// last at zero.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Waterfall {
+    /// Distance between the rows, in plotted y units.
+    pub spacing: f32,
+    /// Scale each series so that its values fill `fill` of its row, centered
+    /// on it, instead of only offsetting them.
+    pub normalize: bool,
+    pub fill: f32,
+}
+
+impl Waterfall {
+    pub fn new(spacing: f32) -> Waterfall {
+        Waterfall {
+            spacing,
+            normalize: false,
+            fill: 0.8,
+        }
+    }
+
+    pub fn normalized(self) -> Waterfall {
+        Waterfall {
+            normalize: true,
+            ..self
+        }
+    }
+
+    pub fn with_fill(self, fill: f32) -> Waterfall {
+        Waterfall { fill, ..self }
+    }
+
+    // The mapping drawing `mapping` in `row` of `rows`, given the series'
+    // raw value range, if known.
+    pub(crate) fn apply(
+        &self,
+        mapping: ValueMapping,
+        row: usize,
+        rows: usize,
+        range: Option<[f32; 2]>,
+    ) -> ValueMapping {
+        let center = self.spacing * rows.saturating_sub(row + 1) as f32;
+
+        let mapped = range
+            .filter(|_| self.normalize)
+            .map(|[lo, hi]| {
+                [lo, hi].map(|y| mapping.scale * transfer(mapping.transfer, y) + mapping.offset)
+            })
+            .filter(|[lo, hi]| lo.is_finite() && hi.is_finite() && hi != lo);
+
+        match mapped {
+            Some([lo, hi]) => {
+                let scale = self.spacing * self.fill / (hi - lo).abs();
+                ValueMapping {
+                    scale: mapping.scale * scale,
+                    offset: (mapping.offset - (lo + hi) * 0.5) * scale + center,
+                    transfer: mapping.transfer,
+                }
+            }
+            None => ValueMapping {
+                offset: mapping.offset + center,
+                ..mapping
+            },
+        }
+    }
+}
+
+// As applied in the line shader.
+fn transfer(transfer: Transfer, y: f32) -> f32 {
+    match transfer {
+        Transfer::Identity => y,
+        Transfer::Decibels => 20.0 * y.log10(),
+        Transfer::PowerDecibels => 10.0 * y.log10(),
+    }
+}
+
+// The range of y values of each series' vertices, for normalizing.
+pub(crate) fn series_ranges(points: &[Vertex], series: &[Range<u32>]) -> Vec<Option<[f32; 2]>> {
+    series
+        .iter()
+        .map(|vertices| {
+            let end = (vertices.end as usize).min(points.len());
+            let start = (vertices.start as usize).min(end);
+            points[start..end]
+                .iter()
+                .map(|v| v.position[1])
+                .filter(|y| y.is_finite())
+                .fold(None::<[f32; 2]>, |range, y| match range {
+                    None => Some([y, y]),
+                    Some([lo, hi]) => Some([f32::min(lo, y), f32::max(hi, y)]),
+                })
+        })
+        .collect()
+}
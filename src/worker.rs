@@ -0,0 +1,316 @@
+use std::{
+    io,
+    sync::{mpsc, Arc, Mutex, PoisonError},
+    thread::{self, JoinHandle},
+};
+
+#[cfg(feature = "egui")]
+use crate::DataNotifier;
+use crate::Vertex;
+
+/// Raw samples to turn into plot vertices on a worker thread.
+#[derive(Clone, Debug)]
+pub struct PrepareJob {
+    /// Returned with the result, to tell jobs apart.
+    pub id: u64,
+    pub samples: Vec<[f64; 2]>,
+    pub color: [f32; 4],
+    /// Drop samples closer than this (in data units) to the line through
+    /// their neighbours, before anything else. Zero keeps every sample.
+    pub tolerance: f64,
+    /// Number of coarser levels of detail to build, each with half of the
+    /// samples of the one before. Every level keeps the minimum and maximum
+    /// of the samples it merges, so peaks survive zooming out.
+    pub levels: usize,
+}
+
+impl PrepareJob {
+    pub fn new(id: u64, samples: Vec<[f64; 2]>, color: [f32; 4]) -> PrepareJob {
+        PrepareJob {
+            id,
+            samples,
+            color,
+            tolerance: 0.0,
+            levels: 0,
+        }
+    }
+
+    pub fn with_tolerance(mut self, tolerance: f64) -> PrepareJob {
+        self.tolerance = tolerance;
+        self
+    }
+
+    pub fn with_levels(mut self, levels: usize) -> PrepareJob {
+        self.levels = levels;
+        self
+    }
+}
+
+/// The vertices of a [`PrepareJob`], ready to be uploaded.
+#[derive(Clone, Debug, Default)]
+pub struct PreparedSeries {
+    pub id: u64,
+    // Full detail first, then each coarser level.
+    levels: Vec<Vec<Vertex>>,
+}
+
+impl PreparedSeries {
+    /// Full detail vertices, e.g. to pass to
+    /// [`GpuAcceleratedPlot::prepare`](crate::GpuAcceleratedPlot::prepare).
+    pub fn vertices(&self) -> &[Vertex] {
+        self.level(0)
+    }
+
+    /// The vertices of level of detail `level`, where 0 is full detail.
+    /// Clamped to the coarsest level built.
+    pub fn level(&self, level: usize) -> &[Vertex] {
+        self.levels
+            .get(level.min(self.levels.len().saturating_sub(1)))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Number of levels, including full detail.
+    pub fn levels(&self) -> usize {
+        self.levels.len()
+    }
+
+    /// A level's vertices as bytes in the plot's vertex layout, for writing
+    /// straight into a buffer.
+    pub fn bytes(&self, level: usize) -> &[u8] {
+        bytemuck::cast_slice(self.level(level))
+    }
+
+    pub fn into_vertices(mut self) -> Vec<Vertex> {
+        if self.levels.is_empty() {
+            Vec::new()
+        } else {
+            self.levels.swap_remove(0)
+        }
+    }
+}
+
+/// Builds series vertices on a pool of worker threads, so that a large
+/// series doesn't stall the UI while its normals and levels of detail are
+/// computed.
+///
+/// Submit jobs from the UI thread, and check for finished series with
+/// [`SeriesPreparer::try_recv`] once per frame. Results arrive in the order
+/// they finish, not the order submitted. Dropping the preparer finishes any
+/// queued jobs and then joins the workers.
+pub struct SeriesPreparer {
+    jobs: Option<mpsc::Sender<PrepareJob>>,
+    results: mpsc::Receiver<PreparedSeries>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl SeriesPreparer {
+    /// Start `threads` workers (at least one). Fails if a thread can't be
+    /// spawned, stopping the workers already started.
+    pub fn new(threads: usize) -> io::Result<SeriesPreparer> {
+        SeriesPreparer::spawn(threads, None)
+    }
+
+    /// Start one worker per available core, minus one for the UI thread.
+    pub fn with_available_parallelism() -> io::Result<SeriesPreparer> {
+        let threads = thread::available_parallelism().map_or(1, |n| n.get());
+        SeriesPreparer::new(threads.saturating_sub(1))
+    }
+
+    /// Like [`SeriesPreparer::new`], but request a repaint whenever a series
+    /// is ready.
+    #[cfg(feature = "egui")]
+    pub fn with_notifier(threads: usize, notifier: DataNotifier) -> io::Result<SeriesPreparer> {
+        SeriesPreparer::spawn(threads, Some(Box::new(move || notifier.notify())))
+    }
+
+    fn spawn(
+        threads: usize,
+        on_ready: Option<Box<dyn Fn() + Send + Sync>>,
+    ) -> io::Result<SeriesPreparer> {
+        let (jobs, queue) = mpsc::channel::<PrepareJob>();
+        let (done, results) = mpsc::channel();
+        let queue = Arc::new(Mutex::new(queue));
+        let on_ready = Arc::new(on_ready);
+
+        let workers = (0..threads.max(1))
+            .map(|i| {
+                let queue = Arc::clone(&queue);
+                let done = done.clone();
+                let on_ready = Arc::clone(&on_ready);
+                thread::Builder::new()
+                    .name(format!("plot-prepare-{}", i))
+                    .spawn(move || loop {
+                        // Only hold the lock while waiting, so that the
+                        // other workers can pick up jobs meanwhile.
+                        let job = queue.lock().unwrap_or_else(PoisonError::into_inner).recv();
+                        let job = match job {
+                            Ok(job) => job,
+                            Err(_) => break,
+                        };
+                        if done.send(prepare_series(job)).is_err() {
+                            break;
+                        }
+                        if let Some(on_ready) = on_ready.as_ref() {
+                            on_ready();
+                        }
+                    })
+            })
+            // On failure the queue's sender is dropped, so the workers already
+            // started exit.
+            .collect::<io::Result<_>>()?;
+
+        Ok(SeriesPreparer {
+            jobs: Some(jobs),
+            results,
+            workers,
+        })
+    }
+
+    /// Queue a job. Returns false if the workers have stopped (i.e. one of
+    /// them panicked), in which case the job is dropped.
+    pub fn submit(&self, job: PrepareJob) -> bool {
+        self.jobs
+            .as_ref()
+            .map_or(false, |jobs| jobs.send(job).is_ok())
+    }
+
+    /// A finished series, if any, without blocking.
+    pub fn try_recv(&self) -> Option<PreparedSeries> {
+        self.results.try_recv().ok()
+    }
+
+    /// Every series finished so far, without blocking.
+    pub fn drain(&self) -> impl Iterator<Item = PreparedSeries> + '_ {
+        self.results.try_iter()
+    }
+
+    pub fn threads(&self) -> usize {
+        self.workers.len()
+    }
+}
+
+impl Drop for SeriesPreparer {
+    fn drop(&mut self) {
+        // Closing the queue lets the workers exit once it's empty.
+        self.jobs = None;
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+/// Run a job on the current thread, e.g. for small series where a round
+/// trip through the pool isn't worth it.
+pub fn prepare_series(job: PrepareJob) -> PreparedSeries {
+    let samples = if job.tolerance > 0.0 {
+        simplify(&job.samples, job.tolerance)
+    } else {
+        job.samples
+    };
+
+    let mut levels = Vec::with_capacity(job.levels + 1);
+    levels.push(expand(&samples, job.color));
+
+    let mut coarse = samples;
+    for _ in 0..job.levels {
+        if coarse.len() <= 4 {
+            break;
+        }
+        coarse = min_max(&coarse);
+        levels.push(expand(&coarse, job.color));
+    }
+
+    PreparedSeries { id: job.id, levels }
+}
+
+// Turn samples into vertex pairs, one on each side of the line, with normals
+// along the bisector of the neighbouring segments (as `Fit::line_vertices`).
+pub(crate) fn expand(samples: &[[f64; 2]], color: [f32; 4]) -> Vec<Vertex> {
+    let mut vertices = Vec::with_capacity(samples.len() * 2);
+
+    for (i, &[x, y]) in samples.iter().enumerate() {
+        let [x0, y0] = samples[i.saturating_sub(1)];
+        let [x1, y1] = samples[(i + 1).min(samples.len() - 1)];
+        let (dx, dy) = ((x1 - x0) as f32, (y1 - y0) as f32);
+        let length = dx.hypot(dy).max(f32::EPSILON);
+        let normal = [-dy / length, dx / length];
+
+        let position = [x as f32, y as f32];
+        vertices.push(Vertex {
+            position,
+            normal,
+            color,
+        });
+        vertices.push(Vertex {
+            position,
+            normal: [-normal[0], -normal[1]],
+            color,
+        });
+    }
+
+    vertices
+}
+
+// Ramer-Douglas-Peucker, with an explicit stack so that long, noisy series
+// can't overflow the worker's.
+fn simplify(samples: &[[f64; 2]], tolerance: f64) -> Vec<[f64; 2]> {
+    if samples.len() < 3 {
+        return samples.to_vec();
+    }
+
+    let mut keep = vec![false; samples.len()];
+    keep[0] = true;
+    keep[samples.len() - 1] = true;
+
+    let mut stack = vec![(0, samples.len() - 1)];
+    while let Some((start, end)) = stack.pop() {
+        let [x0, y0] = samples[start];
+        let [x1, y1] = samples[end];
+        let (dx, dy) = (x1 - x0, y1 - y0);
+        let length = dx.hypot(dy);
+
+        let (farthest, distance) = (start + 1..end)
+            .map(|i| {
+                let [x, y] = samples[i];
+                let distance = if length > 0.0 {
+                    (dy * (x - x0) - dx * (y - y0)).abs() / length
+                } else {
+                    (x - x0).hypot(y - y0)
+                };
+                (i, distance)
+            })
+            .fold(
+                (start, 0.0),
+                |best, next| if next.1 > best.1 { next } else { best },
+            );
+
+        if distance > tolerance {
+            keep[farthest] = true;
+            stack.push((start, farthest));
+            stack.push((farthest, end));
+        }
+    }
+
+    samples
+        .iter()
+        .zip(keep)
+        .filter_map(|(&sample, keep)| keep.then_some(sample))
+        .collect()
+}
+
+// Halve the samples: each group of four becomes its minimum and maximum, in
+// the order they occurred.
+fn min_max(samples: &[[f64; 2]]) -> Vec<[f64; 2]> {
+    samples
+        .chunks(4)
+        .flat_map(|group| {
+            let lowest = group.iter().min_by(|a, b| a[1].total_cmp(&b[1]));
+            let highest = group.iter().max_by(|a, b| a[1].total_cmp(&b[1]));
+            match (lowest, highest) {
+                (Some(&lo), Some(&hi)) if lo[0] <= hi[0] => [lo, hi],
+                (Some(&lo), Some(&hi)) => [hi, lo],
+                _ => unreachable!("chunks are never empty"),
+            }
+        })
+        .collect()
+}